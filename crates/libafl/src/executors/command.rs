@@ -3,8 +3,7 @@
 use alloc::ffi::CString;
 #[cfg(not(unix))]
 use alloc::string::{String, ToString};
-#[cfg(all(feature = "intel_pt", target_os = "linux"))]
-use alloc::vec::Vec;
+use alloc::{borrow::Cow, vec::Vec};
 #[cfg(all(feature = "intel_pt", target_os = "linux"))]
 use core::ffi::CStr;
 use core::{
@@ -24,12 +23,13 @@ use std::os::{fd::RawFd, unix::ffi::OsStrExt};
 use std::{
     io::{Read, Write},
     process::{Child, Command, Stdio},
+    time::Instant,
 };
 
 #[cfg(unix)]
 use libafl_bolts::{AsSlice, tuples::MatchNameRef};
 use libafl_bolts::{
-    InputLocation, StdTargetArgs, StdTargetArgsInner,
+    InputLocation, StdTargetArgs, StdTargetArgsInner, env_input_value,
     ownedref::OwnedSlice,
     tuples::{Handle, MatchName, RefIndexable},
 };
@@ -58,13 +58,24 @@ use typed_builder::TypedBuilder;
 #[cfg(all(target_family = "unix", feature = "fork"))]
 use super::forkserver::ConfigTarget;
 use super::{StdChildArgs, StdChildArgsInner};
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+use crate::executors::hang_backtrace;
 #[cfg(target_os = "linux")]
 use crate::executors::hooks::ExecutorHooksTuple;
+#[cfg(unix)]
+use crate::executors::limits::Confinement;
+#[cfg(target_os = "linux")]
+use crate::executors::sandbox::{self, SandboxInstance};
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+use crate::observers::HangBacktraceObserver;
 use crate::{
     Error,
     executors::{Executor, ExitKind, HasObservers, HasTimeout, SetTimeout},
     inputs::{HasTargetBytes, ToTargetBytesConverter},
-    observers::{ObserversTuple, StdErrObserver, StdOutObserver},
+    observers::{
+        MemoryObserver, ObserversTuple, ResponseObserver, SandboxObserver, StdErrObserver,
+        StdOutObserver,
+    },
     state::HasExecutions,
 };
 
@@ -126,12 +137,31 @@ pub struct StdCommandConfigurator {
     timeout: Duration,
     /// true: input gets delivered via stdin
     input_location: InputLocation,
+    /// The response received when delivering the last input over a socket
+    response: Option<Vec<u8>>,
+    /// The resource limits of the children
+    #[cfg(unix)]
+    confinement: Option<Confinement>,
+    /// The sandbox of the children
+    #[cfg(target_os = "linux")]
+    sandbox: Option<SandboxInstance>,
     /// The Command to execute
     command: Command,
 }
 
 impl CommandConfigurator<Child> for StdCommandConfigurator {
     fn spawn_child(&mut self, target_bytes: OwnedSlice<'_, u8>) -> Result<Child, Error> {
+        if let InputLocation::Env { name } = &self.input_location {
+            self.command.env(name, env_input_value(&target_bytes));
+        }
+        // Commands for argument inputs are created for each run and prepared below.
+        #[cfg(target_os = "linux")]
+        if let Some(sandbox) = &self.sandbox
+            && !matches!(self.input_location, InputLocation::Arg { .. })
+        {
+            sandbox.prepare_exec(&self.command)?;
+        }
+
         let mut cmd = Command::new(self.command.get_program());
         match &mut self.input_location {
             InputLocation::Arg { argnum } => {
@@ -176,6 +206,15 @@ impl CommandConfigurator<Child> for StdCommandConfigurator {
                 if let Some(cwd) = self.command.get_current_dir() {
                     cmd.current_dir(cwd);
                 }
+                #[cfg(unix)]
+                if let Some(confinement) = &self.confinement {
+                    confinement.child_limits().apply(&mut cmd);
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(sandbox) = &self.sandbox {
+                    sandbox.apply(&mut cmd);
+                    sandbox.prepare_exec(&cmd)?;
+                }
                 Ok(cmd.spawn()?)
             }
             InputLocation::StdIn { input_file: _ } => {
@@ -202,7 +241,65 @@ impl CommandConfigurator<Child> for StdCommandConfigurator {
                 out_file.write_buf(&target_bytes)?;
                 Ok(self.command.spawn()?)
             }
+            InputLocation::Socket { delivery } => {
+                let child = self.command.spawn()?;
+                // If the target died before accepting the input, its exit status tells us why.
+                self.response = delivery
+                    .deliver(&target_bytes, self.timeout)
+                    .inspect_err(|err| log::warn!("Could not deliver input to target: {err}"))
+                    .ok();
+                Ok(child)
+            }
+            InputLocation::Env { .. } => Ok(self.command.spawn()?),
+            #[cfg(unix)]
+            InputLocation::Fifo { fifo } => {
+                let child = self.command.spawn()?;
+                // If the target never reads the input, it will time out or exit on its own.
+                if let Err(err) = fifo.write_input(&target_bytes, self.timeout) {
+                    log::warn!("Could not deliver input to target: {err}");
+                }
+                Ok(child)
+            }
+        }
+    }
+
+    fn take_response(&mut self) -> Option<Vec<u8>> {
+        self.response.take()
+    }
+
+    fn terminate_after_delivery(&self) -> bool {
+        matches!(self.input_location, InputLocation::Socket { .. })
+    }
+
+    #[cfg(unix)]
+    fn check_oom(&mut self) -> Result<bool, Error> {
+        match &mut self.confinement {
+            Some(confinement) => confinement.check_oom(),
+            None => Ok(false),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn sandbox_violation(&self) -> Option<Cow<'static, str>> {
+        self.sandbox
+            .as_ref()
+            .and_then(|sandbox| sandbox.violation().cloned())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn target_pid(&self, child_pid: u32) -> i32 {
+        if self
+            .sandbox
+            .as_ref()
+            .is_some_and(SandboxInstance::has_pid_namespace)
+        {
+            // The target is the first process the init of the sandbox spawns.
+            if let Some(pid) = sandbox::outer_pid(child_pid, 2) {
+                return pid;
+            }
+            log::warn!("Could not find the sandboxed target of child {child_pid}");
         }
+        child_pid.cast_signed()
     }
 }
 
@@ -253,6 +350,15 @@ impl CommandConfigurator<Pid> for PTraceCommandConfigurator {
             unistd::{ForkResult, alarm, execve, fork, pipe, write},
         };
 
+        if matches!(
+            self.input_location,
+            InputLocation::Socket { .. } | InputLocation::Fifo { .. }
+        ) {
+            return Err(Error::unsupported(
+                "PTraceCommandConfigurator does not support socket or fifo input delivery",
+            ));
+        }
+
         match unsafe { fork() } {
             Ok(ForkResult::Parent { child }) => Ok(child),
             Ok(ForkResult::Child) => {
@@ -292,6 +398,16 @@ impl CommandConfigurator<Pid> for PTraceCommandConfigurator {
                     InputLocation::File { out_file } => {
                         out_file.write_buf(&target_bytes).unwrap();
                     }
+                    InputLocation::Env { name } => {
+                        let mut var = name.as_bytes().to_vec();
+                        var.push(b'=');
+                        var.extend_from_slice(env_input_value(&target_bytes).as_bytes());
+                        // `env_input_value` cuts the input at the first nul byte.
+                        self.env.push(CString::new(var).unwrap());
+                    }
+                    InputLocation::Socket { .. } | InputLocation::Fifo { .. } => {
+                        unreachable!("checked before forking")
+                    }
                 }
 
                 ptrace::traceme().unwrap();
@@ -327,6 +443,11 @@ pub struct CommandExecutor<C, HT, I, OT, S, T> {
     observers: OT,
     stdout_observer: Option<Handle<StdOutObserver>>,
     stderr_observer: Option<Handle<StdErrObserver>>,
+    response_observer: Option<Handle<ResponseObserver>>,
+    sandbox_observer: Option<Handle<SandboxObserver>>,
+    #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+    hang_backtrace_observer: Option<Handle<HangBacktraceObserver>>,
+    memory_observer: Option<Handle<MemoryObserver>>,
     hooks: HT,
     phantom: PhantomData<(C, I, S)>,
 }
@@ -356,13 +477,19 @@ where
     HT: Debug,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("CommandExecutor")
+        let mut debug = f.debug_struct("CommandExecutor");
+        debug
             .field("inner", &self.configurator)
             .field("observers", &self.observers)
             .field("hooks", &self.hooks)
             .field("stdout_observer", &self.stdout_observer)
             .field("stderr_observer", &self.stderr_observer)
-            .finish()
+            .field("response_observer", &self.response_observer)
+            .field("sandbox_observer", &self.sandbox_observer)
+            .field("memory_observer", &self.memory_observer);
+        #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+        debug.field("hang_backtrace_observer", &self.hang_backtrace_observer);
+        debug.finish()
     }
 }
 
@@ -390,22 +517,102 @@ where
 
         self.observers_mut().pre_exec_all(state, input)?;
         *state.executions_mut() += 1;
+        let start = Instant::now();
         let mut child = self
             .configurator
             .spawn_child(target_bytes_converter.convert_to_target_bytes(state, input))?;
 
-        let exit_kind = child
-            .wait_timeout(self.configurator.timeout())
-            .expect("waiting on child failed")
-            .map(|status| self.configurator.exit_kind_from_status(&status))
-            .unwrap_or_else(|| {
-                // if this fails, there is not much we can do. let's hope it failed because the process finished
-                // in the meantime.
+        // The delivery is part of the execution, and so of its timeout.
+        let mut timeout = self.configurator.timeout().saturating_sub(start.elapsed());
+        let mut terminated = false;
+        if self.configurator.terminate_after_delivery() {
+            // Servers keep running after handling the input, stop them once they are idle,
+            // unless they died already. Busy ones hang, and run into the timeout.
+            #[cfg(unix)]
+            let idle = wait_for_idle(self.configurator.target_pid(child.id()), timeout);
+            #[cfg(not(unix))]
+            let idle = true;
+            if idle && child.try_wait()?.is_none() {
+                // # Safety
+                // The child has not been waited on yet, so its pid is still valid.
+                #[cfg(unix)]
+                unsafe {
+                    libc::kill(child.id().cast_signed(), libc::SIGTERM);
+                }
+                #[cfg(not(unix))]
                 drop(child.kill());
-                // finally, try to wait to properly clean up system resources.
-                drop(child.wait());
-                ExitKind::Timeout
-            });
+                terminated = true;
+                // Give it the whole timeout to shut down, this is no longer the execution.
+                timeout = self.configurator.timeout();
+            } else {
+                timeout = self.configurator.timeout().saturating_sub(start.elapsed());
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        let status = if let Some(handle) = self.memory_observer.clone() {
+            let waited = wait4_timeout(child.id().cast_signed(), timeout)?;
+            waited.map(|(status, rusage)| {
+                self.observers_mut()
+                    .index_mut(&handle)
+                    .observe_rusage(&rusage);
+                status
+            })
+        } else {
+            child
+                .wait_timeout(timeout)
+                .expect("waiting on child failed")
+        };
+        #[cfg(not(target_os = "linux"))]
+        let status = child
+            .wait_timeout(timeout)
+            .expect("waiting on child failed");
+
+        #[cfg(unix)]
+        if let Some(status) = &status
+            && std::os::unix::process::ExitStatusExt::signal(status) == Some(libc::SIGSYS)
+            && let Some(violation) = self.configurator.sandbox_violation()
+            && let Some(sandbox_handle) = self.sandbox_observer.clone()
+        {
+            self.observers_mut()
+                .index_mut(&sandbox_handle)
+                .observe(violation);
+        }
+
+        #[cfg(unix)]
+        let killed = status.as_ref().is_some_and(|status| {
+            std::os::unix::process::ExitStatusExt::signal(status) == Some(libc::SIGKILL)
+        });
+
+        let exit_kind = if let Some(status) = status {
+            if terminated && terminated_by_us(status) {
+                ExitKind::Ok
+            } else {
+                self.configurator.exit_kind_from_status(&status)
+            }
+        } else {
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            if let Some(handle) = self.hang_backtrace_observer.clone() {
+                let pid = self.configurator.target_pid(child.id());
+                match hang_backtrace::capture_stack(Pid::from_raw(pid)) {
+                    Ok(frames) => self.observers_mut().index_mut(&handle).observe(frames),
+                    Err(err) => log::warn!("{err}"),
+                }
+            }
+            // if this fails, there is not much we can do. let's hope it failed because the process finished
+            // in the meantime.
+            drop(child.kill());
+            // finally, try to wait to properly clean up system resources.
+            drop(child.wait());
+            ExitKind::Timeout
+        };
+        // The OOM killer kills with SIGKILL, no need to look otherwise.
+        #[cfg(unix)]
+        let exit_kind = if killed && self.configurator.check_oom()? {
+            ExitKind::Oom
+        } else {
+            exit_kind
+        };
 
         // Manually update stdout/stderr here if we use piped implementation.
         // Reason of not putting into state and pass by post_exec_all is that
@@ -427,6 +634,14 @@ where
             self.observers_mut().index_mut(&stdout_handle).observe(buf);
         }
 
+        if let Some(response) = self.configurator.take_response()
+            && let Some(response_handle) = self.response_observer.clone()
+        {
+            self.observers_mut()
+                .index_mut(&response_handle)
+                .observe(response);
+        }
+
         self.observers_mut()
             .post_exec_child_all(state, input, &exit_kind)?;
         Ok(exit_kind)
@@ -636,7 +851,14 @@ impl CommandExecutorBuilder {
                 }
                 command.stdin(Stdio::piped());
             }
-            InputLocation::File { .. } | InputLocation::Arg { .. } => {
+            #[cfg(unix)]
+            InputLocation::Fifo { .. } => {
+                command.stdin(Stdio::null());
+            }
+            InputLocation::File { .. }
+            | InputLocation::Arg { .. }
+            | InputLocation::Socket { .. }
+            | InputLocation::Env { .. } => {
                 command.stdin(Stdio::null());
             }
         }
@@ -722,20 +944,83 @@ impl CommandExecutorBuilder {
             )));
         }
 
+        #[cfg(unix)]
+        let confinement = self
+            .child_env_inner
+            .resource_limits
+            .as_ref()
+            .map(Confinement::new)
+            .transpose()?;
+        #[cfg(unix)]
+        if let Some(confinement) = &confinement {
+            confinement.child_limits().apply(&mut command);
+        }
+
+        #[cfg(target_os = "linux")]
+        let sandbox = match &self.child_env_inner.sandbox {
+            Some(sandbox) => {
+                let input_file = match &self.target_inner.input_location {
+                    InputLocation::File { out_file } => Some(out_file.path.as_path()),
+                    InputLocation::Fifo { fifo } => Some(fifo.path.as_path()),
+                    InputLocation::Socket { .. } => {
+                        return Err(Error::illegal_argument(
+                            "Socket input delivery is not supported in a sandbox",
+                        ));
+                    }
+                    InputLocation::StdIn { .. }
+                    | InputLocation::Arg { .. }
+                    | InputLocation::Env { .. } => None,
+                };
+                let sandbox = sandbox.instantiate(
+                    program.as_os_str(),
+                    input_file,
+                    self.child_env_inner.current_directory.as_deref(),
+                )?;
+                // The arguments change for each run, so those commands get the sandbox applied then.
+                if !matches!(self.target_inner.input_location, InputLocation::Arg { .. }) {
+                    sandbox.apply(&mut command);
+                }
+                Some(sandbox)
+            }
+            None => None,
+        };
+
         let configurator = StdCommandConfigurator {
             debug_child: self.child_env_inner.debug_child,
             stdout_cap,
             stderr_cap,
             input_location: self.target_inner.input_location.clone(),
+            response: None,
             timeout: self.child_env_inner.timeout,
+            #[cfg(unix)]
+            confinement,
+            #[cfg(target_os = "linux")]
+            sandbox,
             command,
         };
 
-        Ok(configurator.into_executor::<I, OT, S>(
+        let mut executor = configurator.into_executor::<I, OT, S>(
             observers,
             self.child_env_inner.stdout_observer.clone(),
             self.child_env_inner.stderr_observer.clone(),
-        ))
+        );
+        executor
+            .response_observer
+            .clone_from(&self.child_env_inner.response_observer);
+        #[cfg(target_os = "linux")]
+        {
+            executor
+                .sandbox_observer
+                .clone_from(&self.child_env_inner.sandbox_observer);
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            executor
+                .hang_backtrace_observer
+                .clone_from(&self.child_env_inner.hang_backtrace_observer);
+            executor
+                .memory_observer
+                .clone_from(&self.child_env_inner.memory_observer);
+        }
+        Ok(executor)
     }
 }
 
@@ -802,6 +1087,37 @@ pub trait CommandConfigurator<C>: Sized {
     /// Spawns a new process with the given configuration.
     fn spawn_child(&mut self, target_bytes: OwnedSlice<'_, u8>) -> Result<C, Error>;
 
+    /// Takes the response the child sent back while the input was delivered, if any.
+    fn take_response(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// If the child keeps running after the input was delivered, e.g. a server receiving the
+    /// input over a socket. Once it is idle, it gets terminated with `SIGTERM`, which is not
+    /// considered a crash. If it is still busy at the timeout, it is reported as a timeout.
+    fn terminate_after_delivery(&self) -> bool {
+        false
+    }
+
+    /// If the last child was killed for running out of memory, e.g. by the OOM killer of its
+    /// cgroup, see [`crate::executors::limits`]. Only called if it died from `SIGKILL`.
+    fn check_oom(&mut self) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    /// The violation to report if the last child was killed by the seccomp filter of its sandbox,
+    /// see [`crate::executors::sandbox`].
+    fn sandbox_violation(&self) -> Option<Cow<'static, str>> {
+        None
+    }
+
+    /// The pid of the target process of the child with `child_pid`, to capture its stack when it
+    /// times out. Differs from the child if that is only a supervisor, e.g. in a sandbox.
+    #[cfg(unix)]
+    fn target_pid(&self, child_pid: u32) -> i32 {
+        child_pid.cast_signed()
+    }
+
     /// Maps the exit status of the child process to an `ExitKind`.
     #[cfg(unix)]
     #[inline]
@@ -839,6 +1155,11 @@ pub trait CommandConfigurator<C>: Sized {
             hooks: (),
             stderr_observer,
             stdout_observer,
+            response_observer: None,
+            sandbox_observer: None,
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            hang_backtrace_observer: None,
+            memory_observer: None,
             phantom: PhantomData,
         }
     }
@@ -857,11 +1178,134 @@ pub trait CommandConfigurator<C>: Sized {
             hooks,
             stderr_observer,
             stdout_observer,
+            response_observer: None,
+            sandbox_observer: None,
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            hang_backtrace_observer: None,
+            memory_observer: None,
             phantom: PhantomData,
         }
     }
 }
 
+/// If the child was killed by the `SIGTERM` we send after delivering the input.
+#[cfg(unix)]
+fn terminated_by_us(status: std::process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal() == Some(libc::SIGTERM)
+}
+
+/// If the child was killed by the `SIGTERM` we send after delivering the input.
+#[cfg(not(unix))]
+fn terminated_by_us(_status: std::process::ExitStatus) -> bool {
+    false
+}
+
+/// Waits for a server to be done with its input: until none of its threads runs any more, or it
+/// exited. Returns `false` if it is still busy after `timeout`, i.e. it hangs.
+#[cfg(target_os = "linux")]
+pub(crate) fn wait_for_idle(pid: i32, timeout: Duration) -> bool {
+    use std::{fs, thread::sleep};
+
+    /// If all threads of the process are idle, `None` once it is gone
+    fn is_idle(pid: i32) -> Option<bool> {
+        let tasks = fs::read_dir(format!("/proc/{pid}/task")).ok()?;
+        Some(tasks.flatten().all(|task| {
+            // Threads that are gone do not run any more.
+            fs::read_to_string(task.path().join("stat"))
+                .ok()
+                .is_none_or(|stat| {
+                    // The state follows the command name, which may contain anything.
+                    stat.rsplit_once(") ")
+                        .and_then(|(_, rest)| rest.chars().next())
+                        .is_none_or(|state| matches!(state, 'S' | 'Z' | 'X'))
+                })
+        }))
+    }
+
+    let start = Instant::now();
+    // A single look may catch a busy thread in between two syscalls.
+    let mut idle_polls = 0;
+    loop {
+        match is_idle(pid) {
+            None => return true,
+            Some(true) => idle_polls += 1,
+            Some(false) => idle_polls = 0,
+        }
+        if idle_polls >= 2 {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        sleep(Duration::from_millis(1));
+    }
+}
+
+/// Waits for a server to be done with its input. Only Linux can tell, elsewhere it is assumed to be
+/// done right away.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn wait_for_idle(_pid: i32, _timeout: Duration) -> bool {
+    true
+}
+
+/// Waits for the child like `wait_timeout`, but reaps it with `wait4`, to get its `rusage`.
+///
+/// Returns `None` if the child is still running after the timeout.
+#[cfg(target_os = "linux")]
+fn wait4_timeout(
+    pid: i32,
+    timeout: Duration,
+) -> Result<Option<(std::process::ExitStatus, libc::rusage)>, Error> {
+    use std::os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::process::ExitStatusExt,
+    };
+
+    // # Safety
+    // `pidfd_open` only takes the pid and flags, the child is not reaped yet.
+    let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+    if pidfd < 0 {
+        return Err(Error::last_os_error("Failed to open a pidfd for the child"));
+    }
+    // # Safety
+    // The fd was just opened and is owned by nobody else.
+    let pidfd = unsafe { OwnedFd::from_raw_fd(pidfd as i32) };
+
+    // the pidfd becomes readable once the child exited
+    let mut pollfd = libc::pollfd {
+        fd: pidfd.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let remaining_ms = i32::try_from(remaining.as_millis()).unwrap_or(i32::MAX);
+        match unsafe { libc::poll(&raw mut pollfd, 1, remaining_ms) } {
+            0 => return Ok(None),
+            -1 if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted => {}
+            -1 => {
+                return Err(Error::last_os_error(
+                    "Failed to poll the pidfd of the child",
+                ));
+            }
+            _ => break,
+        }
+    }
+
+    let mut status = 0;
+    let mut rusage = core::mem::MaybeUninit::<libc::rusage>::zeroed();
+    // # Safety
+    // `wait4` only writes to the given status and rusage.
+    if unsafe { libc::wait4(pid, &raw mut status, 0, rusage.as_mut_ptr()) } < 0 {
+        return Err(Error::last_os_error("Failed to wait for the child"));
+    }
+    Ok(Some((std::process::ExitStatus::from_raw(status), unsafe {
+        rusage.assume_init()
+    })))
+}
+
 /// waitpid wrapper that ignores some signals sent by the ptraced child
 #[cfg(target_os = "linux")]
 fn waitpid_filtered(pid: Pid, options: Option<WaitPidFlag>) -> Result<WaitStatus, Errno> {
@@ -955,4 +1399,255 @@ mod tests {
 
         assert!(executor.observers.0.output.is_some());
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(unix)]
+    fn test_env_and_fifo_input() {
+        let mut mgr: SimpleEventManager<NopInput, _, NopState<NopInput>> =
+            SimpleEventManager::new(SimpleMonitor::new(|status| {
+                log::info!("{status}");
+            }));
+        let mut fuzzer: NopFuzzer = NopFuzzer::new();
+
+        let stdout = StdOutObserver::new_piped("stdout".into()).unwrap();
+        let mut executor = CommandExecutor::builder()
+            .program("sh")
+            .args(["-c", "printf %s \"$FUZZ_INPUT\""])
+            .env_input("FUZZ_INPUT")
+            .stdout_observer(stdout.handle())
+            .build(tuple_list!(stdout))
+            .unwrap();
+        executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(b"env\0ignored".to_vec()),
+            )
+            .unwrap();
+        assert_eq!(executor.observers.0.output.as_deref(), Some(&b"env"[..]));
+
+        let stdout = StdOutObserver::new_piped("stdout".into()).unwrap();
+        let mut executor = CommandExecutor::builder()
+            .program("cat")
+            .arg_input_fifo(libafl_bolts::fs::get_unique_std_input_file())
+            .stdout_observer(stdout.handle())
+            .build(tuple_list!(stdout))
+            .unwrap();
+        executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(b"fifo".to_vec()),
+            )
+            .unwrap();
+        assert_eq!(executor.observers.0.output.as_deref(), Some(&b"fifo"[..]));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore)]
+    fn test_socket_hang() {
+        use core::time::Duration;
+
+        use libafl_bolts::{SocketDelivery, SocketEndpoint};
+
+        use crate::executors::ExitKind;
+
+        let mut mgr: SimpleEventManager<NopInput, _, NopState<NopInput>> =
+            SimpleEventManager::new(SimpleMonitor::new(|status| {
+                log::info!("{status}");
+            }));
+        let mut fuzzer: NopFuzzer = NopFuzzer::new();
+        // Nobody listens there, the delivery gives up and leaves the targets be.
+        let endpoint = SocketEndpoint::Unix(libafl_bolts::fs::get_unique_std_input_file().into());
+        let delivery = SocketDelivery::new(endpoint).connect_timeout(Duration::from_millis(100));
+
+        // An idle server gets stopped once the input is delivered.
+        let mut executor = CommandExecutor::builder()
+            .program("sleep")
+            .arg("10")
+            .socket_input(delivery.clone())
+            .timeout(Duration::from_millis(500))
+            .build(())
+            .unwrap();
+        let exit_kind = executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(b"idle".to_vec()),
+            )
+            .unwrap();
+        assert_eq!(exit_kind, ExitKind::Ok);
+
+        // A busy one hangs.
+        let mut executor = CommandExecutor::builder()
+            .program("sh")
+            .args(["-c", "while :; do :; done"])
+            .socket_input(delivery)
+            .timeout(Duration::from_millis(500))
+            .build(())
+            .unwrap();
+        let exit_kind = executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(b"busy".to_vec()),
+            )
+            .unwrap();
+        assert_eq!(exit_kind, ExitKind::Timeout);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore)]
+    fn test_sandbox() {
+        use alloc::string::String;
+
+        use crate::{
+            executors::{ExitKind, Sandbox, SeccompFilter},
+            observers::SandboxObserver,
+        };
+
+        if !Sandbox::is_supported() {
+            return;
+        }
+        let mut mgr: SimpleEventManager<NopInput, _, NopState<NopInput>> =
+            SimpleEventManager::new(SimpleMonitor::new(|status| {
+                log::info!("{status}");
+            }));
+        let mut fuzzer: NopFuzzer = NopFuzzer::new();
+
+        // The input file is the only file next to it in the sandbox.
+        let stdout = StdOutObserver::new_piped("stdout".into()).unwrap();
+        let mut executor = CommandExecutor::builder()
+            .program("sh")
+            .args(["-c", "read line < \"$0\"; echo $line; echo .[!.]* *"])
+            .arg_input_file(libafl_bolts::fs::get_unique_std_input_file())
+            .stdout_observer(stdout.handle())
+            .sandbox(Sandbox::new())
+            .build(tuple_list!(stdout))
+            .unwrap();
+        executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(b"sandboxed\n".to_vec()),
+            )
+            .unwrap();
+        let output = String::from_utf8_lossy(executor.observers.0.output.as_deref().unwrap());
+        let (first, files) = output.trim_end().split_once('\n').unwrap();
+        assert_eq!(first, "sandboxed");
+        assert!(files.starts_with(".cur_input") && files.ends_with(" *"));
+
+        let sandbox_observer = SandboxObserver::new("sandbox");
+        let mut executor = CommandExecutor::builder()
+            .program("sh")
+            .args(["-c", "exec /bin/true"])
+            .sandbox(
+                Sandbox::new()
+                    .bind_read_only("/bin")
+                    .bind_read_only("/usr")
+                    .seccomp(SeccompFilter::deny_exec()),
+            )
+            .sandbox_observer(sandbox_observer.handle())
+            .build(tuple_list!(sandbox_observer))
+            .unwrap();
+        let exit_kind = executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(vec![]),
+            )
+            .unwrap();
+        assert_eq!(exit_kind, ExitKind::Crash);
+        assert_eq!(executor.observers.0.violation(), Some("tried to execve"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+    #[ignore = "needs ptrace, which is often forbidden in containers and CI"]
+    fn test_hang_backtrace() {
+        use core::time::Duration;
+
+        use crate::{
+            executors::ExitKind,
+            observers::{HangBacktraceObserver, ObserverWithHashField},
+        };
+
+        let mut mgr: SimpleEventManager<NopInput, _, NopState<NopInput>> =
+            SimpleEventManager::new(SimpleMonitor::new(|status| {
+                log::info!("{status}");
+            }));
+        let mut fuzzer: NopFuzzer = NopFuzzer::new();
+
+        let hang_observer = HangBacktraceObserver::new("hang");
+        let mut executor = CommandExecutor::builder()
+            .program("sleep")
+            .arg("10")
+            .timeout(Duration::from_millis(200))
+            .hang_backtrace_observer(hang_observer.handle())
+            .build(tuple_list!(hang_observer))
+            .unwrap();
+        let exit_kind = executor
+            .run_target(
+                &mut fuzzer,
+                &mut NopState::<NopInput>::new(),
+                &mut mgr,
+                &BytesInput::new(vec![]),
+            )
+            .unwrap();
+        assert_eq!(exit_kind, ExitKind::Timeout);
+        assert!(executor.observers.0.hash().is_some());
+        assert!(!executor.observers.0.frames().is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore)]
+    fn test_memory_observer() {
+        use core::time::Duration;
+
+        use crate::{executors::ExitKind, observers::MemoryObserver};
+
+        let mut mgr: SimpleEventManager<NopInput, _, NopState<NopInput>> =
+            SimpleEventManager::new(SimpleMonitor::new(|status| {
+                log::info!("{status}");
+            }));
+        let mut fuzzer: NopFuzzer = NopFuzzer::new();
+
+        let mut peak_rss = |script: &str| {
+            let memory_observer = MemoryObserver::new("memory");
+            let mut executor = CommandExecutor::builder()
+                .program("sh")
+                .arg("-c")
+                .arg(script)
+                .timeout(Duration::from_secs(5))
+                .memory_observer(memory_observer.handle())
+                .build(tuple_list!(memory_observer))
+                .unwrap();
+            let exit_kind = executor
+                .run_target(
+                    &mut fuzzer,
+                    &mut NopState::<NopInput>::new(),
+                    &mut mgr,
+                    &BytesInput::new(vec![]),
+                )
+                .unwrap();
+            assert_eq!(exit_kind, ExitKind::Ok);
+            executor.observers.0.usage().peak_rss
+        };
+
+        let small = peak_rss("true");
+        let large = peak_rss("x=$(head -c 67108864 /dev/zero | tr '\\0' a)");
+        assert!(small > 0);
+        assert!(large > 64 << 20);
+        assert!(small < large);
+    }
 }
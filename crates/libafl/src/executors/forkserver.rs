@@ -1,5 +1,7 @@
 //! Expose an `Executor` based on a `Forkserver` in order to execute AFL/AFL++ binaries
 
+#[cfg(target_os = "linux")]
+use alloc::borrow::Cow;
 use alloc::{string::ToString, vec::Vec};
 use core::{
     fmt::{self, Debug, Formatter},
@@ -16,17 +18,19 @@ use std::{
     },
     path::PathBuf,
     process::{Child, Command, Stdio},
+    time::Instant,
 };
 
 #[cfg(feature = "regex")]
-use libafl_bolts::tuples::{Handle, Handled};
+use libafl_bolts::tuples::Handled;
 use libafl_bolts::{
-    AsSlice, AsSliceMut, InputLocation, StdTargetArgs, StdTargetArgsInner, Truncate,
+    AsSlice, AsSliceMut, InputLocation, SocketDelivery, StdTargetArgs, StdTargetArgsInner,
+    Truncate,
     core_affinity::CoreId,
-    fs::{InputFile, get_unique_std_input_file},
+    fs::{InputFifo, InputFile, get_unique_std_input_file},
     os::{dup2, last_error_str, pipes::Pipe},
     shmem::{ShMem, ShMemProvider, UnixShMem, UnixShMemProvider},
-    tuples::{MatchNameRef, Prepend, RefIndexable},
+    tuples::{Handle, MatchNameRef, Prepend, RefIndexable},
 };
 use libc::RLIM_INFINITY;
 use nix::{
@@ -40,18 +44,31 @@ use nix::{
 };
 
 use super::{HasTimeout, StdChildArgs, StdChildArgsInner};
+#[cfg(target_os = "linux")]
+use crate::executors::limits::CGROUP_PROCS_ENV_VAR;
 #[cfg(feature = "regex")]
 use crate::observers::{
     AsanBacktraceObserver, get_asan_runtime_flags, get_asan_runtime_flags_with_log_path,
 };
 use crate::{
     Error,
-    executors::{Executor, ExitKind, HasObservers, SetTimeout},
+    executors::{
+        Executor, ExitKind, HasObservers, SetTimeout,
+        command::wait_for_idle,
+        limits::{Confinement, ResourceLimits},
+    },
     inputs::{Input, ToTargetBytesConverter},
     mutators::Tokens,
-    observers::{MapObserver, Observer, ObserversTuple},
+    observers::{MapObserver, Observer, ObserversTuple, ResponseObserver},
     state::HasExecutions,
 };
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+use crate::{executors::hang_backtrace, observers::HangBacktraceObserver};
+#[cfg(target_os = "linux")]
+use crate::{
+    executors::sandbox::{self, SandboxInstance},
+    observers::SandboxObserver,
+};
 
 /// Pinned fd number for forkserver communication
 pub const FORKSRV_FD: i32 = 198;
@@ -84,6 +101,10 @@ pub const FS_NEW_OPT_SHDMEM_FUZZ: i32 = 2_u32 as i32;
 /// Enable autodict option for new forkserver
 #[expect(clippy::cast_possible_wrap)]
 pub const FS_NEW_OPT_AUTODTCT: i32 = 0x00000800_u32 as i32;
+/// Set the input in the environment of each child option for new forkserver, only sent by the
+/// forkserver of `libafl_targets`, see [`INPUT_ENV_NAME_ENV_VAR`]
+#[expect(clippy::cast_possible_wrap)]
+pub const FS_NEW_OPT_ENV_INPUT: i32 = 0x00010000_u32 as i32;
 /// Enable autodict option for old forkserver
 #[expect(clippy::cast_possible_wrap)]
 pub const FS_OPT_AUTODTCT: i32 = 0x10000000_u32 as i32;
@@ -157,6 +178,13 @@ pub const SHM_FUZZ_ENV_VAR: &str = "__AFL_SHM_FUZZ_ID";
 /// Environment variable key for the page size (at least/usually `testcase_size_max + sizeof::<u32>()`)
 pub const SHM_FUZZ_MAP_SIZE_ENV_VAR: &str = "__AFL_SHM_FUZZ_MAP_SIZE";
 
+/// Environment variable key for the name of the environment variable a forkserver child should
+/// receive its input in, see [`InputLocation::Env`]
+pub const INPUT_ENV_NAME_ENV_VAR: &str = "__LIBAFL_INPUT_ENV_NAME";
+/// Environment variable key for the file holding the input for [`INPUT_ENV_NAME_ENV_VAR`], if
+/// the input is not passed via shared memory
+pub const INPUT_ENV_FILE_ENV_VAR: &str = "__LIBAFL_INPUT_ENV_FILE";
+
 /// Environment variable key for shared memory id for edge map
 pub const SHM_ENV_VAR: &str = "__AFL_SHM_ID";
 /// Environment variable key for shared memory id for cmplog map
@@ -351,6 +379,14 @@ pub struct Forkserver {
     last_run_timed_out: i32,
     /// The signal this [`Forkserver`] will use to kill
     kill_signal: Signal,
+    /// The resource limits of the forkserver and its children
+    confinement: Option<Confinement>,
+    /// The sandbox of the forkserver and its children
+    #[cfg(target_os = "linux")]
+    sandbox: Option<SandboxInstance>,
+    /// The last child pid reported from inside the sandbox, and its pid outside
+    #[cfg(target_os = "linux")]
+    last_sandboxed_pid: (i32, i32),
 }
 
 impl Drop for Forkserver {
@@ -418,6 +454,8 @@ impl Forkserver {
         stderr_memfd: Option<RawFd>,
         cwd: Option<PathBuf>,
         core: Option<CoreId>,
+        resource_limits: Option<&ResourceLimits>,
+        #[cfg(target_os = "linux")] sandbox: Option<SandboxInstance>,
     ) -> Result<Self, Error> {
         let Some(coverage_map_size) = coverage_map_size else {
             return Err(Error::unknown(
@@ -518,10 +556,24 @@ impl Forkserver {
             command.current_dir(cwd);
         }
 
+        // The rlimits are inherited by all children. The forkserver stays out of the cgroup, so
+        // the OOM killer never picks it, each child is moved into it after the fork instead.
+        let confinement = resource_limits.map(Confinement::new).transpose()?;
+        if let Some(confinement) = &confinement {
+            confinement
+                .child_limits()
+                .without_cgroup()
+                .apply(&mut command);
+            #[cfg(target_os = "linux")]
+            if let Some(procs) = confinement.cgroup_procs() {
+                command.env(CGROUP_PROCS_ENV_VAR, procs);
+            }
+        }
+
         // # Saftey
         // The pipe file descriptors used for `setpipe` are valid at this point.
-        let fsrv_handle = unsafe {
-            match ConfigTarget::setsid(
+        unsafe {
+            ConfigTarget::setsid(
                 command
                     .env("LD_BIND_NOW", "1")
                     .envs(envs)
@@ -533,15 +585,22 @@ impl Forkserver {
                 st_pipe.write_end().unwrap(),
                 ctl_pipe.read_end().unwrap(),
                 ctl_pipe.write_end().unwrap(),
-            )
-            .spawn()
-            {
-                Ok(fsrv_handle) => fsrv_handle,
-                Err(err) => {
-                    return Err(Error::illegal_state(format!(
-                        "Could not spawn the forkserver: {err:#?}"
-                    )));
-                }
+            );
+        }
+
+        // The sandbox has to be entered last, after the pipes are set up.
+        #[cfg(target_os = "linux")]
+        if let Some(sandbox) = &sandbox {
+            sandbox.apply(&mut command);
+            sandbox.prepare_exec(&command)?;
+        }
+
+        let fsrv_handle = match command.spawn() {
+            Ok(fsrv_handle) => fsrv_handle,
+            Err(err) => {
+                return Err(Error::illegal_state(format!(
+                    "Could not spawn the forkserver: {err:#?}"
+                )));
             }
         };
 
@@ -557,9 +616,62 @@ impl Forkserver {
             status: 0,
             last_run_timed_out: 0,
             kill_signal,
+            confinement,
+            #[cfg(target_os = "linux")]
+            sandbox,
+            #[cfg(target_os = "linux")]
+            last_sandboxed_pid: (0, 0),
         })
     }
 
+    /// The violation to report if the last child was killed by the seccomp filter of the sandbox
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn sandbox_violation(&self) -> Option<&Cow<'static, str>> {
+        self.sandbox.as_ref().and_then(SandboxInstance::violation)
+    }
+
+    /// Translates a child pid reported by the in-target forkserver to a pid we can signal.
+    ///
+    /// In a sandbox with its own pid namespace, the forkserver only knows the pids inside of it.
+    #[must_use]
+    pub fn outer_child_pid(&mut self, pid: i32) -> i32 {
+        #[cfg(target_os = "linux")]
+        if self
+            .sandbox
+            .as_ref()
+            .is_some_and(SandboxInstance::has_pid_namespace)
+        {
+            // Persistent mode reports the same child over and over.
+            if self.last_sandboxed_pid.0 != pid {
+                let outer = sandbox::outer_pid(self.fsrv_handle.id(), pid).unwrap_or_else(|| {
+                    log::warn!("Could not find sandboxed child {pid} outside of the sandbox");
+                    pid
+                });
+                self.last_sandboxed_pid = (pid, outer);
+            }
+            return self.last_sandboxed_pid.1;
+        }
+        pid
+    }
+
+    /// Moves the child with `pid` into the cgroup of the [`ResourceLimits`], if any.
+    pub fn confine_child(&mut self, pid: i32) -> Result<(), Error> {
+        match &mut self.confinement {
+            Some(confinement) => confinement.confine_child(pid),
+            None => Ok(()),
+        }
+    }
+
+    /// If the last child was killed by the OOM killer of the cgroup, see
+    /// [`crate::executors::limits`]. Only call this if it died from `SIGKILL`.
+    pub fn check_oom(&mut self) -> Result<bool, Error> {
+        match &mut self.confinement {
+            Some(confinement) => confinement.check_oom(),
+            None => Ok(false),
+        }
+    }
+
     /// If the last run timed out (as in-target i32)
     #[must_use]
     pub fn last_run_timed_out_raw(&self) -> i32 {
@@ -721,6 +833,13 @@ pub struct ForkserverExecutor<I, OT, S, SHM> {
     asan_obs: Handle<AsanBacktraceObserver>,
     timeout: TimeSpec,
     crash_exitcode: Option<i8>,
+    socket_delivery: Option<SocketDelivery>,
+    response_observer: Option<Handle<ResponseObserver>>,
+    #[cfg(target_os = "linux")]
+    sandbox_observer: Option<Handle<SandboxObserver>>,
+    #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+    hang_backtrace_observer: Option<Handle<HangBacktraceObserver>>,
+    input_fifo: Option<InputFifo>,
 }
 
 impl<I, OT, S, SHM> Debug for ForkserverExecutor<I, OT, S, SHM>
@@ -839,6 +958,7 @@ where
         }
 
         self.forkserver.set_last_run_timed_out(false);
+        let start = Instant::now();
         if let Err(err) = self.forkserver.write_ctl(last_run_timed_out) {
             return Err(Error::unknown(format!(
                 "Unable to request new process from fork server (OOM?): {err:?}"
@@ -857,25 +977,99 @@ where
             ));
         }
 
-        self.forkserver.set_child_pid(Pid::from_raw(pid));
+        let outer_pid = self.forkserver.outer_child_pid(pid);
+        self.forkserver.set_child_pid(Pid::from_raw(outer_pid));
+        self.forkserver.confine_child(outer_pid)?;
+
+        let timeout: Duration = self.timeout.into();
+        let mut wait = timeout;
+        let mut terminated = false;
+        if let Some(delivery) = &self.socket_delivery {
+            // If the target died before accepting the input, its exit status tells us why.
+            let response = delivery
+                .deliver(input, timeout.saturating_sub(start.elapsed()))
+                .inspect_err(|err| log::warn!("Could not deliver input to target: {err}"))
+                .ok();
+            if let Some(response) = response
+                && let Some(handle) = &self.response_observer
+                && let Some(response_observer) = self.observers.get_mut(handle)
+            {
+                response_observer.observe(response);
+            }
+            // Servers keep running after handling the input, stop them once they are idle.
+            // Busy ones hang, and run into the timeout.
+            wait = timeout.saturating_sub(start.elapsed());
+            if wait_for_idle(outer_pid, wait) {
+                let _ = kill(self.forkserver().child_pid(), Signal::SIGTERM);
+                terminated = true;
+                // Give it the whole timeout to shut down, this is no longer the execution.
+                wait = timeout;
+            } else {
+                wait = timeout.saturating_sub(start.elapsed());
+            }
+        }
+
+        if let Some(fifo) = &self.input_fifo {
+            // If the target never reads the input, it will time out or exit on its own.
+            if let Err(err) = fifo.write_input(input, timeout.saturating_sub(start.elapsed())) {
+                log::warn!("Could not deliver input to target: {err}");
+            }
+            // Writing is part of the execution, and so of its timeout.
+            wait = timeout.saturating_sub(start.elapsed());
+        }
 
-        if let Some(status) = self.forkserver.read_st_timed(&self.timeout)? {
+        if let Some(status) = self
+            .forkserver
+            .read_st_timed(&TimeSpec::from_duration(wait))?
+        {
             self.forkserver.set_status(status);
+            let terminated_by_us =
+                terminated && libc::WIFSIGNALED(status) && libc::WTERMSIG(status) == libc::SIGTERM;
             let exitcode_is_crash = if let Some(crash_exitcode) = self.crash_exitcode {
                 (libc::WEXITSTATUS(self.forkserver().status()) as i8) == crash_exitcode
             } else {
                 false
             };
-            if libc::WIFSIGNALED(self.forkserver().status()) || exitcode_is_crash {
+            #[cfg(target_os = "linux")]
+            if libc::WIFSIGNALED(status)
+                && libc::WTERMSIG(status) == libc::SIGSYS
+                && let Some(violation) = self.forkserver.sandbox_violation().cloned()
+                && let Some(handle) = &self.sandbox_observer
+                && let Some(sandbox_observer) = self.observers.get_mut(handle)
+            {
+                sandbox_observer.observe(violation);
+            }
+            if (libc::WIFSIGNALED(self.forkserver().status()) && !terminated_by_us)
+                || exitcode_is_crash
+            {
                 exit_kind = ExitKind::Crash;
                 #[cfg(feature = "regex")]
                 if let Some(asan_observer) = self.observers.get_mut(&self.asan_obs) {
                     asan_observer.parse_asan_output_from_asan_log_file(pid)?;
+                    if libc::WIFSIGNALED(status) {
+                        asan_observer.set_signal(libc::WTERMSIG(status));
+                    }
                 }
             }
+            if libc::WIFSIGNALED(status)
+                && libc::WTERMSIG(status) == libc::SIGKILL
+                && self.forkserver.check_oom()?
+            {
+                exit_kind = ExitKind::Oom;
+            }
         } else {
             self.forkserver.set_last_run_timed_out(true);
 
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            if let Some(handle) = &self.hang_backtrace_observer
+                && let Some(hang_backtrace_observer) = self.observers.get_mut(handle)
+            {
+                match hang_backtrace::capture_stack(self.forkserver.child_pid()) {
+                    Ok(frames) => hang_backtrace_observer.observe(frames),
+                    Err(err) => log::warn!("{err}"),
+                }
+            }
+
             // We need to kill the child in case he has timed out, or we can't get the correct pid in the next call to self.executor.forkserver_mut().read_st()?
             let _ = kill(self.forkserver().child_pid(), self.forkserver.kill_signal);
             if let Err(err) = self.forkserver.read_st() {
@@ -1003,6 +1197,13 @@ where
                 .clone()
                 .unwrap_or(AsanBacktraceObserver::default().handle()),
             crash_exitcode: self.crash_exitcode,
+            socket_delivery: self.socket_delivery(),
+            response_observer: self.child_env_inner.response_observer.clone(),
+            #[cfg(target_os = "linux")]
+            sandbox_observer: self.child_env_inner.sandbox_observer.clone(),
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            hang_backtrace_observer: self.child_env_inner.hang_backtrace_observer.clone(),
+            input_fifo: self.input_fifo(),
         })
     }
 
@@ -1063,6 +1264,13 @@ where
                 .clone()
                 .unwrap_or(AsanBacktraceObserver::default().handle()),
             crash_exitcode: self.crash_exitcode,
+            socket_delivery: self.socket_delivery(),
+            response_observer: self.child_env_inner.response_observer.clone(),
+            #[cfg(target_os = "linux")]
+            sandbox_observer: self.child_env_inner.sandbox_observer.clone(),
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            hang_backtrace_observer: self.child_env_inner.hang_backtrace_observer.clone(),
+            input_fifo: self.input_fifo(),
         })
     }
 
@@ -1087,8 +1295,39 @@ where
                 ));
             }
             InputLocation::File { out_file } => out_file.clone(),
+            InputLocation::Socket { delivery: _ } => {
+                if self.is_persistent {
+                    return Err(Error::illegal_argument(
+                        "forkserver doesn't support socket input delivery in persistent mode",
+                    ));
+                }
+                // Unused by the target, but the forkserver protocol expects an input file.
+                InputFile::create(OsString::from(get_unique_std_input_file()))?
+            }
+            InputLocation::Env { name: _ } => {
+                if self.is_persistent {
+                    return Err(Error::illegal_argument(
+                        "forkserver doesn't support environment variable input in persistent mode",
+                    ));
+                }
+                // The forkserver reads the input from this file and sets the variable in each child.
+                InputFile::create(OsString::from(get_unique_std_input_file()))?
+            }
+            InputLocation::Fifo { fifo: _ } => {
+                // Unused by the target, but the forkserver protocol expects an input file.
+                InputFile::create(OsString::from(get_unique_std_input_file()))?
+            }
         };
 
+        let mut envs = self.target_inner.envs.clone();
+        if let InputLocation::Env { name } = &self.target_inner.input_location {
+            envs.push((INPUT_ENV_NAME_ENV_VAR.into(), name.clone()));
+            envs.push((
+                INPUT_ENV_FILE_ENV_VAR.into(),
+                input_file.path.clone().into(),
+            ));
+        }
+
         let map = match &mut self.shmem_provider {
             None => None,
             Some(provider) => {
@@ -1107,11 +1346,32 @@ where
             }
         };
 
+        #[cfg(target_os = "linux")]
+        let sandbox = match (&self.child_env_inner.sandbox, &self.target_inner.program) {
+            (Some(sandbox), Some(program)) => {
+                let input_path = match &self.target_inner.input_location {
+                    InputLocation::Fifo { fifo } => fifo.path.as_path(),
+                    InputLocation::Socket { .. } => {
+                        return Err(Error::illegal_argument(
+                            "Socket input delivery is not supported in a sandbox",
+                        ));
+                    }
+                    _ => input_file.path.as_path(),
+                };
+                Some(sandbox.instantiate(
+                    program,
+                    Some(input_path),
+                    self.child_env_inner.current_directory.as_deref(),
+                )?)
+            }
+            _ => None,
+        };
+
         let mut forkserver = match &self.target_inner.program {
             Some(t) => Forkserver::new(
                 t.clone(),
                 self.target_inner.arguments.clone(),
-                self.target_inner.envs.clone(),
+                envs,
                 input_file.as_raw_fd(),
                 self.use_stdin(),
                 0,
@@ -1138,6 +1398,9 @@ where
                 }),
                 self.child_env_inner.current_directory.clone(),
                 self.child_env_inner.core,
+                self.child_env_inner.resource_limits.as_ref(),
+                #[cfg(target_os = "linux")]
+                sandbox,
             )?,
             None => {
                 return Err(Error::illegal_argument(
@@ -1209,6 +1472,10 @@ where
             Error::illegal_state(format!("Reading from forkserver failed: {err:?}"))
         })?;
 
+        if status & FS_NEW_OPT_ENV_INPUT == 0 {
+            self.check_env_input_unused()?;
+        }
+
         if status & FS_NEW_OPT_MAPSIZE == FS_NEW_OPT_MAPSIZE {
             let fsrv_map_size = forkserver.read_st().map_err(|err| {
                 Error::illegal_state(format!("Failed to read map size from forkserver: {err:?}"))
@@ -1266,6 +1533,17 @@ where
         Ok(())
     }
 
+    /// Only the forkserver of `libafl_targets` sets the input in the environment of each child,
+    /// which it announces with [`FS_NEW_OPT_ENV_INPUT`].
+    fn check_env_input_unused(&self) -> Result<(), Error> {
+        if matches!(self.target_inner.input_location, InputLocation::Env { .. }) {
+            return Err(Error::illegal_argument(
+                "The forkserver of the target does not set environment variable input, link it against the libafl_targets forkserver",
+            ));
+        }
+        Ok(())
+    }
+
     /// Intialize old forkserver. < v4.20c
     #[expect(clippy::cast_sign_loss)]
     fn initialize_old_forkserver(
@@ -1274,6 +1552,8 @@ where
         map: Option<&SHM>,
         forkserver: &mut Forkserver,
     ) -> Result<(), Error> {
+        self.check_env_input_unused()?;
+
         if status & FS_OPT_ENABLED == FS_OPT_ENABLED && status & FS_OPT_MAPSIZE == FS_OPT_MAPSIZE {
             let fsrv_map_size = fs_opt_get_mapsize(status);
             self.set_map_size(fsrv_map_size)?;
@@ -1440,6 +1720,22 @@ where
         self
     }
 
+    /// The [`SocketDelivery`] to send inputs with, if the input location is a socket.
+    fn socket_delivery(&self) -> Option<SocketDelivery> {
+        match &self.target_inner.input_location {
+            InputLocation::Socket { delivery } => Some(delivery.clone()),
+            _ => None,
+        }
+    }
+
+    /// The [`InputFifo`] to stream inputs into, if the input location is a named pipe.
+    fn input_fifo(&self) -> Option<InputFifo> {
+        match &self.target_inner.input_location {
+            InputLocation::Fifo { fifo } => Some(fifo.clone()),
+            _ => None,
+        }
+    }
+
     /// Determine if the asan observer is present (always false if feature "regex" is disabled)
     #[cfg(feature = "regex")]
     #[must_use]
@@ -1640,4 +1936,177 @@ mod tests {
         };
         assert!(result);
     }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_forkserver_env_input_rejected() {
+        use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+        const MAP_SIZE: usize = 65536;
+        if !Path::new("/bin/bash").exists() {
+            return;
+        }
+        // An AFL++ style forkserver, which does not set the input in the environment
+        let harness = format!(".forkserver_env_harness_{}", std::process::id());
+        fs::write(
+            &harness,
+            "#!/bin/bash\nprintf '\\0\\0\\0\\0' >&199\nexec sleep 10\n",
+        )
+        .unwrap();
+        fs::set_permissions(&harness, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut shmem_provider = UnixShMemProvider::new().unwrap();
+        let mut shmem = shmem_provider.new_shmem(MAP_SIZE).unwrap();
+        // # Safety
+        // There's a slight chance this is racey but very unlikely in the normal use case
+        unsafe {
+            shmem.write_to_env("__AFL_SHM_ID").unwrap();
+        }
+        let shmem_buf: &mut [u8; MAP_SIZE] = shmem.as_slice_mut().try_into().unwrap();
+        let edges_observer = HitcountsMapObserver::new(ConstMapObserver::<_, MAP_SIZE>::new(
+            "shared_mem",
+            shmem_buf,
+        ));
+
+        let executor = ForkserverExecutor::builder()
+            .program(OsString::from(format!("./{harness}")))
+            .env_input("FUZZ_INPUT")
+            .coverage_map_size(MAP_SIZE)
+            .build::<BytesInput, _, NopCorpus<BytesInput>>(tuple_list!(edges_observer));
+        assert!(matches!(executor, Err(Error::IllegalArgument(..))));
+
+        // The libafl_targets forkserver announces it in the handshake
+        fs::write(
+            &harness,
+            "#!/bin/bash\nprintf '\\x01\\x4c\\x46\\x41' >&199\nhead -c 4 <&198 >/dev/null\n\
+            printf '\\x00\\x00\\x01\\x00\\x01\\x4c\\x46\\x41' >&199\nexec sleep 10\n",
+        )
+        .unwrap();
+        let edges_observer = HitcountsMapObserver::new(ConstMapObserver::<_, MAP_SIZE>::new(
+            "shared_mem",
+            shmem.as_slice_mut().try_into().unwrap(),
+        ));
+        let executor = ForkserverExecutor::builder()
+            .program(OsString::from(format!("./{harness}")))
+            .env_input("FUZZ_INPUT")
+            .coverage_map_size(MAP_SIZE)
+            .build::<BytesInput, _, NopCorpus<BytesInput>>(tuple_list!(edges_observer));
+        fs::remove_file(&harness).unwrap();
+        assert!(executor.is_ok());
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore)]
+    fn test_forkserver_sandbox() {
+        use crate::executors::Sandbox;
+
+        const MAP_SIZE: usize = 65536;
+        if !Sandbox::is_supported() {
+            return;
+        }
+
+        let mut shmem_provider = UnixShMemProvider::new().unwrap();
+        let mut shmem = shmem_provider.new_shmem(MAP_SIZE).unwrap();
+        // # Safety
+        // There's a slight chance this is racey but very unlikely in the normal use case
+        unsafe {
+            shmem.write_to_env("__AFL_SHM_ID").unwrap();
+        }
+        let shmem_buf: &mut [u8; MAP_SIZE] = shmem.as_slice_mut().try_into().unwrap();
+        let edges_observer = HitcountsMapObserver::new(ConstMapObserver::<_, MAP_SIZE>::new(
+            "shared_mem",
+            shmem_buf,
+        ));
+
+        // The sandboxed helper processes must not keep the status pipe open, so the failed
+        // handshake gets noticed.
+        let executor = ForkserverExecutor::builder()
+            .program(OsString::from("echo"))
+            .args(vec![OsString::from("@@")])
+            .coverage_map_size(MAP_SIZE)
+            .sandbox(Sandbox::new())
+            .build::<BytesInput, _, NopCorpus<BytesInput>>(tuple_list!(edges_observer));
+        match executor {
+            Err(Error::IllegalState(s, _)) => assert!(s.contains(FAILED_TO_START_FORKSERVER_MSG)),
+            other => panic!("Unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore)]
+    fn test_forkserver_sandbox_run() {
+        use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+        use crate::executors::{ExitKind, Sandbox};
+
+        const MAP_SIZE: usize = 65536;
+        if !Sandbox::is_supported() {
+            return;
+        }
+
+        // A forkserver speaking the old protocol, next to the input file in the current directory
+        if !Path::new("/bin/bash").exists() {
+            return;
+        }
+        let harness = format!(".forkserver_harness_{}", std::process::id());
+        fs::write(
+            &harness,
+            r#"#!/bin/bash
+le32() { printf "$(printf '\\%03o\\%03o\\%03o\\%03o' $(($1 & 255)) $(($1 >> 8 & 255)) $(($1 >> 16 & 255)) $(($1 >> 24 & 255)))"; }
+le32 0 >&199
+while [ "$(dd bs=4 count=1 <&198 2>/dev/null | wc -c)" -eq 4 ]; do
+    sh -c 'read line < "$0"; test "$line" != crash || kill -s SEGV $$' "$1" &
+    pid=$!
+    le32 $pid >&199
+    wait $pid
+    status=$?
+    if [ $status -gt 128 ]; then le32 $((status - 128)) >&199; else le32 $((status << 8)) >&199; fi
+done
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&harness, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut shmem_provider = UnixShMemProvider::new().unwrap();
+        let mut shmem = shmem_provider.new_shmem(MAP_SIZE).unwrap();
+        // # Safety
+        // There's a slight chance this is racey but very unlikely in the normal use case
+        unsafe {
+            shmem.write_to_env("__AFL_SHM_ID").unwrap();
+        }
+        let shmem_buf: &mut [u8; MAP_SIZE] = shmem.as_slice_mut().try_into().unwrap();
+        let edges_observer = HitcountsMapObserver::new(ConstMapObserver::<_, MAP_SIZE>::new(
+            "shared_mem",
+            shmem_buf,
+        ));
+
+        let executor = ForkserverExecutor::builder()
+            .program(OsString::from(format!("./{harness}")))
+            .arg_input_file_std()
+            .coverage_map_size(MAP_SIZE)
+            .sandbox(
+                Sandbox::new()
+                    .bind_read_only("/bin")
+                    .bind_read_only("/usr")
+                    .bind_read_only("/lib")
+                    .bind_read_only("/lib64"),
+            )
+            .build::<BytesInput, _, NopCorpus<BytesInput>>(tuple_list!(edges_observer));
+        let result = executor.map(|mut executor| {
+            (
+                executor.execute_input_uncounted(b"fine\n"),
+                executor.execute_input_uncounted(b"crash\n"),
+            )
+        });
+        fs::remove_file(&harness).unwrap();
+
+        let (fine, crash) = result.unwrap();
+        assert_eq!(fine.unwrap(), ExitKind::Ok);
+        assert_eq!(crash.unwrap(), ExitKind::Crash);
+    }
 }
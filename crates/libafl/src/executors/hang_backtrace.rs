@@ -0,0 +1,378 @@
+//! Captures the stack of a hung child with `ptrace`, right before the executor kills it.
+//!
+//! The stack is unwound with the `.eh_frame` information of the mapped modules, falling back to
+//! frame pointers where there is none. Only supported on `x86_64` and `aarch64`.
+
+use alloc::{string::String, vec::Vec};
+use core::ffi::c_void;
+use std::{collections::HashMap, fs};
+
+use gimli::{
+    BaseAddresses, CfaRule, EhFrame, EhFrameHdr, NativeEndian, Register, RegisterRule,
+    UnwindContext, UnwindSection,
+};
+use nix::{
+    sys::{
+        ptrace,
+        wait::{WaitPidFlag, WaitStatus, waitpid},
+    },
+    unistd::Pid,
+};
+use object::{Object, ObjectSection, ObjectSegment};
+
+use crate::{Error, observers::StackFrame};
+
+/// The maximum number of frames to unwind
+const MAX_FRAMES: usize = 64;
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    use gimli::{Register, X86_64};
+
+    pub const SP: Register = X86_64::RSP;
+    pub const FP: Register = X86_64::RBP;
+    pub const RA: Register = X86_64::RA;
+
+    pub fn registers(pid: nix::unistd::Pid) -> nix::Result<super::Registers> {
+        let regs = nix::sys::ptrace::getregs(pid)?;
+        Ok(super::Registers {
+            pc: regs.rip,
+            sp: regs.rsp,
+            fp: regs.rbp,
+            lr: None,
+        })
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    use gimli::{AArch64, Register};
+
+    pub const SP: Register = AArch64::SP;
+    pub const FP: Register = AArch64::X29;
+    pub const RA: Register = AArch64::X30;
+
+    pub fn registers(pid: nix::unistd::Pid) -> nix::Result<super::Registers> {
+        let regs = nix::sys::ptrace::getregs(pid)?;
+        Ok(super::Registers {
+            pc: regs.pc,
+            sp: regs.sp,
+            fp: regs.regs[29],
+            lr: Some(regs.regs[30]),
+        })
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod arch {
+    use gimli::Register;
+
+    pub const SP: Register = Register(u16::MAX);
+    pub const FP: Register = Register(u16::MAX);
+    pub const RA: Register = Register(u16::MAX);
+
+    pub fn registers(_pid: nix::unistd::Pid) -> nix::Result<super::Registers> {
+        Err(nix::errno::Errno::ENOTSUP)
+    }
+}
+
+/// The registers needed for unwinding
+#[derive(Debug, Clone, Copy)]
+struct Registers {
+    pc: u64,
+    sp: u64,
+    fp: u64,
+    /// The link register, only valid in the innermost frame
+    lr: Option<u64>,
+}
+
+/// An executable mapping of `/proc/<pid>/maps`
+#[derive(Debug)]
+struct Mapping {
+    start: u64,
+    end: u64,
+    offset: u64,
+    path: String,
+}
+
+/// Parses the executable, file-backed mappings of `/proc/<pid>/maps`
+fn parse_maps(maps: &str) -> Vec<Mapping> {
+    maps.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (start, end) = fields.next()?.split_once('-')?;
+            let perms = fields.next()?;
+            let offset = fields.next()?;
+            let path = fields.nth(2)?;
+            if !perms.contains('x') || !path.starts_with('/') {
+                return None;
+            }
+            Some(Mapping {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                offset: u64::from_str_radix(offset, 16).ok()?,
+                path: path.into(),
+            })
+        })
+        .collect()
+}
+
+fn find_mapping(mappings: &[Mapping], address: u64) -> Option<&Mapping> {
+    mappings
+        .iter()
+        .find(|mapping| mapping.start <= address && address < mapping.end)
+}
+
+/// How to recover the caller's registers, owned version of a row of the unwind table
+#[derive(Debug, Clone, Copy)]
+struct FrameRule {
+    cfa_register: Register,
+    cfa_offset: i64,
+    ra: Option<i64>,
+    fp: Option<i64>,
+}
+
+/// Looks up the unwind rule for `address` in the module `data` mapped by `mapping`
+fn frame_rule(data: &[u8], mapping: &Mapping, address: u64) -> Option<FrameRule> {
+    let file = object::File::parse(data).ok()?;
+    // The address the start of the mapping was linked at
+    let mapping_svma = file.segments().find_map(|segment| {
+        let (offset, size) = segment.file_range();
+        (offset <= mapping.offset && mapping.offset < offset + size)
+            .then(|| segment.address() + (mapping.offset - offset))
+    })?;
+    let svma = address - mapping.start + mapping_svma;
+
+    let eh_frame_section = file.section_by_name(".eh_frame")?;
+    let eh_frame = EhFrame::new(eh_frame_section.data().ok()?, NativeEndian);
+    let mut bases = BaseAddresses::default().set_eh_frame(eh_frame_section.address());
+    if let Some(text) = file.section_by_name(".text") {
+        bases = bases.set_text(text.address());
+    }
+    if let Some(got) = file.section_by_name(".got") {
+        bases = bases.set_got(got.address());
+    }
+
+    let mut ctx = UnwindContext::new();
+    let row = if let Some(hdr_section) = file.section_by_name(".eh_frame_hdr") {
+        bases = bases.set_eh_frame_hdr(hdr_section.address());
+        let hdr = EhFrameHdr::new(hdr_section.data().ok()?, NativeEndian)
+            .parse(&bases, size_of::<usize>() as u8)
+            .ok()?;
+        hdr.table()?
+            .unwind_info_for_address(&eh_frame, &bases, &mut ctx, svma, EhFrame::cie_from_offset)
+            .ok()?
+    } else {
+        eh_frame
+            .unwind_info_for_address(&bases, &mut ctx, svma, EhFrame::cie_from_offset)
+            .ok()?
+    };
+
+    let CfaRule::RegisterAndOffset { register, offset } = row.cfa() else {
+        return None;
+    };
+    // Registers without a rule have the default one, i.e. they are not saved.
+    let saved_at = |register| match row.register(register) {
+        Some(RegisterRule::Offset(offset)) => Some(Some(offset)),
+        None | Some(RegisterRule::Undefined | RegisterRule::SameValue) => Some(None),
+        _ => None,
+    };
+    Some(FrameRule {
+        cfa_register: *register,
+        cfa_offset: *offset,
+        ra: saved_at(arch::RA)?,
+        fp: saved_at(arch::FP)?,
+    })
+}
+
+/// Unwinds the stack of a stopped tracee
+struct Unwinder {
+    pid: Pid,
+    mappings: Vec<Mapping>,
+    /// The contents of the mapped files, `None` if they could not be read
+    modules: HashMap<String, Option<Vec<u8>>>,
+}
+
+impl Unwinder {
+    fn read(&self, address: u64) -> Option<u64> {
+        ptrace::read(self.pid, address as *mut c_void)
+            .ok()
+            .map(i64::cast_unsigned)
+    }
+
+    fn mapping(&self, address: u64) -> Option<&Mapping> {
+        find_mapping(&self.mappings, address)
+    }
+
+    fn frame(&self, address: u64) -> StackFrame {
+        match self.mapping(address) {
+            Some(mapping) => StackFrame {
+                address,
+                module: Some(mapping.path.clone()),
+                offset: address - mapping.start + mapping.offset,
+            },
+            None => StackFrame {
+                address,
+                module: None,
+                offset: address,
+            },
+        }
+    }
+
+    fn dwarf_rule(&mut self, address: u64) -> Option<FrameRule> {
+        let Self {
+            pid,
+            mappings,
+            modules,
+        } = self;
+        let mapping = find_mapping(mappings, address)?;
+        // The target may see a different file system, e.g. in a sandbox
+        let data = modules
+            .entry(mapping.path.clone())
+            .or_insert_with(|| fs::read(format!("/proc/{pid}/root{}", mapping.path)).ok())
+            .as_deref()?;
+        frame_rule(data, mapping, address)
+    }
+
+    /// Computes the registers of the caller, `None` at the end of the stack
+    fn step(&mut self, regs: Registers, innermost: bool) -> Option<Registers> {
+        // Return addresses point after the call, which may be the start of the next function.
+        let lookup = if innermost { regs.pc } else { regs.pc - 1 };
+        let caller = if let Some(rule) = self.dwarf_rule(lookup) {
+            let base = if rule.cfa_register == arch::SP {
+                regs.sp
+            } else if rule.cfa_register == arch::FP {
+                regs.fp
+            } else {
+                return None;
+            };
+            let cfa = base.checked_add_signed(rule.cfa_offset)?;
+            let pc = match rule.ra {
+                Some(offset) => self.read(cfa.checked_add_signed(offset)?)?,
+                None => regs.lr?,
+            };
+            let fp = match rule.fp {
+                Some(offset) => self.read(cfa.checked_add_signed(offset)?)?,
+                None => regs.fp,
+            };
+            Registers {
+                pc,
+                sp: cfa,
+                fp,
+                lr: None,
+            }
+        } else {
+            // Both architectures store the caller's frame pointer and return address on top of
+            // the frame.
+            if regs.fp < regs.sp || !regs.fp.is_multiple_of(8) {
+                return None;
+            }
+            Registers {
+                pc: self.read(regs.fp + 8)?,
+                sp: regs.fp + 16,
+                fp: self.read(regs.fp)?,
+                lr: None,
+            }
+        };
+        // The stack grows down, callers have to live above.
+        (caller.pc != 0 && caller.sp > regs.sp).then_some(caller)
+    }
+
+    fn unwind(&mut self, mut regs: Registers) -> Vec<StackFrame> {
+        let mut frames = vec![self.frame(regs.pc)];
+        while frames.len() < MAX_FRAMES {
+            let Some(caller) = self.step(regs, frames.len() == 1) else {
+                break;
+            };
+            frames.push(self.frame(caller.pc));
+            regs = caller;
+        }
+        frames
+    }
+}
+
+/// Stops the running process `pid` with `ptrace` and unwinds the stack of its main thread.
+///
+/// The process is detached again afterwards. Fails if we may not trace it, e.g. due to the
+/// `ptrace_scope` of Yama, or if it exited in the meantime.
+pub fn capture_stack(pid: Pid) -> Result<Vec<StackFrame>, Error> {
+    let err = |what: &str, err: nix::Error| {
+        Error::unknown(format!("Could not {what} hung child {pid}: {err}"))
+    };
+    ptrace::seize(pid, ptrace::Options::empty()).map_err(|e| err("attach to", e))?;
+    let frames = (|| {
+        ptrace::interrupt(pid).map_err(|e| err("interrupt", e))?;
+        match waitpid(pid, Some(WaitPidFlag::__WALL)).map_err(|e| err("wait for", e))? {
+            WaitStatus::PtraceEvent(..) | WaitStatus::Stopped(..) => {}
+            status => {
+                return Err(Error::illegal_state(format!(
+                    "Hung child {pid} did not stop: {status:?}"
+                )));
+            }
+        }
+        let regs = arch::registers(pid).map_err(|e| err("get the registers of", e))?;
+        let maps = fs::read_to_string(format!("/proc/{pid}/maps"))?;
+        let mut unwinder = Unwinder {
+            pid,
+            mappings: parse_maps(&maps),
+            modules: HashMap::new(),
+        };
+        Ok(unwinder.unwind(regs))
+    })();
+    // Fails if it exited in the meantime, nothing we have to clean up then.
+    let _ = ptrace::detach(pid, None);
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::{
+        process::{Command, Stdio},
+        thread::sleep,
+    };
+
+    use nix::unistd::Pid;
+
+    use super::{capture_stack, parse_maps};
+
+    #[test]
+    fn test_parse_maps() {
+        let maps = "\
+55d0c6a00000-55d0c6a02000 r--p 00000000 fd:01 1234 /usr/bin/sleep
+55d0c6a02000-55d0c6a06000 r-xp 00002000 fd:01 1234 /usr/bin/sleep
+7ffd1c5e1000-7ffd1c5e3000 r-xp 00000000 00:00 0 [vdso]
+7f12a0000000-7f12a0001000 rw-p 00000000 00:00 0
+";
+        let mappings = parse_maps(maps);
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].start, 0x55d0_c6a0_2000);
+        assert_eq!(mappings[0].offset, 0x2000);
+        assert_eq!(mappings[0].path, "/usr/bin/sleep");
+    }
+
+    #[test]
+    #[ignore = "needs ptrace, which is often forbidden in containers and CI"]
+    fn test_capture_stack() {
+        let mut child = Command::new("sleep")
+            .arg("10")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        sleep(Duration::from_millis(100));
+
+        let frames = capture_stack(Pid::from_raw(child.id().cast_signed()));
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let frames = frames.unwrap();
+        // Sleeping in the `nanosleep` syscall, called from `main`
+        assert!(frames.len() > 1, "{frames:?}");
+        assert!(frames.iter().all(|frame| frame.address != 0));
+        assert!(
+            frames
+                .iter()
+                .any(|frame| frame.module.as_deref().is_some_and(|m| m.contains("sleep"))),
+            "{frames:?}"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! The [`LeakCheckExecutor`] re-runs inputs that may have leaked memory, to confirm the leak with a
+//! [`LeakChecker`], like libFuzzer's `-detect_leaks`.
+
+use alloc::string::String;
+use core::{fmt::Debug, time::Duration};
+
+use libafl_bolts::tuples::{Handle, MatchNameRef, RefIndexable};
+
+use crate::{
+    Error,
+    executors::{Executor, ExitKind, HasObservers, HasTimeout, SetTimeout},
+    observers::{LeakObserver, ObserversTuple, leak},
+};
+
+/// Confirms the leaks of a run, e.g. with `LeakSanitizer`, see `libafl_targets::LsanLeakChecker`.
+pub trait LeakChecker {
+    /// Called before the input that may have leaked is run again, e.g. to enable leak detection
+    fn pre_rerun(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Checks for leaks after the input was run again, returning the report of the leak, if any
+    fn check(&mut self) -> Result<Option<String>, Error>;
+}
+
+/// Wraps an in-process executor, re-running each input that allocated more than it freed, as seen
+/// by the [`LeakObserver`], to confirm the leak with the [`LeakChecker`].
+///
+/// The observers are run around the re-run as well, so they see the re-run, not the first run.
+///
+/// The report of a confirmed leak is recorded in the [`LeakObserver`], use a
+/// [`crate::feedbacks::LeakFeedback`] as objective to report them.
+#[derive(Debug)]
+pub struct LeakCheckExecutor<E, LC> {
+    executor: E,
+    leak_checker: LC,
+    observer: Handle<LeakObserver>,
+}
+
+impl<E, LC> LeakCheckExecutor<E, LC> {
+    /// Wraps the given executor, with the [`LeakObserver`] among its observers
+    pub fn new(executor: E, leak_checker: LC, observer: Handle<LeakObserver>) -> Self {
+        Self {
+            executor,
+            leak_checker,
+            observer,
+        }
+    }
+
+    /// The wrapped executor
+    pub fn inner(&mut self) -> &mut E {
+        &mut self.executor
+    }
+}
+
+impl<E, EM, I, LC, S, Z> Executor<EM, I, S, Z> for LeakCheckExecutor<E, LC>
+where
+    E: Executor<EM, I, S, Z> + HasObservers,
+    E::Observers: ObserversTuple<I, S>,
+    LC: LeakChecker,
+{
+    fn run_target(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        mgr: &mut EM,
+        input: &I,
+    ) -> Result<ExitKind, Error> {
+        let exit_kind = self.executor.run_target(fuzzer, state, mgr, input)?;
+        // the observers only collect the balance in their post_exec, after this run
+        let (mallocs, frees) = leak::current_balance();
+        if exit_kind != ExitKind::Ok || mallocs <= frees {
+            return Ok(exit_kind);
+        }
+
+        // finish the first run, the post_exec of the re-run is left to the caller
+        let mut observers = self.executor.observers_mut();
+        observers.post_exec_all(state, input, &exit_kind)?;
+        observers.pre_exec_all(state, input)?;
+        self.leak_checker.pre_rerun()?;
+        let exit_kind = self.executor.run_target(fuzzer, state, mgr, input)?;
+        if let Some(report) = self.leak_checker.check()? {
+            let mut observers = self.executor.observers_mut();
+            observers
+                .get_mut(&self.observer)
+                .ok_or_else(|| Error::illegal_state("The leak observer is missing"))?
+                .observe(report);
+        }
+        Ok(exit_kind)
+    }
+}
+
+impl<E, LC> HasObservers for LeakCheckExecutor<E, LC>
+where
+    E: HasObservers,
+{
+    type Observers = E::Observers;
+
+    fn observers(&self) -> RefIndexable<&Self::Observers, Self::Observers> {
+        self.executor.observers()
+    }
+
+    fn observers_mut(&mut self) -> RefIndexable<&mut Self::Observers, Self::Observers> {
+        self.executor.observers_mut()
+    }
+}
+
+impl<E, LC> HasTimeout for LeakCheckExecutor<E, LC>
+where
+    E: HasTimeout,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.executor.timeout()
+    }
+}
+
+impl<E, LC> SetTimeout for LeakCheckExecutor<E, LC>
+where
+    E: SetTimeout,
+{
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.executor.set_timeout(timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use libafl_bolts::{
+        HasLen,
+        rands::StdRand,
+        tuples::{Handled, tuple_list},
+    };
+    #[cfg(feature = "serial_test")]
+    use serial_test::serial;
+
+    use super::{LeakCheckExecutor, LeakChecker};
+    use crate::{
+        Error, ExecutesInput, StdFuzzer,
+        corpus::InMemoryCorpus,
+        events::NopEventManager,
+        executors::{ExitKind, HasObservers, InProcessExecutor},
+        feedbacks::{ConstFeedback, Feedback, LeakFeedback},
+        inputs::BytesInput,
+        observers::{LeakObserver, leak},
+        schedulers::QueueScheduler,
+        state::StdState,
+    };
+
+    /// Counts the re-runs, confirming each
+    struct CountingChecker {
+        reruns: usize,
+    }
+
+    impl LeakChecker for CountingChecker {
+        fn pre_rerun(&mut self) -> Result<(), Error> {
+            self.reruns += 1;
+            Ok(())
+        }
+
+        fn check(&mut self) -> Result<Option<String>, Error> {
+            Ok(Some("Direct leak of 8 byte(s) in 1 object(s)".into()))
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "serial_test", serial)]
+    fn test_leak_check_executor() {
+        // leaks one allocation per byte of the input
+        let mut harness = |input: &BytesInput| {
+            for _ in 0..input.len() {
+                leak::record_malloc();
+            }
+            leak::record_malloc();
+            leak::record_free();
+            ExitKind::Ok
+        };
+        let observer = LeakObserver::new("leak");
+        let mut feedback = LeakFeedback::new(&observer);
+        let handle = observer.handle();
+        let mut objective = ConstFeedback::new(false);
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<BytesInput>::new(),
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        let mut mgr = NopEventManager::new();
+        let mut fuzzer =
+            StdFuzzer::new(QueueScheduler::new(), ConstFeedback::new(false), objective);
+        let executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(observer),
+            &mut fuzzer,
+            &mut state,
+            &mut mgr,
+        )
+        .unwrap();
+        let mut executor = LeakCheckExecutor::new(executor, CountingChecker { reruns: 0 }, handle);
+
+        for (leaks, reruns) in [(0, 0), (2, 1), (0, 1)] {
+            let input = BytesInput::new(vec![0; leaks]);
+            fuzzer
+                .execute_input(&mut state, &mut executor, &mut mgr, &input)
+                .unwrap();
+            assert_eq!(executor.leak_checker.reruns, reruns);
+            let observers = executor.observers();
+            assert_eq!(observers.0.balance(), (leaks + 1, 1));
+            assert_eq!(
+                feedback
+                    .is_interesting(&mut state, &mut mgr, &input, &*observers, &ExitKind::Ok)
+                    .unwrap(),
+                leaks > 0
+            );
+        }
+    }
+}
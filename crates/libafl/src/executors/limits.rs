@@ -0,0 +1,567 @@
+//! Resource limits for targets running in child processes.
+//!
+//! The [`CommandExecutor`](crate::executors::CommandExecutor) and the
+//! [`ForkserverExecutor`](crate::executors::ForkserverExecutor) can confine their children using
+//! [`ResourceLimits`]. On Linux, the children are placed into a dedicated cgroup v2, limiting
+//! `memory.max`, `pids.max` and `cpu.max`. Kills by the cgroup's OOM killer are detected from
+//! `memory.events` and reported as [`ExitKind::Oom`](crate::executors::ExitKind::Oom).
+//!
+//! The cgroups are created below the parent configured with [`ResourceLimits::cgroup_parent`].
+//! Alternatively, [`ResourceLimits::delegate_current_cgroup`] lets the fuzzer move itself into a
+//! `fuzzer` leaf below its current cgroup, which has to be delegated to it, and create the cgroups
+//! next to it. Without either, creating the [`Confinement`] fails.
+//!
+//! The forkserver itself stays with the fuzzer, only the children it forks are placed into the
+//! limited cgroup. The forkserver of `libafl_targets` moves each child right after the fork, before
+//! it runs any target code, see [`CGROUP_PROCS_ENV_VAR`]. The children of other forkservers are
+//! moved by the fuzzer once their pid is reported, so memory they allocate before is not charged.
+//!
+//! If no cgroup can be created, e.g. because the parent cgroup is not delegated to the fuzzer, the
+//! memory and process limits fall back to `setrlimit`. In this mode, running out of memory makes
+//! allocations fail, which the target usually reports as a crash.
+
+use alloc::ffi::CString;
+#[cfg(target_os = "linux")]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(target_os = "linux")]
+use std::{
+    ffi::OsStr,
+    fs,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+use std::{io, os::unix::process::CommandExt, process::Command};
+
+use libafl_bolts::Error;
+
+/// Environment variable key for the `cgroup.procs` file a forked child writes itself to, before
+/// it runs any target code.
+pub const CGROUP_PROCS_ENV_VAR: &str = "__LIBAFL_CGROUP_PROCS";
+
+/// The `cpu.max` period used to express CPU quotas, in microseconds
+#[cfg(target_os = "linux")]
+const CPU_PERIOD_US: u32 = 100_000;
+
+/// The limits to apply to a target running in a child process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceLimits {
+    memory_max: Option<u64>,
+    pids_max: Option<u64>,
+    cpu_quota: Option<f64>,
+    use_cgroup: bool,
+    #[cfg(target_os = "linux")]
+    cgroup_parent: Option<PathBuf>,
+    #[cfg(target_os = "linux")]
+    delegate_current_cgroup: bool,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            memory_max: None,
+            pids_max: None,
+            cpu_quota: None,
+            use_cgroup: true,
+            #[cfg(target_os = "linux")]
+            cgroup_parent: None,
+            #[cfg(target_os = "linux")]
+            delegate_current_cgroup: false,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Creates new [`ResourceLimits`], not limiting anything yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the memory of the target to `bytes`.
+    #[must_use]
+    pub fn memory_max(mut self, bytes: u64) -> Self {
+        self.memory_max = Some(bytes);
+        self
+    }
+
+    /// Limits the number of processes and threads of the target.
+    ///
+    /// Without cgroups, this falls back to `RLIMIT_NPROC`, which counts all processes of the user.
+    #[must_use]
+    pub fn pids_max(mut self, pids: u64) -> Self {
+        self.pids_max = Some(pids);
+        self
+    }
+
+    /// Limits the target to `cpus` CPUs worth of CPU time, e.g. `0.5` for half a core.
+    ///
+    /// Only enforced with cgroups.
+    #[must_use]
+    pub fn cpu_quota(mut self, cpus: f64) -> Self {
+        self.cpu_quota = Some(cpus);
+        self
+    }
+
+    /// Never use cgroups, only `setrlimit`.
+    #[must_use]
+    pub fn no_cgroup(mut self) -> Self {
+        self.use_cgroup = false;
+        self
+    }
+
+    /// Creates the cgroups for the targets below `parent`.
+    ///
+    /// The parent has to be writable by the fuzzer and must not contain processes itself, as
+    /// cgroup v2 only allows enabling controllers for cgroups without processes.
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn cgroup_parent<P: Into<PathBuf>>(mut self, parent: P) -> Self {
+        self.cgroup_parent = Some(parent.into());
+        self
+    }
+
+    /// Creates the cgroups for the targets next to the fuzzer, if no parent is configured.
+    ///
+    /// This moves the whole fuzzer process into a [`Cgroup::FUZZER_LEAF`] below its current
+    /// cgroup, see [`Cgroup::delegated_parent`]. The current cgroup has to be delegated to the
+    /// fuzzer, e.g. by running it with `systemd-run --user --scope -p Delegate=yes`.
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn delegate_current_cgroup(mut self) -> Self {
+        self.delegate_current_cgroup = true;
+        self
+    }
+
+    /// If no limit is set at all
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.memory_max.is_none() && self.pids_max.is_none() && self.cpu_quota.is_none()
+    }
+}
+
+/// A cgroup v2 the target processes are placed in.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct Cgroup {
+    path: PathBuf,
+}
+
+#[cfg(target_os = "linux")]
+impl Cgroup {
+    /// The mount point of the unified cgroup v2 hierarchy
+    pub const ROOT: &'static str = "/sys/fs/cgroup";
+    /// The mount point of the cgroup v2 hierarchy on hybrid systems, which still mount cgroup v1
+    /// at [`Self::ROOT`]
+    pub const HYBRID_ROOT: &'static str = "/sys/fs/cgroup/unified";
+    /// The name of the leaf cgroup the fuzzer moves itself into, see [`Self::delegated_parent`]
+    pub const FUZZER_LEAF: &'static str = "fuzzer";
+
+    /// Creates a new cgroup enforcing `limits`, below the configured parent or the
+    /// [`Self::delegated_parent`], if [`ResourceLimits::delegate_current_cgroup`] is set.
+    pub fn create(limits: &ResourceLimits) -> Result<Self, Error> {
+        static CGROUP_COUNT: AtomicU64 = AtomicU64::new(0);
+
+        let parent = match &limits.cgroup_parent {
+            Some(parent) => parent.clone(),
+            None if limits.delegate_current_cgroup => Self::delegated_parent()?,
+            None => return Err(Self::no_parent_error()),
+        };
+        if !parent.join("cgroup.controllers").exists() {
+            return Err(Error::unsupported(format!(
+                "{} is not a cgroup v2",
+                parent.display()
+            )));
+        }
+
+        let mut controllers = vec![];
+        if limits.memory_max.is_some() {
+            controllers.push("+memory");
+        }
+        if limits.pids_max.is_some() {
+            controllers.push("+pids");
+        }
+        if limits.cpu_quota.is_some() {
+            controllers.push("+cpu");
+        }
+        fs::write(parent.join("cgroup.subtree_control"), controllers.join(" ")).map_err(|err| {
+            Error::illegal_state(format!(
+                "Could not enable cgroup controllers in {}: {err}",
+                parent.display()
+            ))
+        })?;
+
+        let path = parent.join(format!(
+            "libafl-{}-{}",
+            std::process::id(),
+            CGROUP_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir(&path)?;
+        let cgroup = Self { path };
+
+        if let Some(memory_max) = limits.memory_max {
+            cgroup.write("memory.max", &format!("{memory_max}"))?;
+            // Swapping would only make the target slow instead of killing it, ignore if unsupported
+            drop(cgroup.write("memory.swap.max", "0"));
+        }
+        if let Some(pids_max) = limits.pids_max {
+            cgroup.write("pids.max", &format!("{pids_max}"))?;
+        }
+        if let Some(cpus) = limits.cpu_quota {
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let quota = ((cpus * f64::from(CPU_PERIOD_US)) as u64).max(1000);
+            cgroup.write("cpu.max", &format!("{quota} {CPU_PERIOD_US}"))?;
+        }
+        Ok(cgroup)
+    }
+
+    fn no_parent_error() -> Error {
+        Error::illegal_argument(
+            "No cgroup parent for the targets: configure a delegated cgroup without processes with \
+            `ResourceLimits::cgroup_parent`, let the fuzzer move itself into a leaf of its current \
+            cgroup with `ResourceLimits::delegate_current_cgroup`, or only use setrlimit with \
+            `ResourceLimits::no_cgroup`",
+        )
+    }
+
+    /// The cgroup of the current process
+    pub fn current() -> Result<PathBuf, Error> {
+        let cgroups = fs::read_to_string("/proc/self/cgroup")?;
+        let relative = cgroups
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or_else(|| Error::unsupported("Not running in a cgroup v2 hierarchy"))?;
+        let root = if Path::new(Self::ROOT).join("cgroup.controllers").exists() {
+            Self::ROOT
+        } else {
+            Self::HYBRID_ROOT
+        };
+        Ok(Path::new(root).join(relative.trim_start_matches('/')))
+    }
+
+    /// The cgroup to create the cgroups of the targets in, if no parent is configured and
+    /// [`ResourceLimits::delegate_current_cgroup`] is set.
+    ///
+    /// cgroup v2 only allows enabling controllers for cgroups without processes, so the current
+    /// process moves itself into a [`Self::FUZZER_LEAF`] below its current cgroup, which is then
+    /// returned. If it was moved already, by itself or the process it was forked from, the parent
+    /// of the leaf is returned. Fails if other processes remain in the current cgroup.
+    pub fn delegated_parent() -> Result<PathBuf, Error> {
+        let current = Self::current()?;
+        if current.file_name() == Some(OsStr::new(Self::FUZZER_LEAF))
+            && let Some(parent) = current.parent()
+        {
+            return Ok(parent.to_path_buf());
+        }
+
+        let leaf = current.join(Self::FUZZER_LEAF);
+        if let Err(err) = fs::create_dir(&leaf)
+            && err.kind() != io::ErrorKind::AlreadyExists
+        {
+            return Err(Error::illegal_state(format!(
+                "Could not create {}: {err}",
+                leaf.display()
+            )));
+        }
+        // Writing 0 moves the writing process, with all its threads.
+        fs::write(leaf.join("cgroup.procs"), "0").map_err(|err| {
+            Error::illegal_state(format!(
+                "Could not move the fuzzer into {}: {err}",
+                leaf.display()
+            ))
+        })?;
+        if !fs::read_to_string(current.join("cgroup.procs"))?
+            .trim()
+            .is_empty()
+        {
+            return Err(Error::illegal_state(format!(
+                "{} still contains other processes, configure a cgroup parent without processes",
+                current.display()
+            )));
+        }
+        Ok(current)
+    }
+
+    /// Moves the process with `pid` into this cgroup. Its children forked afterwards inherit it.
+    pub fn add_process(&self, pid: i32) -> Result<(), Error> {
+        self.write("cgroup.procs", &format!("{pid}"))
+    }
+
+    /// The path of this cgroup
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of processes killed by the OOM killer of this cgroup so far, from `memory.events`
+    pub fn oom_kills(&self) -> Result<u64, Error> {
+        parse_oom_kills(&fs::read_to_string(self.path.join("memory.events"))?)
+    }
+
+    fn write(&self, file: &str, value: &str) -> Result<(), Error> {
+        fs::write(self.path.join(file), value).map_err(|err| {
+            Error::illegal_state(format!(
+                "Could not write {value} to {}: {err}",
+                self.path.join(file).display()
+            ))
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Cgroup {
+    fn drop(&mut self) {
+        // Kill leftover processes, then remove the cgroup. Ignore errors, there is nothing to do.
+        drop(fs::write(self.path.join("cgroup.kill"), "1"));
+        for _ in 0..100 {
+            if fs::remove_dir(&self.path).is_ok() {
+                break;
+            }
+            std::thread::sleep(core::time::Duration::from_millis(1));
+        }
+    }
+}
+
+/// Parses the `oom_kill` counter from the contents of a `memory.events` file
+#[cfg(target_os = "linux")]
+fn parse_oom_kills(events: &str) -> Result<u64, Error> {
+    events
+        .lines()
+        .find_map(|line| line.strip_prefix("oom_kill "))
+        .ok_or_else(|| Error::illegal_state("No oom_kill counter in memory.events"))?
+        .trim()
+        .parse()
+        .map_err(Into::into)
+}
+
+/// The parts of a [`Confinement`] applied in the child process, between `fork` and `exec`.
+#[derive(Debug, Clone, Default)]
+pub struct ChildLimits {
+    /// The `cgroup.procs` file the child writes its pid to
+    cgroup_procs: Option<CString>,
+    /// The `RLIMIT_AS` to set
+    rlimit_memory: Option<u64>,
+    /// The `RLIMIT_NPROC` to set
+    rlimit_nproc: Option<u64>,
+}
+
+impl ChildLimits {
+    /// These limits without placing the process into the cgroup, for processes that only spawn
+    /// the actual targets, like a forkserver. See [`Confinement::confine_child`].
+    #[must_use]
+    pub fn without_cgroup(&self) -> Self {
+        Self {
+            cgroup_procs: None,
+            ..self.clone()
+        }
+    }
+
+    /// Applies the limits to all processes spawned by `command`.
+    pub fn apply(&self, command: &mut Command) {
+        let limits = self.clone();
+        // # Safety
+        // The closure only calls async-signal-safe libc functions and does not allocate.
+        unsafe {
+            command.pre_exec(move || limits.apply_in_child());
+        }
+    }
+
+    fn apply_in_child(&self) -> io::Result<()> {
+        if let Some(procs) = &self.cgroup_procs {
+            // Writing 0 moves the writing process itself.
+            // # Safety
+            // `procs` is a valid, nul-terminated path.
+            unsafe {
+                let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let ret = libc::write(fd, b"0".as_ptr().cast(), 1);
+                libc::close(fd);
+                if ret < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+        }
+        if let Some(memory) = self.rlimit_memory {
+            #[cfg(target_os = "openbsd")]
+            set_rlimit(libc::RLIMIT_RSS, memory)?;
+            #[cfg(not(target_os = "openbsd"))]
+            set_rlimit(libc::RLIMIT_AS, memory)?;
+        }
+        if let Some(nproc) = self.rlimit_nproc {
+            set_rlimit(libc::RLIMIT_NPROC, nproc)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+type RlimitResource = libc::c_int;
+
+// libc::rlim_t is i64 in freebsd and trivial_numeric_casts check will failed
+#[allow(trivial_numeric_casts)]
+fn set_rlimit(resource: RlimitResource, value: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    // # Safety
+    // `limit` is a valid rlimit struct.
+    if unsafe { libc::setrlimit(resource, &raw const limit) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// The resource limits in effect for the children of an executor, created from [`ResourceLimits`].
+#[derive(Debug)]
+pub struct Confinement {
+    #[cfg(target_os = "linux")]
+    cgroup: Option<Cgroup>,
+    child_limits: ChildLimits,
+    oom_kills: u64,
+    last_confined_pid: i32,
+}
+
+impl Confinement {
+    /// Creates the cgroup for `limits`, or prepares the `setrlimit` fallback if that fails.
+    ///
+    /// Fails if cgroups are used, but neither [`ResourceLimits::cgroup_parent`] nor
+    /// [`ResourceLimits::delegate_current_cgroup`] is set.
+    pub fn new(limits: &ResourceLimits) -> Result<Self, Error> {
+        #[cfg(target_os = "linux")]
+        if limits.use_cgroup && !limits.is_empty() {
+            if limits.cgroup_parent.is_none() && !limits.delegate_current_cgroup {
+                return Err(Cgroup::no_parent_error());
+            }
+            match Cgroup::create(limits) {
+                Ok(cgroup) => {
+                    let procs = cgroup.path.join("cgroup.procs");
+                    let cgroup_procs = CString::new(procs.as_os_str().as_bytes())
+                        .map_err(|_| Error::illegal_argument("Invalid cgroup path"))?;
+                    return Ok(Self {
+                        oom_kills: cgroup.oom_kills().unwrap_or(0),
+                        last_confined_pid: 0,
+                        cgroup: Some(cgroup),
+                        child_limits: ChildLimits {
+                            cgroup_procs: Some(cgroup_procs),
+                            ..ChildLimits::default()
+                        },
+                    });
+                }
+                Err(err) => {
+                    log::warn!("Could not create a cgroup, falling back to setrlimit: {err}");
+                }
+            }
+        }
+
+        if limits.cpu_quota.is_some() {
+            log::warn!("CPU quotas are only supported with cgroups, not limiting CPU usage");
+        }
+        Ok(Self {
+            #[cfg(target_os = "linux")]
+            cgroup: None,
+            child_limits: ChildLimits {
+                cgroup_procs: None,
+                rlimit_memory: limits.memory_max,
+                rlimit_nproc: limits.pids_max,
+            },
+            oom_kills: 0,
+            last_confined_pid: 0,
+        })
+    }
+
+    /// The cgroup the children are placed in, if any
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn cgroup(&self) -> Option<&Cgroup> {
+        self.cgroup.as_ref()
+    }
+
+    /// The `cgroup.procs` file of the cgroup, for the children to move themselves into it, see
+    /// [`CGROUP_PROCS_ENV_VAR`]
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn cgroup_procs(&self) -> Option<PathBuf> {
+        self.cgroup
+            .as_ref()
+            .map(|cgroup| cgroup.path.join("cgroup.procs"))
+    }
+
+    /// The limits to apply to the children, see [`ChildLimits::apply`].
+    #[must_use]
+    pub fn child_limits(&self) -> &ChildLimits {
+        &self.child_limits
+    }
+
+    /// Moves the child with `pid` into the cgroup, if any, for children forked by a process
+    /// confined with [`ChildLimits::without_cgroup`].
+    ///
+    /// Memory the child allocated before is not charged to the cgroup, unless it moved itself
+    /// before, see [`CGROUP_PROCS_ENV_VAR`]. Moving the same child again, e.g. in persistent mode,
+    /// is skipped.
+    pub fn confine_child(&mut self, pid: i32) -> Result<(), Error> {
+        #[cfg(target_os = "linux")]
+        if let Some(cgroup) = &self.cgroup
+            && pid != self.last_confined_pid
+        {
+            cgroup.add_process(pid)?;
+        }
+        self.last_confined_pid = pid;
+        Ok(())
+    }
+
+    /// Checks if the OOM killer struck since the last call, i.e., the last execution ran out of
+    /// memory. Always `false` without a cgroup limiting the memory.
+    ///
+    /// The OOM killer kills with `SIGKILL`, so only call this if the child died from it.
+    pub fn check_oom(&mut self) -> Result<bool, Error> {
+        #[cfg(target_os = "linux")]
+        if let Some(cgroup) = &self.cgroup
+            && let Ok(oom_kills) = cgroup.oom_kills()
+        {
+            let killed = oom_kills > self.oom_kills;
+            self.oom_kills = oom_kills;
+            return Ok(killed);
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use std::process::Command;
+
+    use super::{Confinement, ResourceLimits};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_rlimit_fallback() {
+        let limits = ResourceLimits::new().memory_max(512 << 20).no_cgroup();
+        let mut confinement = Confinement::new(&limits).unwrap();
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "ulimit -v"]);
+        confinement.child_limits().apply(&mut command);
+        let output = command.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "524288");
+        assert!(!confinement.check_oom().unwrap());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_cgroup_needs_parent() {
+        let limits = ResourceLimits::new().memory_max(512 << 20);
+        assert!(Confinement::new(&limits).is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_oom_kills() {
+        let events = "low 0\nhigh 0\nmax 3\noom 2\noom_kill 1\noom_group_kill 0\n";
+        assert_eq!(super::parse_oom_kills(events).unwrap(), 1);
+    }
+}
@@ -14,18 +14,28 @@ pub use forkserver::{Forkserver, ForkserverExecutor};
 pub use inprocess::InProcessExecutor;
 #[cfg(all(feature = "std", unix))]
 pub use inprocess_fork::InProcessForkExecutor;
+#[cfg(all(feature = "std", unix))]
+pub use leak_check::{LeakCheckExecutor, LeakChecker};
 #[cfg(unix)]
 use libafl_bolts::os::unix_signals::Signal;
 use libafl_bolts::tuples::RefIndexable;
 #[cfg(feature = "std")]
 use libafl_bolts::{core_affinity::CoreId, tuples::Handle};
+#[cfg(all(feature = "std", unix))]
+pub use limits::ResourceLimits;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use sandbox::{Sandbox, SeccompAction, SeccompFilter};
 use serde::{Deserialize, Serialize};
 pub use shadow::ShadowExecutor;
 pub use with_observers::WithObservers;
 
 use crate::Error;
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+use crate::observers::HangBacktraceObserver;
+#[cfg(all(feature = "std", target_os = "linux"))]
+use crate::observers::{MemoryObserver, SandboxObserver};
 #[cfg(feature = "std")]
-use crate::observers::{StdErrObserver, StdOutObserver};
+use crate::observers::{ResponseObserver, StdErrObserver, StdOutObserver};
 
 pub mod combined;
 #[cfg(feature = "std")]
@@ -33,11 +43,19 @@ pub mod command;
 pub mod differential;
 #[cfg(all(feature = "std", unix))]
 pub mod forkserver;
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+pub mod hang_backtrace;
 pub mod inprocess;
+#[cfg(all(feature = "std", unix))]
+pub mod leak_check;
+#[cfg(all(feature = "std", unix))]
+pub mod limits;
 pub mod nop;
 /// SAND(<https://github.com/wtdcode/sand-aflpp>) implementation
 #[cfg(feature = "simd")]
 pub mod sand;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod sandbox;
 
 /// The module for inproc fork executor
 #[cfg(all(feature = "std", unix))]
@@ -247,12 +265,29 @@ pub struct StdChildArgsInner {
     pub stderr_observer: Option<Handle<StdErrObserver>>,
     /// The stdout handle of the children
     pub stdout_observer: Option<Handle<StdOutObserver>>,
+    /// The handle for responses the children sent over their input socket
+    pub response_observer: Option<Handle<ResponseObserver>>,
     /// The current directory of the spawned children
     pub current_directory: Option<PathBuf>,
     /// Whether debug child by inheriting stdout/stderr
     pub debug_child: bool,
     /// Core to bind for the children
     pub core: Option<CoreId>,
+    /// Memory, process and CPU limits for the children
+    #[cfg(unix)]
+    pub resource_limits: Option<ResourceLimits>,
+    /// The namespace and seccomp sandbox of the children
+    #[cfg(target_os = "linux")]
+    pub sandbox: Option<Sandbox>,
+    /// The observer for seccomp violations of the children
+    #[cfg(target_os = "linux")]
+    pub sandbox_observer: Option<Handle<SandboxObserver>>,
+    /// The observer for the stacks of children that timed out
+    #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+    pub hang_backtrace_observer: Option<Handle<HangBacktraceObserver>>,
+    /// The observer for the peak memory usage of the children
+    #[cfg(target_os = "linux")]
+    pub memory_observer: Option<Handle<MemoryObserver>>,
 }
 
 #[cfg(feature = "std")]
@@ -262,9 +297,20 @@ impl Default for StdChildArgsInner {
             timeout: Duration::from_secs(5),
             stderr_observer: None,
             stdout_observer: None,
+            response_observer: None,
             current_directory: None,
             debug_child: false,
             core: None,
+            #[cfg(unix)]
+            resource_limits: None,
+            #[cfg(target_os = "linux")]
+            sandbox: None,
+            #[cfg(target_os = "linux")]
+            sandbox_observer: None,
+            #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+            hang_backtrace_observer: None,
+            #[cfg(target_os = "linux")]
+            memory_observer: None,
         }
     }
 }
@@ -299,6 +345,14 @@ pub trait StdChildArgs: Sized {
         self
     }
 
+    #[must_use]
+    /// Sets the observer for responses the child sends back when the input is delivered over a
+    /// socket, see [`libafl_bolts::target_args::SocketDelivery`].
+    fn response_observer(mut self, response: Handle<ResponseObserver>) -> Self {
+        self.inner_mut().response_observer = Some(response);
+        self
+    }
+
     #[must_use]
     /// Sets the working directory for the child process.
     fn current_dir(mut self, current_dir: PathBuf) -> Self {
@@ -326,6 +380,49 @@ pub trait StdChildArgs: Sized {
         self.inner_mut().core = Some(core);
         self
     }
+
+    #[must_use]
+    /// Confine the children with the given [`ResourceLimits`], using a cgroup v2 if possible.
+    /// Children killed by the cgroup's OOM killer are reported as [`ExitKind::Oom`].
+    #[cfg(unix)]
+    fn resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.inner_mut().resource_limits = Some(limits);
+        self
+    }
+
+    #[must_use]
+    /// Runs the children in the given [`Sandbox`], see [`crate::executors::sandbox`].
+    #[cfg(target_os = "linux")]
+    fn sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.inner_mut().sandbox = Some(sandbox);
+        self
+    }
+
+    #[must_use]
+    /// Sets the observer for children violating the seccomp filter of the [`Sandbox`]
+    #[cfg(target_os = "linux")]
+    fn sandbox_observer(mut self, observer: Handle<SandboxObserver>) -> Self {
+        self.inner_mut().sandbox_observer = Some(observer);
+        self
+    }
+
+    #[must_use]
+    /// Captures the stack of children that time out with `ptrace` before killing them, see
+    /// [`crate::executors::hang_backtrace`].
+    #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+    fn hang_backtrace_observer(mut self, observer: Handle<HangBacktraceObserver>) -> Self {
+        self.inner_mut().hang_backtrace_observer = Some(observer);
+        self
+    }
+
+    #[must_use]
+    /// Records the peak RSS of the children from their `rusage`, in the given [`MemoryObserver`].
+    /// Only supported by executors that wait for the target themselves, like the `CommandExecutor`.
+    #[cfg(target_os = "linux")]
+    fn memory_observer(mut self, observer: Handle<MemoryObserver>) -> Self {
+        self.inner_mut().memory_observer = Some(observer);
+        self
+    }
 }
 
 #[cfg(test)]
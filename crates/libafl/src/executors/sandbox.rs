@@ -0,0 +1,1176 @@
+//! Namespace and seccomp sandboxing for targets running in child processes.
+//!
+//! The [`CommandExecutor`](crate::executors::CommandExecutor) and the
+//! [`ForkserverExecutor`](crate::executors::ForkserverExecutor) can run their children inside a
+//! [`Sandbox`]: fresh user, mount, network and pid namespaces, a root filesystem that only contains
+//! read-only binds of the target and its shared libraries, a private `tmpfs` holding the input
+//! file, and an optional seccomp filter.
+//!
+//! Targets killed by the seccomp filter die with `SIGSYS`. If the [`SeccompFilter`] has a
+//! violation description, the executors report it to a
+//! [`SandboxObserver`](crate::observers::SandboxObserver), so that e.g. a target trying to
+//! `execve` can be turned into a distinct objective using a
+//! [`SandboxViolationFeedback`](crate::feedbacks::SandboxViolationFeedback).
+//!
+//! Seccomp only kills the offending process. If the target runs the denied syscall in a forked
+//! subprocess, e.g. using `system`, only the subprocess dies and the violation goes unnoticed,
+//! unless the filter denies `fork` and `clone` as well.
+//!
+//! The sandbox is set up in the child after `fork`, so it does not require any privileges as
+//! long as unprivileged user namespaces are enabled.
+
+use alloc::{
+    borrow::Cow, collections::BTreeSet, ffi::CString, string::String, sync::Arc, vec::Vec,
+};
+use core::{
+    ffi::{CStr, c_char, c_int, c_long, c_ulong},
+    fmt::{self, Debug, Formatter},
+    ptr,
+    sync::atomic::{AtomicI32, AtomicU64, Ordering},
+};
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs, io,
+    os::unix::{ffi::OsStrExt, process::CommandExt},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+use libafl_bolts::Error;
+
+/// The `AUDIT_ARCH_*` value of the architecture we are compiled for, checked by the seccomp filter
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3;
+#[cfg(target_arch = "x86")]
+const AUDIT_ARCH: u32 = 0x4000_0003;
+#[cfg(target_arch = "arm")]
+const AUDIT_ARCH: u32 = 0x4000_0028;
+
+/// Offsets into `struct seccomp_data`
+const SECCOMP_DATA_NR: u32 = 0;
+const SECCOMP_DATA_ARCH: u32 = 4;
+const SECCOMP_DATA_ARG0: u32 = 16;
+
+/// Devices bound into the sandbox, as most targets expect them to exist
+const DEVICES: [&str; 5] = [
+    "/dev/null",
+    "/dev/zero",
+    "/dev/full",
+    "/dev/random",
+    "/dev/urandom",
+];
+
+/// What the seccomp filter does with a syscall it does not allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeccompAction {
+    /// Kill the target with `SIGSYS`, reported as a crash or sandbox violation
+    KillProcess,
+    /// Let the syscall fail with the given `errno`, the target keeps running
+    Errno(u16),
+}
+
+/// A seccomp filter, either allowing or denying a list of syscalls.
+///
+/// Syscalls are given by their number, e.g. [`libc::SYS_execve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeccompFilter {
+    allow_list: bool,
+    syscalls: Vec<c_long>,
+    action: SeccompAction,
+    violation: Option<Cow<'static, str>>,
+}
+
+impl SeccompFilter {
+    /// A filter denying the given `syscalls`, allowing all others.
+    pub fn deny<IT>(syscalls: IT) -> Self
+    where
+        IT: IntoIterator<Item = c_long>,
+    {
+        Self {
+            allow_list: false,
+            syscalls: syscalls.into_iter().collect(),
+            action: SeccompAction::KillProcess,
+            violation: None,
+        }
+    }
+
+    /// A filter allowing only the given `syscalls`, denying all others.
+    ///
+    /// The list needs to include everything the target needs to start up, i.e. the dynamic
+    /// loader and the libc initialization.
+    pub fn allow<IT>(syscalls: IT) -> Self
+    where
+        IT: IntoIterator<Item = c_long>,
+    {
+        Self {
+            allow_list: true,
+            ..Self::deny(syscalls)
+        }
+    }
+
+    /// A filter killing targets that try to execute another program.
+    #[must_use]
+    pub fn deny_exec() -> Self {
+        Self::deny([libc::SYS_execve, libc::SYS_execveat]).violation("tried to execve")
+    }
+
+    /// A filter killing targets that try to open a socket.
+    #[must_use]
+    pub fn deny_network() -> Self {
+        Self::deny([libc::SYS_socket]).violation("tried to open a socket")
+    }
+
+    /// Sets what happens on denied syscalls, [`SeccompAction::KillProcess`] by default.
+    #[must_use]
+    pub fn action(mut self, action: SeccompAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Reports targets killed by this filter as violation with the given description.
+    ///
+    /// Only has an effect with [`SeccompAction::KillProcess`].
+    #[must_use]
+    pub fn violation<D>(mut self, description: D) -> Self
+    where
+        D: Into<Cow<'static, str>>,
+    {
+        self.violation = Some(description.into());
+        self
+    }
+
+    /// Compiles the filter to a BPF program.
+    ///
+    /// The `execve` of `exec_path` is always allowed, as it starts the target itself.
+    fn compile(&self, exec_path: *const c_char) -> Vec<libc::sock_filter> {
+        let action = match self.action {
+            SeccompAction::KillProcess => libc::SECCOMP_RET_KILL_PROCESS,
+            SeccompAction::Errno(errno) => libc::SECCOMP_RET_ERRNO | u32::from(errno),
+        };
+        let (on_match, otherwise) = if self.allow_list {
+            (libc::SECCOMP_RET_ALLOW, action)
+        } else {
+            (action, libc::SECCOMP_RET_ALLOW)
+        };
+        let exec_path = exec_path as u64;
+
+        let mut program = vec![
+            bpf_load(SECCOMP_DATA_ARCH),
+            bpf_jump(libc::BPF_JEQ, AUDIT_ARCH, 1, 0),
+            bpf_ret(libc::SECCOMP_RET_KILL_PROCESS),
+            bpf_load(SECCOMP_DATA_NR),
+        ];
+        // x32 syscalls would bypass the checks below
+        #[cfg(target_arch = "x86_64")]
+        program.extend([
+            bpf_jump(libc::BPF_JGE, 0x4000_0000, 0, 1),
+            bpf_ret(libc::SECCOMP_RET_KILL_PROCESS),
+        ]);
+        program.extend([
+            bpf_jump(libc::BPF_JEQ, libc::SYS_execve as u32, 0, 5),
+            bpf_load(SECCOMP_DATA_ARG0),
+            bpf_jump(libc::BPF_JEQ, exec_path as u32, 0, 3),
+            bpf_load(SECCOMP_DATA_ARG0 + 4),
+            bpf_jump(libc::BPF_JEQ, (exec_path >> 32) as u32, 0, 1),
+            bpf_ret(libc::SECCOMP_RET_ALLOW),
+            bpf_load(SECCOMP_DATA_NR),
+        ]);
+        for syscall in &self.syscalls {
+            program.extend([
+                bpf_jump(libc::BPF_JEQ, syscall.cast_unsigned() as u32, 0, 1),
+                bpf_ret(on_match),
+            ]);
+        }
+        program.push(bpf_ret(otherwise));
+        program
+    }
+}
+
+fn bpf_load(offset: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16,
+        jt: 0,
+        jf: 0,
+        k: offset,
+    }
+}
+
+fn bpf_jump(op: u32, value: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: (libc::BPF_JMP | op | libc::BPF_K) as u16,
+        jt,
+        jf,
+        k: value,
+    }
+}
+
+fn bpf_ret(value: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: (libc::BPF_RET | libc::BPF_K) as u16,
+        jt: 0,
+        jf: 0,
+        k: value,
+    }
+}
+
+/// The sandbox configuration for a target running in a child process.
+///
+/// By default, all namespaces are used and the root filesystem contains the target, its shared
+/// libraries, a few devices, a private `/tmp` and the input file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools)]
+pub struct Sandbox {
+    user_ns: bool,
+    mount_ns: bool,
+    net_ns: bool,
+    pid_ns: bool,
+    bind_dependencies: bool,
+    read_only: Vec<PathBuf>,
+    seccomp: Option<SeccompFilter>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            user_ns: true,
+            mount_ns: true,
+            net_ns: true,
+            pid_ns: true,
+            bind_dependencies: true,
+            read_only: Vec::new(),
+            seccomp: None,
+        }
+    }
+}
+
+impl Sandbox {
+    /// Creates a new [`Sandbox`] using all namespaces, without a seccomp filter.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to use a user namespace, required unless the fuzzer runs as root.
+    #[must_use]
+    pub fn user_namespace(mut self, enable: bool) -> Self {
+        self.user_ns = enable;
+        self
+    }
+
+    /// Whether to use a mount namespace with a minimal root filesystem.
+    #[must_use]
+    pub fn mount_namespace(mut self, enable: bool) -> Self {
+        self.mount_ns = enable;
+        self
+    }
+
+    /// Whether to use a network namespace, leaving the target with an unconfigured loopback device.
+    #[must_use]
+    pub fn network_namespace(mut self, enable: bool) -> Self {
+        self.net_ns = enable;
+        self
+    }
+
+    /// Whether to use a pid namespace, hiding all other processes from the target.
+    #[must_use]
+    pub fn pid_namespace(mut self, enable: bool) -> Self {
+        self.pid_ns = enable;
+        self
+    }
+
+    /// Whether to bind the shared libraries of the target, as listed by `ldd`, into the sandbox.
+    #[must_use]
+    pub fn bind_dependencies(mut self, enable: bool) -> Self {
+        self.bind_dependencies = enable;
+        self
+    }
+
+    /// Makes `path`, a file or directory, available read-only at the same location in the sandbox.
+    #[must_use]
+    pub fn bind_read_only<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.read_only.push(path.into());
+        self
+    }
+
+    /// Filters the syscalls of the target.
+    #[must_use]
+    pub fn seccomp(mut self, filter: SeccompFilter) -> Self {
+        self.seccomp = Some(filter);
+        self
+    }
+
+    /// Checks if unprivileged user and mount namespaces can be used on this system.
+    #[must_use]
+    pub fn is_supported() -> bool {
+        let mut command = Command::new("true");
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // # Safety
+        // `unshare` is async-signal-safe.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS) == 0 {
+                    Ok(())
+                } else {
+                    Err(io::Error::last_os_error())
+                }
+            });
+        }
+        command.status().is_ok_and(|status| status.success())
+    }
+
+    /// Prepares the sandbox for `program`, running in `cwd` and reading `input_file`.
+    ///
+    /// This creates the mount point of the sandbox root and computes everything the child needs,
+    /// as the child may not allocate after `fork`.
+    pub fn instantiate(
+        &self,
+        program: &OsStr,
+        input_file: Option<&Path>,
+        cwd: Option<&Path>,
+    ) -> Result<SandboxInstance, Error> {
+        let cwd = match cwd {
+            Some(cwd) => absolute(cwd)?,
+            None => env::current_dir()?,
+        };
+        let program = resolve_program(program, &cwd)?;
+
+        let mut clone_flags = 0;
+        let mut id_maps = Vec::new();
+        if self.user_ns {
+            clone_flags |= libc::CLONE_NEWUSER;
+            // # Safety
+            // Always succeeds.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            id_maps.push((cstring("/proc/self/setgroups")?, b"deny".to_vec()));
+            id_maps.push((
+                cstring("/proc/self/uid_map")?,
+                format!("0 {uid} 1").into_bytes(),
+            ));
+            id_maps.push((
+                cstring("/proc/self/gid_map")?,
+                format!("0 {gid} 1").into_bytes(),
+            ));
+        }
+        if self.mount_ns {
+            clone_flags |= libc::CLONE_NEWNS;
+        }
+        if self.net_ns {
+            clone_flags |= libc::CLONE_NEWNET;
+        }
+        if self.pid_ns {
+            clone_flags |= libc::CLONE_NEWPID;
+        }
+
+        let root = if self.mount_ns {
+            Some(SandboxRoot::create()?)
+        } else {
+            None
+        };
+        let mounts = match &root {
+            Some(root) => {
+                let mut read_only = self.read_only.clone();
+                read_only.push(program.clone());
+                if self.bind_dependencies {
+                    read_only.extend(dependencies(&program));
+                }
+                let input_file = input_file.map(absolute).transpose()?;
+                MountPlan::new(
+                    &root.path,
+                    &read_only,
+                    input_file.as_deref(),
+                    &cwd,
+                    self.pid_ns,
+                )?
+            }
+            None => MountPlan::default(),
+        };
+
+        Ok(SandboxInstance {
+            inner: Arc::new(SandboxInner {
+                setup: ChildSetup {
+                    clone_flags,
+                    id_maps,
+                    pid_ns: self.pid_ns,
+                    mounts,
+                },
+                exec: Mutex::new(None),
+                seccomp: self.seccomp.clone(),
+                _root: root,
+            }),
+        })
+    }
+}
+
+/// The mount point of the sandbox root, removed once the sandbox is dropped
+#[derive(Debug)]
+struct SandboxRoot {
+    path: PathBuf,
+}
+
+impl SandboxRoot {
+    fn create() -> Result<Self, Error> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let path = env::temp_dir().join(format!(
+            "libafl-sandbox-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for SandboxRoot {
+    fn drop(&mut self) {
+        // The mounts only exist in the namespaces of the children, the directory is empty here.
+        let _ = fs::remove_dir(&self.path);
+    }
+}
+
+/// A mount operation performed in the child
+#[derive(Debug)]
+enum MountOp {
+    Mkdir(CString),
+    Touch(CString),
+    Tmpfs(CString),
+    Bind {
+        src: CString,
+        dst: CString,
+        /// The flags to remount the bind read-only with, if it should be read-only
+        remount: Option<c_ulong>,
+    },
+    Proc(CString),
+}
+
+/// The filesystem of the sandbox
+#[derive(Debug, Default)]
+struct MountPlan {
+    ops: Vec<MountOp>,
+    root: Option<CString>,
+    put_old: CString,
+    cwd: CString,
+}
+
+impl MountPlan {
+    fn new(
+        root: &Path,
+        read_only: &[PathBuf],
+        input_file: Option<&Path>,
+        cwd: &Path,
+        pid_ns: bool,
+    ) -> Result<Self, Error> {
+        let inside = |path: &Path| -> Result<CString, Error> {
+            let relative = path.strip_prefix("/").unwrap_or(path);
+            cpath(&root.join(relative))
+        };
+
+        // All mount points get created before anything is mounted read-only.
+        let mut dirs = BTreeSet::new();
+        let mut files = BTreeSet::new();
+        let mut binds = Vec::new();
+        for path in read_only {
+            let path = absolute(path)?;
+            let Ok(metadata) = fs::metadata(&path) else {
+                log::warn!(
+                    "Not binding {} into the sandbox, it does not exist",
+                    path.display()
+                );
+                continue;
+            };
+            if metadata.is_dir() {
+                dirs.insert(path.clone());
+            } else if let Some(parent) = path.parent() {
+                dirs.insert(parent.to_path_buf());
+                files.insert(path.clone());
+            }
+            binds.push(MountOp::Bind {
+                src: cpath(&path)?,
+                dst: inside(&path)?,
+                remount: Some(remount_flags(&path)?),
+            });
+        }
+        for device in DEVICES {
+            let device = Path::new(device);
+            if device.exists() {
+                files.insert(device.to_path_buf());
+                binds.push(MountOp::Bind {
+                    src: cpath(device)?,
+                    dst: inside(device)?,
+                    remount: None,
+                });
+            }
+        }
+        dirs.insert(PathBuf::from("/dev"));
+        dirs.insert(PathBuf::from("/tmp"));
+        dirs.insert(PathBuf::from("/proc"));
+        dirs.insert(PathBuf::from("/.old"));
+        dirs.insert(cwd.to_path_buf());
+        let input_dir = input_file.and_then(Path::parent);
+
+        let mut ops = vec![MountOp::Tmpfs(cpath(root)?)];
+        // The `tmpfs` layers go first, so that they do not hide the binds below them, e.g. a
+        // target next to the input file. The input lives in a private `tmpfs`, so the target sees
+        // no other files next to it.
+        let mut layers = BTreeSet::new();
+        layers.insert(PathBuf::from("/tmp"));
+        if let Some(input_dir) = input_dir {
+            layers.insert(input_dir.to_path_buf());
+        }
+        for layer in &layers {
+            // The ancestors may be hidden by a previous layer, `Mkdir` ignores existing ones.
+            for ancestor in layer.ancestors().collect::<Vec<_>>().into_iter().rev() {
+                if ancestor != Path::new("/") {
+                    ops.push(MountOp::Mkdir(inside(ancestor)?));
+                }
+            }
+            ops.push(MountOp::Tmpfs(inside(layer)?));
+        }
+
+        let mut created = BTreeSet::new();
+        for dir in &dirs {
+            // Create all ancestors first, `BTreeSet` orders parents before their children.
+            for ancestor in dir.ancestors().collect::<Vec<_>>().into_iter().rev() {
+                if ancestor != Path::new("/") && created.insert(ancestor.to_path_buf()) {
+                    ops.push(MountOp::Mkdir(inside(ancestor)?));
+                }
+            }
+        }
+        for file in &files {
+            ops.push(MountOp::Touch(inside(file)?));
+        }
+        if let Some(input_file) = input_file {
+            ops.push(MountOp::Touch(inside(input_file)?));
+            ops.push(MountOp::Bind {
+                src: cpath(input_file)?,
+                dst: inside(input_file)?,
+                remount: Some(remount_flags(input_file)?),
+            });
+        }
+        ops.extend(binds);
+        if pid_ns {
+            ops.push(MountOp::Proc(inside(Path::new("/proc"))?));
+        } else {
+            ops.push(MountOp::Bind {
+                src: cstring("/proc")?,
+                dst: inside(Path::new("/proc"))?,
+                remount: None,
+            });
+        }
+
+        Ok(Self {
+            ops,
+            root: Some(cpath(root)?),
+            put_old: inside(Path::new("/.old"))?,
+            cwd: cpath(cwd)?,
+        })
+    }
+
+    /// Mounts the sandbox filesystem and makes it the root.
+    ///
+    /// # Safety
+    /// Only called in the child, must be async-signal-safe.
+    unsafe fn mount(&self) -> io::Result<()> {
+        let Some(root) = &self.root else {
+            return Ok(());
+        };
+        unsafe {
+            // Do not propagate our mounts back to the parent namespace.
+            check(libc::mount(
+                ptr::null(),
+                c"/".as_ptr(),
+                ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                ptr::null(),
+            ))?;
+            for op in &self.ops {
+                match op {
+                    MountOp::Mkdir(path) => {
+                        if libc::mkdir(path.as_ptr(), 0o755) != 0
+                            && io::Error::last_os_error().raw_os_error() != Some(libc::EEXIST)
+                        {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                    MountOp::Touch(path) => {
+                        let fd = libc::open(
+                            path.as_ptr(),
+                            libc::O_CREAT | libc::O_WRONLY | libc::O_CLOEXEC,
+                            0o644,
+                        );
+                        check(fd)?;
+                        libc::close(fd);
+                    }
+                    MountOp::Tmpfs(path) => {
+                        check(libc::mount(
+                            c"tmpfs".as_ptr(),
+                            path.as_ptr(),
+                            c"tmpfs".as_ptr(),
+                            libc::MS_NOSUID | libc::MS_NODEV,
+                            c"mode=755".as_ptr().cast(),
+                        ))?;
+                    }
+                    MountOp::Bind { src, dst, remount } => {
+                        check(libc::mount(
+                            src.as_ptr(),
+                            dst.as_ptr(),
+                            ptr::null(),
+                            libc::MS_BIND | libc::MS_REC,
+                            ptr::null(),
+                        ))?;
+                        if let Some(flags) = remount {
+                            check(libc::mount(
+                                ptr::null(),
+                                dst.as_ptr(),
+                                ptr::null(),
+                                *flags,
+                                ptr::null(),
+                            ))?;
+                        }
+                    }
+                    MountOp::Proc(path) => {
+                        // Fails if parts of `/proc` are masked, e.g. in containers. Not fatal, the
+                        // target just has no `/proc` then.
+                        libc::mount(
+                            c"proc".as_ptr(),
+                            path.as_ptr(),
+                            c"proc".as_ptr(),
+                            libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                            ptr::null(),
+                        );
+                    }
+                }
+            }
+
+            check(
+                libc::syscall(libc::SYS_pivot_root, root.as_ptr(), self.put_old.as_ptr()) as c_int,
+            )?;
+            check(libc::chdir(c"/".as_ptr()))?;
+            check(libc::umount2(c"/.old".as_ptr(), libc::MNT_DETACH))?;
+            libc::rmdir(c"/.old".as_ptr());
+            if libc::chdir(self.cwd.as_ptr()) != 0 {
+                check(libc::chdir(c"/".as_ptr()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Everything the child needs to enter the sandbox, computed in the parent
+#[derive(Debug)]
+struct ChildSetup {
+    clone_flags: c_int,
+    id_maps: Vec<(CString, Vec<u8>)>,
+    pid_ns: bool,
+    mounts: MountPlan,
+}
+
+/// The `execve` arguments for the next child, if it executes the target itself
+struct ExecState {
+    program: CString,
+    _args: Vec<CString>,
+    argv: Vec<*const c_char>,
+    _envs: Vec<CString>,
+    envp: Vec<*const c_char>,
+    filter: Vec<libc::sock_filter>,
+}
+
+// # Safety
+// The pointers point into the `CString`s owned by the same struct.
+unsafe impl Send for ExecState {}
+
+struct SandboxInner {
+    setup: ChildSetup,
+    exec: Mutex<Option<ExecState>>,
+    seccomp: Option<SeccompFilter>,
+    _root: Option<SandboxRoot>,
+}
+
+/// A [`Sandbox`] prepared for a specific target, see [`Sandbox::instantiate`].
+#[derive(Clone)]
+pub struct SandboxInstance {
+    inner: Arc<SandboxInner>,
+}
+
+impl Debug for SandboxInstance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SandboxInstance")
+            .field("setup", &self.inner.setup)
+            .field("seccomp", &self.inner.seccomp)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SandboxInstance {
+    /// If the target runs in its own pid namespace.
+    ///
+    /// Pids reported from inside the sandbox need to be translated using [`outer_pid`] then.
+    #[must_use]
+    pub fn has_pid_namespace(&self) -> bool {
+        self.inner.setup.pid_ns
+    }
+
+    /// The description of a seccomp violation, if targets killed by `SIGSYS` violated the filter.
+    #[must_use]
+    pub fn violation(&self) -> Option<&Cow<'static, str>> {
+        self.inner
+            .seccomp
+            .as_ref()
+            .filter(|filter| filter.action == SeccompAction::KillProcess)
+            .and_then(|filter| filter.violation.as_ref())
+    }
+
+    /// Makes `command` enter the sandbox when spawned.
+    ///
+    /// This should be the last `pre_exec` hook of the `command`, and [`Self::prepare_exec`]
+    /// needs to be called before each spawn.
+    pub fn apply(&self, command: &mut Command) {
+        let inner = self.inner.clone();
+        // # Safety
+        // `enter` only uses async-signal-safe functions and does not allocate.
+        unsafe {
+            command.pre_exec(move || enter(&inner));
+        }
+    }
+
+    /// Computes the `execve` arguments of `command` for its next spawn.
+    ///
+    /// With a seccomp filter, the child executes the target itself so the filter can allow exactly
+    /// this `execve`. Note that this does not see whether the environment of `command` was cleared.
+    pub fn prepare_exec(&self, command: &Command) -> Result<(), Error> {
+        let Some(seccomp) = &self.inner.seccomp else {
+            return Ok(());
+        };
+        let cwd = match command.get_current_dir() {
+            Some(cwd) => absolute(cwd)?,
+            None => env::current_dir()?,
+        };
+        let program = cpath(&resolve_program(command.get_program(), &cwd)?)?;
+
+        let args = core::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| cstring(arg.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut vars: Vec<(OsString, OsString)> = env::vars_os().collect();
+        for (key, value) in command.get_envs() {
+            vars.retain(|(k, _)| k != key);
+            if let Some(value) = value {
+                vars.push((key.to_os_string(), value.to_os_string()));
+            }
+        }
+        let envs = vars
+            .iter()
+            .map(|(key, value)| {
+                let mut var = key.as_bytes().to_vec();
+                var.push(b'=');
+                var.extend_from_slice(value.as_bytes());
+                cstring(var)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let argv = args
+            .iter()
+            .map(|arg| arg.as_ptr())
+            .chain(core::iter::once(ptr::null()))
+            .collect();
+        let envp = envs
+            .iter()
+            .map(|var| var.as_ptr())
+            .chain(core::iter::once(ptr::null()))
+            .collect();
+        let filter = seccomp.compile(program.as_ptr());
+
+        *self
+            .inner
+            .exec
+            .lock()
+            .map_err(|_| Error::illegal_state("Sandbox lock poisoned"))? = Some(ExecState {
+            program,
+            _args: args,
+            argv,
+            _envs: envs,
+            envp,
+            filter,
+        });
+        Ok(())
+    }
+}
+
+/// Enters the sandbox in the child, called right before `execve`.
+fn enter(inner: &SandboxInner) -> io::Result<()> {
+    let setup = &inner.setup;
+    // The parent is single-threaded in between `prepare_exec` and the `fork`.
+    let exec = inner
+        .exec
+        .try_lock()
+        .map_err(|_| io::Error::from(io::ErrorKind::WouldBlock))?;
+    if inner.seccomp.is_some() && exec.is_none() {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+
+    // # Safety
+    // All of the following is async-signal-safe and operates on data prepared by the parent.
+    unsafe {
+        if setup.clone_flags != 0 {
+            check(libc::unshare(setup.clone_flags))?;
+        }
+        for (path, content) in &setup.id_maps {
+            write_file(path, content)?;
+        }
+        if setup.pid_ns {
+            spawn_init()?;
+        }
+        setup.mounts.mount()?;
+
+        if let Some(exec) = exec.as_ref() {
+            check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+            let program = libc::sock_fprog {
+                len: exec.filter.len() as u16,
+                filter: exec.filter.as_ptr().cast_mut(),
+            };
+            check(libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &raw const program,
+            ))?;
+            libc::execve(
+                exec.program.as_ptr(),
+                exec.argv.as_ptr(),
+                exec.envp.as_ptr(),
+            );
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// The process signals are forwarded to
+static FORWARD_TO: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn forward_signal(signal: c_int) {
+    let pid = FORWARD_TO.load(Ordering::Relaxed);
+    if pid > 0 {
+        // # Safety
+        // `kill` is async-signal-safe.
+        unsafe {
+            libc::kill(pid, signal);
+        }
+    }
+}
+
+/// Sets up the processes of the pid namespace, only returns in the process executing the target.
+///
+/// The process spawned by the executor stays outside of the namespace and mirrors the exit status
+/// of the target. Its child becomes pid 1 of the namespace and reaps orphans. The target runs as
+/// pid 2, as pid 1 would ignore signals it raises itself, e.g. in `abort`.
+///
+/// # Safety
+/// Must be called in the child after `unshare(CLONE_NEWPID)`.
+unsafe fn spawn_init() -> io::Result<()> {
+    unsafe {
+        // Handlers inherited from the fuzzer, e.g. for `SIGCHLD`, must not run in our processes.
+        for signal in 1..libc::SIGRTMIN() {
+            if signal != libc::SIGKILL && signal != libc::SIGSTOP {
+                libc::signal(signal, libc::SIG_DFL);
+            }
+        }
+
+        let mut status_pipe = [0; 2];
+        check(libc::pipe2(status_pipe.as_mut_ptr(), libc::O_CLOEXEC))?;
+        let [read_end, write_end] = status_pipe;
+
+        let init = check(libc::fork())?;
+        if init > 0 {
+            libc::close(write_end);
+            close_fds_except(read_end);
+            let status = supervise(init, false);
+            let mut target_status = 0_i32;
+            let read = libc::read(read_end, (&raw mut target_status).cast(), size_of::<i32>());
+            exit_like(if read == size_of::<i32>().cast_signed() {
+                target_status
+            } else {
+                status
+            });
+        }
+
+        libc::close(read_end);
+        // Take the namespace down with us if the fuzzer kills the outer process.
+        check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL))?;
+        let target = check(libc::fork())?;
+        if target > 0 {
+            close_fds_except(write_end);
+            let status = supervise(target, true);
+            libc::write(write_end, (&raw const status).cast(), size_of::<i32>());
+            libc::_exit(0);
+        }
+        libc::close(write_end);
+        Ok(())
+    }
+}
+
+/// Forwards signals to `child` and waits for it, reaping all other children if `reap_all`.
+unsafe fn supervise(child: c_int, reap_all: bool) -> c_int {
+    unsafe {
+        FORWARD_TO.store(child, Ordering::Relaxed);
+        for signal in [
+            libc::SIGTERM,
+            libc::SIGINT,
+            libc::SIGHUP,
+            libc::SIGQUIT,
+            libc::SIGUSR1,
+            libc::SIGUSR2,
+            libc::SIGALRM,
+        ] {
+            libc::signal(signal, forward_signal as *const () as libc::sighandler_t);
+        }
+        loop {
+            let mut status = 0;
+            let pid = libc::waitpid(if reap_all { -1 } else { child }, &raw mut status, 0);
+            if pid == child {
+                return status;
+            }
+            if pid < 0 && io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+                libc::_exit(1);
+            }
+        }
+    }
+}
+
+/// Exits the same way as a process with the wait `status` did.
+unsafe fn exit_like(status: c_int) -> ! {
+    unsafe {
+        if libc::WIFSIGNALED(status) {
+            let signal = libc::WTERMSIG(status);
+            libc::signal(signal, libc::SIG_DFL);
+            let mut set = core::mem::zeroed();
+            libc::sigemptyset(&raw mut set);
+            libc::sigaddset(&raw mut set, signal);
+            libc::sigprocmask(libc::SIG_UNBLOCK, &raw const set, ptr::null_mut());
+            libc::kill(libc::getpid(), signal);
+            libc::_exit(128 + signal);
+        }
+        libc::_exit(libc::WEXITSTATUS(status));
+    }
+}
+
+/// Closes all file descriptors above stderr except `keep`, e.g. the error pipe of `Command::spawn`
+unsafe fn close_fds_except(keep: c_int) {
+    unsafe {
+        let below = keep.cast_unsigned();
+        if (below <= 3 || libc::syscall(libc::SYS_close_range, 3_u32, below - 1, 0_u32) == 0)
+            && libc::syscall(libc::SYS_close_range, below + 1, u32::MAX, 0_u32) == 0
+        {
+            return;
+        }
+        let max = c_int::try_from(libc::sysconf(libc::_SC_OPEN_MAX).max(1024)).unwrap_or(1024);
+        for fd in 3..max {
+            if fd != keep {
+                libc::close(fd);
+            }
+        }
+    }
+}
+
+/// Writes `content` to the file at `path` without allocating.
+unsafe fn write_file(path: &CStr, content: &[u8]) -> io::Result<()> {
+    unsafe {
+        let fd = check(libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC))?;
+        let written = libc::write(fd, content.as_ptr().cast(), content.len());
+        let err = io::Error::last_os_error();
+        libc::close(fd);
+        if written == content.len().cast_signed() {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+}
+
+fn check(ret: c_int) -> io::Result<c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+fn cstring<B: Into<Vec<u8>>>(bytes: B) -> Result<CString, Error> {
+    CString::new(bytes).map_err(|_| Error::illegal_argument("Unexpected NUL byte in sandbox path"))
+}
+
+fn cpath(path: &Path) -> Result<CString, Error> {
+    cstring(path.as_os_str().as_bytes())
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, Error> {
+    Ok(std::path::absolute(path)?)
+}
+
+/// Resolves `program` like `execvp` would, relative to `cwd` or using `PATH`.
+fn resolve_program(program: &OsStr, cwd: &Path) -> Result<PathBuf, Error> {
+    if program.as_bytes().contains(&b'/') {
+        return Ok(cwd.join(program));
+    }
+    env::var_os("PATH")
+        .and_then(|paths| {
+            env::split_paths(&paths)
+                .map(|dir| cwd.join(dir).join(program))
+                .find(|candidate| candidate.is_file())
+        })
+        .ok_or_else(|| {
+            Error::illegal_argument(format!("Could not find {} in PATH", program.display()))
+        })
+}
+
+/// The shared libraries `program` links against, as reported by `ldd`
+fn dependencies(program: &Path) -> Vec<PathBuf> {
+    match Command::new("ldd")
+        .arg(program)
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(output) => parse_ldd(&String::from_utf8_lossy(&output.stdout)),
+        Err(err) => {
+            log::warn!("Could not run ldd to find the dependencies of the target: {err}");
+            Vec::new()
+        }
+    }
+}
+
+fn parse_ldd(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let path = line.split_once("=>").map_or(line, |(_, path)| path.trim());
+            let path = path.split(" (").next()?.trim();
+            path.starts_with('/').then(|| PathBuf::from(path))
+        })
+        .collect()
+}
+
+/// The flags to remount a bind of `path` read-only with.
+///
+/// In a user namespace, the flags locked by the original mount have to be kept.
+fn remount_flags(path: &Path) -> Result<c_ulong, Error> {
+    let path = cpath(path)?;
+    // # Safety
+    // `statvfs` only writes to the struct we pass.
+    let stat = unsafe {
+        let mut stat: libc::statvfs = core::mem::zeroed();
+        check(libc::statvfs(path.as_ptr(), &raw mut stat))?;
+        stat
+    };
+    let mut flags = libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY;
+    for (st, ms) in [
+        (libc::ST_NOSUID, libc::MS_NOSUID),
+        (libc::ST_NODEV, libc::MS_NODEV),
+        (libc::ST_NOEXEC, libc::MS_NOEXEC),
+        (libc::ST_NOATIME, libc::MS_NOATIME),
+        (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (libc::ST_RELATIME, libc::MS_RELATIME),
+    ] {
+        if stat.f_flag & st != 0 {
+            flags |= ms;
+        }
+    }
+    Ok(flags)
+}
+
+/// Translates the pid of a process in the pid namespace of a sandbox to its pid outside.
+///
+/// Searches the descendants of `root`, the process spawned by the executor.
+#[must_use]
+pub fn outer_pid(root: u32, inner_pid: i32) -> Option<i32> {
+    let mut pending = vec![root];
+    while let Some(pid) = pending.pop() {
+        if pid != root
+            && let Ok(status) = fs::read_to_string(format!("/proc/{pid}/status"))
+            && status
+                .lines()
+                .find_map(|line| line.strip_prefix("NSpid:"))
+                .and_then(|ids| ids.split_whitespace().last())
+                .and_then(|id| id.parse::<i32>().ok())
+                == Some(inner_pid)
+        {
+            return i32::try_from(pid).ok();
+        }
+        if let Ok(children) = fs::read_to_string(format!("/proc/{pid}/task/{pid}/children")) {
+            pending.extend(
+                children
+                    .split_whitespace()
+                    .filter_map(|id| id.parse::<u32>().ok()),
+            );
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use std::{os::unix::process::ExitStatusExt, path::PathBuf, process::Command};
+
+    use super::{Sandbox, SeccompFilter, parse_ldd};
+
+    #[test]
+    fn test_parse_ldd() {
+        let output = "\tlinux-vdso.so.1 (0x00007ffd4b7e6000)\n\
+            \tlibc.so.6 => /lib/x86_64-linux-gnu/libc.so.6 (0x00007f1e8c200000)\n\
+            \t/lib64/ld-linux-x86-64.so.2 (0x00007f1e8c4d3000)\n";
+        assert_eq!(
+            parse_ldd(output),
+            [
+                PathBuf::from("/lib/x86_64-linux-gnu/libc.so.6"),
+                PathBuf::from("/lib64/ld-linux-x86-64.so.2")
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_sandbox() {
+        if !Sandbox::is_supported() {
+            log::warn!("Namespaces are not supported, skipping the sandbox test");
+            return;
+        }
+
+        // Only the shell itself is available in the sandbox, and it runs as pid 2.
+        let sandbox = Sandbox::new()
+            .instantiate("sh".as_ref(), None, None)
+            .unwrap();
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo $$; test -e /etc/passwd || echo isolated"]);
+        sandbox.prepare_exec(&command).unwrap();
+        sandbox.apply(&mut command);
+        let output = command.output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "2\nisolated\n");
+
+        // Executing another program violates the filter, while starting the target is fine.
+        let sandbox = Sandbox::new()
+            .bind_read_only("/bin")
+            .bind_read_only("/usr")
+            .seccomp(SeccompFilter::deny_exec())
+            .instantiate("sh".as_ref(), None, None)
+            .unwrap();
+        assert_eq!(sandbox.violation().unwrap(), "tried to execve");
+        let mut command = Command::new("sh");
+        command.args(["-c", "exec /bin/true"]);
+        sandbox.prepare_exec(&command).unwrap();
+        sandbox.apply(&mut command);
+        let status = command.status().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGSYS));
+    }
+}
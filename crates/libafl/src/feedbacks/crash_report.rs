@@ -0,0 +1,407 @@
+//! The [`CrashReportFeedback`] collects what is known about a crash or timeout, so the
+//! [`crate::stages::CrashReportStage`] can write it as JSON or SARIF 2.1 report next to the
+//! solution, e.g. to import crashes as code scanning alerts.
+
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Write, hash::Hash};
+use std::path::Path;
+
+use libafl_bolts::{
+    Named, generic_hash_std, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    Error, HasMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverHandle, StateInitializer},
+    observers::{ObserverWithHashField, ObserverWithStacktrace, SymbolizedFrame},
+};
+
+/// The `$schema` of the SARIF reports
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The format of a crash report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrashReportFormat {
+    /// A plain JSON object with all fields of the [`CrashReportMetadata`]
+    Json,
+    /// A SARIF 2.1 log with a single result, e.g. for code scanning
+    Sarif,
+}
+
+impl CrashReportFormat {
+    /// The extension appended to the file name of the solution
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Sarif => "sarif",
+        }
+    }
+}
+
+/// What the [`CrashReportFeedback`] collected about a solution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportMetadata {
+    /// How the run ended
+    pub exit_kind: ExitKind,
+    /// The signal that terminated the target, if known
+    pub signal: Option<i32>,
+    /// The stack trace, innermost frame first
+    pub stack: Vec<SymbolizedFrame>,
+    /// The hash of the stack trace, if the observer has one
+    pub stack_hash: Option<u64>,
+    /// The report the sanitizer printed, if any
+    pub sanitizer_report: Option<String>,
+    /// The hash of the input
+    pub input_hash: String,
+}
+
+impl_serdeany!(CrashReportMetadata);
+
+impl CrashReportMetadata {
+    /// The kind of bug, taken from the sanitizer summary (e.g. `heap-buffer-overflow`), or the
+    /// exit kind
+    #[must_use]
+    pub fn bug_type(&self) -> String {
+        self.sanitizer_report
+            .as_deref()
+            .and_then(|report| {
+                let summary = report
+                    .lines()
+                    .find_map(|line| line.strip_prefix("SUMMARY: "))?;
+                let (_, rest) = summary.split_once(": ")?;
+                rest.split_whitespace().next().map(ToString::to_string)
+            })
+            .unwrap_or_else(|| format!("{:?}", self.exit_kind).to_lowercase())
+    }
+
+    /// A one line description of the solution
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut summary = match self.exit_kind {
+            ExitKind::Timeout => "The target timed out".to_string(),
+            _ => format!("The target crashed with {}", self.bug_type()),
+        };
+        if let Some(frame) = self.stack.first() {
+            if let Some(function) = &frame.function {
+                let _ = write!(summary, " in {function}");
+            } else {
+                let _ = write!(summary, " at {:#x}", frame.address);
+            }
+        }
+        summary
+    }
+
+    /// The plain JSON report of the solution stored at `input_path`
+    #[must_use]
+    pub fn to_json(&self, input_path: &Path, reproduce: &str) -> Value {
+        json!({
+            "summary": self.summary(),
+            "bug_type": self.bug_type(),
+            "exit_kind": format!("{:?}", self.exit_kind),
+            "signal": self.signal,
+            "stack": self.stack,
+            "stack_hash": self.stack_hash.map(|hash| format!("{hash:016x}")),
+            "sanitizer_report": self.sanitizer_report,
+            "input_hash": self.input_hash,
+            "input_path": input_path.to_string_lossy(),
+            "reproduce": reproduce,
+        })
+    }
+
+    /// The SARIF 2.1 log with a single result for the solution stored at `input_path`
+    #[must_use]
+    pub fn to_sarif(&self, input_path: &Path, reproduce: &str) -> Value {
+        let rule_id = self.bug_type();
+        let frames: Vec<Value> = self
+            .stack
+            .iter()
+            .map(|frame| json!({ "location": sarif_location(frame) }))
+            .collect();
+        let mut result = json!({
+            "ruleId": rule_id,
+            "level": "error",
+            "message": { "text": self.summary() },
+            "properties": {
+                "exitKind": format!("{:?}", self.exit_kind),
+                "signal": self.signal,
+                "inputHash": self.input_hash,
+                "inputPath": input_path.to_string_lossy(),
+                "reproduce": reproduce,
+                "sanitizerReport": self.sanitizer_report,
+            },
+        });
+        // Code scanning needs a source location, use the innermost frame that has one
+        if let Some(frame) = self.stack.iter().find(|frame| frame.file.is_some()) {
+            result["locations"] = json!([sarif_location(frame)]);
+        }
+        if !frames.is_empty() {
+            result["stacks"] = json!([{
+                "message": { "text": "Stack trace" },
+                "frames": frames,
+            }]);
+        }
+        if let Some(hash) = self.stack_hash {
+            result["partialFingerprints"] = json!({ "stackHash/v1": format!("{hash:016x}") });
+        }
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "LibAFL",
+                        "informationUri": "https://github.com/AFLplusplus/LibAFL",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": [{
+                            "id": rule_id,
+                            "shortDescription": { "text": rule_id },
+                        }],
+                    },
+                },
+                "results": [result],
+            }],
+        })
+    }
+
+    /// The report in the given format
+    #[must_use]
+    pub fn to_format(
+        &self,
+        format: CrashReportFormat,
+        input_path: &Path,
+        reproduce: &str,
+    ) -> Value {
+        match format {
+            CrashReportFormat::Json => self.to_json(input_path, reproduce),
+            CrashReportFormat::Sarif => self.to_sarif(input_path, reproduce),
+        }
+    }
+}
+
+/// The SARIF `location` of a frame
+fn sarif_location(frame: &SymbolizedFrame) -> Value {
+    let mut physical = json!({ "address": { "absoluteAddress": frame.address } });
+    if let Some(file) = &frame.file {
+        physical["artifactLocation"] = json!({ "uri": file });
+        if let Some(line) = frame.line {
+            physical["region"] = json!({ "startLine": line });
+        }
+    } else if let Some(module) = &frame.module {
+        physical["artifactLocation"] = json!({ "uri": module });
+        if let Some(offset) = frame.module_offset {
+            physical["address"]["relativeAddress"] = json!(offset);
+        }
+    }
+    let mut location = json!({ "physicalLocation": physical });
+    if let Some(function) = &frame.function {
+        location["logicalLocations"] =
+            json!([{ "fullyQualifiedName": function, "kind": "function" }]);
+    }
+    location
+}
+
+/// A feedback that is interesting for crashes and timeouts, like a
+/// [`crate::feedbacks::CrashFeedback`] or a [`crate::feedbacks::TimeoutFeedback`], and adds a
+/// [`CrashReportMetadata`] to them.
+///
+/// The stack, the sanitizer report and the signal come from the given observer, e.g. an
+/// [`crate::observers::AsanBacktraceObserver`], a [`crate::observers::BacktraceObserver`] with
+/// [`crate::observers::HarnessType::InProcess`] and
+/// [`crate::observers::BacktraceObserver::symbolize`], or a
+/// `HangBacktraceObserver` of the `hang_backtrace` feature.
+/// The exit kind in the report is the one the observer saw, so the feedback can be combined
+/// with other objectives in any way.
+/// Add a [`crate::stages::CrashReportStage`] to write the reports.
+#[derive(Debug, Clone)]
+pub struct CrashReportFeedback<O> {
+    o_ref: Handle<O>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl<O> CrashReportFeedback<O>
+where
+    O: Named,
+{
+    /// Creates a new [`CrashReportFeedback`], taking the stack from the given observer
+    #[must_use]
+    pub fn new(observer: &O) -> Self {
+        Self {
+            o_ref: observer.handle(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+}
+
+impl<O, S> StateInitializer<S> for CrashReportFeedback<O> {}
+
+impl<O, EM, I, OT, S> Feedback<EM, I, OT, S> for CrashReportFeedback<O>
+where
+    O: ObserverWithStacktrace + ObserverWithHashField,
+    OT: MatchName,
+    I: Hash,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        _observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let res = matches!(exit_kind, ExitKind::Crash | ExitKind::Timeout);
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let observer = observers
+            .get(&self.o_ref)
+            .ok_or_else(|| Error::illegal_state("The crash report observer is missing"))?;
+        let input_hash = testcase
+            .input()
+            .as_ref()
+            .map(|input| format!("{:016x}", generic_hash_std(input)))
+            .unwrap_or_default();
+        let exit_kind = observer.exit_kind().ok_or_else(|| {
+            Error::illegal_state("The crash report observer did not see the run of the solution")
+        })?;
+        let metadata = CrashReportMetadata {
+            exit_kind,
+            signal: observer.signal(),
+            stack: observer.stacktrace(),
+            stack_hash: observer.hash(),
+            sanitizer_report: observer.sanitizer_report().map(ToString::to_string),
+            input_hash,
+        };
+        testcase.add_metadata(metadata);
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl<O> Named for CrashReportFeedback<O> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("CrashReportFeedback");
+        &NAME
+    }
+}
+
+impl<O> HasObserverHandle for CrashReportFeedback<O> {
+    type Observer = O;
+
+    fn observer_handle(&self) -> &Handle<O> {
+        &self.o_ref
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::{CrashReportFeedback, CrashReportMetadata, SARIF_SCHEMA};
+    use crate::{
+        HasMetadata,
+        corpus::Testcase,
+        executors::ExitKind,
+        feedbacks::Feedback,
+        inputs::BytesInput,
+        observers::{AsanBacktraceObserver, Observer},
+        state::NopState,
+    };
+
+    const ASAN_REPORT: &str = "\
+==1234==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011
+READ of size 1 at 0x602000000011 thread T0
+    #0 0x4f5b2d in parse /src/parser.c:10:5
+    #1 0x4f5c10 in main /src/main.c:20:3
+    #2 0x7f1b2c (/lib/x86_64-linux-gnu/libc.so.6+0x21b96)
+
+0x602000000011 is located 0 bytes to the right of 1-byte region
+allocated by thread T0 here:
+    #0 0x4c1d2e in malloc
+    #1 0x4f5c00 in main /src/main.c:18:9
+
+SUMMARY: AddressSanitizer: heap-buffer-overflow /src/parser.c:10:5 in parse
+";
+
+    #[test]
+    fn test_crash_report() {
+        let mut observer = AsanBacktraceObserver::new("asan");
+        let mut feedback = CrashReportFeedback::new(&observer);
+        let mut state: NopState<BytesInput> = NopState::new();
+        let input = BytesInput::new(b"crash".to_vec());
+        observer.pre_exec(&mut state, &input).unwrap();
+        observer.parse_asan_output(ASAN_REPORT);
+        observer
+            .post_exec(&mut state, &input, &ExitKind::Crash)
+            .unwrap();
+        let observers = tuple_list!(observer);
+
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Crash)
+                .unwrap()
+        );
+        let mut testcase = Testcase::new(input);
+        feedback
+            .append_metadata(&mut state, &mut (), &observers, &mut testcase)
+            .unwrap();
+        let meta = testcase.metadata::<CrashReportMetadata>().unwrap();
+        assert_eq!(meta.bug_type(), "heap-buffer-overflow");
+        // Only the first stack of the report
+        assert_eq!(meta.stack.len(), 3);
+        assert_eq!(meta.stack[0].function.as_deref(), Some("parse"));
+        assert_eq!(meta.stack[1].line, Some(20));
+        assert_eq!(meta.stack[2].module_offset, Some(0x21b96));
+
+        let path = Path::new("solutions/abc");
+        let json = meta.to_json(path, "./target solutions/abc");
+        assert_eq!(json["input_path"], "solutions/abc");
+        assert_eq!(json["exit_kind"], "Crash");
+
+        let sarif = meta.to_sarif(path, "./target solutions/abc");
+        assert_eq!(sarif["$schema"], SARIF_SCHEMA);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "heap-buffer-overflow");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "/src/parser.c"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            10
+        );
+        assert_eq!(result["stacks"][0]["frames"].as_array().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,214 @@
+//! The [`HangFeedback`] reports each hang once per signature, e.g. the stack it hangs in.
+
+use alloc::{borrow::Cow, string::ToString};
+
+use libafl_bolts::{
+    Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    Error, HasMetadata, HasNamedMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{
+        Feedback, HasObserverHandle, NewHashFeedbackMetadata, StateInitializer,
+        new_hash_feedback::HashSetState,
+    },
+    observers::ObserverWithHashField,
+};
+
+/// The prefix of the metadata names
+pub const HANGFEEDBACK_PREFIX: &str = "hangfeedback_metadata_";
+
+/// The signature of a hang, added to the testcases reported by a [`HangFeedback`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HangSignatureMetadata {
+    /// The hash of the signature observer
+    pub signature: u64,
+}
+
+impl_serdeany!(HangSignatureMetadata);
+
+/// A feedback that is interesting for timeouts with a hang signature not seen before.
+///
+/// The signature is the hash of an [`ObserverWithHashField`], usually a `HangBacktraceObserver`
+/// of the `hang_backtrace` feature, holding the stack the target hung in. Use it as objective
+/// instead of a [`crate::feedbacks::TimeoutFeedback`], so the same infinite loop is only
+/// reported once. Timeouts without a signature, e.g. if the stack could not be captured, are
+/// always reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HangFeedback<O> {
+    name: Cow<'static, str>,
+    o_ref: Handle<O>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl<O> HangFeedback<O>
+where
+    O: Named,
+{
+    /// Creates a new [`HangFeedback`], using the hash of the given observer as signature.
+    #[must_use]
+    pub fn new(observer: &O) -> Self {
+        Self {
+            name: Cow::from(HANGFEEDBACK_PREFIX.to_string() + observer.name()),
+            o_ref: observer.handle(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+}
+
+impl<O, S> StateInitializer<S> for HangFeedback<O>
+where
+    S: HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata_checked(&self.name, NewHashFeedbackMetadata::new())?;
+        Ok(())
+    }
+}
+
+impl<O, EM, I, OT, S> Feedback<EM, I, OT, S> for HangFeedback<O>
+where
+    O: ObserverWithHashField + Named,
+    OT: MatchName,
+    S: HasNamedMetadata,
+{
+    fn is_interesting(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let res = if *exit_kind == ExitKind::Timeout {
+            let observer = observers
+                .get(&self.o_ref)
+                .ok_or_else(|| Error::illegal_state("The hang signature observer is missing"))?;
+            match observer.hash() {
+                Some(signature) => state
+                    .named_metadata_map_mut()
+                    .get_mut::<NewHashFeedbackMetadata>(&self.name)
+                    .ok_or_else(|| Error::illegal_state("HangFeedback state is not initialized"))?
+                    .update_hash_set(signature)?,
+                None => true,
+            }
+        } else {
+            false
+        };
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(signature) = observers
+            .get(&self.o_ref)
+            .and_then(ObserverWithHashField::hash)
+        {
+            testcase.add_metadata(HangSignatureMetadata { signature });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl<O> Named for HangFeedback<O> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<O> HasObserverHandle for HangFeedback<O> {
+    type Observer = O;
+
+    fn observer_handle(&self) -> &Handle<O> {
+        &self.o_ref
+    }
+}
+
+#[cfg(all(test, feature = "hang_backtrace", target_os = "linux"))]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::{HangFeedback, HangSignatureMetadata};
+    use crate::{
+        HasMetadata,
+        corpus::Testcase,
+        executors::ExitKind,
+        feedbacks::{Feedback, StateInitializer},
+        inputs::BytesInput,
+        observers::{HangBacktraceObserver, StackFrame},
+        state::NopState,
+    };
+
+    fn frames(offsets: &[u64]) -> Vec<StackFrame> {
+        offsets
+            .iter()
+            .map(|&offset| StackFrame {
+                address: offset,
+                module: Some(String::from("/target")),
+                offset,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hang_feedback() {
+        let observer = HangBacktraceObserver::new("hang");
+        let mut feedback = HangFeedback::new(&observer);
+        let mut state: NopState<BytesInput> = NopState::new();
+        feedback.init_state(&mut state).unwrap();
+        let input = BytesInput::new(vec![]);
+        let mut observers = tuple_list!(observer);
+
+        // Stopped at different places in the same loop
+        for pc in [0x10, 0x18] {
+            observers.0.observe(frames(&[pc, 0x100, 0x200]));
+            let interesting = feedback
+                .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Timeout)
+                .unwrap();
+            assert_eq!(interesting, pc == 0x10);
+        }
+
+        let mut testcase = Testcase::new(input.clone());
+        feedback
+            .append_metadata(&mut state, &mut (), &observers, &mut testcase)
+            .unwrap();
+        assert!(testcase.has_metadata::<HangSignatureMetadata>());
+
+        // Hanging in another loop
+        observers.0.observe(frames(&[0x10, 0x108, 0x200]));
+        assert!(
+            !feedback
+                .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Crash)
+                .unwrap()
+        );
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Timeout)
+                .unwrap()
+        );
+    }
+}
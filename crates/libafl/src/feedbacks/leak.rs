@@ -0,0 +1,120 @@
+//! The [`LeakFeedback`] reports runs with a leak confirmed by the `LeakCheckExecutor`.
+
+use alloc::{borrow::Cow, format, string::String};
+
+use libafl_bolts::{
+    Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    Error, HasMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverHandle, StateInitializer},
+    observers::LeakObserver,
+};
+
+/// The leak of a testcase, added by the [`LeakFeedback`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakReportMetadata {
+    /// The report of the leak checker
+    pub report: String,
+    /// The number of allocations of the run
+    pub mallocs: usize,
+    /// The number of frees of the run
+    pub frees: usize,
+}
+
+impl_serdeany!(LeakReportMetadata);
+
+/// A feedback that is interesting if the [`LeakObserver`] holds the report of a confirmed leak, use
+/// it as objective together with a `LeakCheckExecutor`.
+#[derive(Debug, Clone)]
+pub struct LeakFeedback {
+    name: Cow<'static, str>,
+    o_ref: Handle<LeakObserver>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl LeakFeedback {
+    /// Creates a new [`LeakFeedback`]
+    #[must_use]
+    pub fn new(observer: &LeakObserver) -> Self {
+        Self {
+            name: Cow::from(format!("leakfeedback_{}", observer.name())),
+            o_ref: observer.handle(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+}
+
+impl<S> StateInitializer<S> for LeakFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for LeakFeedback
+where
+    OT: MatchName,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let observer = observers
+            .get(&self.o_ref)
+            .ok_or_else(|| Error::illegal_state("The leak observer is missing"))?;
+        let res = observer.report().is_some();
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(observer) = observers.get(&self.o_ref)
+            && let Some(report) = observer.report()
+        {
+            let (mallocs, frees) = observer.balance();
+            testcase.add_metadata(LeakReportMetadata {
+                report: report.into(),
+                mallocs,
+                frees,
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl Named for LeakFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl HasObserverHandle for LeakFeedback {
+    type Observer = LeakObserver;
+
+    fn observer_handle(&self) -> &Handle<LeakObserver> {
+        &self.o_ref
+    }
+}
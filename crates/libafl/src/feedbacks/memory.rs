@@ -0,0 +1,314 @@
+//! Feedbacks on the [`MemoryUsage`] recorded by the [`MemoryObserver`]: the
+//! [`MaxMemoryFeedback`] keeps inputs pushing the memory usage higher, the
+//! [`MemoryLimitFeedback`] reports inputs exceeding a limit, like libFuzzer's `-rss_limit_mb` and
+//! `-malloc_limit_mb`.
+
+use alloc::{borrow::Cow, format};
+
+use libafl_bolts::{
+    Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    Error, HasMetadata, HasNamedMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverHandle, StateInitializer},
+    observers::{MemoryObserver, MemoryUsage},
+};
+
+/// The memory usage of a testcase, added by the memory feedbacks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MemoryUsageMetadata {
+    /// The memory usage of the run
+    pub usage: MemoryUsage,
+}
+
+impl_serdeany!(MemoryUsageMetadata);
+
+/// The highest memory usage seen by a [`MaxMemoryFeedback`], per value
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MaxMemoryMetadata {
+    /// The highest value seen of each of the memory usage values
+    pub max: MemoryUsage,
+}
+
+impl_serdeany!(MaxMemoryMetadata);
+
+fn memory_usage<OT>(observers: &OT, o_ref: &Handle<MemoryObserver>) -> Result<MemoryUsage, Error>
+where
+    OT: MatchName,
+{
+    observers
+        .get(o_ref)
+        .map(|observer| *observer.usage())
+        .ok_or_else(|| Error::illegal_state("The memory observer is missing"))
+}
+
+/// The log2 bucket of a memory usage value, so that growing by a few bytes is not novel
+fn bucket(bytes: usize) -> u32 {
+    usize::BITS - bytes.leading_zeros()
+}
+
+/// A feedback that is interesting if the peak RSS, the largest allocation or the total allocated
+/// bytes of the run are higher than in all runs before, to fuzz towards memory exhaustion.
+///
+/// The values are compared by their log2, only doubling a value counts as new.
+#[derive(Debug, Clone)]
+pub struct MaxMemoryFeedback {
+    name: Cow<'static, str>,
+    o_ref: Handle<MemoryObserver>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl MaxMemoryFeedback {
+    /// Creates a new [`MaxMemoryFeedback`]
+    #[must_use]
+    pub fn new(observer: &MemoryObserver) -> Self {
+        Self {
+            name: Cow::from(format!("maxmemoryfeedback_{}", observer.name())),
+            o_ref: observer.handle(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+}
+
+impl<S> StateInitializer<S> for MaxMemoryFeedback
+where
+    S: HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata_checked(&self.name, MaxMemoryMetadata::default())?;
+        Ok(())
+    }
+}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for MaxMemoryFeedback
+where
+    OT: MatchName,
+    S: HasNamedMetadata,
+{
+    fn is_interesting(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let usage = memory_usage(observers, &self.o_ref)?;
+        let metadata = state.named_metadata_mut::<MaxMemoryMetadata>(&self.name)?;
+        let res = bucket(usage.peak_rss) > bucket(metadata.max.peak_rss)
+            || bucket(usage.largest_allocation) > bucket(metadata.max.largest_allocation)
+            || bucket(usage.total_allocated) > bucket(metadata.max.total_allocated);
+        if res {
+            metadata.max = metadata.max.max(&usage);
+        }
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let usage = memory_usage(observers, &self.o_ref)?;
+        testcase.add_metadata(MemoryUsageMetadata { usage });
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl Named for MaxMemoryFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl HasObserverHandle for MaxMemoryFeedback {
+    type Observer = MemoryObserver;
+
+    fn observer_handle(&self) -> &Handle<MemoryObserver> {
+        &self.o_ref
+    }
+}
+
+/// A feedback that is interesting if the run exceeded one of the memory limits, use it as
+/// objective.
+#[derive(Debug, Clone)]
+pub struct MemoryLimitFeedback {
+    name: Cow<'static, str>,
+    o_ref: Handle<MemoryObserver>,
+    rss_limit: Option<usize>,
+    malloc_limit: Option<usize>,
+    total_limit: Option<usize>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl MemoryLimitFeedback {
+    /// Creates a new [`MemoryLimitFeedback`] without limits, set them with the builder methods
+    #[must_use]
+    pub fn new(observer: &MemoryObserver) -> Self {
+        Self {
+            name: Cow::from(format!("memorylimitfeedback_{}", observer.name())),
+            o_ref: observer.handle(),
+            rss_limit: None,
+            malloc_limit: None,
+            total_limit: None,
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+
+    /// The limit for the peak RSS, in bytes.
+    ///
+    /// In-process, the observer needs [`MemoryObserver::track_rss`] for this.
+    #[must_use]
+    pub fn rss_limit(mut self, bytes: usize) -> Self {
+        self.rss_limit = Some(bytes);
+        self
+    }
+
+    /// The limit for a single allocation, in bytes
+    #[must_use]
+    pub fn malloc_limit(mut self, bytes: usize) -> Self {
+        self.malloc_limit = Some(bytes);
+        self
+    }
+
+    /// The limit for all allocated bytes of a run, in bytes
+    #[must_use]
+    pub fn total_limit(mut self, bytes: usize) -> Self {
+        self.total_limit = Some(bytes);
+        self
+    }
+
+    /// If the memory usage exceeds any of the limits
+    #[must_use]
+    pub fn exceeded(&self, usage: &MemoryUsage) -> bool {
+        let exceeds = |value: usize, limit: Option<usize>| limit.is_some_and(|limit| value > limit);
+        exceeds(usage.peak_rss, self.rss_limit)
+            || exceeds(usage.largest_allocation, self.malloc_limit)
+            || exceeds(usage.total_allocated, self.total_limit)
+    }
+}
+
+impl<S> StateInitializer<S> for MemoryLimitFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for MemoryLimitFeedback
+where
+    OT: MatchName,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let res = self.exceeded(&memory_usage(observers, &self.o_ref)?);
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let usage = memory_usage(observers, &self.o_ref)?;
+        testcase.add_metadata(MemoryUsageMetadata { usage });
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl Named for MemoryLimitFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl HasObserverHandle for MemoryLimitFeedback {
+    type Observer = MemoryObserver;
+
+    fn observer_handle(&self) -> &Handle<MemoryObserver> {
+        &self.o_ref
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::{MaxMemoryFeedback, MemoryLimitFeedback};
+    use crate::{
+        executors::ExitKind,
+        feedbacks::{Feedback, StateInitializer},
+        observers::{MemoryObserver, MemoryUsage},
+        state::NopState,
+    };
+
+    #[test]
+    fn test_memory_feedbacks() {
+        let observer = MemoryObserver::new("memory");
+        let mut max_feedback = MaxMemoryFeedback::new(&observer);
+        let mut limit_feedback = MemoryLimitFeedback::new(&observer).malloc_limit(1 << 20);
+        let mut state: NopState<()> = NopState::new();
+        max_feedback.init_state(&mut state).unwrap();
+        let mut observers = tuple_list!(observer);
+
+        for (largest_allocation, new_max, over_limit) in [
+            (4096, true, false),
+            (1024, false, false),
+            (4096, false, false),
+            (5000, false, false),
+            (8192, true, false),
+            (2 << 20, true, true),
+        ] {
+            observers.0.observe(MemoryUsage {
+                largest_allocation,
+                ..MemoryUsage::default()
+            });
+            assert_eq!(
+                max_feedback
+                    .is_interesting(&mut state, &mut (), &(), &observers, &ExitKind::Ok)
+                    .unwrap(),
+                new_max
+            );
+            assert_eq!(
+                limit_feedback
+                    .is_interesting(&mut state, &mut (), &(), &observers, &ExitKind::Ok)
+                    .unwrap(),
+                over_limit
+            );
+        }
+    }
+}
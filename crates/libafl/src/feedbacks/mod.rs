@@ -8,20 +8,33 @@ use alloc::borrow::Cow;
 use alloc::vec::Vec;
 use core::{fmt::Debug, marker::PhantomData};
 
+#[cfg(feature = "regex")]
+pub use crash_report::{CrashReportFeedback, CrashReportFormat, CrashReportMetadata};
 pub use differential::DiffFeedback;
+#[cfg(feature = "std")]
+pub use hang::{HangFeedback, HangSignatureMetadata};
+pub use leak::{LeakFeedback, LeakReportMetadata};
 use libafl_bolts::{
     Named,
     tuples::{Handle, Handled, MatchName, MatchNameRef},
 };
 pub use list::*;
 pub use map::*;
+#[cfg(feature = "std")]
+pub use memory::{MaxMemoryFeedback, MemoryLimitFeedback, MemoryUsageMetadata};
 #[cfg(feature = "nautilus")]
 pub use nautilus::*;
 #[cfg(feature = "std")]
 pub use new_hash_feedback::NewHashFeedback;
 #[cfg(feature = "std")]
 pub use new_hash_feedback::NewHashFeedbackMetadata;
+pub use perf::{HitCountsMetadata, PerfFeedback};
+pub use protocol::ProtocolStateFeedback;
+pub use sandbox::{SandboxViolationFeedback, SandboxViolationMetadata};
+#[cfg(feature = "regex")]
+pub use sanitizer::{SanitizerFeedback, SanitizerReportMetadata};
 use serde::{Deserialize, Serialize};
+pub use slowest_input::{SlowestInputFeedback, TotalCountMetadata};
 
 use crate::{Error, corpus::Testcase, executors::ExitKind, observers::TimeObserver};
 
@@ -31,19 +44,32 @@ pub mod capture_feedback;
 pub mod bool;
 pub use bool::BoolValueFeedback;
 
+#[cfg(feature = "regex")]
+pub mod crash_report;
 #[cfg(feature = "std")]
 /// The module for `CustomFilenameToTestcaseFeedback`
 pub mod custom_filename;
 pub mod differential;
+#[cfg(feature = "std")]
+pub mod hang;
+pub mod leak;
 /// The module for list feedback
 pub mod list;
 pub mod map;
+#[cfg(feature = "std")]
+pub mod memory;
 #[cfg(feature = "nautilus")]
 pub mod nautilus;
 #[cfg(feature = "std")]
 pub mod new_hash_feedback;
+pub mod perf;
+pub mod protocol;
+pub mod sandbox;
+#[cfg(feature = "regex")]
+pub mod sanitizer;
 #[cfg(feature = "simd")]
 pub mod simd;
+pub mod slowest_input;
 #[cfg(feature = "std")]
 pub mod stdio;
 pub mod transferred;
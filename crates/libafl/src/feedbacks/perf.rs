@@ -0,0 +1,127 @@
+//! The [`PerfFeedback`] keeps the inputs that raise the maximum hit count of any map entry, like
+//! `PerfFuzz`, and records their hit counts for the [`crate::schedulers::PerfScheduler`].
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use libafl_bolts::{
+    AsIter, Named, impl_serdeany,
+    simd::MaxReducer,
+    tuples::{Handle, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, HasMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{DifferentIsNovel, Feedback, HasObserverHandle, MapFeedback, StateInitializer},
+    observers::{CanTrack, MapObserver},
+};
+
+/// The hit counts of a testcase, added by the [`PerfFeedback`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HitCountsMetadata {
+    /// The map entries that were hit, with their hit count
+    pub counts: Vec<(usize, u64)>,
+}
+
+impl_serdeany!(HitCountsMetadata);
+
+/// A [`MapFeedback`] that is interesting whenever the count of any single entry exceeds its
+/// maximum so far, like `PerfFuzz`, to find inputs with worst-case algorithmic complexity.
+///
+/// Unlike the [`crate::feedbacks::MaxMapFeedback`], it is never SIMD-accelerated, so it works with
+/// maps of any entry type. Use it on an unbucketed map, e.g. the 32-bit edge hitcounts of
+/// `libafl_targets`, together with a [`crate::schedulers::PerfScheduler`].
+/// Each new testcase gets a [`HitCountsMetadata`], use only one [`PerfFeedback`] per corpus.
+#[derive(Debug, Clone)]
+pub struct PerfFeedback<C, O> {
+    map: MapFeedback<C, DifferentIsNovel, O, MaxReducer>,
+}
+
+impl<C, O> PerfFeedback<C, O>
+where
+    C: CanTrack + AsRef<O> + Named,
+{
+    /// Creates a new [`PerfFeedback`] on the given map observer
+    #[must_use]
+    pub fn new(map_observer: &C) -> Self {
+        Self {
+            map: MapFeedback::new(map_observer),
+        }
+    }
+}
+
+impl<C, O, S> StateInitializer<S> for PerfFeedback<C, O>
+where
+    MapFeedback<C, DifferentIsNovel, O, MaxReducer>: StateInitializer<S>,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        self.map.init_state(state)
+    }
+}
+
+impl<C, EM, I, O, OT, S> Feedback<EM, I, OT, S> for PerfFeedback<C, O>
+where
+    MapFeedback<C, DifferentIsNovel, O, MaxReducer>: Feedback<EM, I, OT, S>,
+    C: AsRef<O>,
+    O: MapObserver + for<'it> AsIter<'it, Item = O::Entry>,
+    O::Entry: Into<u64>,
+    OT: MatchName,
+{
+    fn is_interesting(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        input: &I,
+        observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        self.map
+            .is_interesting(state, manager, input, observers, exit_kind)
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.map.last_result()
+    }
+
+    fn append_metadata(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        self.map
+            .append_metadata(state, manager, observers, testcase)?;
+        let observer = observers
+            .get(self.map.observer_handle())
+            .ok_or_else(|| Error::illegal_state("The map observer is missing"))?
+            .as_ref();
+        let initial = observer.initial();
+        let counts = observer
+            .as_iter()
+            .map(|x| *x)
+            .enumerate()
+            .filter(|(_, count)| *count != initial)
+            .map(|(entry, count)| (entry, count.into()))
+            .collect();
+        testcase.add_metadata(HitCountsMetadata { counts });
+        Ok(())
+    }
+}
+
+impl<C, O> Named for PerfFeedback<C, O> {
+    fn name(&self) -> &Cow<'static, str> {
+        self.map.name()
+    }
+}
+
+impl<C, O> HasObserverHandle for PerfFeedback<C, O> {
+    type Observer = C;
+
+    fn observer_handle(&self) -> &Handle<C> {
+        self.map.observer_handle()
+    }
+}
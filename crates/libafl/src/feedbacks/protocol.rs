@@ -0,0 +1,332 @@
+//! Feedback for stateful targets, rewarding new protocol states and state transitions.
+//!
+//! Works with the states recorded by a [`ProtocolStateObserver`], in the spirit of `AFLNet`.
+//! The states can also be extracted from captured target output, such as the responses collected
+//! by a [`ResponseObserver`] or the stdout collected by a [`crate::observers::StdOutObserver`].
+
+use alloc::{borrow::Cow, vec::Vec};
+use core::marker::PhantomData;
+
+use hashbrown::HashSet;
+use libafl_bolts::{
+    Error, Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::observers::{
+    ResponseObserver,
+    stdio::{OutputObserver, ResponseMarker},
+};
+use crate::{
+    HasMetadata, HasNamedMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    observers::protocol::{ProtocolStateObserver, ResponseCodeExtractor},
+};
+
+/// The protocol states and state transitions seen so far, stored in the state.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct ProtocolStateMetadata {
+    /// All states seen so far
+    pub states: HashSet<u32>,
+    /// All transitions between two consecutive states seen so far
+    pub transitions: HashSet<(u32, u32)>,
+}
+
+impl_serdeany!(ProtocolStateMetadata);
+
+/// The protocol states a testcase went through, stored in the testcase.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct ProtocolStateTestcaseMetadata {
+    /// The sequence of states observed when the testcase was added
+    pub states: Vec<u32>,
+    /// The number of distinct states in [`Self::states`]
+    pub depth: usize,
+}
+
+impl_serdeany!(ProtocolStateTestcaseMetadata);
+
+/// A feedback considering an input interesting if it reached a new protocol state or a new
+/// transition between two states.
+///
+/// The states of each added testcase are stored in a [`ProtocolStateTestcaseMetadata`], so that
+/// schedulers can prefer sequences that reach deeper states, see
+/// [`crate::schedulers::ProtocolStateScheduler`].
+///
+/// `T` is the marker of the [`crate::observers::stdio::OutputObserver`] the states are additionally
+/// extracted from, if any.
+#[derive(Debug, Clone)]
+pub struct ProtocolStateFeedback<E, T = ()> {
+    observer_handle: Handle<ProtocolStateObserver<E>>,
+    #[cfg(feature = "std")]
+    output_handle: Option<Handle<OutputObserver<T>>>,
+    states: Vec<u32>,
+    novel_states: Vec<u32>,
+    novel_transitions: Vec<(u32, u32)>,
+    phantom: PhantomData<T>,
+}
+
+impl<E> ProtocolStateFeedback<E> {
+    /// Creates a new [`ProtocolStateFeedback`] for the given [`ProtocolStateObserver`].
+    #[must_use]
+    pub fn new(observer: &ProtocolStateObserver<E>) -> Self {
+        Self {
+            observer_handle: observer.handle(),
+            #[cfg(feature = "std")]
+            output_handle: None,
+            states: Vec::new(),
+            novel_states: Vec::new(),
+            novel_transitions: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new [`ProtocolStateFeedback`] that additionally extracts states from the
+    /// responses captured by the given [`ResponseObserver`], using the extractor of `observer`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn with_responses(
+        observer: &ProtocolStateObserver<E>,
+        responses: &ResponseObserver,
+    ) -> ProtocolStateFeedback<E, ResponseMarker> {
+        ProtocolStateFeedback::with_output_observer(observer, responses)
+    }
+}
+
+impl<E, T> ProtocolStateFeedback<E, T> {
+    /// Creates a new [`ProtocolStateFeedback`] that additionally extracts states from the output
+    /// captured by the given [`OutputObserver`], e.g. the target's stdout, using the extractor of
+    /// `observer`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn with_output_observer(
+        observer: &ProtocolStateObserver<E>,
+        output: &OutputObserver<T>,
+    ) -> Self {
+        Self {
+            observer_handle: observer.handle(),
+            output_handle: Some(output.handle()),
+            states: Vec::new(),
+            novel_states: Vec::new(),
+            novel_transitions: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Collects the states of the last execution into `self.states`.
+    fn collect_states<OT>(&mut self, observers: &OT) -> Result<(), Error>
+    where
+        E: ResponseCodeExtractor,
+        OT: MatchName,
+        T: 'static,
+    {
+        let observer = observers
+            .get(&self.observer_handle)
+            .ok_or_else(|| Error::illegal_state("ProtocolStateObserver is missing"))?;
+        self.states.clear();
+        self.states.extend_from_slice(observer.states());
+        #[cfg(feature = "std")]
+        if let Some(output_handle) = &self.output_handle {
+            let output = observers
+                .get(output_handle)
+                .ok_or_else(|| Error::illegal_state("OutputObserver is missing"))?;
+            if let Some(output) = &output.output {
+                observer.extractor().extract_codes(output, &mut self.states);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_novel(&self) -> bool {
+        !self.novel_states.is_empty() || !self.novel_transitions.is_empty()
+    }
+}
+
+impl<E, S, T> StateInitializer<S> for ProtocolStateFeedback<E, T>
+where
+    S: HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata_checked(self.name(), ProtocolStateMetadata::default())?;
+        Ok(())
+    }
+}
+
+impl<E, EM, I, OT, S, T> Feedback<EM, I, OT, S> for ProtocolStateFeedback<E, T>
+where
+    E: ResponseCodeExtractor,
+    OT: MatchName,
+    S: HasNamedMetadata,
+    T: 'static,
+{
+    fn is_interesting(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        self.collect_states(observers)?;
+        let history = state
+            .named_metadata_map()
+            .get::<ProtocolStateMetadata>(self.name())
+            .ok_or_else(|| Error::illegal_state("ProtocolStateMetadata is missing"))?;
+
+        self.novel_states.clear();
+        self.novel_transitions.clear();
+
+        let states = &self.states;
+        for &s in states {
+            if !history.states.contains(&s) && !self.novel_states.contains(&s) {
+                self.novel_states.push(s);
+            }
+        }
+        for pair in states.windows(2) {
+            let transition = (pair[0], pair[1]);
+            if !history.transitions.contains(&transition)
+                && !self.novel_transitions.contains(&transition)
+            {
+                self.novel_transitions.push(transition);
+            }
+        }
+
+        Ok(self.is_novel())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        Ok(self.is_novel())
+    }
+
+    fn append_metadata(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let history = state
+            .named_metadata_map_mut()
+            .get_mut::<ProtocolStateMetadata>(self.observer_handle.name())
+            .ok_or_else(|| Error::illegal_state("ProtocolStateMetadata is missing"))?;
+        history.states.extend(self.novel_states.drain(..));
+        history.transitions.extend(self.novel_transitions.drain(..));
+
+        let states = self.states.clone();
+        let depth = states.iter().collect::<HashSet<_>>().len();
+        testcase.add_metadata(ProtocolStateTestcaseMetadata { states, depth });
+
+        Ok(())
+    }
+}
+
+impl<E, T> Named for ProtocolStateFeedback<E, T> {
+    #[inline]
+    fn name(&self) -> &Cow<'static, str> {
+        self.observer_handle.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::*;
+    use crate::{
+        events::NopEventManager,
+        inputs::BytesInput,
+        observers::{ObserversTuple as _, protocol::LineCodeExtractor},
+        state::NopState,
+    };
+
+    #[test]
+    fn test_protocol_state_feedback() {
+        let observer = ProtocolStateObserver::new("states", LineCodeExtractor::new());
+        let mut feedback = ProtocolStateFeedback::new(&observer);
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![]);
+        feedback.init_state(&mut state).unwrap();
+        let mut observers = tuple_list!(observer);
+
+        observers.pre_exec_all(&mut state, &input).unwrap();
+        observers.0.observe_output(b"220\n331\n");
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap()
+        );
+        let mut testcase = Testcase::new(input.clone());
+        feedback
+            .append_metadata(&mut state, &mut mgr, &observers, &mut testcase)
+            .unwrap();
+        assert_eq!(
+            testcase
+                .metadata::<ProtocolStateTestcaseMetadata>()
+                .unwrap()
+                .depth,
+            2
+        );
+
+        // the states of the previous execution are cleared, so the same responses are not novel
+        observers.pre_exec_all(&mut state, &input).unwrap();
+        assert!(observers.0.states().is_empty());
+        observers.0.observe_output(b"220\n331\n");
+        assert!(
+            !feedback
+                .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap()
+        );
+
+        // same states, new transition
+        observers.pre_exec_all(&mut state, &input).unwrap();
+        observers.0.observe_output(b"331\n220\n");
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_protocol_state_feedback_responses() {
+        let observer = ProtocolStateObserver::new("states", LineCodeExtractor::new());
+        let mut responses = ResponseObserver::new("responses".into()).unwrap();
+        let mut feedback = ProtocolStateFeedback::with_responses(&observer, &responses);
+        let mut state = NopState::<BytesInput>::new();
+        let mut mgr = NopEventManager::new();
+        let input = BytesInput::new(vec![]);
+        feedback.init_state(&mut state).unwrap();
+
+        responses.observe(b"220 ready\r\n500 unknown\r\n".to_vec());
+        let observers = tuple_list!(observer, responses);
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut mgr, &input, &observers, &ExitKind::Ok)
+                .unwrap()
+        );
+        let mut testcase = Testcase::new(input.clone());
+        feedback
+            .append_metadata(&mut state, &mut mgr, &observers, &mut testcase)
+            .unwrap();
+        assert_eq!(
+            testcase
+                .metadata::<ProtocolStateTestcaseMetadata>()
+                .unwrap()
+                .states,
+            [220, 500]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! The [`SandboxViolationFeedback`] turns seccomp violations into objectives.
+
+use alloc::{borrow::Cow, string::String};
+
+use libafl_bolts::{
+    Error, Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    HasMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    observers::SandboxObserver,
+};
+
+/// Metadata describing the sandbox violation of a testcase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxViolationMetadata {
+    /// The description of the violated seccomp filter, e.g. "tried to execve"
+    pub violation: String,
+}
+
+impl_serdeany!(SandboxViolationMetadata);
+
+/// A feedback that is interesting if the target violated the seccomp filter of its sandbox.
+///
+/// Use it as objective, next to or instead of a `CrashFeedback`, to report violations as a distinct
+/// kind of bug.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxViolationFeedback {
+    o_ref: Handle<SandboxObserver>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl SandboxViolationFeedback {
+    /// Creates a new [`SandboxViolationFeedback`] for the given [`SandboxObserver`].
+    #[must_use]
+    pub fn new(observer: &SandboxObserver) -> Self {
+        Self {
+            o_ref: observer.handle(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+}
+
+impl<S> StateInitializer<S> for SandboxViolationFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for SandboxViolationFeedback
+where
+    OT: MatchName,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let observer = observers
+            .get(&self.o_ref)
+            .ok_or_else(|| Error::illegal_state("SandboxObserver is missing"))?;
+        let interesting = observer.violation().is_some();
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(interesting);
+        }
+        Ok(interesting)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(violation) = observers
+            .get(&self.o_ref)
+            .and_then(|observer| observer.violation())
+        {
+            testcase.add_metadata(SandboxViolationMetadata {
+                violation: violation.into(),
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(Error::illegal_state(
+            "No last result set in `SandboxViolationFeedback`. Either `is_interesting` has never been called or the fuzzer restarted in the meantime.",
+        ))
+    }
+}
+
+impl Named for SandboxViolationFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        self.o_ref.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::{SandboxViolationFeedback, SandboxViolationMetadata};
+    use crate::{
+        HasMetadata,
+        corpus::Testcase,
+        executors::ExitKind,
+        feedbacks::Feedback,
+        inputs::BytesInput,
+        observers::{Observer, SandboxObserver},
+        state::NopState,
+    };
+
+    #[test]
+    fn test_sandbox_violation_feedback() {
+        let mut observer = SandboxObserver::new("sandbox");
+        let mut feedback = SandboxViolationFeedback::new(&observer);
+        let mut state: NopState<BytesInput> = NopState::new();
+        let input = BytesInput::new(vec![]);
+
+        observer.observe("tried to execve".into());
+        let observers = tuple_list!(observer);
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Crash)
+                .unwrap()
+        );
+        let mut testcase = Testcase::new(input.clone());
+        feedback
+            .append_metadata(&mut state, &mut (), &observers, &mut testcase)
+            .unwrap();
+        assert_eq!(
+            testcase
+                .metadata::<SandboxViolationMetadata>()
+                .unwrap()
+                .violation,
+            "tried to execve"
+        );
+
+        let (mut observer, ()) = observers;
+        observer.pre_exec(&mut state, &input).unwrap();
+        let observers = tuple_list!(observer);
+        assert!(
+            !feedback
+                .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Crash)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! The [`SanitizerFeedback`] reports runs a sanitizer found a bug in, filtered by bug type.
+
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+
+use libafl_bolts::{
+    Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    Error, HasMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverHandle, StateInitializer},
+    observers::{SanitizerObserver, SanitizerReport},
+};
+
+/// The sanitizer report of a testcase, added by the [`SanitizerFeedback`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanitizerReportMetadata {
+    /// The parsed report
+    pub report: SanitizerReport,
+}
+
+impl_serdeany!(SanitizerReportMetadata);
+
+/// A feedback that is interesting if the [`SanitizerObserver`] found a report of a bug type
+/// that is not filtered out.
+///
+/// Sanitizers like the `UndefinedBehaviorSanitizer` keep running after a report by default, so the
+/// run may even end with [`ExitKind::Ok`]. Use it as objective, together with a
+/// [`crate::feedbacks::NewHashFeedback`] on the same observer to only report each bug once.
+#[derive(Debug, Clone)]
+pub struct SanitizerFeedback {
+    name: Cow<'static, str>,
+    o_ref: Handle<SanitizerObserver>,
+    /// If set, only these bug types are reported
+    only: Option<Vec<String>>,
+    ignored: Vec<String>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl SanitizerFeedback {
+    /// Creates a new [`SanitizerFeedback`], reporting all bug types
+    #[must_use]
+    pub fn new(observer: &SanitizerObserver) -> Self {
+        Self {
+            name: Cow::from(format!("sanitizerfeedback_{}", observer.name())),
+            o_ref: observer.handle(),
+            only: None,
+            ignored: Vec::new(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+
+    /// Only reports the given bug types, e.g. `null` or `use-of-uninitialized-value`
+    #[must_use]
+    pub fn only<B, T>(mut self, bug_types: B) -> Self
+    where
+        B: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.only = Some(bug_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Does not report the given bug type, e.g. `signed-integer-overflow`
+    #[must_use]
+    pub fn ignore<T>(mut self, bug_type: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.ignored.push(bug_type.into());
+        self
+    }
+
+    /// If reports of this bug type are interesting
+    #[must_use]
+    pub fn reports(&self, bug_type: &str) -> bool {
+        self.only
+            .as_ref()
+            .is_none_or(|only| only.iter().any(|b| b == bug_type))
+            && !self.ignored.iter().any(|b| b == bug_type)
+    }
+}
+
+impl<S> StateInitializer<S> for SanitizerFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for SanitizerFeedback
+where
+    OT: MatchName,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let observer = observers
+            .get(&self.o_ref)
+            .ok_or_else(|| Error::illegal_state("The sanitizer observer is missing"))?;
+        let res = observer
+            .report()
+            .is_some_and(|report| self.reports(&report.bug_type));
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(report) = observers
+            .get(&self.o_ref)
+            .and_then(SanitizerObserver::report)
+        {
+            testcase.add_metadata(SanitizerReportMetadata {
+                report: report.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl Named for SanitizerFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl HasObserverHandle for SanitizerFeedback {
+    type Observer = SanitizerObserver;
+
+    fn observer_handle(&self) -> &Handle<SanitizerObserver> {
+        &self.o_ref
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::SanitizerFeedback;
+    use crate::{
+        executors::ExitKind,
+        feedbacks::Feedback,
+        inputs::BytesInput,
+        observers::{Observer, Sanitizer, SanitizerObserver},
+        state::NopState,
+    };
+
+    #[test]
+    fn test_sanitizer_feedback() {
+        let observer = SanitizerObserver::new("ubsan", Sanitizer::Undefined);
+        let mut feedback = SanitizerFeedback::new(&observer).ignore("signed-integer-overflow");
+        let mut state: NopState<BytesInput> = NopState::new();
+        let input = BytesInput::new(vec![]);
+        let mut observers = tuple_list!(observer);
+
+        for (output, interesting) in [
+            ("", false),
+            (
+                "a.c:3:5: runtime error: signed integer overflow: 2147483647 + 1\n",
+                false,
+            ),
+            (
+                "a.c:7:9: runtime error: load of null pointer of type 'int'\n",
+                true,
+            ),
+        ] {
+            observers.0.pre_exec(&mut state, &input).unwrap();
+            observers.0.parse_output(output);
+            assert_eq!(
+                feedback
+                    .is_interesting(&mut state, &mut (), &input, &observers, &ExitKind::Ok)
+                    .unwrap(),
+                interesting
+            );
+        }
+
+        let feedback = SanitizerFeedback::new(&observers.0).only(["null"]);
+        assert!(feedback.reports("null"));
+        assert!(!feedback.reports("alignment"));
+
+        std::fs::remove_dir_all(observers.0.log_dir()).unwrap();
+    }
+}
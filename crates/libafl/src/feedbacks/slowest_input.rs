@@ -0,0 +1,224 @@
+//! The [`SlowestInputFeedback`] reports the inputs with the highest total hit count of a map, the
+//! slowest ones in executed edges or instructions, like `PerfFuzz`.
+
+use alloc::{borrow::Cow, format};
+use core::marker::PhantomData;
+
+use libafl_bolts::{
+    AsIter, Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    Error, HasMetadata, HasNamedMetadata,
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverHandle, StateInitializer},
+    observers::MapObserver,
+};
+
+/// The total hit count of a testcase, added by the [`SlowestInputFeedback`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TotalCountMetadata {
+    /// The sum of all entries of the map
+    pub total: u64,
+}
+
+impl_serdeany!(TotalCountMetadata);
+
+/// The highest total hit count seen by a [`SlowestInputFeedback`]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SlowestInputMetadata {
+    /// The highest sum of all entries of the map seen so far
+    pub max_total: u64,
+}
+
+impl_serdeany!(SlowestInputMetadata);
+
+/// A feedback that is interesting if the sum of all entries of an unbucketed hit count map is higher
+/// than in all runs before, and at least the minimum count, if set. Use it as objective to report
+/// the slowest inputs.
+#[derive(Debug, Clone)]
+pub struct SlowestInputFeedback<C, O> {
+    name: Cow<'static, str>,
+    map_ref: Handle<C>,
+    min_count: u64,
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+    phantom: PhantomData<O>,
+}
+
+impl<C, O> SlowestInputFeedback<C, O>
+where
+    C: Named,
+{
+    /// Creates a new [`SlowestInputFeedback`] on the given map observer
+    #[must_use]
+    pub fn new(map_observer: &C) -> Self {
+        Self {
+            name: Cow::from(format!("slowestinputfeedback_{}", map_observer.name())),
+            map_ref: map_observer.handle(),
+            min_count: 0,
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Only reports inputs with a total hit count of at least `min_count`
+    #[must_use]
+    pub fn min_count(mut self, min_count: u64) -> Self {
+        self.min_count = min_count;
+        self
+    }
+}
+
+impl<C, O> SlowestInputFeedback<C, O>
+where
+    C: AsRef<O>,
+    O: MapObserver + for<'it> AsIter<'it, Item = O::Entry>,
+    O::Entry: Into<u64>,
+{
+    /// The sum of all entries of the observed map
+    fn total<OT>(&self, observers: &OT) -> Result<u64, Error>
+    where
+        OT: MatchName,
+    {
+        let observer = observers
+            .get(&self.map_ref)
+            .ok_or_else(|| Error::illegal_state("The map observer is missing"))?
+            .as_ref();
+        Ok(observer
+            .as_iter()
+            .fold(0_u64, |total, count| total.saturating_add((*count).into())))
+    }
+}
+
+impl<C, O, S> StateInitializer<S> for SlowestInputFeedback<C, O>
+where
+    S: HasNamedMetadata,
+{
+    fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
+        state.add_named_metadata_checked(&self.name, SlowestInputMetadata::default())?;
+        Ok(())
+    }
+}
+
+impl<C, EM, I, O, OT, S> Feedback<EM, I, OT, S> for SlowestInputFeedback<C, O>
+where
+    C: AsRef<O>,
+    O: MapObserver + for<'it> AsIter<'it, Item = O::Entry>,
+    O::Entry: Into<u64>,
+    OT: MatchName,
+    S: HasNamedMetadata,
+{
+    fn is_interesting(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let total = self.total(observers)?;
+
+        let metadata = state.named_metadata_mut::<SlowestInputMetadata>(&self.name)?;
+        let res = total >= self.min_count && total > metadata.max_total;
+        if res {
+            metadata.max_total = total;
+        }
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let total = self.total(observers)?;
+        testcase.add_metadata(TotalCountMetadata { total });
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl<C, O> Named for SlowestInputFeedback<C, O> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<C, O> HasObserverHandle for SlowestInputFeedback<C, O> {
+    type Observer = C;
+
+    fn observer_handle(&self) -> &Handle<C> {
+        &self.map_ref
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::tuples::tuple_list;
+
+    use super::SlowestInputFeedback;
+    use crate::{
+        corpus::Testcase,
+        events::NopEventManager,
+        executors::ExitKind,
+        feedbacks::{Feedback, PerfFeedback, StateInitializer},
+        observers::StdMapObserver,
+        state::NopState,
+    };
+
+    #[test]
+    fn test_slowest_input_feedback() {
+        let mut observer = StdMapObserver::owned("counts", vec![0_u32; 4]);
+        let mut slowest = SlowestInputFeedback::new(&observer).min_count(10);
+        let mut perf = PerfFeedback::new(&observer);
+        let mut state: NopState<()> = NopState::new();
+        slowest.init_state(&mut state).unwrap();
+        perf.init_state(&mut state).unwrap();
+
+        // (counts, new maximum hit count of an edge, new slowest input)
+        for (run, new_max, slower) in [
+            ([1, 2, 3, 0], true, false),
+            ([1, 2, 3, 0], false, false),
+            ([0, 2, 9, 0], true, true),
+            ([9, 1, 1, 0], true, false),
+            ([0, 0, 0, 30], true, true),
+        ] {
+            observer.copy_from_slice(&run);
+            let observers = tuple_list!(observer.clone());
+            let mut manager = NopEventManager::new();
+            assert_eq!(
+                perf.is_interesting(&mut state, &mut manager, &(), &observers, &ExitKind::Ok)
+                    .unwrap(),
+                new_max
+            );
+            if new_max {
+                let mut testcase = Testcase::new(());
+                perf.append_metadata(&mut state, &mut manager, &observers, &mut testcase)
+                    .unwrap();
+            }
+            assert_eq!(
+                slowest
+                    .is_interesting(&mut state, &mut manager, &(), &observers, &ExitKind::Ok)
+                    .unwrap(),
+                slower
+            );
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! An input made of a sequence of messages, for stateful targets such as network servers.
+//!
+//! A [`MessageSequenceInput`] is a [`ListInput`] whose parts are sent to the target one after the
+//! other, in the spirit of `AFLNet`, so their order is meaningful.
+//! Use the message-level mutators of [`crate::mutators::messages`] to insert, duplicate, delete or
+//! reorder messages, and [`ListInput::map_to_mutate_on_random_part`] to havoc within a single
+//! message.
+
+use crate::inputs::{BytesInput, ListInput};
+
+/// A sequence of messages, e.g. the requests sent to a stateful network server in order.
+pub type MessageSequenceInput<I = BytesInput> = ListInput<I>;
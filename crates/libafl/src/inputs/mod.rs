@@ -18,6 +18,11 @@ pub use generalized::*;
 pub mod bytessub;
 pub use bytessub::BytesSubInput;
 
+#[cfg(feature = "multipart_inputs")]
+pub mod messages;
+#[cfg(feature = "multipart_inputs")]
+pub use messages::MessageSequenceInput;
+
 #[cfg(feature = "multipart_inputs")]
 pub mod multi;
 #[cfg(feature = "multipart_inputs")]
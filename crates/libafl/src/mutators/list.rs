@@ -9,7 +9,7 @@ use tuple_list::{tuple_list, tuple_list_type};
 use crate::{
     corpus::Corpus,
     generators::Generator,
-    inputs::{Input, ListInput, multi::MultipartInput},
+    inputs::{Input, ListInput},
     mutators::{MutationResult, Mutator},
     random_corpus_id,
     state::{HasCorpus, HasMaxSize, HasRand},
@@ -76,20 +76,17 @@ impl<G> Named for GenerateToAppendMutator<G> {
     }
 }
 
-/// Mutator that removes the last entry from a [`MultipartInput`].
+/// Mutator that removes the last entry from a [`ListInput`].
 ///
 /// Returns [`MutationResult::Skipped`] if the input is empty.
 #[derive(Debug)]
 pub struct RemoveLastEntryMutator;
 
-impl<I, K, S> Mutator<MultipartInput<I, K>, S> for RemoveLastEntryMutator
-where
-    K: Default,
-{
+impl<I, S> Mutator<ListInput<I>, S> for RemoveLastEntryMutator {
     fn mutate(
         &mut self,
         _state: &mut S,
-        input: &mut MultipartInput<I, K>,
+        input: &mut ListInput<I>,
     ) -> Result<MutationResult, Error> {
         match input.pop_part() {
             Some(_) => Ok(MutationResult::Mutated),
@@ -112,22 +109,18 @@ impl Named for RemoveLastEntryMutator {
     }
 }
 
-/// Mutator that removes a random entry from a [`MultipartInput`].
+/// Mutator that removes a random entry from a [`ListInput`].
 ///
 /// Returns [`MutationResult::Skipped`] if the input is empty.
 #[derive(Debug)]
 pub struct RemoveRandomEntryMutator;
 
-impl<I, K, S> Mutator<MultipartInput<I, K>, S> for RemoveRandomEntryMutator
+impl<I, S> Mutator<ListInput<I>, S> for RemoveRandomEntryMutator
 where
     S: HasRand,
 {
-    fn mutate(
-        &mut self,
-        state: &mut S,
-        input: &mut MultipartInput<I, K>,
-    ) -> Result<MutationResult, Error> {
-        match MultipartInput::len(input) {
+    fn mutate(&mut self, state: &mut S, input: &mut ListInput<I>) -> Result<MutationResult, Error> {
+        match input.len() {
             0 => Ok(MutationResult::Skipped),
             len => {
                 // Safety: null checks are done above
@@ -155,21 +148,16 @@ impl Named for RemoveRandomEntryMutator {
     }
 }
 
-/// Mutator that inserts a random part from another [`MultipartInput`] into the current input.
+/// Mutator that inserts a random part from another [`ListInput`] into the current input.
 #[derive(Debug)]
 pub struct CrossoverInsertMutator;
 
-impl<I, K, S> Mutator<MultipartInput<I, K>, S> for CrossoverInsertMutator
+impl<I, S> Mutator<ListInput<I>, S> for CrossoverInsertMutator
 where
-    S: HasCorpus<MultipartInput<I, K>> + HasMaxSize + HasRand,
+    S: HasCorpus<ListInput<I>> + HasMaxSize + HasRand,
     I: Clone,
-    K: Clone,
 {
-    fn mutate(
-        &mut self,
-        state: &mut S,
-        input: &mut MultipartInput<I, K>,
-    ) -> Result<MutationResult, Error> {
+    fn mutate(&mut self, state: &mut S, input: &mut ListInput<I>) -> Result<MutationResult, Error> {
         let current_idx = match input.len() {
             0 => return Ok(MutationResult::Skipped),
             len => state
@@ -184,12 +172,12 @@ where
 
         let other_len = other.len();
 
-        let (key, part) = match other_len {
+        let part = match other_len {
             0 => return Ok(MutationResult::Skipped),
             len => other.parts()[other_idx_raw % len].clone(),
         };
 
-        input.insert_part(current_idx, (key, part));
+        input.insert_part(current_idx, part);
         Ok(MutationResult::Mutated)
     }
     #[inline]
@@ -208,21 +196,16 @@ impl Named for CrossoverInsertMutator {
     }
 }
 
-/// Mutator that replaces a random part from the current [`MultipartInput`] with a random part from another input.
+/// Mutator that replaces a random part from the current [`ListInput`] with a random part from another input.
 #[derive(Debug)]
 pub struct CrossoverReplaceMutator;
 
-impl<I, K, S> Mutator<MultipartInput<I, K>, S> for CrossoverReplaceMutator
+impl<I, S> Mutator<ListInput<I>, S> for CrossoverReplaceMutator
 where
-    S: HasCorpus<MultipartInput<I, K>> + HasMaxSize + HasRand,
+    S: HasCorpus<ListInput<I>> + HasMaxSize + HasRand,
     I: Clone,
-    K: Clone,
 {
-    fn mutate(
-        &mut self,
-        state: &mut S,
-        input: &mut MultipartInput<I, K>,
-    ) -> Result<MutationResult, Error> {
+    fn mutate(&mut self, state: &mut S, input: &mut ListInput<I>) -> Result<MutationResult, Error> {
         let current_idx = match input.len() {
             0 => return Ok(MutationResult::Skipped),
             len => state
@@ -237,13 +220,13 @@ where
 
         let other_len = other.len();
 
-        let (key, part) = match other_len {
+        let part = match other_len {
             0 => return Ok(MutationResult::Skipped),
             len => other.parts()[other_idx_raw % len].clone(),
         };
 
         input.remove_part_at_index(current_idx);
-        input.insert_part(current_idx, (key, part));
+        input.insert_part(current_idx, part);
         Ok(MutationResult::Mutated)
     }
     #[inline]
@@ -261,3 +244,84 @@ impl Named for CrossoverReplaceMutator {
         &Cow::Borrowed("CrossoverReplaceMutator")
     }
 }
+
+/// Mutator that duplicates a random entry of a [`ListInput`], inserting the copy right after the
+/// original.
+///
+/// Returns [`MutationResult::Skipped`] if the input is empty.
+#[derive(Debug)]
+pub struct DuplicateRandomEntryMutator;
+
+impl<I, S> Mutator<ListInput<I>, S> for DuplicateRandomEntryMutator
+where
+    S: HasRand,
+    I: Clone,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut ListInput<I>) -> Result<MutationResult, Error> {
+        let Some(len) = NonZero::new(input.len()) else {
+            return Ok(MutationResult::Skipped);
+        };
+        let index = state.rand_mut().below(len);
+        let part = input.parts()[index].clone();
+        input.insert_part(index + 1, part);
+        Ok(MutationResult::Mutated)
+    }
+    #[inline]
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _new_corpus_id: Option<crate::corpus::CorpusId>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Named for DuplicateRandomEntryMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        &Cow::Borrowed("DuplicateRandomEntryMutator")
+    }
+}
+
+/// Mutator that reorders a [`ListInput`] by swapping two random entries.
+///
+/// Returns [`MutationResult::Skipped`] if the input has less than two entries.
+#[derive(Debug)]
+pub struct SwapRandomEntriesMutator;
+
+impl<I, S> Mutator<ListInput<I>, S> for SwapRandomEntriesMutator
+where
+    S: HasRand,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut ListInput<I>) -> Result<MutationResult, Error> {
+        let len = input.len();
+        if len < 2 {
+            return Ok(MutationResult::Skipped);
+        }
+        // Safety: len is at least 2
+        let first = state
+            .rand_mut()
+            .below(unsafe { NonZero::new_unchecked(len) });
+        let mut second = state
+            .rand_mut()
+            .below(unsafe { NonZero::new_unchecked(len - 1) });
+        if second >= first {
+            second += 1;
+        }
+        input.parts_mut().swap(first, second);
+        Ok(MutationResult::Mutated)
+    }
+    #[inline]
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _new_corpus_id: Option<crate::corpus::CorpusId>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Named for SwapRandomEntriesMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        &Cow::Borrowed("SwapRandomEntriesMutator")
+    }
+}
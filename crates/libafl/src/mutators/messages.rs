@@ -0,0 +1,128 @@
+//! Message-level mutators for [`MessageSequenceInput`](crate::inputs::MessageSequenceInput)s, built
+//! from the [`crate::mutators::list`] mutators. See [`crate::inputs::messages`] for details.
+
+use tuple_list::{tuple_list, tuple_list_type};
+
+use crate::mutators::list::{
+    CrossoverInsertMutator, DuplicateRandomEntryMutator, RemoveRandomEntryMutator,
+    SwapRandomEntriesMutator,
+};
+
+/// The message-level mutators for a [`crate::inputs::MessageSequenceInput`].
+pub type MessageSequenceMutators = tuple_list_type!(
+    CrossoverInsertMutator,
+    DuplicateRandomEntryMutator,
+    RemoveRandomEntryMutator,
+    SwapRandomEntriesMutator
+);
+
+/// Create the message-level mutators for a [`crate::inputs::MessageSequenceInput`]: insert a message from another
+/// sequence, duplicate, delete or reorder messages.
+///
+/// To havoc within a single message, combine them with mutators mapped through
+/// [`crate::inputs::ListInput::map_to_mutate_on_random_part`].
+#[must_use]
+pub fn message_sequence_mutations() -> MessageSequenceMutators {
+    tuple_list!(
+        CrossoverInsertMutator,
+        DuplicateRandomEntryMutator,
+        RemoveRandomEntryMutator,
+        SwapRandomEntriesMutator
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::rands::StdRand;
+
+    use super::*;
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus},
+        feedbacks::ConstFeedback,
+        inputs::{BytesInput, MessageSequenceInput},
+        mutators::{MutationResult, Mutator, MutatorsTuple as _, havoc_mutations_no_crossover},
+        state::StdState,
+    };
+
+    type TestInput = MessageSequenceInput<BytesInput>;
+
+    fn sequence(messages: &[&[u8]]) -> TestInput {
+        MessageSequenceInput::from(messages.iter().map(|m| BytesInput::new(m.to_vec())))
+    }
+
+    fn test_state()
+    -> StdState<InMemoryCorpus<TestInput>, TestInput, StdRand, InMemoryCorpus<TestInput>> {
+        let mut corpus = InMemoryCorpus::new();
+        corpus.add(sequence(&[b"QUIT"]).into()).unwrap();
+
+        StdState::new(
+            StdRand::with_seed(1337),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut ConstFeedback::new(false),
+            &mut ConstFeedback::new(false),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_message_insert() {
+        let mut state = test_state();
+        let mut input = sequence(&[b"USER a", b"PASS b"]);
+        assert_eq!(
+            CrossoverInsertMutator
+                .mutate(&mut state, &mut input)
+                .unwrap(),
+            MutationResult::Mutated
+        );
+        assert_eq!(input.len(), 3);
+        assert!(input.parts().contains(&BytesInput::new(b"QUIT".to_vec())));
+    }
+
+    #[test]
+    fn test_message_duplicate_delete_swap() {
+        let mut state = test_state();
+        let mut input = sequence(&[b"USER a", b"PASS b"]);
+
+        DuplicateRandomEntryMutator
+            .mutate(&mut state, &mut input)
+            .unwrap();
+        assert_eq!(input.len(), 3);
+
+        RemoveRandomEntryMutator
+            .mutate(&mut state, &mut input)
+            .unwrap();
+        assert_eq!(input.len(), 2);
+
+        let mut input = sequence(&[b"USER a", b"PASS b"]);
+        SwapRandomEntriesMutator
+            .mutate(&mut state, &mut input)
+            .unwrap();
+        assert_eq!(input.parts(), sequence(&[b"PASS b", b"USER a"]).parts());
+
+        let mut input = sequence(&[]);
+        for result in [
+            DuplicateRandomEntryMutator.mutate(&mut state, &mut input),
+            RemoveRandomEntryMutator.mutate(&mut state, &mut input),
+            SwapRandomEntriesMutator.mutate(&mut state, &mut input),
+        ] {
+            assert_eq!(result.unwrap(), MutationResult::Skipped);
+        }
+    }
+
+    #[test]
+    fn test_message_havoc() {
+        let mut state = test_state();
+        let mut mutators = TestInput::map_to_mutate_on_random_part(havoc_mutations_no_crossover());
+        let mut input = sequence(&[b"USER a", b"PASS b"]);
+        let before = input.clone();
+        let mut mutated = false;
+        for _ in 0..16 {
+            mutated |=
+                mutators.mutate_all(&mut state, &mut input).unwrap() == MutationResult::Mutated;
+        }
+        assert!(mutated);
+        assert_eq!(input.len(), 2);
+        assert_ne!(input.parts(), before.parts());
+    }
+}
@@ -27,6 +27,10 @@ pub mod mapping;
 pub use mapping::*;
 pub mod tuneable;
 pub use tuneable::*;
+#[cfg(feature = "multipart_inputs")]
+pub mod messages;
+#[cfg(feature = "multipart_inputs")]
+pub use messages::{MessageSequenceMutators, message_sequence_mutations};
 
 #[cfg(feature = "lua_mutator")]
 pub mod lua;
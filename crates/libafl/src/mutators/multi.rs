@@ -1,11 +1,13 @@
 //! Mutator definitions for [`MultipartInput`]s. See [`crate::inputs::multi`] for details.
 
+use alloc::borrow::Cow;
 use core::{
     cmp::{Ordering, min},
+    fmt::Debug,
     num::NonZero,
 };
 
-use libafl_bolts::{Error, rands::Rand};
+use libafl_bolts::{Error, Named, rands::Rand};
 
 use crate::{
     corpus::{Corpus, CorpusId},
@@ -398,3 +400,409 @@ where
         Ok(())
     }
 }
+
+/// Mutator that replaces a random part of the current [`MultipartInput`] with a part of the
+/// same key taken from another testcase.
+///
+/// Unlike the byte-level crossover mutators, the whole part is swapped, so the structure of the
+/// part is preserved. Returns [`MutationResult::Skipped`] if the other testcase has no part with
+/// the chosen key.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeyedPartCrossoverMutator;
+
+impl KeyedPartCrossoverMutator {
+    /// Create a new [`KeyedPartCrossoverMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<I, K, S> Mutator<MultipartInput<I, K>, S> for KeyedPartCrossoverMutator
+where
+    S: HasCorpus<MultipartInput<I, K>> + HasRand,
+    I: Clone,
+    K: Clone + PartialEq,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I, K>,
+    ) -> Result<MutationResult, Error> {
+        let Some(len) = NonZero::new(input.len()) else {
+            return Ok(MutationResult::Skipped);
+        };
+        let choice = state.rand_mut().below(len);
+        let part_choice = state.rand_mut().next() as usize;
+
+        let id = random_corpus_id!(state.corpus(), state.rand_mut());
+        if let Some(cur) = state.corpus().current()
+            && id == *cur
+        {
+            // Swapping a part with itself or an equal-keyed sibling is handled by other mutators.
+            return Ok(MutationResult::Skipped);
+        }
+
+        let mut other_testcase = state.corpus().get(id)?.borrow_mut();
+        let other = other_testcase.load_input(state.corpus())?;
+
+        let key = &input.parts()[choice].0;
+        let parts = other.with_key(key).count();
+        if parts == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let (_, part) = other.with_key(key).nth(part_choice % parts).unwrap();
+        input.parts_mut()[choice].1 = part.clone();
+
+        Ok(MutationResult::Mutated)
+    }
+
+    #[inline]
+    fn post_exec(&mut self, _state: &mut S, _new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Named for KeyedPartCrossoverMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        &Cow::Borrowed("KeyedPartCrossoverMutator")
+    }
+}
+
+/// Mutator that inserts a part with a given key, taken from another testcase, at a random position
+/// of the current [`MultipartInput`].
+///
+/// Returns [`MutationResult::Skipped`] if the other testcase has no part with the configured key.
+#[derive(Debug, Clone)]
+pub struct KeyedPartInsertMutator<K> {
+    key: K,
+    name: Cow<'static, str>,
+}
+
+impl<K> KeyedPartInsertMutator<K>
+where
+    K: Debug,
+{
+    /// Create a new [`KeyedPartInsertMutator`] inserting parts with the given key.
+    #[must_use]
+    pub fn new(key: K) -> Self {
+        let name = Cow::Owned(format!("KeyedPartInsertMutator<{key:?}>"));
+        Self { key, name }
+    }
+}
+
+impl<I, K, S> Mutator<MultipartInput<I, K>, S> for KeyedPartInsertMutator<K>
+where
+    S: HasCorpus<MultipartInput<I, K>> + HasRand,
+    I: Clone,
+    K: Clone + PartialEq,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I, K>,
+    ) -> Result<MutationResult, Error> {
+        let part_choice = state.rand_mut().next() as usize;
+        // `len + 1` to allow appending at the end
+        let target = state
+            .rand_mut()
+            .below(NonZero::new(input.len() + 1).unwrap());
+
+        let id = random_corpus_id!(state.corpus(), state.rand_mut());
+        let mut other_testcase = state.corpus().get(id)?.borrow_mut();
+        let other = other_testcase.load_input(state.corpus())?;
+
+        let parts = other.with_key(&self.key).count();
+        if parts == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let (_, part) = other.with_key(&self.key).nth(part_choice % parts).unwrap();
+        input.insert_part(target, (self.key.clone(), part.clone()));
+
+        Ok(MutationResult::Mutated)
+    }
+
+    #[inline]
+    fn post_exec(&mut self, _state: &mut S, _new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<K> Named for KeyedPartInsertMutator<K> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+/// Mutator that removes a random part with a given key from a [`MultipartInput`].
+///
+/// Returns [`MutationResult::Skipped`] if the input has no part with the configured key.
+#[derive(Debug, Clone)]
+pub struct KeyedPartRemoveMutator<K> {
+    key: K,
+    name: Cow<'static, str>,
+}
+
+impl<K> KeyedPartRemoveMutator<K>
+where
+    K: Debug,
+{
+    /// Create a new [`KeyedPartRemoveMutator`] removing parts with the given key.
+    #[must_use]
+    pub fn new(key: K) -> Self {
+        let name = Cow::Owned(format!("KeyedPartRemoveMutator<{key:?}>"));
+        Self { key, name }
+    }
+}
+
+impl<I, K, S> Mutator<MultipartInput<I, K>, S> for KeyedPartRemoveMutator<K>
+where
+    S: HasRand,
+    K: PartialEq,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I, K>,
+    ) -> Result<MutationResult, Error> {
+        let Some(parts) = NonZero::new(input.with_key(&self.key).count()) else {
+            return Ok(MutationResult::Skipped);
+        };
+        let part_choice = state.rand_mut().below(parts);
+        let (idx, _) = input.with_key(&self.key).nth(part_choice).unwrap();
+        input.remove_part_at_index(idx);
+
+        Ok(MutationResult::Mutated)
+    }
+
+    #[inline]
+    fn post_exec(&mut self, _state: &mut S, _new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<K> Named for KeyedPartRemoveMutator<K> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+/// Mutator that reorders a [`MultipartInput`] by swapping two random parts.
+///
+/// Returns [`MutationResult::Skipped`] if the input has less than two parts.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PartSwapMutator;
+
+impl PartSwapMutator {
+    /// Create a new [`PartSwapMutator`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<I, K, S> Mutator<MultipartInput<I, K>, S> for PartSwapMutator
+where
+    S: HasRand,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I, K>,
+    ) -> Result<MutationResult, Error> {
+        let len = input.len();
+        if len < 2 {
+            return Ok(MutationResult::Skipped);
+        }
+        // Safety: len is at least 2
+        let first = state
+            .rand_mut()
+            .below(unsafe { NonZero::new_unchecked(len) });
+        let mut second = state
+            .rand_mut()
+            .below(unsafe { NonZero::new_unchecked(len - 1) });
+        if second >= first {
+            second += 1;
+        }
+        input.parts_mut().swap(first, second);
+
+        Ok(MutationResult::Mutated)
+    }
+
+    #[inline]
+    fn post_exec(&mut self, _state: &mut S, _new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Named for PartSwapMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        &Cow::Borrowed("PartSwapMutator")
+    }
+}
+
+/// Mutator that applies an inner mutator only to parts with a given key.
+///
+/// This allows per-key mutator selection, e.g. fuzzing headers and payload of a
+/// [`MultipartInput`] with different strategies, by combining multiple [`KeyedMutator`]s in a
+/// scheduled mutator. A random part with the configured key is chosen for each mutation.
+/// Returns [`MutationResult::Skipped`] if the input has no part with the configured key.
+#[derive(Debug, Clone)]
+pub struct KeyedMutator<K, M> {
+    key: K,
+    inner: M,
+    name: Cow<'static, str>,
+}
+
+impl<K, M> KeyedMutator<K, M>
+where
+    K: Debug,
+    M: Named,
+{
+    /// Create a new [`KeyedMutator`] applying `inner` to parts with the given key.
+    #[must_use]
+    pub fn new(key: K, inner: M) -> Self {
+        let name = Cow::Owned(format!("KeyedMutator<{key:?}, {}>", inner.name()));
+        Self { key, inner, name }
+    }
+
+    /// The key of the parts this mutator applies to.
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<I, K, M, S> Mutator<MultipartInput<I, K>, S> for KeyedMutator<K, M>
+where
+    M: Mutator<I, S>,
+    S: HasRand,
+    K: PartialEq,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I, K>,
+    ) -> Result<MutationResult, Error> {
+        let Some(parts) = NonZero::new(input.with_key(&self.key).count()) else {
+            return Ok(MutationResult::Skipped);
+        };
+        let part_choice = state.rand_mut().below(parts);
+        let (_, part) = input.with_key_mut(&self.key).nth(part_choice).unwrap();
+        self.inner.mutate(state, part)
+    }
+
+    fn post_exec(&mut self, state: &mut S, new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_id)
+    }
+}
+
+impl<K, M> Named for KeyedMutator<K, M> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use libafl_bolts::rands::StdRand;
+
+    use super::*;
+    use crate::{
+        corpus::InMemoryCorpus,
+        feedbacks::ConstFeedback,
+        inputs::{BytesInput, HasTargetBytes},
+        mutators::ByteIncMutator,
+        state::StdState,
+    };
+
+    fn multipart(parts: &[(&str, &[u8])]) -> TestInput {
+        MultipartInput::from(
+            parts
+                .iter()
+                .map(|(k, v)| (String::from(*k), BytesInput::new(v.to_vec()))),
+        )
+    }
+
+    type TestInput = MultipartInput<BytesInput, String>;
+
+    fn test_state()
+    -> StdState<InMemoryCorpus<TestInput>, TestInput, StdRand, InMemoryCorpus<TestInput>> {
+        let mut corpus = InMemoryCorpus::new();
+        corpus
+            .add(multipart(&[("header", b"other"), ("payload", b"data")]).into())
+            .unwrap();
+
+        StdState::new(
+            StdRand::with_seed(1337),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut ConstFeedback::new(false),
+            &mut ConstFeedback::new(false),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_keyed_part_crossover() {
+        let mut state = test_state();
+        let mut input = multipart(&[("header", b"mine")]);
+
+        let res = KeyedPartCrossoverMutator::new()
+            .mutate(&mut state, &mut input)
+            .unwrap();
+        assert_eq!(res, MutationResult::Mutated);
+        assert_eq!(input.len(), 1);
+        assert_eq!(&*input.parts()[0].1.target_bytes(), b"other");
+    }
+
+    #[test]
+    fn test_keyed_part_insert_remove() {
+        let mut state = test_state();
+        let mut input = multipart(&[("header", b"mine")]);
+
+        let mut insert = KeyedPartInsertMutator::new(String::from("payload"));
+        assert_eq!(
+            insert.mutate(&mut state, &mut input).unwrap(),
+            MutationResult::Mutated
+        );
+        assert_eq!(input.with_key(&String::from("payload")).count(), 1);
+
+        let mut remove = KeyedPartRemoveMutator::new(String::from("header"));
+        assert_eq!(
+            remove.mutate(&mut state, &mut input).unwrap(),
+            MutationResult::Mutated
+        );
+        assert_eq!(
+            remove.mutate(&mut state, &mut input).unwrap(),
+            MutationResult::Skipped
+        );
+        assert_eq!(input.keys().collect::<Vec<_>>(), [&String::from("payload")]);
+    }
+
+    #[test]
+    fn test_part_swap_and_keyed() {
+        let mut state = test_state();
+        let mut input = multipart(&[("header", b"\x00"), ("payload", b"\x00")]);
+
+        assert_eq!(
+            PartSwapMutator::new()
+                .mutate(&mut state, &mut input)
+                .unwrap(),
+            MutationResult::Mutated
+        );
+        assert_eq!(
+            input.keys().collect::<Vec<_>>(),
+            [&String::from("payload"), &String::from("header")]
+        );
+
+        let mut keyed = KeyedMutator::new(String::from("payload"), ByteIncMutator::new());
+        keyed.mutate(&mut state, &mut input).unwrap();
+        assert_eq!(&*input.parts()[0].1.target_bytes(), b"\x01");
+        assert_eq!(&*input.parts()[1].1.target_bytes(), b"\x00");
+    }
+}
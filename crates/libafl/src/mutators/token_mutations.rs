@@ -439,6 +439,7 @@ impl TokenReplace {
 
 /// A `I2SRandReplace` [`Mutator`] replaces a random matching input-2-state comparison operand with the other.
 /// It needs a valid [`CmpValuesMetadata`] in the state.
+/// If the metadata knows the input offsets of the comparison, the replacement starts at one of them.
 #[derive(Debug, Default)]
 pub struct I2SRandReplace;
 
@@ -468,7 +469,19 @@ where
 
         let idx = state.rand_mut().below(cmps_len);
 
-        let off = state.rand_mut().below(size);
+        // Start at one of the input offsets the comparison depends on, if known
+        let taint_len = state
+            .metadata_map()
+            .get::<CmpValuesMetadata>()
+            .and_then(|meta| meta.taints.get(idx))
+            .map_or(0, Vec::len);
+        let off = if let Some(taint_len) = NonZero::new(taint_len) {
+            let taint_idx = state.rand_mut().below(taint_len);
+            let meta = state.metadata_map().get::<CmpValuesMetadata>().unwrap();
+            meta.taints[idx][taint_idx].min(size.get() - 1)
+        } else {
+            state.rand_mut().below(size)
+        };
         let len = input.mutator_bytes().len();
         let bytes = input.mutator_bytes_mut();
 
@@ -94,6 +94,10 @@ pub struct CmpValuesMetadata {
     /// A `list` of values.
     #[serde(skip)]
     pub list: Vec<CmpValues>,
+    /// The input offsets each comparison of the `list` depends on, if known, e.g. from taint
+    /// tracking. Empty, or of the same length as the `list`.
+    #[serde(skip)]
+    pub taints: Vec<Vec<usize>>,
 }
 
 libafl_bolts::impl_serdeany!(CmpValuesMetadata);
@@ -115,7 +119,10 @@ impl CmpValuesMetadata {
     /// Creates a new [`struct@CmpValuesMetadata`]
     #[must_use]
     pub fn new() -> Self {
-        Self { list: vec![] }
+        Self {
+            list: vec![],
+            taints: vec![],
+        }
     }
 
     /// Add comparisons to a metadata from a `CmpObserver`. `cmp_map` is mutable in case
@@ -126,6 +133,7 @@ impl CmpValuesMetadata {
         CM: CmpMap,
     {
         self.list.clear();
+        self.taints.clear();
         let count = usable_count;
         for i in 0..count {
             let execs = cmp_map.usable_executions_for(i);
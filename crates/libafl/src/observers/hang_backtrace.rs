@@ -0,0 +1,114 @@
+//! The [`HangBacktraceObserver`] holds the stack of a child that timed out, captured right before
+//! the executor killed it.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+use libafl_bolts::{Error, Named, hash_std};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executors::ExitKind,
+    observers::{Observer, ObserverWithHashField},
+};
+
+/// A frame of the stack of a hung child
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackFrame {
+    /// The address of the instruction, in the address space of the child
+    pub address: u64,
+    /// The file mapped at `address`, if any
+    pub module: Option<String>,
+    /// The offset of `address` in `module`, stable across runs with different base addresses
+    pub offset: u64,
+}
+
+/// Records the stack of a child that timed out. Executors fill it when configured with
+/// `hang_backtrace_observer`, see `executors::StdChildArgs`.
+///
+/// Like the `BacktraceObserver`, it offers a hash of the stack, so it can be used with a
+/// `NewHashFeedback` to deduplicate hangs by the location they hang at.
+/// The innermost frame is left out of the hash, as where exactly the child was stopped within
+/// its loop is random.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HangBacktraceObserver {
+    name: Cow<'static, str>,
+    frames: Vec<StackFrame>,
+    hash: Option<u64>,
+    exit_kind: Option<ExitKind>,
+}
+
+impl HangBacktraceObserver {
+    /// Creates a new [`HangBacktraceObserver`] with the given name.
+    #[must_use]
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Self {
+            name: name.into(),
+            frames: Vec::new(),
+            hash: None,
+            exit_kind: None,
+        }
+    }
+
+    /// The stack of the last run, innermost frame first, empty if it did not time out
+    #[must_use]
+    pub fn frames(&self) -> &[StackFrame] {
+        &self.frames
+    }
+
+    /// How the last run ended, `None` if it did not run yet
+    #[must_use]
+    pub fn last_exit_kind(&self) -> Option<ExitKind> {
+        self.exit_kind
+    }
+
+    /// Records the stack of the hung child, called by the executor.
+    pub fn observe(&mut self, frames: Vec<StackFrame>) {
+        let mut bytes = Vec::new();
+        for frame in frames.iter().skip(1) {
+            if let Some(module) = &frame.module {
+                bytes.extend_from_slice(module.as_bytes());
+            }
+            bytes.extend_from_slice(&frame.offset.to_le_bytes());
+        }
+        self.hash = Some(hash_std(&bytes));
+        self.frames = frames;
+    }
+
+    fn clear(&mut self) {
+        self.frames.clear();
+        self.hash = None;
+        self.exit_kind = None;
+    }
+}
+
+impl ObserverWithHashField for HangBacktraceObserver {
+    fn hash(&self) -> Option<u64> {
+        self.hash
+    }
+}
+
+impl<I, S> Observer<I, S> for HangBacktraceObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.clear();
+        Ok(())
+    }
+
+    fn pre_exec_child(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.clear();
+        Ok(())
+    }
+
+    fn post_exec(&mut self, _state: &mut S, _input: &I, exit_kind: &ExitKind) -> Result<(), Error> {
+        self.exit_kind = Some(*exit_kind);
+        Ok(())
+    }
+}
+
+impl Named for HangBacktraceObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
@@ -0,0 +1,127 @@
+//! The [`LeakObserver`] detects runs that allocated more than they freed, like libFuzzer's
+//! `-detect_leaks`.
+//!
+//! The allocations are reported by malloc and free hooks, e.g. the ones of `libafl_targets`,
+//! through [`record_malloc`] and [`record_free`]. As an unbalanced run is not necessarily a leak,
+//! the `LeakCheckExecutor` re-runs it to confirm the leak, e.g. with `LeakSanitizer`.
+
+use alloc::{borrow::Cow, string::String};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use libafl_bolts::{Error, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, observers::Observer};
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static MALLOCS: AtomicUsize = AtomicUsize::new(0);
+static FREES: AtomicUsize = AtomicUsize::new(0);
+
+/// Records an allocation by the target, called by malloc hooks.
+///
+/// Allocations outside of runs with a [`LeakObserver`] are ignored.
+pub fn record_malloc() {
+    if RUNNING.load(Ordering::Relaxed) {
+        MALLOCS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Records a free by the target, called by free hooks.
+///
+/// Frees outside of runs with a [`LeakObserver`] are ignored.
+pub fn record_free() {
+    if RUNNING.load(Ordering::Relaxed) {
+        FREES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The number of allocations and frees recorded so far in the current run.
+///
+/// The [`LeakObserver`] only collects them in its `post_exec`, this is for the executor to check the
+/// balance right after the run.
+#[must_use]
+pub fn current_balance() -> (usize, usize) {
+    (
+        MALLOCS.load(Ordering::Relaxed),
+        FREES.load(Ordering::Relaxed),
+    )
+}
+
+/// Records if the last run allocated more than it freed, and the report of the leak, once
+/// confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakObserver {
+    name: Cow<'static, str>,
+    mallocs: usize,
+    frees: usize,
+    report: Option<String>,
+}
+
+impl LeakObserver {
+    /// Creates a new [`LeakObserver`] with the given name.
+    #[must_use]
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Self {
+            name: name.into(),
+            mallocs: 0,
+            frees: 0,
+            report: None,
+        }
+    }
+
+    /// If the last run allocated more than it freed, so it may have leaked
+    #[must_use]
+    pub fn unbalanced(&self) -> bool {
+        self.mallocs > self.frees
+    }
+
+    /// The number of allocations and frees of the last run
+    #[must_use]
+    pub fn balance(&self) -> (usize, usize) {
+        (self.mallocs, self.frees)
+    }
+
+    /// The report of the confirmed leak of the last run, if any
+    #[must_use]
+    pub fn report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+
+    /// Records the report of a confirmed leak, called by the executor.
+    pub fn observe(&mut self, report: String) {
+        self.report = Some(report);
+    }
+}
+
+impl<I, S> Observer<I, S> for LeakObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.mallocs = 0;
+        self.frees = 0;
+        self.report = None;
+        MALLOCS.store(0, Ordering::Relaxed);
+        FREES.store(0, Ordering::Relaxed);
+        RUNNING.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        RUNNING.store(false, Ordering::Relaxed);
+        self.mallocs = MALLOCS.load(Ordering::Relaxed);
+        self.frees = FREES.load(Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Named for LeakObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
@@ -0,0 +1,315 @@
+//! The [`MemoryObserver`] records the memory usage of the last run, to find memory exhaustion bugs
+//! like libFuzzer's `-rss_limit_mb` and `-malloc_limit_mb`.
+//!
+//! In-process, the allocations are reported by malloc hooks, e.g. the ones of `libafl_targets`,
+//! through [`record_allocation`]. Out of process, the peak RSS of the child comes from its
+//! `rusage`, see [`MemoryObserver::observe_rusage`].
+
+use alloc::borrow::Cow;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use libafl_bolts::{Error, Named, ownedref::OwnedRefMut, shmem::ShMem};
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, observers::Observer};
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static LARGEST_ALLOCATION: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Records an allocation of `size` bytes by the target, called by malloc hooks.
+///
+/// Allocations outside of runs with a [`MemoryObserver`] are ignored.
+pub fn record_allocation(size: usize) {
+    if RUNNING.load(Ordering::Relaxed) {
+        LARGEST_ALLOCATION.fetch_max(size, Ordering::Relaxed);
+        TOTAL_ALLOCATED.fetch_add(size, Ordering::Relaxed);
+    }
+}
+
+/// The memory usage of a run, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// The peak resident set size
+    pub peak_rss: usize,
+    /// The largest single allocation
+    pub largest_allocation: usize,
+    /// All allocated bytes, freed or not
+    pub total_allocated: usize,
+}
+
+impl MemoryUsage {
+    /// If any of the values is higher than in `other`
+    #[must_use]
+    pub fn exceeds(&self, other: &Self) -> bool {
+        self.peak_rss > other.peak_rss
+            || self.largest_allocation > other.largest_allocation
+            || self.total_allocated > other.total_allocated
+    }
+
+    /// The maximum of each value of both
+    #[must_use]
+    pub fn max(&self, other: &Self) -> Self {
+        Self {
+            peak_rss: self.peak_rss.max(other.peak_rss),
+            largest_allocation: self.largest_allocation.max(other.largest_allocation),
+            total_allocated: self.total_allocated.max(other.total_allocated),
+        }
+    }
+}
+
+/// Records the [`MemoryUsage`] of the last run.
+///
+/// In-process, only the allocations are recorded by default, see [`MemoryObserver::track_rss`]
+/// for the peak RSS. Executors running children report their peak RSS instead, e.g. the
+/// `CommandExecutor` with a memory observer set.
+/// With an `InProcessForkExecutor`, the child records its usage, create the observer with
+/// [`MemoryObserver::from_shmem`] so the parent sees it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoryObserver {
+    name: Cow<'static, str>,
+    usage: MemoryUsage,
+    /// The usage reported by the executor or by a forked child, if any
+    child_usage: OwnedRefMut<'static, Option<MemoryUsage>>,
+    track_rss: bool,
+}
+
+impl MemoryObserver {
+    /// Creates a new [`MemoryObserver`] with the given name.
+    #[must_use]
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Self {
+            name: name.into(),
+            usage: MemoryUsage::default(),
+            child_usage: OwnedRefMut::owned(None),
+            track_rss: false,
+        }
+    }
+
+    /// Creates a new [`MemoryObserver`] with the given name, sharing the usage recorded by a
+    /// forked child through the given [`ShMem`].
+    ///
+    /// # Panics
+    /// Panics if the given shared mem is too small to hold the usage
+    ///
+    /// # Safety
+    /// The shared memory must outlive the observer, and must not be used for anything else.
+    pub unsafe fn from_shmem<S, SHM>(name: S, shmem: &mut SHM) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+        SHM: ShMem,
+    {
+        let ptr = shmem.as_mut_ptr_of::<Option<MemoryUsage>>().unwrap();
+        unsafe { ptr.write(None) };
+        Self {
+            child_usage: unsafe { OwnedRefMut::from_mut_ptr(ptr) },
+            ..Self::new(name)
+        }
+    }
+
+    /// Also records the peak RSS of in-process runs.
+    ///
+    /// On Linux, this resets the peak RSS through `/proc/self/clear_refs` before each run and
+    /// reads `/proc/self/status` after it, which slows down fast targets noticeably. Elsewhere,
+    /// the peak RSS only grows.
+    #[must_use]
+    pub fn track_rss(mut self, track_rss: bool) -> Self {
+        self.track_rss = track_rss;
+        self
+    }
+
+    /// The memory usage of the last run
+    #[must_use]
+    pub fn usage(&self) -> &MemoryUsage {
+        &self.usage
+    }
+
+    /// Records the memory usage of the child of the last run, called by the executor.
+    pub fn observe(&mut self, usage: MemoryUsage) {
+        self.usage = usage;
+        *self.child_usage.as_mut() = Some(usage);
+    }
+
+    /// Starts recording the allocations of this process
+    fn start(&self) {
+        LARGEST_ALLOCATION.store(0, Ordering::Relaxed);
+        TOTAL_ALLOCATED.store(0, Ordering::Relaxed);
+        if self.track_rss {
+            reset_peak_rss();
+        }
+        RUNNING.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops recording and returns the usage of this process
+    fn stop(&self) -> MemoryUsage {
+        RUNNING.store(false, Ordering::Relaxed);
+        MemoryUsage {
+            peak_rss: if self.track_rss { peak_rss() } else { 0 },
+            largest_allocation: LARGEST_ALLOCATION.load(Ordering::Relaxed),
+            total_allocated: TOTAL_ALLOCATED.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records the peak RSS of the child of the last run from its `rusage`, as returned by `wait4`.
+    #[cfg(unix)]
+    pub fn observe_rusage(&mut self, rusage: &libc::rusage) {
+        self.observe(MemoryUsage {
+            peak_rss: maxrss_bytes(rusage),
+            ..MemoryUsage::default()
+        });
+    }
+}
+
+/// The `ru_maxrss` in bytes, it is in kilobytes except on Apple platforms.
+#[cfg(unix)]
+fn maxrss_bytes(rusage: &libc::rusage) -> usize {
+    let maxrss = usize::try_from(rusage.ru_maxrss).unwrap_or_default();
+    if cfg!(target_vendor = "apple") {
+        maxrss
+    } else {
+        maxrss * 1024
+    }
+}
+
+/// Resets the peak RSS of this process, only possible on Linux
+fn reset_peak_rss() {
+    #[cfg(target_os = "linux")]
+    if let Err(err) = std::fs::write("/proc/self/clear_refs", "5") {
+        log::debug!("Failed to reset the peak RSS: {err}");
+    }
+}
+
+/// The peak RSS of this process, in bytes
+fn peak_rss() -> usize {
+    #[cfg(target_os = "linux")]
+    if let Ok(status) = std::fs::read_to_string("/proc/self/status")
+        && let Some(kilobytes) = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))
+            .and_then(|value| {
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+            })
+    {
+        return kilobytes * 1024;
+    }
+
+    #[cfg(unix)]
+    {
+        let mut rusage = core::mem::MaybeUninit::<libc::rusage>::zeroed();
+        // # Safety
+        // `getrusage` only writes to the given struct.
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, rusage.as_mut_ptr()) } == 0 {
+            let rusage = unsafe { rusage.assume_init() };
+            return maxrss_bytes(&rusage);
+        }
+    }
+
+    0
+}
+
+impl<I, S> Observer<I, S> for MemoryObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.usage = MemoryUsage::default();
+        *self.child_usage.as_mut() = None;
+        self.start();
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        let own_usage = self.stop();
+        self.usage = self.child_usage.as_ref().unwrap_or(own_usage);
+        Ok(())
+    }
+
+    fn pre_exec_child(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.usage = MemoryUsage::default();
+        self.start();
+        Ok(())
+    }
+
+    fn post_exec_child(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        let usage = self.stop();
+        // Executors of other processes call this in the parent, after reporting the usage
+        if self.child_usage.as_ref().is_none() {
+            self.observe(usage);
+        }
+        Ok(())
+    }
+}
+
+impl Named for MemoryObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryObserver, MemoryUsage, record_allocation};
+    use crate::{executors::ExitKind, observers::Observer, state::NopState};
+
+    #[test]
+    fn test_memory_observer() {
+        let mut observer = MemoryObserver::new("memory").track_rss(true);
+        let mut state: NopState<()> = NopState::new();
+
+        record_allocation(1 << 20);
+        observer.pre_exec(&mut state, &()).unwrap();
+        record_allocation(16);
+        record_allocation(4096);
+        observer.post_exec(&mut state, &(), &ExitKind::Ok).unwrap();
+        record_allocation(1 << 20);
+
+        assert_eq!(observer.usage().largest_allocation, 4096);
+        assert_eq!(observer.usage().total_allocated, 4112);
+        #[cfg(unix)]
+        assert!(observer.usage().peak_rss > 0);
+
+        let child = MemoryUsage {
+            peak_rss: 1 << 30,
+            ..MemoryUsage::default()
+        };
+        observer.pre_exec(&mut state, &()).unwrap();
+        observer.observe(child);
+        observer.post_exec(&mut state, &(), &ExitKind::Ok).unwrap();
+        assert_eq!(*observer.usage(), child);
+        assert!(child.exceeds(&MemoryUsage::default()));
+        assert!(!child.exceeds(&child.max(&MemoryUsage::default())));
+
+        // A forked child records its own usage, without the peak RSS by default
+        let mut observer = MemoryObserver::new("memory");
+        observer.pre_exec(&mut state, &()).unwrap();
+        observer.pre_exec_child(&mut state, &()).unwrap();
+        record_allocation(64);
+        observer
+            .post_exec_child(&mut state, &(), &ExitKind::Ok)
+            .unwrap();
+        observer.post_exec(&mut state, &(), &ExitKind::Ok).unwrap();
+        assert_eq!(
+            *observer.usage(),
+            MemoryUsage {
+                peak_rss: 0,
+                largest_allocation: 64,
+                total_allocated: 64,
+            }
+        );
+    }
+}
@@ -7,16 +7,40 @@ pub use cmp::*;
 #[cfg(feature = "std")]
 pub mod stdio;
 #[cfg(feature = "std")]
-pub use stdio::{StdErrObserver, StdOutObserver};
+pub use stdio::{ResponseObserver, StdErrObserver, StdOutObserver};
 
 #[cfg(feature = "regex")]
 pub mod stacktrace;
 #[cfg(feature = "regex")]
 pub use stacktrace::*;
 
+#[cfg(feature = "regex")]
+pub mod sanitizer;
+#[cfg(feature = "regex")]
+pub use sanitizer::{Sanitizer, SanitizerObserver, SanitizerReport};
+
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+pub mod hang_backtrace;
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+pub use hang_backtrace::{HangBacktraceObserver, StackFrame};
+
+pub mod leak;
+pub use leak::LeakObserver;
+
 pub mod map;
 pub use map::*;
 
+#[cfg(feature = "std")]
+pub mod memory;
+#[cfg(feature = "std")]
+pub use memory::{MemoryObserver, MemoryUsage};
+
+pub mod protocol;
+pub use protocol::{LineCodeExtractor, ProtocolStateObserver, ResponseCodeExtractor};
+
+pub mod sandbox;
+pub use sandbox::SandboxObserver;
+
 pub mod value;
 
 /// List observer
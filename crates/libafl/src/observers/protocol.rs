@@ -0,0 +1,160 @@
+//! Observers tracking the protocol state of stateful targets, such as network servers.
+//!
+//! The [`ProtocolStateObserver`] extracts the sequence of response codes a target produced during
+//! one execution, in the spirit of `AFLNet`. The codes are treated as protocol states, which the
+//! [`crate::feedbacks::protocol::ProtocolStateFeedback`] uses to reward new states and state
+//! transitions.
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, observers::Observer};
+
+/// Extracts response codes, used as protocol states, from the raw output of a target.
+pub trait ResponseCodeExtractor {
+    /// Extract all response codes found in `output`, in order, and append them to `codes`.
+    fn extract_codes(&self, output: &[u8], codes: &mut Vec<u32>);
+}
+
+/// A [`ResponseCodeExtractor`] for line-based protocols, like FTP, SMTP or RTSP.
+///
+/// For each line of the output, an optional prefix is skipped and the leading decimal number is
+/// parsed as the response code. Lines that do not start with the prefix or a number are ignored.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LineCodeExtractor {
+    prefix: Vec<u8>,
+}
+
+impl LineCodeExtractor {
+    /// Create a new [`LineCodeExtractor`] parsing the number at the start of each line, e.g.
+    /// `220` in `220 Service ready`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new [`LineCodeExtractor`] parsing the number following `prefix`, e.g. `200` in
+    /// `RTSP/1.0 200 OK` for the prefix `RTSP/1.0 `.
+    #[must_use]
+    pub fn with_prefix(prefix: &[u8]) -> Self {
+        Self {
+            prefix: prefix.to_vec(),
+        }
+    }
+}
+
+impl ResponseCodeExtractor for LineCodeExtractor {
+    fn extract_codes(&self, output: &[u8], codes: &mut Vec<u32>) {
+        for line in output.split(|&b| b == b'\n') {
+            let Some(rest) = line.strip_prefix(self.prefix.as_slice()) else {
+                continue;
+            };
+            let mut code: Option<u32> = None;
+            for digit in rest.iter().take_while(|b| b.is_ascii_digit()) {
+                let Some(next) = code
+                    .unwrap_or_default()
+                    .checked_mul(10)
+                    .and_then(|c| c.checked_add(u32::from(digit - b'0')))
+                else {
+                    break;
+                };
+                code = Some(next);
+            }
+            if let Some(code) = code {
+                codes.push(code);
+            }
+        }
+    }
+}
+
+/// An observer recording the protocol states, i.e. response codes, a target went through during
+/// one execution.
+///
+/// The states are extracted from the target's responses using a [`ResponseCodeExtractor`].
+/// Whoever receives the responses, e.g. the executor talking to the target or the harness, hands
+/// them to [`ProtocolStateObserver::observe_output`]. Captured stdout, as collected by
+/// [`crate::observers::StdOutObserver`], can be passed in the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolStateObserver<E> {
+    name: Cow<'static, str>,
+    extractor: E,
+    states: Vec<u32>,
+}
+
+impl<E> ProtocolStateObserver<E> {
+    /// Create a new [`ProtocolStateObserver`] using the given [`ResponseCodeExtractor`].
+    #[must_use]
+    pub fn new(name: &'static str, extractor: E) -> Self {
+        Self {
+            name: Cow::from(name),
+            extractor,
+            states: Vec::new(),
+        }
+    }
+
+    /// The states observed during the last execution, in order.
+    #[must_use]
+    pub fn states(&self) -> &[u32] {
+        &self.states
+    }
+
+    /// Record a single state directly, for targets that report their state without output parsing.
+    pub fn push_state(&mut self, state: u32) {
+        self.states.push(state);
+    }
+
+    /// The extractor used to parse the target output.
+    #[must_use]
+    pub fn extractor(&self) -> &E {
+        &self.extractor
+    }
+}
+
+impl<E> ProtocolStateObserver<E>
+where
+    E: ResponseCodeExtractor,
+{
+    /// Extract the states from the given target output and append them to the observed states.
+    pub fn observe_output(&mut self, output: &[u8]) {
+        self.extractor.extract_codes(output, &mut self.states);
+    }
+}
+
+impl<E> Named for ProtocolStateObserver<E> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<E, I, S> Observer<I, S> for ProtocolStateObserver<E> {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.states.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{LineCodeExtractor, ResponseCodeExtractor};
+
+    #[test]
+    fn test_line_code_extractor() {
+        let mut codes = Vec::new();
+        LineCodeExtractor::new().extract_codes(
+            b"220 ready\r\n331 password required\r\nnot a code\r\n230 logged in",
+            &mut codes,
+        );
+        assert_eq!(codes, [220, 331, 230]);
+
+        codes.clear();
+        LineCodeExtractor::with_prefix(b"RTSP/1.0 ").extract_codes(
+            b"RTSP/1.0 200 OK\r\nCSeq: 1\r\nRTSP/1.0 454 Session\r\n",
+            &mut codes,
+        );
+        assert_eq!(codes, [200, 454]);
+    }
+}
@@ -0,0 +1,56 @@
+//! The [`SandboxObserver`] records if the target violated the seccomp filter of its sandbox.
+
+use alloc::borrow::Cow;
+
+use libafl_bolts::{Error, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::observers::Observer;
+
+/// Records the seccomp violation of the last run, reported by executors running their children in
+/// a sandbox, see `executors::sandbox`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxObserver {
+    name: Cow<'static, str>,
+    violation: Option<Cow<'static, str>>,
+}
+
+impl SandboxObserver {
+    /// Creates a new [`SandboxObserver`] with the given name.
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            violation: None,
+        }
+    }
+
+    /// The description of the violation of the last run, if any
+    #[must_use]
+    pub fn violation(&self) -> Option<&str> {
+        self.violation.as_deref()
+    }
+
+    /// Records a violation, called by the executor.
+    pub fn observe(&mut self, violation: Cow<'static, str>) {
+        self.violation = Some(violation);
+    }
+}
+
+impl<I, S> Observer<I, S> for SandboxObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.violation = None;
+        Ok(())
+    }
+
+    fn pre_exec_child(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.violation = None;
+        Ok(())
+    }
+}
+
+impl Named for SandboxObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
@@ -0,0 +1,570 @@
+//! The [`SanitizerObserver`] parses the reports of the `UndefinedBehaviorSanitizer`, the
+//! `MemorySanitizer` and the `ThreadSanitizer` into a bug type, a location, a stack and a hash of them.
+//!
+//! Use it with a [`crate::feedbacks::SanitizerFeedback`] to pick the bug types to report, and
+//! with a [`crate::feedbacks::NewHashFeedback`] to deduplicate the reports.
+
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::ChildStderr,
+    sync::LazyLock,
+};
+
+use libafl_bolts::{Error, Named, hash_std};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    executors::ExitKind,
+    observers::{Observer, ObserverWithHashField, ObserverWithStacktrace, SymbolizedFrame},
+};
+
+/// The number of frames of the stack that are part of the hash
+const HASHED_FRAMES: usize = 8;
+
+/// Matches the message of an `UndefinedBehaviorSanitizer` report
+static UBSAN_REPORT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^(.*?)(?::(\d+):\d+)?: runtime error: (.*)$").unwrap());
+
+/// Matches the header of a `MemorySanitizer` report
+static MSAN_REPORT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| report_header_regex(Sanitizer::Memory));
+
+/// Matches the header of a `ThreadSanitizer` report
+static TSAN_REPORT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| report_header_regex(Sanitizer::Thread));
+
+/// Matches the location in the summary of a report
+static SUMMARY_LOCATION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" (\S+?):(\d+)(?::\d+)?(?: in |$)").unwrap());
+
+/// Matches a frame of a stack, see [`parse_frames`]
+static FRAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*#\d+(?: 0x([0-9a-f]+))?(?: in)?(?: (.*?))??(?: (\S+?):(\d+)(?::\d+)?)?(?: \(([^()]*)\+0x([0-9a-f]+)\))?(?: \(BuildId: [0-9a-f]+\))?\s*$",
+    )
+    .unwrap()
+});
+
+/// The regex matching the header of a report of `sanitizer`, with the bug type
+fn report_header_regex(sanitizer: Sanitizer) -> Regex {
+    Regex::new(&format!(
+        r"(?m)^(?:==\d+==)?(?:WARNING|ERROR): {}: ([^(\n]*)",
+        sanitizer.report_name()
+    ))
+    .unwrap()
+}
+
+/// A sanitizer the [`SanitizerObserver`] parses the reports of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Sanitizer {
+    /// The `UndefinedBehaviorSanitizer`, `-fsanitize=undefined`
+    Undefined,
+    /// The `MemorySanitizer`, `-fsanitize=memory`
+    Memory,
+    /// The `ThreadSanitizer`, `-fsanitize=thread`
+    Thread,
+}
+
+impl Sanitizer {
+    /// The environment variable holding the runtime options of this sanitizer
+    #[must_use]
+    pub fn options_env(self) -> &'static str {
+        match self {
+            Self::Undefined => "UBSAN_OPTIONS",
+            Self::Memory => "MSAN_OPTIONS",
+            Self::Thread => "TSAN_OPTIONS",
+        }
+    }
+
+    /// The name of the sanitizer in its reports
+    #[must_use]
+    pub fn report_name(self) -> &'static str {
+        match self {
+            Self::Undefined => "UndefinedBehaviorSanitizer",
+            Self::Memory => "MemorySanitizer",
+            Self::Thread => "ThreadSanitizer",
+        }
+    }
+}
+
+/// A report of a sanitizer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizerReport {
+    /// The sanitizer that printed the report
+    pub sanitizer: Sanitizer,
+    /// The kind of the bug, e.g. `signed-integer-overflow`, `use-of-uninitialized-value` or
+    /// `data-race`. For the `UndefinedBehaviorSanitizer`, this is the name of the check as passed
+    /// to `-fsanitize=`.
+    pub bug_type: String,
+    /// The source file the bug was found in, if known
+    pub file: Option<String>,
+    /// The line in `file`
+    pub line: Option<u32>,
+    /// The first stack of the report, innermost frame first
+    pub stack: Vec<SymbolizedFrame>,
+}
+
+impl SanitizerReport {
+    /// Parses the first report of the given sanitizer in `output`, e.g. the captured stderr
+    #[must_use]
+    pub fn parse(sanitizer: Sanitizer, output: &str) -> Option<Self> {
+        let (bug_type, location, rest) = match sanitizer {
+            Sanitizer::Undefined => {
+                let m = UBSAN_REPORT_REGEX.captures(output)?;
+                let location = m
+                    .get(2)
+                    .map(|line| (m[1].to_string(), line.as_str().parse().ok()));
+                let summary_type = summary(output, sanitizer)
+                    .and_then(|summary| summary.split_whitespace().next().map(ToString::to_string))
+                    .filter(|bug_type| bug_type != "undefined-behavior");
+                let bug_type = summary_type.unwrap_or_else(|| ubsan_check(&m[3]).to_string());
+                (bug_type, location, &output[m.get(0).unwrap().end()..])
+            }
+            Sanitizer::Memory | Sanitizer::Thread => {
+                let matcher = if sanitizer == Sanitizer::Memory {
+                    &*MSAN_REPORT_REGEX
+                } else {
+                    &*TSAN_REPORT_REGEX
+                };
+                let m = matcher.captures(output)?;
+                let bug_type = m[1].split_whitespace().collect::<Vec<_>>().join("-");
+                (bug_type, None, &output[m.get(0).unwrap().end()..])
+            }
+        };
+
+        let stack = parse_frames(rest);
+        let (file, line) = location
+            .or_else(|| {
+                let summary = summary(output, sanitizer)?;
+                let m = SUMMARY_LOCATION_REGEX.captures(summary)?;
+                Some((m[1].to_string(), m[2].parse().ok()))
+            })
+            .or_else(|| {
+                let frame = stack.iter().find(|frame| frame.file.is_some())?;
+                Some((frame.file.clone()?, frame.line))
+            })
+            .map_or((None, None), |(file, line)| (Some(file), line));
+
+        Some(Self {
+            sanitizer,
+            bug_type,
+            file,
+            line,
+            stack,
+        })
+    }
+
+    /// The hash of the report, independent of the addresses, so it is stable across runs
+    #[must_use]
+    pub fn hash(&self) -> u64 {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.sanitizer.report_name().as_bytes());
+        bytes.extend_from_slice(self.bug_type.as_bytes());
+        if let Some(file) = &self.file {
+            bytes.extend_from_slice(file.as_bytes());
+        }
+        bytes.extend_from_slice(&self.line.unwrap_or_default().to_le_bytes());
+        for frame in self.stack.iter().take(HASHED_FRAMES) {
+            for part in [&frame.function, &frame.file, &frame.module]
+                .into_iter()
+                .flatten()
+            {
+                bytes.extend_from_slice(part.as_bytes());
+            }
+            bytes.extend_from_slice(&frame.line.unwrap_or_default().to_le_bytes());
+            if frame.function.is_none() {
+                bytes.extend_from_slice(&frame.module_offset.unwrap_or_default().to_le_bytes());
+            }
+        }
+        hash_std(&bytes)
+    }
+}
+
+/// The text after `SUMMARY: <Sanitizer>: `
+fn summary(output: &str, sanitizer: Sanitizer) -> Option<&str> {
+    let prefix = format!("SUMMARY: {}: ", sanitizer.report_name());
+    output.lines().find_map(|line| line.strip_prefix(&prefix))
+}
+
+/// The name of the `-fsanitize=` check of an `UndefinedBehaviorSanitizer` message, for runtimes that
+/// only print `undefined-behavior` in the summary
+fn ubsan_check(message: &str) -> &'static str {
+    const CHECKS: &[(&str, &str)] = &[
+        ("unsigned integer overflow", "unsigned-integer-overflow"),
+        ("signed integer overflow", "signed-integer-overflow"),
+        ("negation of", "signed-integer-overflow"),
+        ("division by zero", "integer-divide-by-zero"),
+        ("shift exponent", "shift"),
+        ("left shift of", "shift"),
+        ("null pointer returned", "returns-nonnull-attribute"),
+        ("null pointer passed", "nonnull-attribute"),
+        ("offset to null pointer", "pointer-overflow"),
+        ("null pointer", "null"),
+        ("misaligned address", "alignment"),
+        ("out of bounds", "bounds"),
+        ("not a valid value for type", "bool"),
+        ("unreachable program point", "unreachable"),
+        ("end of a value-returning function", "return"),
+        ("pointer index expression", "pointer-overflow"),
+        (
+            "outside the range of representable values",
+            "float-cast-overflow",
+        ),
+        ("implicit conversion", "implicit-conversion"),
+        ("incorrect function type", "function"),
+        ("variable length array bound", "vla-bound"),
+        ("which does not point to an object of type", "vptr"),
+    ];
+    CHECKS
+        .iter()
+        .find(|(pattern, _)| message.contains(pattern))
+        .map_or("undefined-behavior", |(_, check)| check)
+}
+
+/// Parses the first stack in `output`, with frames like `#0 0x4f5b2d in main /src/test.c:10:5`,
+/// `#1 0x7f1b (/lib/libc.so.6+0x21b96)` or, for the `ThreadSanitizer`, `#0 main /src/test.c:10:5
+/// (test+0x4a0f38)`
+fn parse_frames(output: &str) -> Vec<SymbolizedFrame> {
+    let mut frames = Vec::new();
+    for line in output.lines() {
+        let Some(m) = FRAME_REGEX.captures(line) else {
+            if frames.is_empty() {
+                continue;
+            }
+            break;
+        };
+        frames.push(SymbolizedFrame {
+            address: m
+                .get(1)
+                .and_then(|address| u64::from_str_radix(address.as_str(), 16).ok())
+                .unwrap_or_default(),
+            function: m
+                .get(2)
+                .map(|function| function.as_str().trim())
+                .filter(|function| !function.is_empty())
+                .map(ToString::to_string),
+            file: m.get(3).map(|file| file.as_str().to_string()),
+            line: m.get(4).and_then(|line| line.as_str().parse().ok()),
+            module: m.get(5).map(|module| module.as_str().to_string()),
+            module_offset: m
+                .get(6)
+                .and_then(|offset| u64::from_str_radix(offset.as_str(), 16).ok()),
+        });
+    }
+    frames
+}
+
+/// An observer for the reports of a [`Sanitizer`] other than the `AddressSanitizer`, see the
+/// [`crate::observers::AsanBacktraceObserver`] for that one.
+///
+/// Set the variable [`Sanitizer::options_env`] of the target to [`SanitizerObserver::options`],
+/// so the sanitizer writes its reports to a log file the observer reads after each run.
+/// Alternatively, pass the captured stderr to [`SanitizerObserver::parse_output`].
+/// Each observer handles one sanitizer, use one for each sanitizer the target is built with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanitizerObserver {
+    name: Cow<'static, str>,
+    sanitizer: Sanitizer,
+    log_dir: PathBuf,
+    /// Set if the observer created the log dir, removing it once the last clone is dropped
+    #[serde(skip)]
+    owned_log_dir: Option<Arc<OwnedLogDir>>,
+    report: Option<SanitizerReport>,
+    output: Option<String>,
+    exit_kind: Option<ExitKind>,
+}
+
+/// A log dir created by a [`SanitizerObserver`], removed on drop
+#[derive(Debug)]
+struct OwnedLogDir(PathBuf);
+
+impl Drop for OwnedLogDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.0) {
+            log::warn!(
+                "Could not remove the sanitizer log dir {}: {err}",
+                self.0.display()
+            );
+        }
+    }
+}
+
+impl SanitizerObserver {
+    /// Creates a new [`SanitizerObserver`] for the given sanitizer, with a new log dir in the
+    /// temp dir. The log dir is removed once the observer and all its clones are dropped.
+    #[must_use]
+    pub fn new<S>(name: S, sanitizer: Sanitizer) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        let log_dir = std::env::temp_dir().join(format!(
+            "libafl_{}_{}",
+            sanitizer.options_env().to_lowercase(),
+            uuid::Uuid::new_v4()
+        ));
+        let mut observer = Self::with_log_dir(name, sanitizer, log_dir);
+        observer.owned_log_dir = Some(Arc::new(OwnedLogDir(observer.log_dir.clone())));
+        observer
+    }
+
+    /// Creates a new [`SanitizerObserver`] for the given sanitizer, writing the logs to the given
+    /// dir. The dir must not be shared with other observers, it is created if needed and not
+    /// removed by the observer.
+    #[must_use]
+    pub fn with_log_dir<S, P>(name: S, sanitizer: Sanitizer, log_dir: P) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+        P: Into<PathBuf>,
+    {
+        let log_dir = log_dir.into();
+        if let Err(err) = fs::create_dir_all(&log_dir) {
+            log::warn!(
+                "Could not create the sanitizer log dir {}: {err}",
+                log_dir.display()
+            );
+        }
+        Self {
+            name: name.into(),
+            sanitizer,
+            log_dir,
+            owned_log_dir: None,
+            report: None,
+            output: None,
+            exit_kind: None,
+        }
+    }
+
+    /// The sanitizer this observer parses the reports of
+    #[must_use]
+    pub fn sanitizer(&self) -> Sanitizer {
+        self.sanitizer
+    }
+
+    /// The runtime options for the target, to be set in [`Sanitizer::options_env`].
+    ///
+    /// They make the sanitizer write its reports to the log of this observer, with stacks.
+    #[must_use]
+    pub fn options(&self) -> String {
+        format!(
+            "log_path={}:print_stacktrace=1:symbolize=1",
+            self.log_dir.join("log").display()
+        )
+    }
+
+    /// The directory the sanitizer writes its logs to
+    #[must_use]
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    /// The report of the last run, if any
+    #[must_use]
+    pub fn report(&self) -> Option<&SanitizerReport> {
+        self.report.as_ref()
+    }
+
+    /// Parses the output of the target, e.g. the captured stderr
+    pub fn parse_output(&mut self, output: &str) {
+        if let Some(report) = SanitizerReport::parse(self.sanitizer, output) {
+            self.report = Some(report);
+            self.output = Some(output.to_string());
+        }
+    }
+
+    /// Reads the output from the child stderr and parses it.
+    pub fn parse_output_from_childstderr(&mut self, stderr: &mut ChildStderr) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf)?;
+        self.parse_output(&String::from_utf8_lossy(&buf));
+        Ok(())
+    }
+
+    /// Reads and removes the logs the sanitizer wrote to `log.<pid>`, in a directory of
+    /// their own
+    fn parse_logs(&mut self) -> Result<(), Error> {
+        let mut output = String::new();
+        // The log dir only holds the logs of this observer
+        for entry in fs::read_dir(&self.log_dir)? {
+            output += &read_log(&entry?.path())?;
+        }
+        if !output.is_empty() {
+            self.parse_output(&output);
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.report = None;
+        self.output = None;
+        self.exit_kind = None;
+    }
+}
+
+fn read_log(path: &Path) -> Result<String, Error> {
+    let log = fs::read(path)?;
+    fs::remove_file(path)?;
+    Ok(String::from_utf8_lossy(&log).into_owned())
+}
+
+impl ObserverWithHashField for SanitizerObserver {
+    fn hash(&self) -> Option<u64> {
+        self.report.as_ref().map(SanitizerReport::hash)
+    }
+}
+
+impl ObserverWithStacktrace for SanitizerObserver {
+    fn stacktrace(&self) -> Vec<SymbolizedFrame> {
+        self.report
+            .as_ref()
+            .map(|report| report.stack.clone())
+            .unwrap_or_default()
+    }
+
+    fn sanitizer_report(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    fn exit_kind(&self) -> Option<ExitKind> {
+        self.exit_kind
+    }
+}
+
+impl<I, S> Observer<I, S> for SanitizerObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.clear();
+        Ok(())
+    }
+
+    fn pre_exec_child(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.clear();
+        Ok(())
+    }
+
+    fn post_exec(&mut self, _state: &mut S, _input: &I, exit_kind: &ExitKind) -> Result<(), Error> {
+        self.exit_kind = Some(*exit_kind);
+        self.parse_logs()
+    }
+
+    fn post_exec_child(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.exit_kind = Some(*exit_kind);
+        self.parse_logs()
+    }
+}
+
+impl Named for SanitizerObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sanitizer, SanitizerObserver, SanitizerReport};
+
+    #[test]
+    fn test_ubsan_report() {
+        let output = "\
+/src/math.c:12:14: runtime error: signed integer overflow: 2147483647 + 1 cannot be represented in type 'int'
+    #0 0x4c5b2d in add /src/math.c:12:14
+    #1 0x4c5c10 in main /src/main.c:20:3
+    #2 0x7f1b2c in __libc_start_main (/lib/x86_64-linux-gnu/libc.so.6+0x21b96)
+    #3 0x7f1b5e  (/lib/x86_64-linux-gnu/libc.so.6+0x21bc8)
+
+SUMMARY: UndefinedBehaviorSanitizer: undefined-behavior /src/math.c:12:14 in
+";
+        let report = SanitizerReport::parse(Sanitizer::Undefined, output).unwrap();
+        assert_eq!(report.bug_type, "signed-integer-overflow");
+        assert_eq!(report.file.as_deref(), Some("/src/math.c"));
+        assert_eq!(report.line, Some(12));
+        assert_eq!(report.stack.len(), 4);
+        assert_eq!(
+            report.stack[2].function.as_deref(),
+            Some("__libc_start_main")
+        );
+        assert_eq!(report.stack[2].module_offset, Some(0x21b96));
+        assert_eq!(report.stack[3].function, None);
+        assert_eq!(report.stack[3].module_offset, Some(0x21bc8));
+
+        // The addresses differ between runs, the hash must not
+        let moved = output.replace("0x4c5", "0x5d6");
+        let moved = SanitizerReport::parse(Sanitizer::Undefined, &moved).unwrap();
+        assert_eq!(report.hash(), moved.hash());
+
+        let null = "/src/list.c:7:20: runtime error: member access within null pointer of type 'struct node'\n";
+        let null = SanitizerReport::parse(Sanitizer::Undefined, null).unwrap();
+        assert_eq!(null.bug_type, "null");
+        assert_ne!(report.hash(), null.hash());
+    }
+
+    #[test]
+    fn test_msan_report() {
+        let output = "\
+==4242==WARNING: MemorySanitizer: use-of-uninitialized-value
+    #0 0x49a6d7 in parse /src/parse.c:5:9
+    #1 0x49a7f1 in main /src/main.c:9:3
+
+SUMMARY: MemorySanitizer: use-of-uninitialized-value /src/parse.c:5:9 in parse
+";
+        let report = SanitizerReport::parse(Sanitizer::Memory, output).unwrap();
+        assert_eq!(report.bug_type, "use-of-uninitialized-value");
+        assert_eq!(report.file.as_deref(), Some("/src/parse.c"));
+        assert_eq!(report.line, Some(5));
+        assert_eq!(report.stack.len(), 2);
+        assert!(SanitizerReport::parse(Sanitizer::Thread, output).is_none());
+    }
+
+    #[test]
+    fn test_tsan_report() {
+        let output = "\
+==================
+WARNING: ThreadSanitizer: data race (pid=1234)
+  Write of size 4 at 0x7b0400000000 by thread T1:
+    #0 worker /src/race.c:4:10 (race+0x4a0f38) (BuildId: 4f7b0e2c)
+    #1 <null> <null> (libc.so.6+0x94ac2)
+
+  Previous write of size 4 at 0x7b0400000000 by main thread:
+    #0 main /src/race.c:11:5 (race+0x4a0fa1)
+
+SUMMARY: ThreadSanitizer: data race /src/race.c:4:10 in worker
+";
+        let report = SanitizerReport::parse(Sanitizer::Thread, output).unwrap();
+        assert_eq!(report.bug_type, "data-race");
+        assert_eq!(report.file.as_deref(), Some("/src/race.c"));
+        assert_eq!(report.line, Some(4));
+        assert_eq!(report.stack.len(), 2);
+        assert_eq!(report.stack[0].function.as_deref(), Some("worker"));
+        assert_eq!(report.stack[0].module.as_deref(), Some("race"));
+    }
+
+    #[test]
+    fn test_log_dir_cleanup() {
+        let observer = SanitizerObserver::new("ubsan", Sanitizer::Undefined);
+        let log_dir = observer.log_dir().to_path_buf();
+        let clone = observer.clone();
+        drop(observer);
+        assert!(log_dir.is_dir());
+        drop(clone);
+        assert!(!log_dir.exists());
+
+        let dir = std::env::temp_dir().join(format!("libafl_test_msan_{}", std::process::id()));
+        let observer = SanitizerObserver::with_log_dir("msan", Sanitizer::Memory, &dir);
+        drop(observer);
+        assert!(dir.is_dir());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
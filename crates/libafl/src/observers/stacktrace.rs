@@ -1,8 +1,10 @@
 //! the ``StacktraceObserver`` looks up the stacktrace on the execution thread and computes a hash for it for dedupe
 
-#[cfg(feature = "casr")]
-use alloc::string::ToString;
-use alloc::{borrow::Cow, string::String, vec::Vec};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::fmt::Debug;
 #[cfg(feature = "casr")]
 use core::hash::{Hash, Hasher};
@@ -13,9 +15,10 @@ use std::{
     io::Read,
     path::Path,
     process::ChildStderr,
+    sync::LazyLock,
 };
 
-use backtrace::Backtrace;
+use backtrace::{Backtrace, BacktraceSymbol};
 use libafl_bolts::{Named, ownedref::OwnedRefMut, shmem::ShMem};
 #[allow(unused_imports)] // expect breaks here for some reason
 #[cfg(feature = "casr")]
@@ -34,19 +37,105 @@ use libcasr::{
         STACK_FRAME_FUNCTION_IGNORE_REGEXES, Stacktrace, StacktraceEntry,
     },
 };
-#[cfg(not(feature = "casr"))]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use super::ObserverWithHashField;
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+use crate::observers::HangBacktraceObserver;
 use crate::{Error, executors::ExitKind, observers::Observer};
 
-#[cfg(not(feature = "casr"))]
+/// A frame of a stack trace, with the symbols resolved where possible
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolizedFrame {
+    /// The address of the instruction
+    pub address: u64,
+    /// The function the instruction belongs to
+    pub function: Option<String>,
+    /// The source file of the instruction
+    pub file: Option<String>,
+    /// The line in `file`
+    pub line: Option<u32>,
+    /// The binary or library the instruction belongs to
+    pub module: Option<String>,
+    /// The offset of the instruction in `module`
+    pub module_offset: Option<u64>,
+}
+
+/// A trait for [`Observer`]`s` holding the stack trace of the last run, e.g. for crash reports
+pub trait ObserverWithStacktrace {
+    /// The stack trace of the last run, innermost frame first
+    fn stacktrace(&self) -> Vec<SymbolizedFrame>;
+
+    /// The report a sanitizer printed in the last run, if any
+    fn sanitizer_report(&self) -> Option<&str> {
+        None
+    }
+
+    /// The signal that terminated the last run, if known
+    fn signal(&self) -> Option<i32> {
+        None
+    }
+
+    /// How the last run ended, as seen in `post_exec`, or `None` if it did not run yet
+    fn exit_kind(&self) -> Option<ExitKind>;
+}
+
+/// Collects the backtrace via [`Backtrace`] and resolves its symbols.
+///
+/// If called from a signal handler, the frames of the handler are left out.
+#[must_use]
+pub fn collect_symbolized_backtrace() -> Vec<SymbolizedFrame> {
+    symbolize_backtrace(&mut Backtrace::new())
+}
+
+/// Resolves the symbols of the given [`Backtrace`], leaving out the frames of a signal handler
+fn symbolize_backtrace(b: &mut Backtrace) -> Vec<SymbolizedFrame> {
+    b.resolve();
+    let frames = b.frames();
+    let start = frames
+        .iter()
+        .rposition(|frame| {
+            frame.symbols().iter().any(|symbol| {
+                symbol
+                    .name()
+                    .and_then(|name| name.as_str())
+                    .is_some_and(|name| {
+                        ["__restore_rt", "_sigtramp", "__kernel_rt_sigreturn"].contains(&name)
+                    })
+            })
+        })
+        .map_or(1, |idx| idx + 1);
+    frames[start.min(frames.len())..]
+        .iter()
+        .map(|frame| {
+            let symbol = frame.symbols().first();
+            SymbolizedFrame {
+                address: frame.ip() as u64,
+                function: symbol
+                    .and_then(BacktraceSymbol::name)
+                    .map(|name| name.to_string()),
+                file: symbol
+                    .and_then(|s| s.filename())
+                    .map(|file| file.to_string_lossy().into_owned()),
+                line: symbol.and_then(BacktraceSymbol::lineno),
+                module: None,
+                module_offset: None,
+            }
+        })
+        .collect()
+}
+
 /// Collects the backtrace via [`Backtrace`] and [`Debug`]
 /// ([`Debug`] is currently used for dev purposes, symbols hash will be used eventually)
 #[must_use]
 pub fn collect_backtrace() -> u64 {
-    let b = Backtrace::new_unresolved();
+    hash_backtrace(&mut Backtrace::new_unresolved())
+}
+
+#[cfg(not(feature = "casr"))]
+/// Hashes the addresses of the given [`Backtrace`]
+fn hash_backtrace(b: &mut Backtrace) -> u64 {
     if b.frames().is_empty() {
         return 0;
     }
@@ -66,10 +155,8 @@ pub fn collect_backtrace() -> u64 {
 }
 
 #[cfg(feature = "casr")]
-/// Collects the backtrace via [`Backtrace`]
-#[must_use]
-pub fn collect_backtrace() -> u64 {
-    let mut b = Backtrace::new_unresolved();
+/// Hashes the filtered, symbolized stack of the given [`Backtrace`]
+fn hash_backtrace(b: &mut Backtrace) -> u64 {
     if b.frames().is_empty() {
         return 0;
     }
@@ -116,6 +203,11 @@ pub struct BacktraceObserver<'a> {
     observer_name: Cow<'static, str>,
     hash: OwnedRefMut<'a, Option<u64>>,
     harness_type: HarnessType,
+    /// If the stack of a crash is symbolized, see [`Self::symbolize`]
+    symbolize: bool,
+    /// The symbolized stack of the last crash, only collected for [`HarnessType::InProcess`]
+    frames: Vec<SymbolizedFrame>,
+    exit_kind: Option<ExitKind>,
 }
 
 impl<'a> BacktraceObserver<'a> {
@@ -134,6 +226,9 @@ impl<'a> BacktraceObserver<'a> {
             observer_name: observer_name.into(),
             hash: backtrace_hash,
             harness_type,
+            symbolize: false,
+            frames: Vec::new(),
+            exit_kind: None,
         }
     }
 
@@ -153,6 +248,9 @@ impl<'a> BacktraceObserver<'a> {
             observer_name: observer_name.into(),
             hash: backtrace_hash,
             harness_type,
+            symbolize: false,
+            frames: Vec::new(),
+            exit_kind: None,
         }
     }
 
@@ -198,6 +296,17 @@ impl<'a> BacktraceObserver<'a> {
         *self.hash.as_mut() = None;
     }
 
+    /// Also resolves the symbols of the stack of a crash, for its
+    /// [`ObserverWithStacktrace::stacktrace`], e.g. for crash reports.
+    ///
+    /// Only for [`HarnessType::InProcess`]. This runs in the crash handler and is slow, by default
+    /// only the hash is collected.
+    #[must_use]
+    pub fn symbolize(mut self) -> Self {
+        self.symbolize = true;
+        self
+    }
+
     /// Fill the hash value if the harness type is external
     pub fn fill_external(&mut self, hash: u64, exit_kind: &ExitKind) {
         if self.harness_type == HarnessType::External {
@@ -217,13 +326,30 @@ impl ObserverWithHashField for BacktraceObserver<'_> {
     }
 }
 
+impl ObserverWithStacktrace for BacktraceObserver<'_> {
+    fn stacktrace(&self) -> Vec<SymbolizedFrame> {
+        self.frames.clone()
+    }
+
+    fn exit_kind(&self) -> Option<ExitKind> {
+        self.exit_kind
+    }
+}
+
 impl<I, S> Observer<I, S> for BacktraceObserver<'_> {
     fn post_exec(&mut self, _state: &mut S, _input: &I, exit_kind: &ExitKind) -> Result<(), Error> {
+        self.exit_kind = Some(*exit_kind);
         if self.harness_type == HarnessType::InProcess {
             if *exit_kind == ExitKind::Crash {
-                self.update_hash(collect_backtrace());
+                // We may be in the crash handler, walk the stack only once
+                let mut backtrace = Backtrace::new_unresolved();
+                self.update_hash(hash_backtrace(&mut backtrace));
+                if self.symbolize {
+                    self.frames = symbolize_backtrace(&mut backtrace);
+                }
             } else {
                 self.clear_hash();
+                self.frames.clear();
             }
         }
         Ok(())
@@ -278,6 +404,9 @@ pub fn get_asan_runtime_flags() -> String {
 pub struct AsanBacktraceObserver {
     observer_name: Cow<'static, str>,
     hash: Option<u64>,
+    report: Option<String>,
+    signal: Option<i32>,
+    exit_kind: Option<ExitKind>,
 }
 
 impl AsanBacktraceObserver {
@@ -291,6 +420,9 @@ impl AsanBacktraceObserver {
         Self {
             observer_name: observer_name.into(),
             hash: None,
+            report: None,
+            signal: None,
+            exit_kind: None,
         }
     }
 
@@ -305,6 +437,9 @@ impl AsanBacktraceObserver {
         Self {
             observer_name: observer_name.into(),
             hash: None,
+            report: None,
+            signal: None,
+            exit_kind: None,
         }
     }
 
@@ -342,6 +477,7 @@ impl AsanBacktraceObserver {
             hash ^= u64::from_str_radix(g.as_str(), 16).unwrap();
         });
         self.update_hash(hash);
+        self.update_report(output);
     }
 
     #[cfg(feature = "casr")]
@@ -357,12 +493,83 @@ impl AsanBacktraceObserver {
             hash = s.finish();
         }
         self.update_hash(hash);
+        self.update_report(output);
     }
 
     /// Updates the hash value of this observer.
     fn update_hash(&mut self, hash: u64) {
         self.hash = Some(hash);
     }
+
+    /// Keeps the report and the signal it names, if any.
+    fn update_report(&mut self, output: &str) {
+        self.signal = parse_asan_signal(output);
+        self.report = Some(output.to_string());
+    }
+
+    /// Sets the signal that terminated the target, called by the executor.
+    pub fn set_signal(&mut self, signal: i32) {
+        self.signal = Some(signal);
+    }
+}
+
+/// Matches the signal in an ASAN report
+static ASAN_SIGNAL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("Sanitizer: (SEGV|BUS|FPE|ILL|ABRT|TRAP) ").unwrap());
+
+/// Matches a frame of an ASAN report
+static ASAN_FRAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*#(\d+) 0x([0-9a-f]+)(?: in (.*?))?(?:\s+\((.+)\+0x([0-9a-f]+)\)| ([^ ]+?):(\d+)(?::\d+)?)?\s*$").unwrap()
+});
+
+/// The signal an ASAN report names, e.g. `AddressSanitizer: SEGV on unknown address`
+fn parse_asan_signal(report: &str) -> Option<i32> {
+    let signal = ASAN_SIGNAL_REGEX.captures(report)?.get(1)?.as_str();
+    #[cfg(unix)]
+    {
+        Some(match signal {
+            "SEGV" => libc::SIGSEGV,
+            "BUS" => libc::SIGBUS,
+            "FPE" => libc::SIGFPE,
+            "ILL" => libc::SIGILL,
+            "ABRT" => libc::SIGABRT,
+            _ => libc::SIGTRAP,
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        None
+    }
+}
+
+/// Parses the first stack of an ASAN report, with frames like
+/// `#0 0x4f5b2d in main /src/test.c:10:5` or `#1 0x7f1b (/lib/libc.so.6+0x21b96)`
+fn parse_asan_frames(report: &str) -> Vec<SymbolizedFrame> {
+    let mut frames = Vec::new();
+    for line in report.lines() {
+        let Some(m) = ASAN_FRAME_REGEX.captures(line) else {
+            if frames.is_empty() {
+                continue;
+            }
+            break;
+        };
+        // The allocation and free stacks follow, starting at #0 again
+        if &m[1] == "0" && !frames.is_empty() {
+            break;
+        }
+        frames.push(SymbolizedFrame {
+            address: u64::from_str_radix(&m[2], 16).unwrap_or_default(),
+            function: m.get(3).map(|f| f.as_str().to_string()),
+            file: m.get(6).map(|f| f.as_str().to_string()),
+            line: m.get(7).and_then(|l| l.as_str().parse().ok()),
+            module: m.get(4).map(|module| module.as_str().to_string()),
+            module_offset: m
+                .get(5)
+                .and_then(|o| u64::from_str_radix(o.as_str(), 16).ok()),
+        });
+    }
+    frames
 }
 
 impl ObserverWithHashField for AsanBacktraceObserver {
@@ -372,16 +579,68 @@ impl ObserverWithHashField for AsanBacktraceObserver {
     }
 }
 
+impl ObserverWithStacktrace for AsanBacktraceObserver {
+    fn stacktrace(&self) -> Vec<SymbolizedFrame> {
+        self.report
+            .as_deref()
+            .map(parse_asan_frames)
+            .unwrap_or_default()
+    }
+
+    fn sanitizer_report(&self) -> Option<&str> {
+        self.report.as_deref()
+    }
+
+    fn signal(&self) -> Option<i32> {
+        self.signal
+    }
+
+    fn exit_kind(&self) -> Option<ExitKind> {
+        self.exit_kind
+    }
+}
+
 impl Default for AsanBacktraceObserver {
     fn default() -> Self {
         Self::new("AsanBacktraceObserver")
     }
 }
 
-impl<I, S> Observer<I, S> for AsanBacktraceObserver {}
+impl<I, S> Observer<I, S> for AsanBacktraceObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.report = None;
+        self.signal = None;
+        self.exit_kind = None;
+        Ok(())
+    }
+
+    fn post_exec(&mut self, _state: &mut S, _input: &I, exit_kind: &ExitKind) -> Result<(), Error> {
+        self.exit_kind = Some(*exit_kind);
+        Ok(())
+    }
+}
 
 impl Named for AsanBacktraceObserver {
     fn name(&self) -> &Cow<'static, str> {
         &self.observer_name
     }
 }
+
+#[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+impl ObserverWithStacktrace for HangBacktraceObserver {
+    fn stacktrace(&self) -> Vec<SymbolizedFrame> {
+        self.frames()
+            .iter()
+            .map(|frame| SymbolizedFrame {
+                address: frame.address,
+                module: frame.module.clone(),
+                module_offset: frame.module.as_ref().map(|_| frame.offset),
+                ..SymbolizedFrame::default()
+            })
+            .collect()
+    }
+
+    fn exit_kind(&self) -> Option<ExitKind> {
+        self.last_exit_kind()
+    }
+}
@@ -204,6 +204,10 @@ pub struct StdOutMarker;
 #[derive(Debug, Clone)]
 pub struct StdErrMarker;
 
+/// Marker traits to mark socket responses for the `OutputObserver`
+#[derive(Debug, Clone)]
+pub struct ResponseMarker;
+
 impl<T> OutputObserver<T> {
     // This is the best we can do on macOS because
     // - macos doesn't have memfd_create
@@ -351,3 +355,8 @@ impl<T> AsMut<Self> for OutputObserver<T> {
 pub type StdOutObserver = OutputObserver<StdOutMarker>;
 /// An observer that captures stderr of a target.
 pub type StdErrObserver = OutputObserver<StdErrMarker>;
+/// An observer that captures the data a target sent back over its input socket, see
+/// [`libafl_bolts::target_args::SocketDelivery`].
+///
+/// The executor fills it directly, so create it with [`OutputObserver::new_piped`].
+pub type ResponseObserver = OutputObserver<ResponseMarker>;
@@ -0,0 +1,254 @@
+//! The [`EdgeIndexScheduler`] keeps an index from each edge to the [`Testcase`] that first covered
+//! it, to answer "which input first reached this edge" when debugging coverage.
+
+use alloc::vec::Vec;
+use core::{marker::PhantomData, time::Duration};
+#[cfg(feature = "std")]
+use std::{fs::File, io::BufWriter, path::Path};
+
+use hashbrown::HashMap;
+use libafl_bolts::{current_time, impl_serdeany, tuples::MatchName};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, HasMetadata,
+    corpus::{Corpus, CorpusId, Testcase},
+    feedbacks::MapIndexesMetadata,
+    observers::CanTrack,
+    require_index_tracking,
+    schedulers::{RemovableScheduler, Scheduler},
+    state::{HasCorpus, HasExecutions, HasStartTime},
+};
+
+/// The first discovery of an edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeDiscovery {
+    /// The testcase that first covered the edge
+    pub id: CorpusId,
+    /// The time since the start of the fuzzer
+    pub time: Duration,
+    /// The number of executions of the fuzzer at that time
+    pub executions: u64,
+}
+
+/// An entry of an [`EdgeIndexMetadata`] dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeIndexEntry {
+    /// The index of the edge in the map
+    pub edge: usize,
+    /// The PC of the edge, if known
+    pub pc: Option<usize>,
+    /// The first discovery of the edge
+    pub discovery: EdgeDiscovery,
+}
+
+/// A state metadata holding the first discovery of each edge, maintained by the
+/// [`EdgeIndexScheduler`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct EdgeIndexMetadata {
+    /// map index -> first discovery
+    pub map: HashMap<usize, EdgeDiscovery>,
+}
+
+impl_serdeany!(EdgeIndexMetadata);
+
+impl EdgeIndexMetadata {
+    /// The first discovery of the given edge, if it was covered
+    #[must_use]
+    pub fn first(&self, edge: usize) -> Option<&EdgeDiscovery> {
+        self.map.get(&edge)
+    }
+
+    /// The edges first covered by the given testcase
+    #[must_use]
+    pub fn edges_of(&self, id: CorpusId) -> Vec<usize> {
+        let mut edges: Vec<usize> = self
+            .map
+            .iter()
+            .filter(|(_, discovery)| discovery.id == id)
+            .map(|(edge, _)| *edge)
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    /// All entries of the index, sorted by edge, with the PC of each edge from `pc_of`, e.g.
+    /// `libafl_targets::sanitizer_cov_pc_of_edge`
+    pub fn entries<F>(&self, pc_of: F) -> Vec<EdgeIndexEntry>
+    where
+        F: Fn(usize) -> Option<usize>,
+    {
+        let mut entries: Vec<EdgeIndexEntry> = self
+            .map
+            .iter()
+            .map(|(edge, discovery)| EdgeIndexEntry {
+                edge: *edge,
+                pc: pc_of(*edge),
+                discovery: *discovery,
+            })
+            .collect();
+        entries.sort_unstable_by_key(|entry| entry.edge);
+        entries
+    }
+
+    /// Dumps the index as JSON to `path`, for the `edge_index` tool
+    #[cfg(feature = "std")]
+    pub fn dump<P, F>(&self, path: P, pc_of: F) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        F: Fn(usize) -> Option<usize>,
+    {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, &self.entries(pc_of))
+            .map_err(|err| Error::serialize(format!("Failed to dump the edge index: {err}")))
+    }
+}
+
+/// A [`Scheduler`] wrapper recording, for each edge, the [`Testcase`] that first covered it in the
+/// [`EdgeIndexMetadata`] of the state.
+///
+/// The edges come from the [`MapIndexesMetadata`] of each added testcase, so the map feedback needs
+/// to track indexes. The index is kept when a testcase is removed, as it records its history.
+#[derive(Debug, Clone)]
+pub struct EdgeIndexScheduler<CS, O> {
+    base: CS,
+    phantom: PhantomData<O>,
+}
+
+impl<CS, O> EdgeIndexScheduler<CS, O>
+where
+    O: CanTrack,
+{
+    /// Creates a new [`EdgeIndexScheduler`] that wraps a `base` [`Scheduler`].
+    ///
+    /// Pass the edges observer, it needs to track indexes.
+    pub fn new(_observer: &O, base: CS) -> Self {
+        require_index_tracking!("EdgeIndexScheduler", O);
+        Self {
+            base,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The wrapped scheduler
+    pub fn base(&self) -> &CS {
+        &self.base
+    }
+}
+
+impl<CS, I, O, S> RemovableScheduler<I, S> for EdgeIndexScheduler<CS, O>
+where
+    CS: RemovableScheduler<I, S>,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut S,
+        id: CorpusId,
+        testcase: &Option<Testcase<I>>,
+    ) -> Result<(), Error> {
+        self.base.on_remove(state, id, testcase)
+    }
+
+    fn on_replace(&mut self, state: &mut S, id: CorpusId, prev: &Testcase<I>) -> Result<(), Error> {
+        self.base.on_replace(state, id, prev)
+    }
+}
+
+impl<CS, I, O, S> Scheduler<I, S> for EdgeIndexScheduler<CS, O>
+where
+    CS: Scheduler<I, S>,
+    S: HasCorpus<I> + HasMetadata + HasExecutions + HasStartTime,
+{
+    fn on_add(&mut self, state: &mut S, id: CorpusId) -> Result<(), Error> {
+        self.base.on_add(state, id)?;
+
+        let edges = {
+            let testcase = state.corpus().get(id)?.borrow();
+            match testcase.metadata_map().get::<MapIndexesMetadata>() {
+                Some(meta) => meta.list.clone(),
+                None => return Ok(()),
+            }
+        };
+        let discovery = EdgeDiscovery {
+            id,
+            time: current_time().saturating_sub(*state.start_time()),
+            executions: *state.executions(),
+        };
+        let index = state.metadata_or_insert_with(EdgeIndexMetadata::default);
+        for edge in edges {
+            index.map.entry(edge).or_insert(discovery);
+        }
+        Ok(())
+    }
+
+    fn on_evaluation<OT>(&mut self, state: &mut S, input: &I, observers: &OT) -> Result<(), Error>
+    where
+        OT: MatchName,
+    {
+        self.base.on_evaluation(state, input, observers)
+    }
+
+    fn next(&mut self, state: &mut S) -> Result<CorpusId, Error> {
+        self.base.next(state)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut S,
+        next_id: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        self.base.set_current_scheduled(state, next_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::rands::StdRand;
+
+    use super::{EdgeIndexMetadata, EdgeIndexScheduler};
+    use crate::{
+        HasMetadata,
+        corpus::{Corpus, InMemoryCorpus, Testcase},
+        feedbacks::MapIndexesMetadata,
+        inputs::BytesInput,
+        observers::{CanTrack, StdMapObserver},
+        schedulers::{QueueScheduler, Scheduler},
+        state::{HasCorpus, StdState},
+    };
+
+    #[test]
+    fn test_edge_index_scheduler() {
+        let observer = StdMapObserver::owned("edges", vec![0_u8; 8]).track_indices();
+        let mut scheduler = EdgeIndexScheduler::new(&observer, QueueScheduler::new());
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<BytesInput>::new(),
+            InMemoryCorpus::new(),
+            &mut (),
+            &mut (),
+        )
+        .unwrap();
+
+        let mut ids = vec![];
+        for edges in [vec![1, 2], vec![2, 5]] {
+            let mut testcase = Testcase::new(BytesInput::new(vec![0]));
+            testcase.add_metadata(MapIndexesMetadata::new(edges));
+            let id = state.corpus_mut().add(testcase).unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+            ids.push(id);
+        }
+
+        let index = state.metadata::<EdgeIndexMetadata>().unwrap();
+        assert_eq!(index.first(2).unwrap().id, ids[0]);
+        assert_eq!(index.first(5).unwrap().id, ids[1]);
+        assert!(index.first(3).is_none());
+        assert_eq!(index.edges_of(ids[0]), vec![1, 2]);
+        let entries = index.entries(|edge| Some(0x1000 + edge));
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].pc, Some(0x1005));
+    }
+}
@@ -14,11 +14,17 @@ pub use minimizer::{
     IndexesLenTimeMinimizerScheduler, LenTimeMinimizerScheduler, MinimizerScheduler,
 };
 
+pub mod edge_index;
+pub use edge_index::{EdgeIndexMetadata, EdgeIndexScheduler};
+
+pub mod perf;
+pub use perf::PerfScheduler;
+
 pub mod powersched;
 pub use powersched::{PowerQueueScheduler, SchedulerMetadata};
 
 pub mod probabilistic_sampling;
-pub use probabilistic_sampling::ProbabilitySamplingScheduler;
+pub use probabilistic_sampling::{ProbabilitySamplingScheduler, ProtocolStateScheduler};
 
 pub mod accounting;
 pub use accounting::CoverageAccountingScheduler;
@@ -0,0 +1,289 @@
+//! The [`PerfScheduler`] prefers the [`Testcase`]`s` holding the maximum hit count of any edge, like
+//! `PerfFuzz`, to find algorithmic complexity bugs.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use hashbrown::HashMap;
+use libafl_bolts::{rands::Rand, tuples::MatchName};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, HasMetadata,
+    corpus::{Corpus, CorpusId, Testcase},
+    feedbacks::HitCountsMetadata,
+    schedulers::{
+        RemovableScheduler, Scheduler,
+        minimizer::{DEFAULT_SKIP_NON_FAVORED_PROB, IsFavoredMetadata},
+    },
+    state::{HasCorpus, HasRand},
+};
+
+/// A state metadata holding the testcase with the maximum hit count for each map entry
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct TopCountsMetadata {
+    /// map index -> (corpus index, hit count)
+    pub map: HashMap<usize, (CorpusId, u64)>,
+}
+
+libafl_bolts::impl_serdeany!(TopCountsMetadata);
+
+impl TopCountsMetadata {
+    /// Getter for map
+    #[must_use]
+    pub fn map(&self) -> &HashMap<usize, (CorpusId, u64)> {
+        &self.map
+    }
+}
+
+/// The [`PerfScheduler`] favors each [`Testcase`] holding the maximum hit count seen so far of any
+/// map entry, and skips the others with a probability, like `PerfFuzz`.
+///
+/// Use it together with a [`crate::feedbacks::PerfFeedback`] over an unbucketed hit count map, e.g.
+/// the 32-bit edge counters of `libafl_targets`. The hit counts of each testcase are taken from the
+/// [`HitCountsMetadata`] the feedback adds to it.
+#[derive(Debug, Clone)]
+pub struct PerfScheduler<C, CS> {
+    base: CS,
+    skip_non_favored_prob: f64,
+    phantom: PhantomData<C>,
+}
+
+impl<C, CS> PerfScheduler<C, CS> {
+    /// Creates a new [`PerfScheduler`] that wraps a `base` [`Scheduler`], for the hit counts of
+    /// the given map observer.
+    pub fn new(map_observer: &C, base: CS) -> Self {
+        Self::with_skip_prob(map_observer, base, DEFAULT_SKIP_NON_FAVORED_PROB)
+    }
+
+    /// Creates a new [`PerfScheduler`] that wraps a `base` [`Scheduler`], skipping the non-favored
+    /// [`Testcase`]s with the given probability.
+    pub fn with_skip_prob(_map_observer: &C, base: CS, skip_non_favored_prob: f64) -> Self {
+        Self {
+            base,
+            skip_non_favored_prob,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The wrapped scheduler
+    pub fn base(&self) -> &CS {
+        &self.base
+    }
+}
+
+impl<C, CS> PerfScheduler<C, CS> {
+    /// Makes the testcase `id` the top one of each entry for which it holds a new maximum hit count
+    fn update_top_counts<I, S>(state: &mut S, id: CorpusId) -> Result<(), Error>
+    where
+        S: HasCorpus<I> + HasMetadata,
+    {
+        let counts = state
+            .corpus()
+            .get(id)?
+            .borrow()
+            .metadata_map()
+            .get::<HitCountsMetadata>()
+            .map(|meta| meta.counts.clone())
+            .unwrap_or_default();
+        let mut favored = false;
+        let mut dethroned = Vec::new();
+        {
+            let top_counts = state.metadata_or_insert_with(TopCountsMetadata::default);
+            for (entry, count) in counts {
+                match top_counts.map.get(&entry) {
+                    Some((_, top)) if *top >= count => {}
+                    previous => {
+                        if let Some((previous_id, _)) = previous {
+                            dethroned.push(*previous_id);
+                        }
+                        top_counts.map.insert(entry, (id, count));
+                        favored = true;
+                    }
+                }
+            }
+            dethroned.retain(|previous_id| {
+                *previous_id != id
+                    && !top_counts
+                        .map
+                        .values()
+                        .any(|(top_id, _)| top_id == previous_id)
+            });
+        }
+
+        for previous_id in dethroned {
+            if let Ok(testcase) = state.corpus().get(previous_id) {
+                let _ = testcase
+                    .borrow_mut()
+                    .metadata_map_mut()
+                    .remove::<IsFavoredMetadata>();
+            }
+        }
+        if favored {
+            state
+                .corpus()
+                .get(id)?
+                .borrow_mut()
+                .add_metadata(IsFavoredMetadata {});
+        }
+        Ok(())
+    }
+}
+
+impl<C, CS, I, S> RemovableScheduler<I, S> for PerfScheduler<C, CS>
+where
+    CS: RemovableScheduler<I, S>,
+    S: HasCorpus<I> + HasMetadata,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut S,
+        id: CorpusId,
+        testcase: &Option<Testcase<I>>,
+    ) -> Result<(), Error> {
+        self.base.on_remove(state, id, testcase)?;
+        // the maximum stays the same, the next testcase reaching it becomes the top one
+        if let Some(meta) = state.metadata_map_mut().get_mut::<TopCountsMetadata>() {
+            meta.map.retain(|_, (top_id, _)| *top_id != id);
+        }
+        Ok(())
+    }
+
+    fn on_replace(&mut self, state: &mut S, id: CorpusId, prev: &Testcase<I>) -> Result<(), Error> {
+        self.base.on_replace(state, id, prev)?;
+        // the new testcase competes with its own hit counts for the entries of the previous one
+        if let Some(meta) = state.metadata_map_mut().get_mut::<TopCountsMetadata>() {
+            meta.map.retain(|_, (top_id, _)| *top_id != id);
+        }
+        let _ = state
+            .corpus()
+            .get(id)?
+            .borrow_mut()
+            .metadata_map_mut()
+            .remove::<IsFavoredMetadata>();
+        Self::update_top_counts(state, id)
+    }
+}
+
+impl<C, CS, I, S> Scheduler<I, S> for PerfScheduler<C, CS>
+where
+    CS: Scheduler<I, S>,
+    S: HasCorpus<I> + HasMetadata + HasRand,
+{
+    /// Called when a [`Testcase`] is added to the corpus
+    fn on_add(&mut self, state: &mut S, id: CorpusId) -> Result<(), Error> {
+        self.base.on_add(state, id)?;
+        Self::update_top_counts(state, id)
+    }
+
+    fn on_evaluation<OT>(&mut self, state: &mut S, input: &I, observers: &OT) -> Result<(), Error>
+    where
+        OT: MatchName,
+    {
+        self.base.on_evaluation(state, input, observers)
+    }
+
+    /// Gets the next entry, skipping the non-favored ones with a probability
+    fn next(&mut self, state: &mut S) -> Result<CorpusId, Error> {
+        let mut id = self.base.next(state)?;
+        while {
+            !state
+                .corpus()
+                .get(id)?
+                .borrow()
+                .has_metadata::<IsFavoredMetadata>()
+        } && state.rand_mut().coinflip(self.skip_non_favored_prob)
+        {
+            id = self.base.next(state)?;
+        }
+        Ok(id)
+    }
+
+    /// Set current fuzzed corpus id and `scheduled_count`
+    fn set_current_scheduled(
+        &mut self,
+        _state: &mut S,
+        _next_id: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        // We do nothing here, the inner scheduler will take care of it
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PerfScheduler, TopCountsMetadata};
+    use crate::{
+        HasMetadata,
+        corpus::{Corpus, CorpusId, InMemoryCorpus, Testcase},
+        feedbacks::HitCountsMetadata,
+        inputs::BytesInput,
+        observers::StdMapObserver,
+        schedulers::{QueueScheduler, RemovableScheduler, Scheduler, minimizer::IsFavoredMetadata},
+        state::{HasCorpus, StdState},
+    };
+
+    fn testcase(counts: &[u64]) -> Testcase<BytesInput> {
+        let mut testcase = Testcase::new(BytesInput::new(vec![0]));
+        testcase.add_metadata(HitCountsMetadata {
+            counts: counts
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|(_, count)| *count != 0)
+                .collect(),
+        });
+        testcase
+    }
+
+    #[test]
+    fn test_perf_scheduler() {
+        let observer = StdMapObserver::owned("counts", vec![0_u32; 4]);
+        let mut scheduler = PerfScheduler::new(&observer, QueueScheduler::new());
+        let mut state = StdState::new(
+            libafl_bolts::rands::StdRand::with_seed(0),
+            InMemoryCorpus::<BytesInput>::new(),
+            InMemoryCorpus::new(),
+            &mut (),
+            &mut (),
+        )
+        .unwrap();
+
+        let mut ids = vec![];
+        for run in [[1, 5, 0, 0], [1, 9, 0, 0], [0, 9, 0, 0]] {
+            let id = state.corpus_mut().add(testcase(&run)).unwrap();
+            scheduler.on_add(&mut state, id).unwrap();
+            ids.push(id);
+        }
+
+        let favored = |state: &StdState<InMemoryCorpus<BytesInput>, _, _, _>, id: CorpusId| {
+            state
+                .corpus()
+                .get(id)
+                .unwrap()
+                .borrow()
+                .has_metadata::<IsFavoredMetadata>()
+        };
+        // the first one still holds the maximum of entry 0, the second one the one of entry 1
+        assert!(favored(&state, ids[0]));
+        assert!(favored(&state, ids[1]));
+        assert!(!favored(&state, ids[2]));
+        let top_counts = state.metadata::<TopCountsMetadata>().unwrap();
+        assert_eq!(top_counts.map()[&1], (ids[1], 9));
+
+        // the replacement of the first one only hits entry 2
+        let prev = state
+            .corpus_mut()
+            .replace(ids[0], testcase(&[0, 0, 3, 0]))
+            .unwrap();
+        scheduler.on_replace(&mut state, ids[0], &prev).unwrap();
+        let top_counts = state.metadata::<TopCountsMetadata>().unwrap();
+        assert!(!top_counts.map().contains_key(&0));
+        assert_eq!(top_counts.map()[&2], (ids[0], 3));
+        assert!(favored(&state, ids[0]));
+    }
+}
@@ -11,7 +11,9 @@ use serde::{Deserialize, Serialize};
 use crate::{
     Error, HasMetadata,
     corpus::{Corpus, CorpusId, Testcase},
-    schedulers::{RemovableScheduler, Scheduler, TestcaseScore},
+    schedulers::{
+        RemovableScheduler, Scheduler, TestcaseScore, testcase_score::ProtocolStateTestcaseScore,
+    },
     state::{HasCorpus, HasRand},
 };
 
@@ -181,6 +183,11 @@ impl<F> Default for ProbabilitySamplingScheduler<F> {
     }
 }
 
+/// A [`ProbabilitySamplingScheduler`] for stateful targets, picking message sequences
+/// proportionally to the number of distinct protocol states they reach, as recorded by
+/// [`crate::feedbacks::ProtocolStateFeedback`].
+pub type ProtocolStateScheduler = ProbabilitySamplingScheduler<ProtocolStateTestcaseScore>;
+
 #[cfg(test)]
 #[cfg(feature = "std")]
 mod tests {
@@ -188,10 +195,11 @@ mod tests {
 
     use libafl_bolts::rands::StdRand;
 
+    use super::{ProbabilityMetadata, ProtocolStateScheduler};
     use crate::{
-        Error,
+        Error, HasMetadata,
         corpus::{Corpus, InMemoryCorpus, Testcase},
-        feedbacks::ConstFeedback,
+        feedbacks::{ConstFeedback, protocol::ProtocolStateTestcaseMetadata},
         inputs::bytes::BytesInput,
         schedulers::{ProbabilitySamplingScheduler, Scheduler, TestcaseScore},
         state::{HasCorpus, StdState},
@@ -255,4 +263,41 @@ mod tests {
         assert_eq!(next_id1, next_id2);
         assert_ne!(next_id1, next_id3);
     }
+
+    #[test]
+    fn test_protocol_state_scheduler() {
+        // # Safety
+        // No concurrency per testcase
+        #[cfg(any(not(feature = "serdeany_autoreg"), miri))]
+        unsafe {
+            super::ProbabilityMetadata::register();
+        }
+
+        let mut scheduler = ProtocolStateScheduler::new();
+
+        let mut corpus = InMemoryCorpus::new();
+        let shallow = Testcase::new(BytesInput::new(vec![0_u8; 4]));
+        let mut deep = Testcase::new(BytesInput::new(vec![1_u8; 4]));
+        deep.add_metadata(ProtocolStateTestcaseMetadata {
+            states: vec![220, 331, 230],
+            depth: 3,
+        });
+        let shallow_id = corpus.add(shallow).unwrap();
+        let deep_id = corpus.add(deep).unwrap();
+
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut ConstFeedback::new(false),
+            &mut ConstFeedback::new(false),
+        )
+        .unwrap();
+        scheduler.on_add(&mut state, shallow_id).unwrap();
+        scheduler.on_add(&mut state, deep_id).unwrap();
+
+        let meta = state.metadata::<ProbabilityMetadata>().unwrap();
+        assert!((meta.map[&shallow_id] - 1.0).abs() < f64::EPSILON);
+        assert!((meta.map[&deep_id] - 4.0).abs() < f64::EPSILON);
+    }
 }
@@ -7,7 +7,7 @@ use num_traits::Zero;
 use crate::{
     Error, HasMetadata,
     corpus::{Corpus, SchedulerTestcaseMetadata, Testcase},
-    feedbacks::MapIndexesMetadata,
+    feedbacks::{MapIndexesMetadata, protocol::ProtocolStateTestcaseMetadata},
     schedulers::{
         minimizer::{IsFavoredMetadata, TopRatedsMetadata},
         powersched::{BaseSchedule, SchedulerMetadata},
@@ -353,3 +353,18 @@ where
         Ok(weight)
     }
 }
+
+/// Score testcases by the number of distinct protocol states they reach, as recorded by
+/// [`crate::feedbacks::ProtocolStateFeedback`]. Sequences reaching deeper states are preferred.
+#[derive(Debug, Clone)]
+pub struct ProtocolStateTestcaseScore {}
+
+impl<I, S> TestcaseScore<I, S> for ProtocolStateTestcaseScore {
+    #[expect(clippy::cast_precision_loss)]
+    fn compute(_state: &S, entry: &mut Testcase<I>) -> Result<f64, Error> {
+        Ok(entry
+            .metadata_map()
+            .get::<ProtocolStateTestcaseMetadata>()
+            .map_or(1.0, |meta| 1.0 + meta.depth as f64))
+    }
+}
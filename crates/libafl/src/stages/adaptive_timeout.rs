@@ -0,0 +1,259 @@
+//! The adaptive timeout stage derives the executor timeout from the exec times measured by the
+//! [`crate::stages::CalibrationStage`], similar to what AFL does when no `-t` is given.
+
+use alloc::vec::Vec;
+use core::{marker::PhantomData, time::Duration};
+
+use libafl_bolts::impl_serdeany;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, HasMetadata,
+    corpus::{CorpusId, HasCurrentCorpusId},
+    executors::SetTimeout,
+    stages::{Restartable, Stage},
+    state::HasCurrentTestcase,
+};
+
+/// AFL's `EXEC_TM_ROUND`, timeouts are rounded up to a multiple of it
+const TIMEOUT_ROUND: Duration = Duration::from_millis(20);
+
+/// The exec time statistic the adaptive timeout is a multiple of
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeoutBase {
+    /// The average exec time of all calibrated testcases
+    Average,
+    /// The given percentile (in `(0, 100]`) of the exec times of all calibrated testcases
+    Percentile(f64),
+}
+
+/// The exec times observed so far and the timeout derived from them.
+///
+/// The [`crate::stages::VerifyTimeoutsStage`] uses this timeout as base, if present.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdaptiveTimeoutMetadata {
+    /// The calibrated exec times, sorted
+    exec_times: Vec<Duration>,
+    /// The sum of `exec_times`
+    total: Duration,
+    /// The last sampled testcase, to not count it twice after a restart
+    last_sampled: Option<CorpusId>,
+    /// The timeout currently set on the executor
+    timeout: Duration,
+}
+
+impl_serdeany!(AdaptiveTimeoutMetadata);
+
+impl AdaptiveTimeoutMetadata {
+    /// The timeout currently set on the executor
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// The number of exec times the timeout is derived from
+    #[must_use]
+    pub fn samples(&self) -> usize {
+        self.exec_times.len()
+    }
+
+    fn add_sample(&mut self, exec_time: Duration) {
+        let idx = self.exec_times.partition_point(|t| *t <= exec_time);
+        self.exec_times.insert(idx, exec_time);
+        self.total += exec_time;
+    }
+
+    /// Returns the statistic over all samples, or `None` if there are none
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn base(&self, base: TimeoutBase) -> Option<Duration> {
+        if self.exec_times.is_empty() {
+            return None;
+        }
+        let len = self.exec_times.len();
+        match base {
+            TimeoutBase::Average => Some(self.total / len as u32),
+            TimeoutBase::Percentile(percentile) => {
+                let rank = libm::ceil(percentile / 100.0 * len as f64) as usize;
+                Some(self.exec_times[rank.clamp(1, len) - 1])
+            }
+        }
+    }
+}
+
+/// Sets the executor timeout to a multiple of the exec times measured by the
+/// [`crate::stages::CalibrationStage`], clamped to the given bounds.
+///
+/// Place it right after the [`crate::stages::CalibrationStage`]. Each newly calibrated testcase
+/// is added to the statistic and the timeout is updated, so it keeps up as the corpus grows.
+/// Until the first testcase is calibrated, the timeout the executor was built with applies.
+#[derive(Debug, Clone)]
+pub struct AdaptiveTimeoutStage<E, I> {
+    base: TimeoutBase,
+    multiplier: f64,
+    min: Duration,
+    max: Duration,
+    /// If the timeout from the metadata was applied to the executor, needed after restarts
+    applied: bool,
+    phantom: PhantomData<(E, I)>,
+}
+
+impl<E, I> AdaptiveTimeoutStage<E, I> {
+    /// Creates a new [`AdaptiveTimeoutStage`] with timeouts in `min..=max`.
+    ///
+    /// By default, the timeout is five times the average exec time, like in AFL.
+    #[must_use]
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            base: TimeoutBase::Average,
+            multiplier: 5.0,
+            min,
+            max,
+            applied: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Derive the timeout from the given percentile (in `(0, 100]`) instead of the average
+    #[must_use]
+    pub fn percentile(mut self, percentile: f64) -> Self {
+        assert!(
+            percentile > 0.0 && percentile <= 100.0,
+            "percentile must be in (0, 100]"
+        );
+        self.base = TimeoutBase::Percentile(percentile);
+        self
+    }
+
+    /// Sets the factor the exec time statistic is multiplied with
+    #[must_use]
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// The timeout for the given exec time statistic, rounded up like in AFL and clamped to the
+    /// bounds
+    fn timeout_for(&self, base: Duration) -> Duration {
+        let timeout = base.mul_f64(self.multiplier);
+        let rounded = timeout.as_nanos().div_ceil(TIMEOUT_ROUND.as_nanos());
+        let rounded = TIMEOUT_ROUND.saturating_mul(u32::try_from(rounded).unwrap_or(u32::MAX));
+        rounded.clamp(self.min, self.max)
+    }
+}
+
+impl<E, EM, I, S, Z> Stage<E, EM, S, Z> for AdaptiveTimeoutStage<E, I>
+where
+    E: SetTimeout,
+    S: HasCurrentTestcase<I> + HasCurrentCorpusId + HasMetadata,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        let id = state
+            .current_corpus_id()?
+            .ok_or_else(|| Error::illegal_state("No current corpus id"))?;
+        // The calibration stage measures each testcase once, before its first fuzzing round
+        let exec_time = {
+            let testcase = state.current_testcase()?;
+            if testcase.scheduled_count() == 0 {
+                *testcase.exec_time()
+            } else {
+                None
+            }
+        };
+
+        let meta = state.metadata_or_insert_with(AdaptiveTimeoutMetadata::default);
+        let mut changed = !self.applied && meta.timeout != Duration::ZERO;
+        if let Some(exec_time) = exec_time
+            && meta.last_sampled != Some(id)
+        {
+            meta.last_sampled = Some(id);
+            meta.add_sample(exec_time);
+            let timeout = self.timeout_for(meta.base(self.base).unwrap());
+            if timeout != meta.timeout {
+                log::info!(
+                    "Adaptive timeout: {:?} -> {timeout:?} ({} samples)",
+                    meta.timeout,
+                    meta.samples()
+                );
+                meta.timeout = timeout;
+                changed = true;
+            }
+        }
+
+        if changed {
+            executor.set_timeout(meta.timeout);
+            self.applied = true;
+        }
+        Ok(())
+    }
+}
+
+impl<E, I, S> Restartable<S> for AdaptiveTimeoutStage<E, I> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::{AdaptiveTimeoutMetadata, AdaptiveTimeoutStage, TimeoutBase};
+
+    #[test]
+    fn test_adaptive_timeout_base() {
+        let mut meta = AdaptiveTimeoutMetadata::default();
+        assert_eq!(meta.base(TimeoutBase::Average), None);
+        for ms in [40, 10, 30, 20] {
+            meta.add_sample(Duration::from_millis(ms));
+        }
+        assert_eq!(
+            meta.base(TimeoutBase::Average),
+            Some(Duration::from_millis(25))
+        );
+        assert_eq!(
+            meta.base(TimeoutBase::Percentile(50.0)),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            meta.base(TimeoutBase::Percentile(100.0)),
+            Some(Duration::from_millis(40))
+        );
+        assert_eq!(
+            meta.base(TimeoutBase::Percentile(1.0)),
+            Some(Duration::from_millis(10))
+        );
+    }
+
+    #[test]
+    fn test_adaptive_timeout_bounds() {
+        let stage =
+            AdaptiveTimeoutStage::<(), ()>::new(Duration::from_millis(50), Duration::from_secs(1));
+        // Rounded up to 20ms
+        assert_eq!(
+            stage.timeout_for(Duration::from_millis(21)),
+            Duration::from_millis(120)
+        );
+        assert_eq!(
+            stage.timeout_for(Duration::from_micros(100)),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            stage.timeout_for(Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+    }
+}
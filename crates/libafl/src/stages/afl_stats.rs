@@ -50,6 +50,12 @@ use crate::{
 /// AFL++'s default stats update interval
 pub const AFL_FUZZER_STATS_UPDATE_INTERVAL_SECS: u64 = 60;
 
+/// The interval at which the status screen is redrawn
+pub const AFL_STATUS_SCREEN_UPDATE_INTERVAL_SECS: u64 = 1;
+
+/// The width of the status screen, within its borders
+const STATUS_SCREEN_WIDTH: usize = 77;
+
 /// `CalibrationTime` - Use in conjunction with `TimeTrackingFeedback`
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CalibrationTime(pub Duration);
@@ -85,6 +91,8 @@ libafl_bolts::impl_serdeany!(FuzzTime);
 
 /// The [`AflStatsStage`] is a Stage that calculates and writes
 /// AFL++'s `fuzzer_stats` and `plot_data` information.
+///
+/// Optionally, it also draws AFL++'s status screen on stdout, see [`AflStatusScreen`].
 #[derive(Debug, Clone)]
 pub struct AflStatsStage<C, I, O> {
     map_observer_handle: Handle<C>,
@@ -126,6 +134,10 @@ pub struct AflStatsStage<C, I, O> {
     autotokens_enabled: bool,
     /// The core we are bound to
     core_id: CoreId,
+    /// draw the status screen on stdout
+    status_screen: bool,
+    /// the last time that we drew the status screen
+    last_screen_time: Duration,
     phantom: PhantomData<(I, O)>,
 }
 
@@ -302,7 +314,9 @@ where
         self.maybe_update_max_depth(&testcase);
 
         // See if we actually need to run the stage, if not, avoid dynamic value computation.
-        if !self.check_interval() {
+        let report = self.check_interval();
+        let redraw = self.check_screen_interval();
+        if !report && !redraw {
             return Ok(());
         }
 
@@ -338,10 +352,12 @@ where
         } else {
             0
         };
+        let now = current_time().as_secs();
+        let run_time = now.saturating_sub(self.start_time);
         let stats = AflFuzzerStats {
             start_time: self.start_time,
-            last_update: self.last_report_time.as_secs(),
-            run_time: self.last_report_time.as_secs() - self.start_time,
+            last_update: now,
+            run_time,
             fuzzer_pid: self.pid,
             cycles_done: queue_cycles,
             cycles_wo_find: self.cycles_wo_finds,
@@ -359,7 +375,7 @@ where
                 .as_secs(),
             trim_time: 0, // TODO
             execs_done: total_executions,
-            execs_per_sec: *state.executions(),     // TODO
+            execs_per_sec: total_executions / run_time.max(1),
             execs_ps_last_min: *state.executions(), // TODO
             max_depth: self.max_depth,
             corpus_count: corpus_size,
@@ -401,6 +417,12 @@ where
             target_mode: &self.target_mode,
             command_line: &self.command_line,
         };
+        if redraw {
+            Self::draw_status_screen(&stats)?;
+        }
+        if !report {
+            return Ok(());
+        }
         let plot_data = AFLPlotData {
             corpus_count: &stats.corpus_count,
             cur_item: &stats.cur_item,
@@ -549,6 +571,15 @@ where
         Ok(())
     }
 
+    /// Redraws the status screen on stdout, over the previous one.
+    fn draw_status_screen(stats: &AflFuzzerStats) -> Result<(), Error> {
+        let mut stdout = std::io::stdout().lock();
+        // move the cursor home and clear the terminal
+        write!(stdout, "\x1b[H\x1b[2J{}", stats.status_screen())?;
+        stdout.flush()?;
+        Ok(())
+    }
+
     fn write_plot_data(&self, plot_data: &AFLPlotData) -> Result<(), Error> {
         let mut file = OpenOptions::new().append(true).open(
             self.plot_file_path
@@ -630,6 +661,22 @@ where
         }
         false
     }
+
+    fn check_screen_interval(&mut self) -> bool {
+        if !self.status_screen {
+            return false;
+        }
+        let cur = current_time();
+        if cur
+            .checked_sub(self.last_screen_time)
+            .is_none_or(|elapsed| elapsed.as_secs() >= AFL_STATUS_SCREEN_UPDATE_INTERVAL_SECS)
+        {
+            self.last_screen_time = cur;
+            return true;
+        }
+        false
+    }
+
     fn maybe_update_cycles(&mut self, queue_cycles: u64) {
         if queue_cycles > self.cycles_done {
             self.cycles_done += 1;
@@ -725,6 +772,278 @@ impl Display for AflFuzzerStats<'_> {
         Ok(())
     }
 }
+
+impl<'a> AflFuzzerStats<'a> {
+    /// AFL++'s status screen for these stats
+    #[must_use]
+    pub fn status_screen(&'a self) -> AflStatusScreen<'a> {
+        AflStatusScreen { stats: self }
+    }
+}
+
+/// AFL++'s status screen, the live view of the [`AflFuzzerStats`] in the terminal.
+///
+/// The yields of the single mutations are not tracked, and shown as `n/a`.
+#[derive(Debug, Clone)]
+pub struct AflStatusScreen<'a> {
+    stats: &'a AflFuzzerStats<'a>,
+}
+
+impl AflStatusScreen<'_> {
+    /// A horizontal line, with the columns joining above and below it, and the titles of the
+    /// sections below it
+    fn fmt_line(
+        f: &mut core::fmt::Formatter<'_>,
+        (left, right): (char, char),
+        above: &[usize],
+        below: &[usize],
+        titles: &[&str],
+    ) -> core::fmt::Result {
+        let mut line: Vec<char> = (0..STATUS_SCREEN_WIDTH)
+            .map(|i| match (above.contains(&i), below.contains(&i)) {
+                (true, true) => '┼',
+                (true, false) => '┴',
+                (false, true) => '┬',
+                (false, false) => '─',
+            })
+            .collect();
+        let starts = core::iter::once(0).chain(below.iter().map(|split| split + 1));
+        for (start, title) in starts.zip(titles) {
+            for (i, c) in format!(" {title} ").chars().enumerate() {
+                line[start + 1 + i] = c;
+            }
+        }
+        writeln!(f, "{left}{}{right}", line.into_iter().collect::<String>())
+    }
+
+    /// A row of labeled values, one per section
+    fn fmt_row(
+        f: &mut core::fmt::Formatter<'_>,
+        splits: &[usize],
+        cells: &[(usize, &str, String)],
+    ) -> core::fmt::Result {
+        write!(f, "│")?;
+        let mut start = 0;
+        for (i, (label_width, label, value)) in cells.iter().enumerate() {
+            let width = splits.get(i).copied().unwrap_or(STATUS_SCREEN_WIDTH) - start;
+            let cell = if label.is_empty() {
+                String::new()
+            } else {
+                format!(" {label:>label_width$} : {value}")
+            };
+            let cell: String = cell.chars().take(width).collect();
+            write!(f, "{cell:<width$}│")?;
+            start += width + 1;
+        }
+        writeln!(f)
+    }
+}
+
+/// Formats a time span as AFL++ does
+fn format_secs(secs: u64) -> String {
+    format!(
+        "{} days, {} hrs, {} min, {} sec",
+        secs / 86400,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+impl Display for AflStatusScreen<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let stats = self.stats;
+        let since = |count: u64, time: Duration| {
+            if count == 0 {
+                "none seen yet".to_string()
+            } else {
+                format_secs(stats.last_update.saturating_sub(time.as_secs()))
+            }
+        };
+        let na = || "n/a".to_string();
+
+        let title = format!(
+            "{} {{{}}} ({})",
+            stats.afl_version,
+            stats.target_mode.trim(),
+            stats.afl_banner
+        );
+        writeln!(f, "{title:^width$}", width = STATUS_SCREEN_WIDTH + 2)?;
+
+        let splits = [52];
+        Self::fmt_line(
+            f,
+            ('┌', '┐'),
+            &[],
+            &splits,
+            &["process timing", "overall results"],
+        )?;
+        let rows = [
+            (
+                ("run time", format_secs(stats.run_time)),
+                ("cycles done", stats.cycles_done.to_string()),
+            ),
+            (
+                ("last new find", format_secs(stats.time_wo_finds)),
+                ("corpus count", stats.corpus_count.to_string()),
+            ),
+            (
+                (
+                    "last saved crash",
+                    since(stats.saved_crashes, stats.last_crash),
+                ),
+                ("saved crashes", stats.saved_crashes.to_string()),
+            ),
+            (
+                ("last saved hang", since(stats.saved_hangs, stats.last_hang)),
+                ("saved hangs", stats.saved_hangs.to_string()),
+            ),
+        ];
+        for ((label, value), (right_label, right_value)) in rows {
+            Self::fmt_row(
+                f,
+                &splits,
+                &[(16, label, value), (13, right_label, right_value)],
+            )?;
+        }
+
+        let above = splits;
+        let splits = [36];
+        Self::fmt_line(
+            f,
+            ('├', '┤'),
+            &above,
+            &splits,
+            &["cycle progress", "map coverage"],
+        )?;
+        let rows = [
+            (
+                (
+                    "now processing",
+                    format!(
+                        "{} ({:.2}%)",
+                        stats.cur_item,
+                        percent(stats.cur_item, stats.corpus_count)
+                    ),
+                ),
+                ("map density", format!("{:.2}%", stats.bitmap_cvg)),
+            ),
+            (
+                ("cycles wo finds", stats.cycles_wo_find.to_string()),
+                (
+                    "edges found",
+                    format!("{}/{}", stats.edges_found, stats.total_edges),
+                ),
+            ),
+        ];
+        for ((label, value), (right_label, right_value)) in rows {
+            Self::fmt_row(
+                f,
+                &splits,
+                &[(15, label, value), (17, right_label, right_value)],
+            )?;
+        }
+
+        Self::fmt_line(
+            f,
+            ('├', '┤'),
+            &splits,
+            &splits,
+            &["stage progress", "findings in depth"],
+        )?;
+        let rows = [
+            (
+                ("total execs", stats.execs_done.to_string()),
+                (
+                    "favored items",
+                    format!(
+                        "{} ({:.2}%)",
+                        stats.corpus_favored,
+                        percent(stats.corpus_favored, stats.corpus_count)
+                    ),
+                ),
+            ),
+            (
+                ("exec speed", format!("{}/sec", stats.execs_per_sec)),
+                ("unstable edges", stats.var_byte_count.to_string()),
+            ),
+            (
+                ("slowest exec", format!("{} ms", stats.slowest_exec_ms)),
+                ("execs since crash", stats.execs_since_crash.to_string()),
+            ),
+            (
+                ("exec timeout", format!("{} ms", stats.exec_timeout)),
+                ("peak rss", format!("{} MB", stats.peak_rss_mb)),
+            ),
+        ];
+        for ((label, value), (right_label, right_value)) in rows {
+            Self::fmt_row(
+                f,
+                &splits,
+                &[(15, label, value), (17, right_label, right_value)],
+            )?;
+        }
+
+        let above = splits;
+        let splits = [50];
+        Self::fmt_line(
+            f,
+            ('├', '┤'),
+            &above,
+            &splits,
+            &["fuzzing strategy yields", "item geometry"],
+        )?;
+        let rows = [
+            (("bit flips", na()), ("levels", stats.max_depth.to_string())),
+            (
+                ("byte flips", na()),
+                ("pending", stats.pending_total.to_string()),
+            ),
+            (
+                ("arithmetics", na()),
+                ("pend fav", stats.pending_favs.to_string()),
+            ),
+            (
+                ("known ints", na()),
+                ("own finds", stats.corpus_found.to_string()),
+            ),
+            (
+                (
+                    "dictionary",
+                    format!("{} auto entries", stats.auto_dict_entries),
+                ),
+                ("imported", stats.corpus_imported.to_string()),
+            ),
+            (
+                ("havoc/splice", na()),
+                ("stability", format!("{:.2}%", stats.stability)),
+            ),
+            (("py/custom/rq", na()), ("", String::new())),
+            (("trim/eff", na()), ("", String::new())),
+        ];
+        for ((label, value), (right_label, right_value)) in rows {
+            Self::fmt_row(
+                f,
+                &splits,
+                &[(12, label, value), (9, right_label, right_value)],
+            )?;
+        }
+        Self::fmt_line(f, ('└', '┘'), &splits, &[], &[])?;
+
+        let cpu = format!("[cpu{:03}]", stats.cpu_affinity);
+        writeln!(f, "{cpu:>width$}", width = STATUS_SCREEN_WIDTH + 2)
+    }
+}
+
 /// Get the command used to invoke the fuzzer
 #[must_use]
 pub fn get_run_cmdline() -> Cow<'static, str> {
@@ -747,6 +1066,7 @@ pub struct AflStatsStageBuilder<C, I, O> {
     banner: String,
     version: String,
     target_mode: String,
+    status_screen: bool,
     phantom_data: PhantomData<(I, O)>,
 }
 
@@ -768,6 +1088,7 @@ where
             banner: String::default(),
             version: String::default(),
             target_mode: String::default(),
+            status_screen: false,
             phantom_data: PhantomData,
         }
     }
@@ -846,6 +1167,15 @@ where
         self
     }
 
+    /// Draw AFL++'s status screen on stdout, redrawn every
+    /// [`AFL_STATUS_SCREEN_UPDATE_INTERVAL_SECS`] between two testcases.
+    /// Nothing else should print to stdout meanwhile.
+    #[must_use]
+    pub fn status_screen(mut self, status_screen: bool) -> Self {
+        self.status_screen = status_screen;
+        self
+    }
+
     fn create_plot_data_file(path: &Path) -> Result<(), Error> {
         if path.exists() {
             // check if it contains any data
@@ -911,7 +1241,86 @@ where
             dict_count: self.dict_count,
             core_id: self.core_id.unwrap_or(CoreId(0)),
             autotokens_enabled: self.uses_autotokens,
+            status_screen: self.status_screen,
+            last_screen_time: Duration::from_secs(0),
             phantom: PhantomData,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, string::ToString, vec::Vec};
+    use core::time::Duration;
+
+    use super::{AflFuzzerStats, STATUS_SCREEN_WIDTH, format_secs};
+
+    #[test]
+    fn test_status_screen() {
+        let banner = Cow::Borrowed("./target @@");
+        let version = Cow::Borrowed("0.13.2");
+        let target_mode = Cow::Borrowed("persistent ");
+        let stats = AflFuzzerStats {
+            start_time: 1000,
+            last_update: 91000,
+            run_time: 90000,
+            fuzzer_pid: 1,
+            cycles_done: 3,
+            cycles_wo_find: 1,
+            time_wo_finds: 62,
+            fuzz_time: 0,
+            calibration_time: 0,
+            sync_time: 0,
+            trim_time: 0,
+            execs_done: 123_456_789,
+            execs_per_sec: 1371,
+            execs_ps_last_min: 0,
+            corpus_count: 200,
+            corpus_favored: 50,
+            corpus_found: 150,
+            corpus_imported: 0,
+            max_depth: 7,
+            cur_item: 20,
+            pending_favs: 0,
+            pending_total: 12,
+            corpus_variable: 0,
+            stability: 99.5,
+            bitmap_cvg: 1.25,
+            saved_crashes: 2,
+            saved_hangs: 0,
+            last_find: Duration::from_secs(90938),
+            last_crash: Duration::from_secs(90000),
+            last_hang: Duration::from_secs(1000),
+            execs_since_crash: 10,
+            exec_timeout: 1000,
+            slowest_exec_ms: 12,
+            peak_rss_mb: 30,
+            cpu_affinity: 1,
+            edges_found: 819,
+            total_edges: 65536,
+            var_byte_count: 4,
+            havoc_expansion: 0,
+            auto_dict_entries: 5,
+            testcache_size: 0,
+            testcache_count: 0,
+            testcache_evict: 0,
+            afl_banner: &banner,
+            afl_version: &version,
+            target_mode: &target_mode,
+            command_line: "libafl-fuzz -i in -o out ./target @@",
+        };
+        let screen = stats.status_screen().to_string();
+        let lines: Vec<&str> = screen.lines().collect();
+
+        assert!(lines[0].contains("0.13.2 {persistent} (./target @@)"));
+        for line in &lines[1..lines.len() - 1] {
+            assert_eq!(line.chars().count(), STATUS_SCREEN_WIDTH + 2, "{line}");
+        }
+        assert!(screen.contains("last saved crash : 0 days, 0 hrs, 16 min, 40 sec"));
+        assert!(screen.contains("last saved hang : none seen yet"));
+        assert!(screen.contains("now processing : 20 (10.00%)"));
+        assert!(lines[lines.len() - 1].ends_with("[cpu001]"));
+
+        assert_eq!(format_secs(90061), "1 days, 1 hrs, 1 min, 1 sec");
+    }
+}
@@ -269,6 +269,11 @@ where
             state.add_metadata(UnstableEntriesMetadata::new());
         }
 
+        // The average exec time, used by the schedulers and the `AdaptiveTimeoutStage`
+        state
+            .current_testcase_mut()?
+            .set_exec_time(total_time / (iter as u32));
+
         // If weighted scheduler or powerscheduler is used, update it
         if state.has_metadata::<SchedulerMetadata>() {
             let observers = executor.observers();
@@ -297,9 +302,6 @@ where
 
             let mut testcase = state.current_testcase_mut()?;
 
-            testcase.set_exec_time(total_time / (iter as u32));
-            // log::trace!("time: {:#?}", testcase.exec_time());
-
             // If the testcase doesn't have its own `SchedulerTestcaseMetadata`, create it.
             let data = if let Ok(metadata) = testcase.metadata_mut::<SchedulerTestcaseMetadata>() {
                 metadata
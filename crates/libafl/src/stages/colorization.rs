@@ -198,7 +198,7 @@ where
 
         // println!("Replaced bytes: {:#?}", changed_bytes);
         // Now replace with random values (This is type_replace)
-        Self::type_replace(changed_bytes, state);
+        type_replace(changed_bytes, state);
 
         // println!("Replaced bytes: {:#?}", changed_bytes);
         // What we do is now to separate the input into smaller regions
@@ -330,97 +330,101 @@ where
 
         Ok(hash)
     }
+}
 
-    /// Replace bytes with random values but following certain rules
-    #[expect(clippy::needless_range_loop)]
-    fn type_replace(bytes: &mut [u8], state: &mut S) {
-        let len = bytes.len();
-        for idx in 0..len {
-            let c = match bytes[idx] {
-                0x41..=0x46 => {
-                    // 'A' + 1 + rand('F' - 'A')
-                    0x41 + 1 + state.rand_mut().below(nonzero!(5)) as u8
-                }
-                0x61..=0x66 => {
-                    // 'a' + 1 + rand('f' - 'a')
-                    0x61 + 1 + state.rand_mut().below(nonzero!(5)) as u8
-                }
-                0x30 => {
-                    // '0' -> '1'
-                    0x31
-                }
-                0x31 => {
-                    // '1' -> '0'
-                    0x30
-                }
-                0x32..=0x39 => {
-                    // '2' + 1 + rand('9' - '2')
-                    0x32 + 1 + state.rand_mut().below(nonzero!(7)) as u8
-                }
-                0x47..=0x5a => {
-                    // 'G' + 1 + rand('Z' - 'G')
-                    0x47 + 1 + state.rand_mut().below(nonzero!(19)) as u8
-                }
-                0x67..=0x7a => {
-                    // 'g' + 1 + rand('z' - 'g')
-                    0x67 + 1 + state.rand_mut().below(nonzero!(19)) as u8
-                }
-                0x21..=0x2a => {
-                    // '!' + 1 + rand('*' - '!');
-                    0x21 + 1 + state.rand_mut().below(nonzero!(9)) as u8
-                }
-                0x2c..=0x2e => {
-                    // ',' + 1 + rand('.' - ',')
-                    0x2c + 1 + state.rand_mut().below(nonzero!(2)) as u8
-                }
-                0x3a..=0x40 => {
-                    // ':' + 1 + rand('@' - ':')
-                    0x3a + 1 + state.rand_mut().below(nonzero!(6)) as u8
-                }
-                0x5b..=0x60 => {
-                    // '[' + 1 + rand('`' - '[')
-                    0x5b + 1 + state.rand_mut().below(nonzero!(5)) as u8
-                }
-                0x7b..=0x7e => {
-                    // '{' + 1 + rand('~' - '{')
-                    0x7b + 1 + state.rand_mut().below(nonzero!(3)) as u8
-                }
-                0x2b => {
-                    // '+' -> '/'
-                    0x2f
-                }
-                0x2f => {
-                    // '/' -> '+'
-                    0x2b
-                }
-                0x20 => {
-                    // ' ' -> '\t'
-                    0x9
-                }
-                0x9 => {
-                    // '\t' -> ' '
-                    0x20
-                }
-                0xd => {
-                    // '\r' -> '\n'
-                    0xa
-                }
-                0xa => {
-                    // '\n' -> '\r'
-                    0xd
-                }
-                0x0 => 0x1,
-                0x1 | 0xff => 0x0,
-                _ => {
-                    if bytes[idx] < 32 {
-                        bytes[idx] ^ 0x1f
-                    } else {
-                        bytes[idx] ^ 0x7f
-                    }
+/// Replace bytes with random values but following certain rules, so the replaced input keeps the
+/// type of its bytes (digits, letters, whitespace...), like the `type_replace` of AFL++
+#[expect(clippy::needless_range_loop)]
+pub fn type_replace<S>(bytes: &mut [u8], state: &mut S)
+where
+    S: HasRand,
+{
+    let len = bytes.len();
+    for idx in 0..len {
+        let c = match bytes[idx] {
+            0x41..=0x46 => {
+                // 'A' + 1 + rand('F' - 'A')
+                0x41 + 1 + state.rand_mut().below(nonzero!(5)) as u8
+            }
+            0x61..=0x66 => {
+                // 'a' + 1 + rand('f' - 'a')
+                0x61 + 1 + state.rand_mut().below(nonzero!(5)) as u8
+            }
+            0x30 => {
+                // '0' -> '1'
+                0x31
+            }
+            0x31 => {
+                // '1' -> '0'
+                0x30
+            }
+            0x32..=0x39 => {
+                // '2' + 1 + rand('9' - '2')
+                0x32 + 1 + state.rand_mut().below(nonzero!(7)) as u8
+            }
+            0x47..=0x5a => {
+                // 'G' + 1 + rand('Z' - 'G')
+                0x47 + 1 + state.rand_mut().below(nonzero!(19)) as u8
+            }
+            0x67..=0x7a => {
+                // 'g' + 1 + rand('z' - 'g')
+                0x67 + 1 + state.rand_mut().below(nonzero!(19)) as u8
+            }
+            0x21..=0x2a => {
+                // '!' + 1 + rand('*' - '!');
+                0x21 + 1 + state.rand_mut().below(nonzero!(9)) as u8
+            }
+            0x2c..=0x2e => {
+                // ',' + 1 + rand('.' - ',')
+                0x2c + 1 + state.rand_mut().below(nonzero!(2)) as u8
+            }
+            0x3a..=0x40 => {
+                // ':' + 1 + rand('@' - ':')
+                0x3a + 1 + state.rand_mut().below(nonzero!(6)) as u8
+            }
+            0x5b..=0x60 => {
+                // '[' + 1 + rand('`' - '[')
+                0x5b + 1 + state.rand_mut().below(nonzero!(5)) as u8
+            }
+            0x7b..=0x7e => {
+                // '{' + 1 + rand('~' - '{')
+                0x7b + 1 + state.rand_mut().below(nonzero!(3)) as u8
+            }
+            0x2b => {
+                // '+' -> '/'
+                0x2f
+            }
+            0x2f => {
+                // '/' -> '+'
+                0x2b
+            }
+            0x20 => {
+                // ' ' -> '\t'
+                0x9
+            }
+            0x9 => {
+                // '\t' -> ' '
+                0x20
+            }
+            0xd => {
+                // '\r' -> '\n'
+                0xa
+            }
+            0xa => {
+                // '\n' -> '\r'
+                0xd
+            }
+            0x0 => 0x1,
+            0x1 | 0xff => 0x0,
+            _ => {
+                if bytes[idx] < 32 {
+                    bytes[idx] ^ 0x1f
+                } else {
+                    bytes[idx] ^ 0x7f
                 }
-            };
+            }
+        };
 
-            bytes[idx] = c;
-        }
+        bytes[idx] = c;
     }
 }
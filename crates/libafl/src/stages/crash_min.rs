@@ -0,0 +1,690 @@
+//! Minimizes crashing inputs while preserving their crash signature: the [`ExitKind`] and the
+//! stack hash of a backtrace observer, instead of the coverage like the
+//! [`crate::stages::StdTMinMutationalStage`].
+//!
+//! The [`CrashMinimizer`] first removes chunks of halving size (delta debugging), then replaces
+//! blocks with a canonical byte, `'0'` by default, like `afl-tmin`. Run it on the solutions with
+//! the [`CrashMinimizationStage`], or on single inputs with [`CrashMinimizer::minimize`], e.g. in
+//! a replay tool. The [`HangMinimizationStage`] minimizes the hangs reported by a
+//! [`crate::feedbacks::HangFeedback`], keeping their hang signature.
+//! Note: Will NOT work with in process executors, as the minimized inputs crash the fuzzer.
+
+use alloc::{borrow::Cow, vec::Vec};
+use core::marker::PhantomData;
+
+use hashbrown::HashSet;
+use libafl_bolts::{
+    Named, impl_serdeany,
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::feedbacks::HangSignatureMetadata;
+use crate::{
+    Error, ExecutesInput, HasMetadata,
+    corpus::{Corpus, CorpusId, Testcase},
+    executors::{ExitKind, HasObservers},
+    inputs::{HasMutatorBytes, ResizableMutator},
+    observers::ObserverWithHashField,
+    stages::{Restartable, Stage},
+    state::HasSolutions,
+};
+
+/// The default maximum number of executions to minimize a single input
+pub const DEFAULT_CRASH_MIN_EXECS: usize = 5000;
+
+/// What a minimized input has to reproduce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashSignature {
+    /// How the run ended
+    pub exit_kind: ExitKind,
+    /// The stack hash of the run, if the observer has one
+    pub hash: Option<u64>,
+}
+
+/// The outcome of [`CrashMinimizer::minimize`]
+#[derive(Debug, Clone)]
+pub enum CrashMinimization<I> {
+    /// The input was minimized, keeping its signature
+    Minimized {
+        /// The minimized input
+        input: I,
+        /// The signature the minimized input reproduces
+        signature: CrashSignature,
+    },
+    /// The input did not crash (or time out, or run out of memory), there is nothing to minimize
+    NotReproduced,
+}
+
+/// Added to solutions minimized by the [`CrashMinimizationStage`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashMinimizedMetadata {
+    /// The signature the minimized input reproduces
+    pub signature: CrashSignature,
+    /// The length of the input before minimization
+    pub original_len: usize,
+}
+
+impl_serdeany!(CrashMinimizedMetadata);
+
+/// The solutions replaced by the [`CrashMinimizationStage`] and the [`HangMinimizationStage`], for
+/// the `CrashReportStage` to write their reports again. It takes the ids it handled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct ReplacedSolutionsMetadata {
+    /// The ids of the replaced solutions
+    pub ids: Vec<CorpusId>,
+}
+
+impl_serdeany!(ReplacedSolutionsMetadata);
+
+/// The solutions the [`CrashMinimizationStage`] already tried to minimize
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct CrashMinimizationProgressMetadata {
+    done: HashSet<CorpusId>,
+}
+
+impl_serdeany!(CrashMinimizationProgressMetadata);
+
+/// The hangs the [`HangMinimizationStage`] already tried to minimize
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct HangMinimizationProgressMetadata {
+    done: HashSet<CorpusId>,
+}
+
+#[cfg(feature = "std")]
+impl_serdeany!(HangMinimizationProgressMetadata);
+
+/// Minimizes inputs while keeping their [`CrashSignature`]
+#[derive(Debug, Clone)]
+pub struct CrashMinimizer<O> {
+    observer_handle: Handle<O>,
+    max_execs: usize,
+    canonical_byte: u8,
+}
+
+impl<O> CrashMinimizer<O>
+where
+    O: ObserverWithHashField + Handled,
+{
+    /// Creates a new [`CrashMinimizer`], using the stack hash of the given observer, usually a
+    /// [`crate::observers::BacktraceObserver`] or [`crate::observers::AsanBacktraceObserver`]
+    #[must_use]
+    pub fn new(observer: &O) -> Self {
+        Self {
+            observer_handle: observer.handle(),
+            max_execs: DEFAULT_CRASH_MIN_EXECS,
+            canonical_byte: b'0',
+        }
+    }
+
+    /// Sets the maximum number of executions to minimize a single input
+    #[must_use]
+    pub fn max_execs(mut self, max_execs: usize) -> Self {
+        self.max_execs = max_execs;
+        self
+    }
+
+    /// Sets the byte to replace the bytes not needed for the crash with
+    #[must_use]
+    pub fn canonical_byte(mut self, canonical_byte: u8) -> Self {
+        self.canonical_byte = canonical_byte;
+        self
+    }
+
+    /// Runs the input and returns its [`CrashSignature`]
+    pub fn signature<E, EM, I, S, Z>(
+        &self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+        input: &I,
+    ) -> Result<CrashSignature, Error>
+    where
+        E: HasObservers,
+        E::Observers: MatchName,
+        Z: ExecutesInput<E, EM, I, S>,
+    {
+        let exit_kind = fuzzer.execute_input(state, executor, manager, input)?;
+        let hash = executor
+            .observers()
+            .get(&self.observer_handle)
+            .ok_or_else(|| Error::illegal_state("The stack hash observer is missing"))?
+            .hash();
+        Ok(CrashSignature { exit_kind, hash })
+    }
+
+    /// Minimizes a crashing input, returning the minimized input and the signature it keeps, or
+    /// [`CrashMinimization::NotReproduced`] if it does not crash (or time out, or run out of
+    /// memory).
+    pub fn minimize<E, EM, I, S, Z>(
+        &self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+        input: &I,
+    ) -> Result<CrashMinimization<I>, Error>
+    where
+        E: HasObservers,
+        E::Observers: MatchName,
+        I: HasMutatorBytes + ResizableMutator<u8> + Clone,
+        Z: ExecutesInput<E, EM, I, S>,
+    {
+        let signature = self.signature(fuzzer, executor, state, manager, input)?;
+        if signature.exit_kind == ExitKind::Ok {
+            return Ok(CrashMinimization::NotReproduced);
+        }
+        let input = self.minimize_keeping(fuzzer, executor, state, manager, input, signature)?;
+        Ok(CrashMinimization::Minimized { input, signature })
+    }
+
+    /// Minimizes an input, which reproduces `signature`, keeping it.
+    fn minimize_keeping<E, EM, I, S, Z>(
+        &self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+        input: &I,
+        signature: CrashSignature,
+    ) -> Result<I, Error>
+    where
+        E: HasObservers,
+        E::Observers: MatchName,
+        I: HasMutatorBytes + ResizableMutator<u8> + Clone,
+        Z: ExecutesInput<E, EM, I, S>,
+    {
+        let mut candidate = input.clone();
+        let mut reproduces = |bytes: &[u8]| {
+            candidate.resize(bytes.len(), 0);
+            candidate.mutator_bytes_mut().copy_from_slice(bytes);
+            Ok(self.signature(fuzzer, executor, state, manager, &candidate)? == signature)
+        };
+        let mut bytes = input.mutator_bytes().to_vec();
+        let mut budget = self.max_execs;
+        remove_chunks(&mut bytes, &mut budget, &mut reproduces)?;
+        canonicalize(
+            &mut bytes,
+            self.canonical_byte,
+            &mut budget,
+            &mut reproduces,
+        )?;
+
+        let mut minimized = input.clone();
+        minimized.resize(bytes.len(), 0);
+        minimized.mutator_bytes_mut().copy_from_slice(&bytes);
+        Ok(minimized)
+    }
+}
+
+/// Removes chunks of halving size, as long as the input still reproduces.
+fn remove_chunks<F>(
+    bytes: &mut Vec<u8>,
+    budget: &mut usize,
+    reproduces: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(&[u8]) -> Result<bool, Error>,
+{
+    let mut chunk = bytes.len().div_ceil(2);
+    while chunk > 0 {
+        let mut start = 0;
+        while start < bytes.len() {
+            if *budget == 0 {
+                return Ok(());
+            }
+            *budget -= 1;
+            let end = (start + chunk).min(bytes.len());
+            let mut candidate = bytes.clone();
+            candidate.drain(start..end);
+            if reproduces(&candidate)? {
+                // The next chunk moved to `start`.
+                *bytes = candidate;
+            } else {
+                start = end;
+            }
+        }
+        chunk /= 2;
+    }
+    Ok(())
+}
+
+/// Replaces blocks of halving size with the canonical byte, as long as the input still
+/// reproduces.
+fn canonicalize<F>(
+    bytes: &mut [u8],
+    canonical_byte: u8,
+    budget: &mut usize,
+    reproduces: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(&[u8]) -> Result<bool, Error>,
+{
+    let mut block = bytes.len().div_ceil(2);
+    while block > 0 {
+        for start in (0..bytes.len()).step_by(block) {
+            let end = (start + block).min(bytes.len());
+            if bytes[start..end].iter().all(|&b| b == canonical_byte) {
+                continue;
+            }
+            if *budget == 0 {
+                return Ok(());
+            }
+            *budget -= 1;
+            let mut candidate = bytes.to_vec();
+            candidate[start..end].fill(canonical_byte);
+            if reproduces(&candidate)? {
+                bytes.copy_from_slice(&candidate);
+            }
+        }
+        block /= 2;
+    }
+    Ok(())
+}
+
+/// Minimizes each new solution with a [`CrashMinimizer`], replacing it with the minimized input.
+///
+/// The metadata of the solution is kept, a [`CrashMinimizedMetadata`] is added.
+/// Note: Will NOT work with in process executors, as the minimized inputs crash the fuzzer.
+#[derive(Debug)]
+pub struct CrashMinimizationStage<I, O> {
+    minimizer: CrashMinimizer<O>,
+    phantom: PhantomData<I>,
+}
+
+impl<I, O> CrashMinimizationStage<I, O> {
+    /// Creates a new [`CrashMinimizationStage`] with the given minimizer
+    #[must_use]
+    pub fn new(minimizer: CrashMinimizer<O>) -> Self {
+        Self {
+            minimizer,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, EM, I, O, S, Z> Stage<E, EM, S, Z> for CrashMinimizationStage<I, O>
+where
+    E: HasObservers,
+    E::Observers: MatchName,
+    I: HasMutatorBytes + ResizableMutator<u8> + Clone,
+    O: ObserverWithHashField + Handled,
+    S: HasSolutions<I> + HasMetadata,
+    Z: ExecutesInput<E, EM, I, S>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let solution_ids: Vec<CorpusId> = state.solutions().ids().collect();
+        for id in solution_ids {
+            {
+                // Mark it before running it, so we do not retry it if it crashes the fuzzer.
+                let progress =
+                    state.metadata_or_insert_with(CrashMinimizationProgressMetadata::default);
+                if !progress.done.insert(id) {
+                    continue;
+                }
+            }
+
+            let input = state.solutions().cloned_input_for_id(id)?;
+            let (minimized, signature) = match self
+                .minimizer
+                .minimize(fuzzer, executor, state, manager, &input)?
+            {
+                CrashMinimization::Minimized { input, signature } => (input, signature),
+                CrashMinimization::NotReproduced => {
+                    log::info!("Solution {id} does not reproduce, not minimizing it");
+                    continue;
+                }
+            };
+            log::info!(
+                "Minimized solution {id} from {} to {} bytes",
+                input.len(),
+                minimized.len()
+            );
+
+            replace_solution(state, id, minimized, signature, input.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the solution `id` with its minimized input, keeping its metadata, and records it in
+/// the [`ReplacedSolutionsMetadata`].
+fn replace_solution<I, S>(
+    state: &mut S,
+    id: CorpusId,
+    minimized: I,
+    signature: CrashSignature,
+    original_len: usize,
+) -> Result<(), Error>
+where
+    S: HasSolutions<I> + HasMetadata,
+{
+    let mut testcase = Testcase::from(minimized);
+    *testcase.metadata_map_mut() = state.solutions().get(id)?.borrow().metadata_map().clone();
+    testcase.add_metadata(CrashMinimizedMetadata {
+        signature,
+        original_len,
+    });
+    state.solutions_mut().replace(id, testcase)?;
+    state
+        .metadata_or_insert_with(ReplacedSolutionsMetadata::default)
+        .ids
+        .push(id);
+    Ok(())
+}
+
+impl<I, O, S> Restartable<S> for CrashMinimizationStage<I, O> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // The progress is tracked per solution
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<I, O> Named for CrashMinimizationStage<I, O> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("crash_min");
+        &NAME
+    }
+}
+
+/// Minimizes each new hang reported by a [`crate::feedbacks::HangFeedback`], replacing it with a
+/// smaller input that still times out with the same hang signature.
+///
+/// Only solutions with a [`HangSignatureMetadata`] are minimized, using a [`CrashMinimizer`] on the
+/// signature observer of the feedback, usually a `HangBacktraceObserver`. As each try runs into
+/// the timeout, consider lowering [`CrashMinimizer::max_execs`].
+/// The metadata of the solution is kept, a [`CrashMinimizedMetadata`] is added.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct HangMinimizationStage<I, O> {
+    minimizer: CrashMinimizer<O>,
+    phantom: PhantomData<I>,
+}
+
+#[cfg(feature = "std")]
+impl<I, O> HangMinimizationStage<I, O> {
+    /// Creates a new [`HangMinimizationStage`] with the given minimizer, which has to use the
+    /// signature observer of the [`crate::feedbacks::HangFeedback`]
+    #[must_use]
+    pub fn new(minimizer: CrashMinimizer<O>) -> Self {
+        Self {
+            minimizer,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E, EM, I, O, S, Z> Stage<E, EM, S, Z> for HangMinimizationStage<I, O>
+where
+    E: HasObservers,
+    E::Observers: MatchName,
+    I: HasMutatorBytes + ResizableMutator<u8> + Clone,
+    O: ObserverWithHashField + Handled,
+    S: HasSolutions<I> + HasMetadata,
+    Z: ExecutesInput<E, EM, I, S>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let solution_ids: Vec<CorpusId> = state.solutions().ids().collect();
+        for id in solution_ids {
+            let Some(hang) = state
+                .solutions()
+                .get(id)?
+                .borrow()
+                .metadata_map()
+                .get::<HangSignatureMetadata>()
+                .copied()
+            else {
+                continue;
+            };
+            if !state
+                .metadata_or_insert_with(HangMinimizationProgressMetadata::default)
+                .done
+                .insert(id)
+            {
+                continue;
+            }
+
+            let input = state.solutions().cloned_input_for_id(id)?;
+            let signature = CrashSignature {
+                exit_kind: ExitKind::Timeout,
+                hash: Some(hang.signature),
+            };
+            if self
+                .minimizer
+                .signature(fuzzer, executor, state, manager, &input)?
+                != signature
+            {
+                log::info!("Hang {id} does not reproduce, not minimizing it");
+                continue;
+            }
+            let minimized = self
+                .minimizer
+                .minimize_keeping(fuzzer, executor, state, manager, &input, signature)?;
+            log::info!(
+                "Minimized hang {id} from {} to {} bytes",
+                input.len(),
+                minimized.len()
+            );
+            replace_solution(state, id, minimized, signature, input.len())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I, O, S> Restartable<S> for HangMinimizationStage<I, O> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // The progress is tracked per solution
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I, O> Named for HangMinimizationStage<I, O> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("hang_min");
+        &NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{canonicalize, remove_chunks};
+
+    /// Crashes if the input contains `"AB"` followed by a `'!'` somewhere later
+    fn crashes(bytes: &[u8]) -> bool {
+        bytes
+            .windows(2)
+            .position(|w| w == b"AB")
+            .is_some_and(|pos| bytes[pos + 2..].contains(&b'!'))
+    }
+
+    #[test]
+    fn test_crash_min() {
+        let mut bytes: Vec<u8> = b"hello AB world, this is a long input! with a tail".to_vec();
+        let mut budget = 10_000;
+        remove_chunks(&mut bytes, &mut budget, &mut |b: &[u8]| Ok(crashes(b))).unwrap();
+        assert_eq!(bytes, b"AB!");
+
+        let mut bytes = b"xxABxx!x".to_vec();
+        canonicalize(&mut bytes, b'0', &mut budget, &mut |b: &[u8]| {
+            Ok(crashes(b))
+        })
+        .unwrap();
+        assert_eq!(bytes, b"00AB00!0");
+    }
+
+    #[test]
+    #[cfg(all(feature = "hang_backtrace", target_os = "linux"))]
+    fn test_hang_min() {
+        use alloc::{string::String, vec};
+
+        use libafl_bolts::{
+            rands::StdRand,
+            tuples::{RefIndexable, tuple_list, tuple_list_type},
+        };
+
+        use crate::{
+            Error, ExecutesInput, HasMetadata,
+            corpus::{Corpus, InMemoryCorpus, Testcase},
+            executors::{ExitKind, HasObservers},
+            feedbacks::{
+                ConstFeedback, Feedback, HangFeedback, HangSignatureMetadata, StateInitializer,
+            },
+            inputs::{BytesInput, HasMutatorBytes},
+            observers::{HangBacktraceObserver, Observer, StackFrame},
+            stages::{CrashMinimizer, HangMinimizationStage, Stage},
+            state::{HasSolutions, StdState},
+        };
+
+        /// Hangs after `"loop"`, in a stack depending on the byte following it
+        struct HangingTarget {
+            observers: tuple_list_type!(HangBacktraceObserver),
+        }
+
+        impl HasObservers for HangingTarget {
+            type Observers = tuple_list_type!(HangBacktraceObserver);
+
+            fn observers(&self) -> RefIndexable<&Self::Observers, Self::Observers> {
+                RefIndexable::from(&self.observers)
+            }
+
+            fn observers_mut(&mut self) -> RefIndexable<&mut Self::Observers, Self::Observers> {
+                RefIndexable::from(&mut self.observers)
+            }
+        }
+
+        struct Runner;
+
+        impl<S> ExecutesInput<HangingTarget, (), BytesInput, S> for Runner {
+            fn execute_input(
+                &mut self,
+                state: &mut S,
+                executor: &mut HangingTarget,
+                _manager: &mut (),
+                input: &BytesInput,
+            ) -> Result<ExitKind, Error> {
+                let observer = &mut executor.observers.0;
+                observer.pre_exec(state, input)?;
+                let bytes = input.mutator_bytes();
+                let Some(pos) = bytes.windows(4).position(|w| w == b"loop") else {
+                    return Ok(ExitKind::Ok);
+                };
+                let caller = bytes.get(pos + 4).copied().unwrap_or_default();
+                observer.observe(
+                    [0x10, 0x100 + u64::from(caller)]
+                        .into_iter()
+                        .map(|offset| StackFrame {
+                            address: offset,
+                            module: Some(String::from("/target")),
+                            offset,
+                        })
+                        .collect(),
+                );
+                Ok(ExitKind::Timeout)
+            }
+        }
+
+        let observer = HangBacktraceObserver::new("hang");
+        let mut feedback = HangFeedback::new(&observer);
+        let mut stage = HangMinimizationStage::new(CrashMinimizer::new(&observer));
+        let mut executor = HangingTarget {
+            observers: tuple_list!(observer),
+        };
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<BytesInput>::new(),
+            InMemoryCorpus::new(),
+            &mut ConstFeedback::new(false),
+            &mut ConstFeedback::new(false),
+        )
+        .unwrap();
+        feedback.init_state(&mut state).unwrap();
+
+        // Report the hang like the objective would
+        let input = BytesInput::new(b"a long prefix, then loopA and a suffix".to_vec());
+        let exit_kind = Runner
+            .execute_input(&mut state, &mut executor, &mut (), &input)
+            .unwrap();
+        assert!(
+            feedback
+                .is_interesting(&mut state, &mut (), &input, &executor.observers, &exit_kind)
+                .unwrap()
+        );
+        let mut testcase = Testcase::new(input);
+        feedback
+            .append_metadata(&mut state, &mut (), &executor.observers, &mut testcase)
+            .unwrap();
+        let signature = *testcase.metadata::<HangSignatureMetadata>().unwrap();
+        let hang = state.solutions_mut().add(testcase).unwrap();
+        // Crashes are left to the crash minimizer
+        let crash = state
+            .solutions_mut()
+            .add(Testcase::new(BytesInput::new(vec![b'x'; 8])))
+            .unwrap();
+
+        stage
+            .perform(&mut Runner, &mut executor, &mut state, &mut ())
+            .unwrap();
+
+        let testcase = state.solutions().get(hang).unwrap().borrow();
+        assert_eq!(testcase.input().as_ref().unwrap().mutator_bytes(), b"loopA");
+        assert_eq!(
+            *testcase.metadata::<HangSignatureMetadata>().unwrap(),
+            signature
+        );
+        drop(testcase);
+        let testcase = state.solutions().get(crash).unwrap().borrow();
+        assert_eq!(
+            testcase.input().as_ref().unwrap().mutator_bytes(),
+            [b'x'; 8]
+        );
+    }
+
+    #[test]
+    fn test_crash_min_budget() {
+        let mut bytes: Vec<u8> = b"hello AB world!".to_vec();
+        let mut budget = 1;
+        remove_chunks(&mut bytes, &mut budget, &mut |b: &[u8]| Ok(crashes(b))).unwrap();
+        assert_eq!(budget, 0);
+        assert!(crashes(&bytes));
+    }
+}
@@ -0,0 +1,164 @@
+//! The [`CrashReportStage`] writes a report next to each new solution, from the
+//! [`CrashReportMetadata`] the [`crate::feedbacks::CrashReportFeedback`] added to it.
+
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+use core::{hash::Hash, marker::PhantomData};
+use std::{ffi::OsString, fs, path::PathBuf};
+
+use libafl_bolts::{Named, generic_hash_std, impl_serdeany};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, HasMetadata,
+    corpus::{Corpus, CorpusId},
+    feedbacks::{CrashReportFormat, CrashReportMetadata},
+    stages::{Restartable, Stage, crash_min::ReplacedSolutionsMetadata},
+    state::HasSolutions,
+};
+
+/// The number of solutions the [`CrashReportStage`] already wrote reports for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    expect(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct CrashReportProgressMetadata {
+    processed: usize,
+}
+
+impl_serdeany!(CrashReportProgressMetadata);
+
+/// Writes a report for each new solution with a [`CrashReportMetadata`], in each of the given
+/// formats.
+///
+/// The report is stored next to the solution, as `<solution>.json` or `<solution>.sarif`, so
+/// the solutions need to be on disk, e.g. in an `OnDiskCorpus`.
+/// Only the solutions added since the last run are handled. If a solution is replaced later by a
+/// [`crate::stages::CrashMinimizationStage`], which records it in the
+/// [`ReplacedSolutionsMetadata`], its report is written again for the new input. The stack is
+/// kept from the original run, which the minimized input reproduces.
+#[derive(Debug)]
+pub struct CrashReportStage<I> {
+    formats: Vec<CrashReportFormat>,
+    reproduce_command: Option<String>,
+    phantom: PhantomData<I>,
+}
+
+impl<I> CrashReportStage<I> {
+    /// Creates a new [`CrashReportStage`] writing reports in the given formats
+    #[must_use]
+    pub fn new<F>(formats: F) -> Self
+    where
+        F: IntoIterator<Item = CrashReportFormat>,
+    {
+        Self {
+            formats: formats.into_iter().collect(),
+            reproduce_command: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The command reproducing a solution, added to the reports.
+    ///
+    /// `@@` is replaced with the path of the solution, like in AFL.
+    #[must_use]
+    pub fn reproduce_command<S>(mut self, command: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.reproduce_command = Some(command.into());
+        self
+    }
+
+    fn reproduce(&self, path: &str) -> String {
+        match &self.reproduce_command {
+            Some(command) => command.replace("@@", path),
+            None => format!("Run the target with the input {path}"),
+        }
+    }
+
+    /// Writes the reports for the solution `id`, if it has a [`CrashReportMetadata`]
+    fn write_reports<S>(&self, state: &S, id: CorpusId) -> Result<(), Error>
+    where
+        I: Clone + Hash,
+        S: HasSolutions<I>,
+    {
+        let testcase = state.solutions().get(id)?.borrow();
+        let Some(metadata) = testcase.metadata_map().get::<CrashReportMetadata>() else {
+            return Ok(());
+        };
+        let Some(path) = testcase.file_path() else {
+            log::warn!("Solution {id} is not on disk, not writing a report");
+            return Ok(());
+        };
+        let mut metadata = metadata.clone();
+        let input_hash = generic_hash_std(&state.solutions().cloned_input_for_id(id)?);
+        metadata.input_hash = format!("{input_hash:016x}");
+
+        let reproduce = self.reproduce(&path.to_string_lossy());
+        for format in &self.formats {
+            let report = metadata.to_format(*format, path, &reproduce);
+            let mut report_path = OsString::from(path);
+            report_path.push(".");
+            report_path.push(format.extension());
+            let serialized = serde_json::to_vec_pretty(&report).map_err(|err| {
+                Error::serialize(format!("Failed to json-ify crash report: {err:?}"))
+            })?;
+            fs::write(PathBuf::from(report_path), serialized)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E, EM, I, S, Z> Stage<E, EM, S, Z> for CrashReportStage<I>
+where
+    I: Clone + Hash,
+    S: HasSolutions<I> + HasMetadata,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        let processed = state
+            .metadata_or_insert_with(CrashReportProgressMetadata::default)
+            .processed;
+        let mut ids: Vec<CorpusId> = state.solutions().ids().skip(processed).collect();
+        let count = processed + ids.len();
+        if let Ok(replaced) = state.metadata_mut::<ReplacedSolutionsMetadata>() {
+            for id in replaced.ids.drain(..) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        for id in ids {
+            self.write_reports(state, id)?;
+        }
+        state
+            .metadata_mut::<CrashReportProgressMetadata>()?
+            .processed = count;
+        Ok(())
+    }
+}
+
+impl<I, S> Restartable<S> for CrashReportStage<I> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // The progress is tracked per solution
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<I> Named for CrashReportStage<I> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("crash_report");
+        &NAME
+    }
+}
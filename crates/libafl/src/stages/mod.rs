@@ -12,10 +12,16 @@ use alloc::{
 };
 use core::{fmt, marker::PhantomData};
 
+pub use adaptive_timeout::{AdaptiveTimeoutMetadata, AdaptiveTimeoutStage, TimeoutBase};
 #[cfg(feature = "std")]
 pub use afl_stats::{AflStatsStage, CalibrationTime, FuzzTime, SyncTime};
 pub use calibrate::{CalibrationStage, run_target_with_timing};
 pub use colorization::*;
+#[cfg(feature = "std")]
+pub use crash_min::HangMinimizationStage;
+pub use crash_min::{CrashMinimization, CrashMinimizationStage, CrashMinimizer};
+#[cfg(feature = "regex")]
+pub use crash_report::CrashReportStage;
 #[cfg(all(feature = "std", unix))]
 #[cfg(feature = "std")]
 pub use dump::*;
@@ -60,10 +66,14 @@ pub use shadow::*;
 pub mod replay;
 pub use replay::*;
 
+pub mod adaptive_timeout;
 #[cfg(feature = "std")]
 pub mod afl_stats;
 pub mod calibrate;
 pub mod colorization;
+pub mod crash_min;
+#[cfg(feature = "regex")]
+pub mod crash_report;
 #[cfg(feature = "std")]
 pub mod dump;
 pub mod dynamic;
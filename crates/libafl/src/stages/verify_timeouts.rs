@@ -14,11 +14,13 @@ use crate::{
     executors::{Executor, HasObservers, SetTimeout},
     inputs::BytesInput,
     observers::ObserversTuple,
-    stages::{Restartable, Stage},
+    stages::{AdaptiveTimeoutMetadata, Restartable, Stage},
 };
 
 /// Stage that re-runs inputs deemed as timeouts with double the timeout to assert that they are
 /// not false positives. AFL++ style.
+/// If an [`crate::stages::AdaptiveTimeoutStage`] is used, its current timeout is doubled instead
+/// of the configured one.
 /// Note: Will NOT work with in process executors due to the potential for restarts/crashes when
 /// running inputs.
 #[derive(Debug)]
@@ -100,6 +102,13 @@ where
         if timeouts.count() == 0 {
             return Ok(());
         }
+        // An `AdaptiveTimeoutStage` may have changed the timeout since construction
+        if let Some(meta) = state.metadata_map().get::<AdaptiveTimeoutMetadata>()
+            && meta.timeout() != Duration::ZERO
+        {
+            self.original_timeout = meta.timeout();
+            self.doubled_timeout = meta.timeout() * 2;
+        }
         executor.set_timeout(self.doubled_timeout);
         *self.capture_timeouts.borrow_mut() = false;
         while let Some(input) = timeouts.pop() {
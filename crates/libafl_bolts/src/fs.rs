@@ -1,12 +1,12 @@
 //! `LibAFL` functionality for filesystem interaction
 
+#[cfg(unix)]
+use alloc::ffi::CString;
 use alloc::{string::String, sync::Arc, vec::Vec};
 use core::{
     sync::atomic::{AtomicU64, Ordering},
     time::Duration,
 };
-#[cfg(unix)]
-use std::os::unix::prelude::{AsRawFd, RawFd};
 use std::{
     fs::{self, File, OpenOptions, remove_file},
     io::{Seek, Write},
@@ -14,6 +14,16 @@ use std::{
     sync::OnceLock,
     time::SystemTime,
 };
+#[cfg(unix)]
+use std::{
+    io::ErrorKind,
+    os::unix::{
+        fs::{FileTypeExt, OpenOptionsExt},
+        prelude::{AsRawFd, OsStrExt, RawFd},
+    },
+    thread::sleep,
+    time::Instant,
+};
 
 use crate::Error;
 
@@ -140,6 +150,121 @@ impl InputFile {
     }
 }
 
+/// A named pipe (FIFO) to stream fuzzer input into.
+/// The target opens the FIFO for reading and reads the input until it sees the end of the stream.
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct InputFifo {
+    /// The path to this [`InputFifo`]
+    pub path: PathBuf,
+    /// The ref count for this [`InputFifo`].
+    /// Once it reaches 0, the named pipe will be removed.
+    pub rc: Arc<()>,
+}
+
+#[cfg(unix)]
+impl Eq for InputFifo {}
+
+#[cfg(unix)]
+impl PartialEq for InputFifo {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+#[cfg(unix)]
+impl InputFifo {
+    /// Creates a new named pipe at the given path, or reuses it if a named pipe already exists there
+    pub fn create<P>(path: P) -> Result<Self, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => {}
+            Ok(_) => {
+                return Err(Error::illegal_argument(format!(
+                    "{} already exists and is not a named pipe",
+                    path.display()
+                )));
+            }
+            Err(_) => {
+                let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+                    Error::illegal_argument(format!("Invalid fifo path {}", path.display()))
+                })?;
+                // # Safety
+                // `c_path` is a valid, nul-terminated string.
+                if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+            }
+        }
+        Ok(Self {
+            path,
+            rc: Arc::new(()),
+        })
+    }
+
+    /// Streams the given buffer into the named pipe, then closes it so that the reader sees the end
+    /// of the input.
+    ///
+    /// Waits up to `timeout` for the target to open the pipe and to consume the input.
+    /// If the target closes the pipe early, the rest of the input is dropped silently.
+    pub fn write_input(&self, buf: &[u8], timeout: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        // Opening a FIFO for writing without a reader fails with `ENXIO` in non-blocking mode.
+        let mut file = loop {
+            match OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&self.path)
+            {
+                Ok(file) => break file,
+                Err(err) if err.raw_os_error() == Some(libc::ENXIO) => {
+                    if Instant::now() >= deadline {
+                        return Err(Error::unknown(format!(
+                            "Target did not open the fifo {} in time",
+                            self.path.display()
+                        )));
+                    }
+                    sleep(Duration::from_millis(1));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        let mut written = 0;
+        while written < buf.len() {
+            match file.write(&buf[written..]) {
+                Ok(len) => written += len,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    if Instant::now() >= deadline {
+                        return Err(Error::unknown(format!(
+                            "Target did not consume the input from fifo {} in time",
+                            self.path.display()
+                        )));
+                    }
+                    sleep(Duration::from_millis(1));
+                }
+                Err(err) if err.kind() == ErrorKind::BrokenPipe => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl Drop for InputFifo {
+    fn drop(&mut self) {
+        if Arc::into_inner(core::mem::take(&mut self.rc)).is_some() {
+            // try to remove the fifo, but ignore errors
+            drop(remove_file(&self.path));
+        }
+    }
+}
+
 /// Finds new files in the given directory, taking the last time we looked at this path as parameter.
 /// This method works recursively.
 /// If `last` is `None`, it'll load all file.
@@ -209,4 +334,27 @@ mod test {
         drop(one);
         assert_eq!("Welp", fs::read_to_string(two.path.as_path()).unwrap());
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_input_fifo() {
+        use alloc::vec::Vec;
+        use core::time::Duration;
+        use std::{io::Read, thread};
+
+        use crate::fs::InputFifo;
+
+        let fifo = InputFifo::create("test_input_fifo.tmp").unwrap();
+        let path = fifo.path.clone();
+        let reader = thread::spawn(move || {
+            let mut buf = Vec::new();
+            fs::File::open(path).unwrap().read_to_end(&mut buf).unwrap();
+            buf
+        });
+        let input = vec![0x41; 256 * 1024];
+        fifo.write_input(&input, Duration::from_secs(10)).unwrap();
+        assert_eq!(reader.join().unwrap(), input);
+        drop(fifo);
+        assert!(fs::metadata("test_input_fifo.tmp").is_err());
+    }
 }
@@ -1,16 +1,34 @@
 //! Shared implementation of afl style arguments
 
 use alloc::{borrow::ToOwned, vec::Vec};
+use core::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+#[cfg(unix)]
+use std::os::unix::{ffi::OsStrExt, net::UnixStream};
 use std::{
     ffi::{OsStr, OsString},
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, UdpSocket},
     path::PathBuf,
+    thread::sleep,
+    time::Instant,
 };
 
-use crate::fs::{InputFile, get_unique_std_input_file};
+#[cfg(unix)]
+use crate::fs::InputFifo;
+use crate::{
+    Error,
+    fs::{InputFile, get_unique_std_input_file},
+};
 
 /// How to deliver input to an external program
 /// `StdIn`: The target reads from stdin
 /// `File`: The target reads from the specified [`InputFile`]
+/// `Socket`: The target accepts the input on a socket, see [`SocketDelivery`]
+/// `Env`: The target reads the input from an environment variable
+/// `Fifo`: The target reads from the specified [`InputFifo`]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InputLocation {
     /// Mutate a commandline argument to deliver an input
@@ -29,6 +47,41 @@ pub enum InputLocation {
         /// The file to write input to. The target should read input from this location.
         out_file: InputFile,
     },
+    /// Deliver the input over a socket exposed by the target after it started, e.g. for network
+    /// daemons. The executor terminates the target once the input was delivered.
+    Socket {
+        /// Where and how to send the input
+        delivery: SocketDelivery,
+    },
+    /// Deliver the input in the environment variable `name`, set for each execution.
+    /// As environment variables can't hold nul bytes, the input is cut at the first nul byte.
+    /// For forkserver targets, the forkserver of the target sets the variable in each child, so
+    /// they have to be linked against the `libafl_targets` forkserver, AFL++ targets are rejected.
+    Env {
+        /// The name of the environment variable
+        name: OsString,
+    },
+    /// Stream the input into the specified [`InputFifo`], which the target opens for reading.
+    #[cfg(unix)]
+    Fifo {
+        /// The named pipe to write input to. The target should read input from this location.
+        fifo: InputFifo,
+    },
+}
+
+/// The value of the environment variable for [`InputLocation::Env`], holding the input up to the
+/// first nul byte.
+#[must_use]
+pub fn env_input_value(input: &[u8]) -> OsString {
+    let len = input.iter().position(|&b| b == 0).unwrap_or(input.len());
+    #[cfg(unix)]
+    {
+        OsStr::from_bytes(&input[..len]).to_owned()
+    }
+    #[cfg(not(unix))]
+    {
+        OsString::from(String::from_utf8_lossy(&input[..len]).into_owned())
+    }
 }
 
 impl Default for InputLocation {
@@ -96,6 +149,42 @@ pub trait StdTargetArgs: Sized {
         )
     }
 
+    /// Deliver the input over a socket the target listens on, see [`SocketDelivery`].
+    #[must_use]
+    fn socket_input(self, delivery: SocketDelivery) -> Self {
+        self.input(InputLocation::Socket { delivery })
+    }
+
+    /// Deliver the input in the environment variable `name`, see [`InputLocation::Env`].
+    #[must_use]
+    fn env_input<K>(self, name: K) -> Self
+    where
+        K: AsRef<OsStr>,
+    {
+        self.input(InputLocation::Env {
+            name: name.as_ref().to_owned(),
+        })
+    }
+
+    /// Stream the input into a named pipe created at `path`, see [`InputLocation::Fifo`].
+    /// The target has to know the path on its own, use [`Self::arg_input_fifo`] to pass it as
+    /// argument instead.
+    #[cfg(unix)]
+    #[must_use]
+    fn fifo_input<P: Into<PathBuf>>(self, path: P) -> Self {
+        let fifo = InputFifo::create(path).unwrap();
+        self.input(InputLocation::Fifo { fifo })
+    }
+
+    /// Place the path of a named pipe created at `path` at this position and stream the input into
+    /// it, see [`InputLocation::Fifo`].
+    #[cfg(unix)]
+    #[must_use]
+    fn arg_input_fifo<P: Into<PathBuf>>(self, path: P) -> Self {
+        let path = path.into();
+        self.arg(&path).fifo_input(path)
+    }
+
     /// Set input
     #[must_use]
     fn input(mut self, input: InputLocation) -> Self {
@@ -128,7 +217,11 @@ pub trait StdTargetArgs: Sized {
                 InputLocation::File { out_file } => out_file.path == path,
                 InputLocation::StdIn { input_file } =>
                     input_file.as_ref().is_none_or(|of| of.path == path),
-                InputLocation::Arg { argnum: _ } => false,
+                #[cfg(unix)]
+                InputLocation::Fifo { fifo: _ } => false,
+                InputLocation::Arg { argnum: _ }
+                | InputLocation::Socket { delivery: _ }
+                | InputLocation::Env { name: _ } => false,
             },
             "Already specified an input file under a different name. This is not supported"
         );
@@ -189,7 +282,8 @@ pub trait StdTargetArgs: Sized {
     /// Parse afl style command line
     ///
     /// Replaces `@@` with the path to the input file generated by the fuzzer. If `@@` is omitted,
-    /// `stdin` is used to pass the test case instead.
+    /// `stdin` is used to pass the test case instead. If an [`InputLocation::Fifo`] was set before,
+    /// `@@` is replaced with the path to the named pipe.
     ///
     /// Interprets the first argument as the path to the program as long as it is not set yet.
     /// You have to omit the program path in case you have set it already. Otherwise
@@ -218,6 +312,10 @@ pub trait StdTargetArgs: Sized {
                         // If the input file name has been modified, use this one
                         moved = moved.arg_input_file(&out_file.path);
                     }
+                    #[cfg(unix)]
+                    InputLocation::Fifo { fifo } => {
+                        moved = moved.arg(&fifo.path);
+                    }
                     _ => {
                         moved = moved.arg_input_file_std();
                     }
@@ -231,3 +329,346 @@ pub trait StdTargetArgs: Sized {
         moved
     }
 }
+
+/// A socket a target accepts its input on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketEndpoint {
+    /// Connect to a TCP port
+    Tcp(SocketAddr),
+    /// Send datagrams to a UDP port
+    Udp(SocketAddr),
+    /// Connect to a Unix domain socket at the given path
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Configures how an input is delivered to a target over a [`SocketEndpoint`].
+///
+/// As the target needs some time to start up, connecting is retried until the `connect_timeout`
+/// elapsed. The input is optionally split into messages after each occurrence of a delimiter,
+/// with a delay between messages. After each message, responses are read until the socket is
+/// closed or nothing arrived for `response_timeout`. All of this stays within the timeout of the
+/// execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketDelivery {
+    /// Where the target listens
+    pub endpoint: SocketEndpoint,
+    /// If set, the input is split into messages after each occurrence of this delimiter
+    pub message_delimiter: Option<Vec<u8>>,
+    /// Delay between two messages
+    pub message_delay: Duration,
+    /// How long to retry connecting to the target
+    pub connect_timeout: Duration,
+    /// How long to wait for (further) response data after each message
+    pub response_timeout: Duration,
+}
+
+impl SocketDelivery {
+    /// Create a new [`SocketDelivery`] sending the whole input as one message to `endpoint`.
+    #[must_use]
+    pub fn new(endpoint: SocketEndpoint) -> Self {
+        Self {
+            endpoint,
+            message_delimiter: None,
+            message_delay: Duration::ZERO,
+            connect_timeout: Duration::from_secs(1),
+            response_timeout: Duration::from_millis(100),
+        }
+    }
+
+    /// Split the input into messages after each occurrence of `delimiter`, e.g. `\r\n`.
+    #[must_use]
+    pub fn message_delimiter(mut self, delimiter: &[u8]) -> Self {
+        self.message_delimiter = Some(delimiter.to_vec());
+        self
+    }
+
+    /// Wait for `delay` between sending two messages.
+    #[must_use]
+    pub fn message_delay(mut self, delay: Duration) -> Self {
+        self.message_delay = delay;
+        self
+    }
+
+    /// Retry connecting to the target for at most `timeout`.
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Wait at most `timeout` for response data after each message.
+    #[must_use]
+    pub fn response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+
+    /// Split the input into the messages to send. The delimiter stays part of each message.
+    ///
+    /// An empty input is sent as a single empty message, with or without a delimiter, so the
+    /// target is still connected to and its response, e.g. a greeting, is read.
+    #[must_use]
+    pub fn messages<'a>(&self, input: &'a [u8]) -> Vec<&'a [u8]> {
+        let Some(delimiter) = self.message_delimiter.as_deref().filter(|d| !d.is_empty()) else {
+            return vec![input];
+        };
+        let mut messages = vec![];
+        let mut start = 0;
+        let mut pos = 0;
+        while pos + delimiter.len() <= input.len() {
+            if &input[pos..pos + delimiter.len()] == delimiter {
+                pos += delimiter.len();
+                messages.push(&input[start..pos]);
+                start = pos;
+            } else {
+                pos += 1;
+            }
+        }
+        if start < input.len() || input.is_empty() {
+            messages.push(&input[start..]);
+        }
+        messages
+    }
+
+    /// Connect to the target, send the input and return all response data received.
+    ///
+    /// Everything, including connecting and waiting for responses, happens within `timeout`, the
+    /// time budget of the execution. Once it is used up, the response received so far is returned.
+    pub fn deliver(&self, input: &[u8], timeout: Duration) -> Result<Vec<u8>, Error> {
+        let deadline = Deadline::after(timeout);
+        let mut response = vec![];
+        match &self.endpoint {
+            SocketEndpoint::Tcp(addr) => {
+                let mut stream = self.connect_with_retry(deadline, || TcpStream::connect(addr))?;
+                stream.set_nodelay(true)?;
+                self.send_stream(&mut stream, input, deadline, &mut response)?;
+            }
+            #[cfg(unix)]
+            SocketEndpoint::Unix(path) => {
+                let mut stream = self.connect_with_retry(deadline, || UnixStream::connect(path))?;
+                self.send_stream(&mut stream, input, deadline, &mut response)?;
+            }
+            SocketEndpoint::Udp(addr) => {
+                let local: SocketAddr = if addr.is_ipv4() {
+                    (Ipv4Addr::UNSPECIFIED, 0).into()
+                } else {
+                    (Ipv6Addr::UNSPECIFIED, 0).into()
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                let mut buf = vec![0; 0x10000];
+                for (i, message) in self.messages(input).into_iter().enumerate() {
+                    if i > 0 && !self.message_delay.is_zero() {
+                        sleep(self.message_delay.min(deadline.remaining()));
+                    }
+                    // The target may not be bound yet, retry until it is.
+                    self.connect_with_retry(deadline, || socket.send(message))?;
+                    loop {
+                        let Some(wait) = deadline.limit(self.response_timeout) else {
+                            return Ok(response);
+                        };
+                        socket.set_read_timeout(Some(wait))?;
+                        match socket.recv(&mut buf) {
+                            Ok(len) => response.extend_from_slice(&buf[..len]),
+                            Err(err) if is_timeout(&err) => break,
+                            Err(err) if err.kind() == ErrorKind::ConnectionRefused => break,
+                            Err(err) => return Err(err.into()),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(response)
+    }
+
+    fn connect_with_retry<T>(
+        &self,
+        deadline: Deadline,
+        mut connect: impl FnMut() -> std::io::Result<T>,
+    ) -> Result<T, Error> {
+        let start = Instant::now();
+        loop {
+            match connect() {
+                Ok(stream) => return Ok(stream),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        ErrorKind::ConnectionRefused | ErrorKind::NotFound
+                    ) && start.elapsed() < self.connect_timeout
+                        && !deadline.remaining().is_zero() =>
+                {
+                    sleep(Duration::from_millis(1));
+                }
+                Err(err) => {
+                    return Err(Error::os_error(
+                        err,
+                        format!("Could not connect to target at {:?}", self.endpoint),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn send_stream<T: TimedStream>(
+        &self,
+        stream: &mut T,
+        input: &[u8],
+        deadline: Deadline,
+        response: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let mut buf = [0; 0x1000];
+        for (i, message) in self.messages(input).into_iter().enumerate() {
+            if i > 0 && !self.message_delay.is_zero() {
+                sleep(self.message_delay.min(deadline.remaining()));
+            }
+            let Some(wait) = deadline.limit(Duration::MAX) else {
+                return Ok(());
+            };
+            stream.set_timeouts(wait)?;
+            match stream.write_all(message) {
+                Ok(()) => {}
+                // The target closed the connection, nothing more to send.
+                Err(err) if is_closed(&err) => return Ok(()),
+                // The target does not read its input, the executor reports the timeout.
+                Err(err) if is_timeout(&err) => return Ok(()),
+                Err(err) => return Err(err.into()),
+            }
+            loop {
+                let Some(wait) = deadline.limit(self.response_timeout) else {
+                    return Ok(());
+                };
+                stream.set_timeouts(wait)?;
+                match stream.read(&mut buf) {
+                    Ok(0) => return Ok(()),
+                    Ok(len) => response.extend_from_slice(&buf[..len]),
+                    Err(err) if is_timeout(&err) => break,
+                    Err(err) if is_closed(&err) => return Ok(()),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The end of the time budget of a delivery
+#[derive(Debug, Clone, Copy)]
+struct Deadline(Option<Instant>);
+
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self(Instant::now().checked_add(timeout))
+    }
+
+    fn remaining(self) -> Duration {
+        self.0.map_or(Duration::MAX, |end| {
+            end.saturating_duration_since(Instant::now())
+        })
+    }
+
+    /// `wait`, cut to the remaining time, or `None` once the budget is used up
+    fn limit(self, wait: Duration) -> Option<Duration> {
+        Some(wait.min(self.remaining())).filter(|wait| !wait.is_zero())
+    }
+}
+
+/// A stream with read and write timeouts
+trait TimedStream: Read + Write {
+    fn set_timeouts(&self, timeout: Duration) -> std::io::Result<()>;
+}
+
+impl TimedStream for TcpStream {
+    fn set_timeouts(&self, timeout: Duration) -> std::io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+#[cfg(unix)]
+impl TimedStream for UnixStream {
+    fn set_timeouts(&self, timeout: Duration) -> std::io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+fn is_closed(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::BrokenPipe | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+        time::Instant,
+    };
+
+    use super::{SocketDelivery, SocketEndpoint, env_input_value};
+
+    #[test]
+    fn test_messages() {
+        let delivery = SocketDelivery::new(SocketEndpoint::Tcp(([127, 0, 0, 1], 0).into()))
+            .message_delimiter(b"\r\n");
+        assert_eq!(
+            delivery.messages(b"USER a\r\nPASS b\r\nQUIT"),
+            [&b"USER a\r\n"[..], b"PASS b\r\n", b"QUIT"]
+        );
+        assert_eq!(delivery.messages(b""), [b""]);
+        assert_eq!(
+            SocketDelivery::new(SocketEndpoint::Tcp(([127, 0, 0, 1], 0).into())).messages(b""),
+            [b""]
+        );
+    }
+
+    #[test]
+    fn test_env_input_value() {
+        assert_eq!(env_input_value(b"abc"), "abc");
+        assert_eq!(env_input_value(b"ab\0c"), "ab");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_deliver_tcp() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 16];
+            let len = stream.read(&mut buf).unwrap();
+            stream.write_all(&buf[..len]).unwrap();
+        });
+
+        let response = SocketDelivery::new(SocketEndpoint::Tcp(addr))
+            .deliver(b"ping", Duration::from_secs(5))
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(response, b"ping");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_deliver_budget() {
+        // Nobody listens, retrying to connect stops once the budget of the execution is used up.
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let start = Instant::now();
+        let result = SocketDelivery::new(SocketEndpoint::Tcp(addr))
+            .connect_timeout(Duration::from_secs(10))
+            .deliver(b"ping", Duration::from_millis(50));
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -8,6 +8,10 @@ use core::str;
     feature = "cmplog-instructions",
     feature = "ctx",
     feature = "dump-cfg",
+    feature = "data-coverage",
+    feature = "laf-intel",
+    feature = "instrument-list",
+    feature = "lto-edges",
 ))]
 use std::path::PathBuf;
 #[cfg(any(
@@ -18,6 +22,10 @@ use std::path::PathBuf;
     feature = "cmplog-instructions",
     feature = "ctx",
     feature = "dump-cfg",
+    feature = "data-coverage",
+    feature = "laf-intel",
+    feature = "instrument-list",
+    feature = "lto-edges",
 ))]
 use std::process::Command;
 use std::{env, fs::File, io::Write, path::Path};
@@ -30,6 +38,10 @@ use std::{env, fs::File, io::Write, path::Path};
     feature = "cmplog-instructions",
     feature = "ctx",
     feature = "dump-cfg",
+    feature = "data-coverage",
+    feature = "laf-intel",
+    feature = "instrument-list",
+    feature = "lto-edges",
 ))]
 #[expect(clippy::too_many_arguments)]
 fn build_pass(
@@ -390,6 +402,54 @@ pub const LIBAFL_CC_LLVM_VERSION: Option<usize> = None;
         false,
     );
 
+    #[cfg(feature = "data-coverage")]
+    build_pass(
+        bindir_path,
+        out_dir,
+        &cxxflags,
+        &ldflags,
+        src_dir,
+        "data-coverage-pass.cc",
+        None,
+        true,
+    );
+
+    #[cfg(feature = "laf-intel")]
+    build_pass(
+        bindir_path,
+        out_dir,
+        &cxxflags,
+        &ldflags,
+        src_dir,
+        "laf-intel-pass.cc",
+        None,
+        true,
+    );
+
+    #[cfg(feature = "instrument-list")]
+    build_pass(
+        bindir_path,
+        out_dir,
+        &cxxflags,
+        &ldflags,
+        src_dir,
+        "instrument-list-pass.cc",
+        None,
+        true,
+    );
+
+    #[cfg(feature = "lto-edges")]
+    build_pass(
+        bindir_path,
+        out_dir,
+        &cxxflags,
+        &ldflags,
+        src_dir,
+        "lto-edges-pass.cc",
+        None,
+        true,
+    );
+
     cc::Build::new()
         .file(src_dir.join("no-link-rt.c"))
         .compile("no-link-rt");
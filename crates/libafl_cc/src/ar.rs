@@ -22,6 +22,7 @@ pub struct ArWrapper {
     configurations: Vec<crate::Configuration>,
     parse_args_called: bool,
     base_args: Vec<String>,
+    lto: bool,
 }
 
 #[expect(clippy::match_same_arms)] // for the linking = false wip for "shared"
@@ -90,6 +91,11 @@ impl ToolWrapper for ArWrapper {
                     i += 1;
                     continue;
                 }
+                "--libafl-lto" => {
+                    self.lto = true;
+                    i += 1;
+                    continue;
+                }
                 "--libafl-configurations" if i + 1 < args.len() => {
                     self.configurations.extend(
                         args[i + 1]
@@ -187,6 +193,13 @@ impl ToolWrapper for ArWrapper {
 
         args.push(LLVM_AR_PATH.to_string());
 
+        // The system ranlib cannot index bitcode objects, `llvm-ar s` does the same
+        if self.lto && self.is_ranlib() {
+            args.push("s".to_string());
+            args.extend(base_args.into_iter().filter(|arg| !arg.starts_with('-')));
+            return Ok(args);
+        }
+
         args.extend_from_slice(base_args.as_slice());
 
         if self.need_libafl_arg && !self.has_libafl_arg {
@@ -231,10 +244,28 @@ impl ArWrapper {
             configurations: vec![crate::Configuration::Default],
             parse_args_called: false,
             base_args: vec![],
+            lto: false,
             is_silent: false,
         }
     }
 
+    /// Archive the bitcode objects of the LTO mode of the [`crate::ClangWrapper`], call this
+    /// before calling `parse_args`.
+    ///
+    /// `llvm-ar` indexes the bitcode symbols, so the final link finds them. In LTO mode, the
+    /// wrapper also replaces `ranlib`, when its name ends with `ranlib`, e.g. for
+    /// `-DCMAKE_RANLIB`. The `--libafl-lto` argument sets it too.
+    pub fn lto(&mut self, value: bool) -> &'_ mut Self {
+        self.lto = value;
+        self
+    }
+
+    /// If the wrapper is called as `ranlib`
+    fn is_ranlib(&self) -> bool {
+        let name = self.name.strip_suffix(".exe").unwrap_or(&self.name);
+        name.ends_with("ranlib")
+    }
+
     /// Set if linking
     pub fn linking(&mut self, value: bool) -> &'_ mut Self {
         self.linking = value;
@@ -247,3 +278,26 @@ impl ArWrapper {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArWrapper, ToolWrapper};
+
+    #[test]
+    fn test_lto_ranlib() {
+        let args = ArWrapper::new()
+            .lto(true)
+            .parse_args(&["my-ranlib", "-D", "libfoo.a"])
+            .unwrap()
+            .command()
+            .unwrap();
+        assert_eq!(&args[1..], ["s", "libfoo.a"]);
+
+        let args = ArWrapper::new()
+            .parse_args(&["my-ar", "rcs", "libfoo.a", "a.o", "--libafl-lto"])
+            .unwrap()
+            .command()
+            .unwrap();
+        assert_eq!(&args[1..], ["rcs", "libfoo.a", "a.o"]);
+    }
+}
@@ -39,6 +39,16 @@ pub enum LLVMPasses {
     Ctx,
     /// Function logging
     FunctionLogging,
+    /// Report the addresses of loads and stores for data coverage
+    DataCoverage,
+    /// Split multi-byte comparisons and switches into byte comparisons, like laf-intel
+    LafIntel,
+    /// Filter the instrumented functions, added by [`ClangWrapper::allowlist`] and
+    /// [`ClangWrapper::denylist`]
+    InstrumentList,
+    /// Collision-free edge coverage over the whole program, run at link time by
+    /// [`ClangWrapper::lto`]
+    LtoEdges,
 }
 
 impl LLVMPasses {
@@ -65,6 +75,16 @@ impl LLVMPasses {
             LLVMPasses::FunctionLogging => {
                 PathBuf::from(env!("OUT_DIR")).join(format!("function-logging.{}", dll_extension()))
             }
+            LLVMPasses::DataCoverage => PathBuf::from(env!("OUT_DIR"))
+                .join(format!("data-coverage-pass.{}", dll_extension())),
+            LLVMPasses::InstrumentList => PathBuf::from(env!("OUT_DIR"))
+                .join(format!("instrument-list-pass.{}", dll_extension())),
+            LLVMPasses::LafIntel => {
+                PathBuf::from(env!("OUT_DIR")).join(format!("laf-intel-pass.{}", dll_extension()))
+            }
+            LLVMPasses::LtoEdges => {
+                PathBuf::from(env!("OUT_DIR")).join(format!("lto-edges-pass.{}", dll_extension()))
+            }
         }
     }
 }
@@ -98,6 +118,9 @@ pub struct ClangWrapper {
     passes: Vec<LLVMPasses>,
     passes_args: Vec<String>,
     passes_linking_args: Vec<String>,
+    allowlist: Option<PathBuf>,
+    denylist: Option<PathBuf>,
+    lto: bool,
 }
 
 #[expect(clippy::match_same_arms)] // for the linking = false wip for "shared"
@@ -203,6 +226,21 @@ impl ToolWrapper for ClangWrapper {
                     i += 1;
                     continue;
                 }
+                "--libafl-allowlist" if i + 1 < args.len() => {
+                    self.allowlist = Some(PathBuf::from(args[i + 1].as_ref()));
+                    i += 2;
+                    continue;
+                }
+                "--libafl-denylist" if i + 1 < args.len() => {
+                    self.denylist = Some(PathBuf::from(args[i + 1].as_ref()));
+                    i += 2;
+                    continue;
+                }
+                "--libafl-lto" => {
+                    self.lto = true;
+                    i += 1;
+                    continue;
+                }
                 "--libafl-configurations" if i + 1 < args.len() => {
                     self.configurations.extend(
                         args[i + 1]
@@ -248,6 +286,13 @@ impl ToolWrapper for ClangWrapper {
         self.linking = linking;
         self.shared = shared;
 
+        if self.allowlist.is_none() {
+            self.allowlist = std::env::var_os("LIBAFL_ALLOWLIST").map(PathBuf::from);
+        }
+        if self.denylist.is_none() {
+            self.denylist = std::env::var_os("LIBAFL_DENYLIST").map(PathBuf::from);
+        }
+
         new_args.push("-g".into());
         if self.optimize {
             new_args.push("-O3".into());
@@ -399,6 +444,30 @@ impl ToolWrapper for ClangWrapper {
             return Ok(args);
         }
 
+        // The instrument list pass goes first, to filter the functions before any instrumentation
+        if !self.is_asm && (self.allowlist.is_some() || self.denylist.is_some()) {
+            let path = LLVMPasses::InstrumentList
+                .path()
+                .into_os_string()
+                .into_string()
+                .unwrap();
+            args.push("-Xclang".into());
+            args.push("-load".into());
+            args.push("-Xclang".into());
+            args.push(path.clone());
+            args.push("-Xclang".into());
+            args.push(format!("-fpass-plugin={path}"));
+            for (option, list) in [
+                ("libafl_allowlist", &self.allowlist),
+                ("libafl_denylist", &self.denylist),
+            ] {
+                if let Some(list) = list {
+                    args.push("-mllvm".into());
+                    args.push(format!("-{option}={}", list.display()));
+                }
+            }
+        }
+
         for pass in &self.passes {
             use_pass = true;
             // https://github.com/llvm/llvm-project/issues/56137
@@ -420,6 +489,27 @@ impl ToolWrapper for ClangWrapper {
                 args.push(passes_arg.into());
             }
         }
+        if self.lto && !self.is_asm {
+            // Bitcode objects, the edges are numbered once the whole program is linked
+            args.push("-flto=full".into());
+            if self.linking {
+                if LIBAFL_CC_LLVM_VERSION.is_some_and(|version| version < 15) {
+                    return Err(Error::Unknown(
+                        "The LTO mode needs LLVM 15 or later, to load passes in the linker"
+                            .to_string(),
+                    ));
+                }
+                args.push("-fuse-ld=lld".into());
+                args.push(format!(
+                    "-Wl,--load-pass-plugin={}",
+                    LLVMPasses::LtoEdges
+                        .path()
+                        .into_os_string()
+                        .into_string()
+                        .unwrap()
+                ));
+            }
+        }
         if self.linking {
             if self.x_set {
                 args.push("-x".into());
@@ -542,6 +632,9 @@ impl ClangWrapper {
             passes: vec![],
             passes_args: vec![],
             passes_linking_args: vec![],
+            allowlist: None,
+            denylist: None,
+            lto: false,
             is_silent: false,
         }
     }
@@ -594,6 +687,53 @@ impl ClangWrapper {
         self
     }
 
+    /// Only instrument the functions matching the given AFL++-style allowlist, call this before
+    /// calling `parse_args`.
+    ///
+    /// Each line of the list is a `fun: <glob>` (or `function:`) matching the mangled or demangled
+    /// function name, or a `src: <glob>` (or `source:`, or no prefix) matching the end of the
+    /// source file path, `#` starts a comment. The functions that match no entry get no sanitizer
+    /// coverage, `CmpLog`, ctx or other `LibAFL` pass instrumentation.
+    ///
+    /// The `--libafl-allowlist <file>` argument and the `LIBAFL_ALLOWLIST` environment variable
+    /// set it as well.
+    pub fn allowlist<P>(&mut self, path: P) -> &'_ mut Self
+    where
+        P: AsRef<Path>,
+    {
+        self.allowlist = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Do not instrument the functions matching the given AFL++-style denylist, call this before
+    /// calling `parse_args`.
+    ///
+    /// The list has the format of the [`ClangWrapper::allowlist`], and takes precedence over it.
+    /// The `--libafl-denylist <file>` argument and the `LIBAFL_DENYLIST` environment variable set
+    /// it as well.
+    pub fn denylist<P>(&mut self, path: P) -> &'_ mut Self
+    where
+        P: AsRef<Path>,
+    {
+        self.denylist = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Build in LTO mode, like `afl-clang-lto`, call this before calling `parse_args`.
+    ///
+    /// The objects are compiled to bitcode, and the final link, with `lld`, numbers the edges of
+    /// the whole program with the [`LLVMPasses::LtoEdges`] pass: each edge gets its own entry of
+    /// the edges map, and the exact number of edges sizes the observers of the
+    /// `lto_edges` feature of `libafl_targets`. Do not add the `sancov` coverage on top of it.
+    ///
+    /// Static libraries need to be archived with the [`crate::ArWrapper`], libtool builds need
+    /// the [`crate::LibtoolWrapper`] in LTO mode as well. The `--libafl-lto` argument sets it
+    /// too. Needs LLVM 15 or later.
+    pub fn lto(&mut self, value: bool) -> &'_ mut Self {
+        self.lto = value;
+        self
+    }
+
     /// Set if linking
     pub fn linking(&mut self, value: bool) -> &'_ mut Self {
         self.linking = value;
@@ -622,4 +762,45 @@ mod tests {
             println!("Ignored error {res:?} - clang is probably not installed.");
         }
     }
+
+    #[test]
+    fn test_instrument_lists() {
+        let args = ClangWrapper::new()
+            .allowlist("allow.txt")
+            .parse_args(&["my-clang", "-c", "a.c", "--libafl-denylist", "deny.txt"])
+            .unwrap()
+            .command()
+            .unwrap();
+        assert!(args.contains(&"-libafl_allowlist=allow.txt".to_string()));
+        assert!(args.contains(&"-libafl_denylist=deny.txt".to_string()));
+        assert!(!args.contains(&"--libafl-denylist".to_string()));
+    }
+
+    #[test]
+    fn test_lto() {
+        let args = ClangWrapper::new()
+            .parse_args(&["my-clang", "-c", "a.c", "--libafl-lto"])
+            .unwrap()
+            .command()
+            .unwrap();
+        assert!(args.contains(&"-flto=full".to_string()));
+        assert!(
+            !args
+                .iter()
+                .any(|arg| arg.starts_with("-Wl,--load-pass-plugin"))
+        );
+
+        let mut linker = ClangWrapper::new();
+        linker
+            .lto(true)
+            .parse_args(&["my-clang", "a.o", "-o", "a"])
+            .unwrap();
+        if let Ok(args) = linker.command() {
+            assert!(args.contains(&"-fuse-ld=lld".to_string()));
+            assert!(
+                args.iter()
+                    .any(|arg| arg.starts_with("-Wl,--load-pass-plugin"))
+            );
+        }
+    }
 }
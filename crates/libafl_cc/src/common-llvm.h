@@ -39,12 +39,23 @@ static uint32_t RandBelow(uint32_t max) {
   return (uint32_t)rand() % (max + 1);
 }
 
+/* The attribute of the functions filtered out by the allowlist or denylist,
+   set by the instrument list pass */
+#define LIBAFL_NO_INSTRUMENT_ATTR "libafl-no-instrument"
+
+/* Function that passes the allowlist and denylist of libafl_cc */
+static inline bool isInInstrumentList(const llvm::Function *F) {
+  return !F->hasFnAttribute(LIBAFL_NO_INSTRUMENT_ATTR);
+}
+
 /* Function that we never instrument or analyze */
 /* Note: this ignore check is also called in isInInstrumentList() */
 static inline bool isIgnoreFunction(const llvm::Function *F) {
   // Starting from "LLVMFuzzer" these are functions used in libfuzzer based
   // fuzzing campaign installations, e.g. oss-fuzz
 
+  if (!isInInstrumentList(F)) { return true; }
+
   static constexpr const char *ignoreList[] = {
 
       "asan.",
@@ -0,0 +1,141 @@
+/*
+   LibAFL - Data coverage LLVM pass
+   --------------------------------------------------
+
+   Instruments loads and stores to report the accessed address, along with a
+   random id of the instruction site, to the data coverage runtime of
+   libafl_targets.
+
+   Copyright 2025 AFLplusplus Project. All rights reserved.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at:
+
+     http://www.apache.org/licenses/LICENSE-2.0
+
+*/
+
+#include "common-llvm.h"
+
+#include <time.h>
+
+#include "llvm/IR/Instructions.h"
+#include "llvm/IR/DataLayout.h"
+
+using namespace llvm;
+
+static cl::opt<bool>     Debug("debug-data-coverage", cl::desc("Debug prints"),
+                               cl::init(false), cl::NotHidden);
+static cl::opt<uint32_t> InstRatio(
+    "data_cov_ratio",
+    cl::desc("Percentage of the memory accesses to instrument"), cl::init(100),
+    cl::NotHidden);
+static cl::opt<bool> StoresOnly("data_cov_stores_only",
+                                cl::desc("Only instrument the stores"),
+                                cl::init(false), cl::NotHidden);
+static cl::opt<bool> WithStack(
+    "data_cov_stack",
+    cl::desc("Also instrument the accesses to local variables"),
+    cl::init(false), cl::NotHidden);
+
+namespace {
+
+class DataCoverage : public PassInfoMixin<DataCoverage> {
+ public:
+  DataCoverage() {
+  }
+
+  PreservedAnalyses run(Module &M, ModuleAnalysisManager &MAM);
+
+ private:
+  bool isInteresting(Instruction &I, Value *&Ptr) {
+    if (I.getMetadata("nosanitize")) { return false; }
+
+    if (auto *LI = dyn_cast<LoadInst>(&I)) {
+      if (StoresOnly) { return false; }
+      Ptr = LI->getPointerOperand();
+    } else if (auto *SI = dyn_cast<StoreInst>(&I)) {
+      Ptr = SI->getPointerOperand();
+    } else {
+      return false;
+    }
+
+    // the accesses to locals only depend on the stack layout
+    if (!WithStack && isa<AllocaInst>(Ptr->stripPointerCasts())) {
+      return false;
+    }
+    return true;
+  }
+};
+
+}  // namespace
+
+extern "C" ::llvm::PassPluginLibraryInfo LLVM_ATTRIBUTE_WEAK
+llvmGetPassPluginInfo() {
+  return {LLVM_PLUGIN_API_VERSION, "DataCoverage", "v0.1",
+          /* lambda to insert our pass into the pass pipeline. */
+          [](PassBuilder &PB) {
+            PB.registerOptimizerLastEPCallback(
+                [](ModulePassManager &MPM, OptimizationLevel OL
+#if LLVM_VERSION_MAJOR >= 20
+                   ,
+                   ThinOrFullLTOPhase Phase
+#endif
+                ) { MPM.addPass(DataCoverage()); });
+          }};
+}
+
+PreservedAnalyses DataCoverage::run(Module &M, ModuleAnalysisManager &MAM) {
+  LLVMContext  &C = M.getContext();
+  Type         *VoidTy = Type::getVoidTy(C);
+  IntegerType  *Int32Ty = IntegerType::getInt32Ty(C);
+  IntegerType  *IntPtrTy = M.getDataLayout().getIntPtrType(C);
+  uint32_t      rand_seed;
+  unsigned long inst_accesses = 0;
+
+  if (!InstRatio || InstRatio > 100)
+    FATAL("Bad value of the instrumentation ratio (must be between 1 and 100)");
+
+  FunctionCallee dataCovHook =
+      M.getOrInsertFunction("__libafl_targets_data_cov", VoidTy, Int32Ty,
+                            IntPtrTy);
+
+  /* Setup random() so we get Actually Random(TM) */
+  rand_seed = time(NULL);
+  srand(rand_seed);
+
+  for (auto &F : M) {
+    if (isIgnoreFunction(&F)) { continue; }
+
+    std::vector<std::pair<Instruction *, Value *>> accesses;
+    for (auto &BB : F) {
+      for (auto &I : BB) {
+        Value *Ptr = nullptr;
+        if (!isInteresting(I, Ptr)) { continue; }
+        if (RandBelow(99) >= InstRatio) { continue; }
+        accesses.push_back({&I, Ptr});
+      }
+    }
+
+    for (auto &[I, Ptr] : accesses) {
+      IRBuilder<> IRB(I);
+      Value      *Site = ConstantInt::get(Int32Ty, rand());
+      Value      *Addr = IRB.CreatePtrToInt(Ptr, IntPtrTy);
+      IRB.CreateCall(dataCovHook, {Site, Addr})
+          ->setMetadata(M.getMDKindID("nosanitize"), MDNode::get(C, None));
+      inst_accesses++;
+    }
+  }
+
+  if (Debug) {
+    if (!inst_accesses)
+      fprintf(stderr, "No memory accesses found.\n");
+    else
+      fprintf(stderr, "Instrumented %lu memory accesses (ratio %u%%).\n",
+              inst_accesses, (unsigned)InstRatio);
+  }
+
+  if (inst_accesses) { return PreservedAnalyses::none(); }
+  return PreservedAnalyses::all();
+}
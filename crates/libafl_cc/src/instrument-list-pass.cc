@@ -0,0 +1,176 @@
+/*
+   LibAFL - Instrument list LLVM pass
+   --------------------------------------------------
+
+   Marks the functions filtered out by an AFL++-style allowlist or denylist,
+   so the sanitizer coverage and the other LibAFL passes leave them alone.
+
+   The lists hold one entry per line, `#` starts a comment:
+     fun: <glob>  (or function:) matches the (mangled or demangled) name
+     src: <glob>  (or source:) matches the end of the source file path
+     <glob>       is the same as src: <glob>
+
+   Copyright 2025 AFLplusplus Project. All rights reserved.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at:
+
+     http://www.apache.org/licenses/LICENSE-2.0
+
+*/
+
+#include "common-llvm.h"
+
+#include <fstream>
+#include <list>
+#include <string>
+#include <vector>
+
+#include "llvm/Demangle/Demangle.h"
+#include "llvm/IR/DebugInfoMetadata.h"
+#include "llvm/Support/GlobPattern.h"
+
+using namespace llvm;
+
+static cl::opt<std::string> AllowList(
+    "libafl_allowlist",
+    cl::desc("Only instrument the functions matching this list"),
+    cl::init(""), cl::NotHidden);
+static cl::opt<std::string> DenyList(
+    "libafl_denylist",
+    cl::desc("Do not instrument the functions matching this list"),
+    cl::init(""), cl::NotHidden);
+static cl::opt<bool> Debug("debug-instrument-list", cl::desc("Debug prints"),
+                           cl::init(false), cl::NotHidden);
+
+namespace {
+
+struct InstrumentList {
+  /* Older GlobPatterns refer to their pattern, keep them in place */
+  std::list<std::string>   globs;
+  std::vector<GlobPattern> functions;
+  std::vector<GlobPattern> files;
+
+  bool empty() const {
+    return functions.empty() && files.empty();
+  }
+
+  bool matches(const std::string &name, const std::string &demangled,
+               const std::string &file) const {
+    for (auto &pattern : functions) {
+      if (pattern.match(name) || pattern.match(demangled)) { return true; }
+    }
+    for (auto &pattern : files) {
+      if (pattern.match(file)) { return true; }
+    }
+    return false;
+  }
+};
+
+/* Reads an AFL++-style instrument list */
+InstrumentList readInstrumentList(const std::string &path) {
+  InstrumentList list;
+  if (path.empty()) { return list; }
+
+  std::ifstream file(path);
+  if (!file.is_open()) { FATAL("Could not open the list %s\n", path.c_str()); }
+
+  std::string line;
+  while (std::getline(file, line)) {
+    StringRef entry(line);
+    entry = entry.split('#').first.trim();
+    if (entry.empty()) { continue; }
+
+    bool is_function = false;
+    for (StringRef prefix : {"fun:", "function:"}) {
+      if (entry.consume_front(prefix)) { is_function = true; }
+    }
+    for (StringRef prefix : {"src:", "source:"}) {
+      entry.consume_front(prefix);
+    }
+    entry = entry.trim();
+    if (entry.empty()) { continue; }
+
+    /* Source files match the end of the path, like `*` + glob */
+    std::string &glob = list.globs.emplace_back(
+        is_function || entry.front() == '/' ? entry.str() : "*" + entry.str());
+    auto pattern = GlobPattern::create(glob);
+    if (!pattern) {
+      FATAL("Invalid pattern %s in %s\n", glob.c_str(), path.c_str());
+    }
+    if (is_function) {
+      list.functions.push_back(std::move(*pattern));
+    } else {
+      list.files.push_back(std::move(*pattern));
+    }
+  }
+  return list;
+}
+
+class InstrumentListPass : public PassInfoMixin<InstrumentListPass> {
+ public:
+  InstrumentListPass() {
+  }
+
+  PreservedAnalyses run(Module &M, ModuleAnalysisManager &MAM);
+};
+
+}  // namespace
+
+extern "C" ::llvm::PassPluginLibraryInfo LLVM_ATTRIBUTE_WEAK
+llvmGetPassPluginInfo() {
+  return {LLVM_PLUGIN_API_VERSION, "InstrumentList", "v0.1",
+          /* lambda to insert our pass into the pass pipeline. */
+          [](PassBuilder &PB) {
+            /* before any instrumentation */
+            PB.registerPipelineStartEPCallback(
+                [](ModulePassManager &MPM, OptimizationLevel OL) {
+                  MPM.addPass(InstrumentListPass());
+                });
+          }};
+}
+
+PreservedAnalyses InstrumentListPass::run(Module &M,
+                                          ModuleAnalysisManager &MAM) {
+  InstrumentList allow = readInstrumentList(AllowList);
+  InstrumentList deny = readInstrumentList(DenyList);
+  if (allow.empty() && deny.empty()) { return PreservedAnalyses::all(); }
+
+  unsigned skipped = 0;
+  for (auto &F : M) {
+    if (F.isDeclaration()) { continue; }
+
+    std::string name = F.getName().str();
+    std::string demangled = demangle(name);
+    std::string file = M.getSourceFileName();
+    if (DISubprogram *SP = F.getSubprogram()) {
+      file = SP->getFilename().str();
+      if (!SP->getDirectory().empty() && !file.empty() && file.front() != '/') {
+        file = SP->getDirectory().str() + "/" + file;
+      }
+    }
+
+    bool denied = deny.matches(name, demangled, file);
+    bool allowed = allow.empty() || allow.matches(name, demangled, file);
+    if (allowed && !denied) { continue; }
+
+    if (Debug) {
+      fprintf(stderr, "Not instrumenting %s (%s)\n", demangled.c_str(),
+              file.c_str());
+    }
+    F.addFnAttr(LIBAFL_NO_INSTRUMENT_ATTR);
+#if LLVM_VERSION_MAJOR >= 13
+    F.addFnAttr(Attribute::NoSanitizeCoverage);
+#endif
+    skipped++;
+  }
+
+  if (Debug) {
+    fprintf(stderr, "Skipped %u functions of %s.\n", skipped,
+            M.getSourceFileName().c_str());
+  }
+
+  if (skipped) { return PreservedAnalyses::none(); }
+  return PreservedAnalyses::all();
+}
@@ -0,0 +1,378 @@
+/*
+   LibAFL - laf-intel LLVM pass
+   --------------------------------------------------
+
+   Splits multi-byte integer comparisons and switches into chains of byte
+   comparisons, and optionally transforms strcmp, strncmp, memcmp and bcmp
+   calls with a constant operand into byte comparisons, so plain edge coverage
+   makes progress on each byte of a magic value.
+
+   Based on the laf-intel passes of AFL++, see
+   https://lafintel.wordpress.com/
+
+   Copyright 2025 AFLplusplus Project. All rights reserved.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at:
+
+     http://www.apache.org/licenses/LICENSE-2.0
+
+*/
+
+#include "common-llvm.h"
+
+#include <map>
+#include <set>
+#include <vector>
+
+#include "llvm/IR/Instructions.h"
+#include "llvm/Analysis/ValueTracking.h"
+
+using namespace llvm;
+
+static cl::opt<bool> Debug("debug-laf-intel", cl::desc("Debug prints"),
+                           cl::init(false), cl::NotHidden);
+static cl::opt<bool> SplitCompares(
+    "laf_split_compares",
+    cl::desc("Split the integer comparisons with a constant into bytes"),
+    cl::init(true), cl::NotHidden);
+static cl::opt<bool> SplitSwitches(
+    "laf_split_switches",
+    cl::desc("Split the switches into byte comparisons"), cl::init(true),
+    cl::NotHidden);
+static cl::opt<bool> TransformCompares(
+    "laf_transform_compares",
+    cl::desc("Transform strcmp, strncmp, memcmp and bcmp with a constant "
+             "operand into byte comparisons"),
+    cl::init(false), cl::NotHidden);
+
+namespace {
+
+class LafIntel : public PassInfoMixin<LafIntel> {
+ public:
+  LafIntel() {
+  }
+
+  PreservedAnalyses run(Module &M, ModuleAnalysisManager &MAM);
+
+ private:
+  unsigned splitSwitches(Function &F);
+  unsigned splitCompares(Function &F);
+  unsigned transformCompares(Function &F);
+};
+
+/* Only integers of more than one whole byte are worth splitting */
+bool isSplittable(Type *Ty) {
+  auto *IntTy = dyn_cast<IntegerType>(Ty);
+  return IntTy && IntTy->getBitWidth() > 8 && IntTy->getBitWidth() % 8 == 0;
+}
+
+/* Extracts the byte `idx` of `V`, 0 being the least significant one */
+Value *extractByte(IRBuilder<> &IRB, Value *V, unsigned idx) {
+  Value *Shifted =
+      idx ? IRB.CreateLShr(V, ConstantInt::get(V->getType(), idx * 8)) : V;
+  return IRB.CreateTrunc(Shifted, IRB.getInt8Ty());
+}
+
+/* The predicate without its equality, e.g. ugt for uge */
+CmpInst::Predicate strictPredicate(CmpInst::Predicate P) {
+  switch (P) {
+    case CmpInst::ICMP_UGE:
+      return CmpInst::ICMP_UGT;
+    case CmpInst::ICMP_ULE:
+      return CmpInst::ICMP_ULT;
+    case CmpInst::ICMP_SGE:
+      return CmpInst::ICMP_SGT;
+    case CmpInst::ICMP_SLE:
+      return CmpInst::ICMP_SLT;
+    default:
+      return P;
+  }
+}
+
+/* The unsigned counterpart of the predicate, for the bytes without the sign */
+CmpInst::Predicate unsignedPredicate(CmpInst::Predicate P) {
+  switch (P) {
+    case CmpInst::ICMP_SGT:
+      return CmpInst::ICMP_UGT;
+    case CmpInst::ICMP_SGE:
+      return CmpInst::ICMP_UGE;
+    case CmpInst::ICMP_SLT:
+      return CmpInst::ICMP_ULT;
+    case CmpInst::ICMP_SLE:
+      return CmpInst::ICMP_ULE;
+    default:
+      return P;
+  }
+}
+
+}  // namespace
+
+extern "C" ::llvm::PassPluginLibraryInfo LLVM_ATTRIBUTE_WEAK
+llvmGetPassPluginInfo() {
+  return {LLVM_PLUGIN_API_VERSION, "LafIntel", "v0.1",
+          /* lambda to insert our pass into the pass pipeline. */
+          [](PassBuilder &PB) {
+            PB.registerOptimizerLastEPCallback(
+                [](ModulePassManager &MPM, OptimizationLevel OL
+#if LLVM_VERSION_MAJOR >= 20
+                   ,
+                   ThinOrFullLTOPhase Phase
+#endif
+                ) { MPM.addPass(LafIntel()); });
+          }};
+}
+
+/* Replaces each switch by a chain of byte comparisons per case */
+unsigned LafIntel::splitSwitches(Function &F) {
+  LLVMContext              &C = F.getContext();
+  IntegerType              *Int8Ty = IntegerType::getInt8Ty(C);
+  std::vector<SwitchInst *> switches;
+
+  for (auto &BB : F) {
+    if (auto *SI = dyn_cast_or_null<SwitchInst>(BB.getTerminator())) {
+      if (SI->getNumCases() && isSplittable(SI->getCondition()->getType())) {
+        switches.push_back(SI);
+      }
+    }
+  }
+
+  for (SwitchInst *SI : switches) {
+    BasicBlock  *Orig = SI->getParent();
+    BasicBlock  *Default = SI->getDefaultDest();
+    Value       *Cond = SI->getCondition();
+    unsigned     bytes = Cond->getType()->getIntegerBitWidth() / 8;
+    IRBuilder<>  IRB(SI);
+    std::vector<Value *> condBytes;
+    for (unsigned i = 0; i < bytes; i++) {
+      condBytes.push_back(extractByte(IRB, Cond, i));
+    }
+
+    std::vector<std::pair<ConstantInt *, BasicBlock *>> cases;
+    for (auto Case : SI->cases()) {
+      cases.push_back({Case.getCaseValue(), Case.getCaseSuccessor()});
+    }
+    std::set<BasicBlock *> successors(succ_begin(Orig), succ_end(Orig));
+
+    /* The new blocks branching to each successor, to fix up their phis */
+    std::map<BasicBlock *, std::vector<BasicBlock *>> preds;
+
+    /* Build the chain backwards, the first byte compared is the most
+       significant one, a mismatch moves on to the next case */
+    BasicBlock *Next = Default;
+    for (auto it = cases.rbegin(); it != cases.rend(); ++it) {
+      const APInt &Val = it->first->getValue();
+      BasicBlock  *Match = it->second;
+      for (unsigned i = 0; i < bytes; i++) {
+        BasicBlock *B = BasicBlock::Create(C, "laf.switch", &F, Default);
+        IRBuilder<> BIRB(B);
+        uint64_t    byte = Val.lshr(i * 8).trunc(8).getZExtValue();
+        Value      *Eq =
+            BIRB.CreateICmpEQ(condBytes[i], ConstantInt::get(Int8Ty, byte));
+        BIRB.CreateCondBr(Eq, Match, Next);
+        preds[Match].push_back(B);
+        preds[Next].push_back(B);
+        Match = B;
+      }
+      Next = Match;
+    }
+
+    SI->eraseFromParent();
+    BranchInst::Create(Next, Orig);
+
+    for (BasicBlock *Succ : successors) {
+      for (PHINode &PN : Succ->phis()) {
+        Value *V = PN.getIncomingValueForBlock(Orig);
+        while (PN.getBasicBlockIndex(Orig) != -1) {
+          PN.removeIncomingValue(Orig, false);
+        }
+        for (BasicBlock *Pred : preds[Succ]) {
+          PN.addIncoming(V, Pred);
+        }
+      }
+    }
+  }
+
+  return switches.size();
+}
+
+/* Replaces each integer comparison with a constant by a chain of byte
+   comparisons, from the most significant byte */
+unsigned LafIntel::splitCompares(Function &F) {
+  LLVMContext            &C = F.getContext();
+  IntegerType            *Int1Ty = IntegerType::getInt1Ty(C);
+  std::vector<ICmpInst *> cmps;
+
+  for (auto &BB : F) {
+    for (auto &I : BB) {
+      auto *Cmp = dyn_cast<ICmpInst>(&I);
+      if (!Cmp || !isSplittable(Cmp->getOperand(0)->getType())) { continue; }
+      bool const0 = isa<Constant>(Cmp->getOperand(0));
+      bool const1 = isa<Constant>(Cmp->getOperand(1));
+      if (const0 == const1) { continue; }
+      cmps.push_back(Cmp);
+    }
+  }
+
+  for (ICmpInst *Cmp : cmps) {
+    CmpInst::Predicate P = Cmp->getPredicate();
+    Value             *A = Cmp->getOperand(0);
+    Value             *B = Cmp->getOperand(1);
+    unsigned           bytes = A->getType()->getIntegerBitWidth() / 8;
+    bool               isEquality = Cmp->isEquality();
+
+    BasicBlock *BB = Cmp->getParent();
+    BasicBlock *End = BB->splitBasicBlock(Cmp, "laf.cmp.end");
+    BB->getTerminator()->eraseFromParent();
+
+    IRBuilder<> EIRB(Cmp);
+    PHINode    *PN = EIRB.CreatePHI(Int1Ty, bytes);
+
+    BasicBlock *Cur = BB;
+    for (unsigned n = 0; n < bytes; n++) {
+      unsigned    i = bytes - 1 - n;
+      IRBuilder<> IRB(Cur);
+      Value      *a = extractByte(IRB, A, i);
+      Value      *b = extractByte(IRB, B, i);
+
+      /* Only the most significant byte holds the sign, the lower ones are
+         compared unsigned */
+      CmpInst::Predicate BP = n ? unsignedPredicate(P) : P;
+
+      if (!i) {
+        Value *Res = IRB.CreateICmp(BP, a, b);
+        IRB.CreateBr(End);
+        PN->addIncoming(Res, Cur);
+        break;
+      }
+
+      /* A differing byte decides the result */
+      Value *Res = isEquality ? (Value *)ConstantInt::get(
+                                    Int1Ty, P == CmpInst::ICMP_NE)
+                              : IRB.CreateICmp(strictPredicate(BP), a, b);
+      BasicBlock *Next = BasicBlock::Create(C, "laf.cmp", &F, End);
+      IRB.CreateCondBr(IRB.CreateICmpNE(a, b), End, Next);
+      PN->addIncoming(Res, Cur);
+      Cur = Next;
+    }
+
+    Cmp->replaceAllUsesWith(PN);
+    Cmp->eraseFromParent();
+  }
+
+  return cmps.size();
+}
+
+/* Replaces each call to strcmp, strncmp, memcmp and bcmp with a constant
+   operand by a chain of byte comparisons, returning the difference of the
+   first differing bytes */
+unsigned LafIntel::transformCompares(Function &F) {
+  LLVMContext            &C = F.getContext();
+  IntegerType            *Int8Ty = IntegerType::getInt8Ty(C);
+  std::vector<CallInst *> calls;
+
+  for (auto &BB : F) {
+    for (auto &I : BB) {
+      auto *Call = dyn_cast<CallInst>(&I);
+      if (!Call || Call->isMustTailCall()) { continue; }
+      Function *Callee = Call->getCalledFunction();
+      if (!Callee || !Call->getType()->isIntegerTy()) { continue; }
+      StringRef Name = Callee->getName();
+      if (Name == "strcmp" && Call->arg_size() == 2) {
+        calls.push_back(Call);
+      } else if ((Name == "strncmp" || Name == "memcmp" || Name == "bcmp") &&
+                 Call->arg_size() == 3 &&
+                 isa<ConstantInt>(Call->getArgOperand(2))) {
+        calls.push_back(Call);
+      }
+    }
+  }
+
+  unsigned transformed = 0;
+  for (CallInst *Call : calls) {
+    StringRef Name = Call->getCalledFunction()->getName();
+    StringRef Str0, Str1;
+    bool      const0 = getConstantStringInfo(Call->getArgOperand(0), Str0);
+    bool      const1 = getConstantStringInfo(Call->getArgOperand(1), Str1);
+    if (const0 == const1) { continue; }
+
+    Value    *Var = Call->getArgOperand(const0 ? 1 : 0);
+    StringRef Str = const0 ? Str0 : Str1;
+
+    /* The number of bytes to compare, including the terminating NUL of
+       strings */
+    uint64_t len = Str.size() + 1;
+    if (Name != "strcmp") {
+      uint64_t n = cast<ConstantInt>(Call->getArgOperand(2))->getZExtValue();
+      if (Name == "strncmp") {
+        len = std::min(len, n);
+      } else if (n > Str.size()) {
+        /* memcmp and bcmp compare past NULs, all bytes need to be known */
+        continue;
+      } else {
+        len = n;
+      }
+    }
+    if (!len) { continue; }
+
+    BasicBlock *BB = Call->getParent();
+    BasicBlock *End = BB->splitBasicBlock(Call, "laf.strcmp.end");
+    BB->getTerminator()->eraseFromParent();
+
+    IRBuilder<> EIRB(Call);
+    PHINode    *PN = EIRB.CreatePHI(Call->getType(), len);
+
+    BasicBlock *Cur = BB;
+    for (uint64_t i = 0; i < len; i++) {
+      IRBuilder<> IRB(Cur);
+      Value      *Ptr = IRB.CreateConstInBoundsGEP1_64(Int8Ty, Var, i);
+      Value      *v = IRB.CreateLoad(Int8Ty, Ptr);
+      Value      *c = ConstantInt::get(Int8Ty, i < Str.size() ? (uint8_t)Str[i] : 0);
+
+      /* The result is the difference of the first differing bytes, 0 if all
+         are equal */
+      Value *vExt = IRB.CreateZExt(v, Call->getType());
+      Value *cExt = IRB.CreateZExt(c, Call->getType());
+      Value *Res = const0 ? IRB.CreateSub(cExt, vExt) : IRB.CreateSub(vExt, cExt);
+      PN->addIncoming(Res, Cur);
+
+      if (i + 1 == len) {
+        IRB.CreateBr(End);
+        break;
+      }
+      BasicBlock *Next = BasicBlock::Create(C, "laf.strcmp", &F, End);
+      IRB.CreateCondBr(IRB.CreateICmpNE(v, c), End, Next);
+      Cur = Next;
+    }
+
+    Call->replaceAllUsesWith(PN);
+    Call->eraseFromParent();
+    transformed++;
+  }
+
+  return transformed;
+}
+
+PreservedAnalyses LafIntel::run(Module &M, ModuleAnalysisManager &MAM) {
+  unsigned transformed = 0, switches = 0, cmps = 0;
+
+  for (auto &F : M) {
+    if (F.isDeclaration() || isIgnoreFunction(&F)) { continue; }
+
+    /* The byte comparisons created by the first steps are not split again */
+    if (TransformCompares) { transformed += transformCompares(F); }
+    if (SplitSwitches) { switches += splitSwitches(F); }
+    if (SplitCompares) { cmps += splitCompares(F); }
+  }
+
+  if (Debug) {
+    fprintf(stderr,
+            "Transformed %u string comparisons, split %u switches and %u "
+            "comparisons.\n",
+            transformed, switches, cmps);
+  }
+
+  if (transformed || switches || cmps) { return PreservedAnalyses::none(); }
+  return PreservedAnalyses::all();
+}
@@ -80,6 +80,9 @@ pub enum Configuration {
     GenerateCoverageProfile,
     /// Instrumenting for cmplog/redqueen
     CmpLog,
+    /// Tracking the input offsets of the comparisons with `DataFlowSanitizer`, for the
+    /// `DfsanTracingStage` of `libafl_targets`
+    DataFlowSanitizer,
     /// A compound `Configuration`, made up of a list of other `Configuration`s
     Compound(Vec<Self>),
 }
@@ -105,6 +108,10 @@ impl Configuration {
                 vec!["-fsanitize-coverage=trace-pc-guard".to_string()]
             }
             Configuration::CmpLog => vec!["-fsanitize-coverage=trace-cmp".to_string()],
+            Configuration::DataFlowSanitizer => vec![
+                "-fsanitize=dataflow".to_string(),
+                "-fsanitize-coverage=trace-cmp".to_string(),
+            ],
             Configuration::GenerateCoverageProfile => {
                 vec![
                     "-fprofile-instr-generate".to_string(),
@@ -156,6 +163,7 @@ impl str::FromStr for Configuration {
             "coverage" => Configuration::GenerateCoverageMap,
             "llvm-cov" => Configuration::GenerateCoverageProfile,
             "cmplog" => Configuration::CmpLog,
+            "dfsan" => Configuration::DataFlowSanitizer,
             _ => Configuration::Default,
         })
     }
@@ -170,6 +178,7 @@ impl core::fmt::Display for Configuration {
             Configuration::GenerateCoverageMap => write!(f, "coverage"),
             Configuration::GenerateCoverageProfile => write!(f, "llvm-cov"),
             Configuration::CmpLog => write!(f, "cmplog"),
+            Configuration::DataFlowSanitizer => write!(f, "dfsan"),
             Configuration::Compound(configurations) => {
                 let mut result: Vec<String> = vec![];
                 for configuration in configurations {
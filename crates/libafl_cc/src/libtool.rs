@@ -21,6 +21,7 @@ pub struct LibtoolWrapper {
     configurations: Vec<crate::Configuration>,
     parse_args_called: bool,
     base_args: Vec<String>,
+    lto: bool,
 }
 
 #[expect(clippy::match_same_arms)] // for the linking = false wip for "shared"
@@ -89,6 +90,11 @@ impl ToolWrapper for LibtoolWrapper {
                     i += 1;
                     continue;
                 }
+                "--libafl-lto" => {
+                    self.lto = true;
+                    i += 1;
+                    continue;
+                }
                 "--libafl-configurations" if i + 1 < args.len() => {
                     self.configurations.extend(
                         args[i + 1]
@@ -223,6 +229,12 @@ impl ToolWrapper for LibtoolWrapper {
             args.extend_from_slice(&configuration.to_flags()?);
         }
 
+        // libtool drops the unknown flags when linking, unless passed with -Xcompiler
+        if self.lto {
+            args.push("-Xcompiler".to_string());
+            args.push("--libafl-lto".to_string());
+        }
+
         if self.need_libafl_arg && !self.has_libafl_arg {
             return Ok(args);
         }
@@ -266,10 +278,21 @@ impl LibtoolWrapper {
             configurations: vec![crate::Configuration::Default],
             parse_args_called: false,
             base_args: vec![],
+            lto: false,
             is_silent: false,
         }
     }
 
+    /// Compile and link in the LTO mode of the [`crate::ClangWrapper`], call this before calling
+    /// `parse_args`.
+    ///
+    /// Passes `--libafl-lto` on to the compiler wrapper that libtool calls, for the compiled
+    /// objects and the final link. The `--libafl-lto` argument sets it too.
+    pub fn lto(&mut self, value: bool) -> &'_ mut Self {
+        self.lto = value;
+        self
+    }
+
     /// Set if linking
     pub fn linking(&mut self, value: bool) -> &'_ mut Self {
         self.linking = value;
@@ -0,0 +1,167 @@
+/*
+   LibAFL - LTO edges LLVM pass
+   --------------------------------------------------
+
+   Collision-free edge coverage, like afl-clang-lto. The pass runs at link
+   time, over the whole program, and gives each edge its own id in the edges
+   map: the ids are dense, from 0, and the exact number of edges is exported
+   as __libafl_lto_map_size for the runtime of libafl_targets.
+
+   Edges into blocks with several predecessors get a block of their own, the
+   other edges are instrumented in their destination block. The edges always
+   taken after their source block are covered by that block.
+
+   Copyright 2025 AFLplusplus Project. All rights reserved.
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at:
+
+     http://www.apache.org/licenses/LICENSE-2.0
+
+*/
+
+#include "common-llvm.h"
+
+#include <set>
+#include <vector>
+
+#include "llvm/IR/Instructions.h"
+#include "llvm/Transforms/Utils/BasicBlockUtils.h"
+
+using namespace llvm;
+
+static cl::opt<bool> Debug("debug-lto-edges", cl::desc("Debug prints"),
+                           cl::init(false), cl::NotHidden);
+
+namespace {
+
+class LtoEdges : public PassInfoMixin<LtoEdges> {
+ public:
+  LtoEdges() {
+  }
+
+  PreservedAnalyses run(Module &M, ModuleAnalysisManager &MAM);
+
+ private:
+  /* Whether the edges into BB can be split into blocks of their own */
+  bool canSplitEdgesInto(BasicBlock *BB) {
+    if (BB->isEHPad() || BB->hasAddressTaken()) { return false; }
+    for (BasicBlock *Pred : predecessors(BB)) {
+      Instruction *TI = Pred->getTerminator();
+      if (isa<IndirectBrInst>(TI) || isa<CallBrInst>(TI)) { return false; }
+    }
+    return true;
+  }
+
+  /* Splits the edges from Pred to BB, the same edge for all the successors */
+  BasicBlock *splitEdge(BasicBlock *Pred, BasicBlock *BB) {
+    Instruction *TI = Pred->getTerminator();
+    for (unsigned i = 0; i < TI->getNumSuccessors(); i++) {
+      if (TI->getSuccessor(i) != BB) { continue; }
+      if (BasicBlock *Split = SplitCriticalEdge(
+              TI, i, CriticalEdgeSplittingOptions().setMergeIdenticalEdges())) {
+        return Split;
+      }
+      break;
+    }
+    return SplitEdge(Pred, BB);
+  }
+};
+
+}  // namespace
+
+extern "C" ::llvm::PassPluginLibraryInfo LLVM_ATTRIBUTE_WEAK
+llvmGetPassPluginInfo() {
+  return {LLVM_PLUGIN_API_VERSION, "LtoEdges", "v0.1",
+          /* lambda to insert our pass into the pass pipeline. */
+          [](PassBuilder &PB) {
+#if LLVM_VERSION_MAJOR >= 15
+            /* once, over the whole program */
+            PB.registerFullLinkTimeOptimizationLastEPCallback(
+                [](ModulePassManager &MPM, OptimizationLevel OL) {
+                  MPM.addPass(LtoEdges());
+                });
+#else
+            /* no pass plugins at link time, only over a merged module */
+            PB.registerOptimizerLastEPCallback(
+                [](ModulePassManager &MPM, OptimizationLevel OL) {
+                  MPM.addPass(LtoEdges());
+                });
+#endif
+          }};
+}
+
+PreservedAnalyses LtoEdges::run(Module &M, ModuleAnalysisManager &MAM) {
+  LLVMContext &C = M.getContext();
+  IntegerType *Int8Ty = IntegerType::getInt8Ty(C);
+  IntegerType *Int32Ty = IntegerType::getInt32Ty(C);
+  IntegerType *IntPtrTy = M.getDataLayout().getIntPtrType(C);
+  PointerType *MapPtrTy = PointerType::get(Int8Ty, 0);
+  ConstantInt *One = ConstantInt::get(Int8Ty, 1);
+  uint32_t     edges = 0;
+
+  Constant *AFLMapPtr = M.getOrInsertGlobal("__afl_area_ptr", MapPtrTy);
+
+  auto instrument = [&](BasicBlock *BB) {
+    BasicBlock::iterator IP = BB->getFirstInsertionPt();
+    if (IP == BB->end()) { return; }
+    IRBuilder<> IRB(&(*IP));
+
+    LoadInst *MapPtr = IRB.CreateLoad(MapPtrTy, AFLMapPtr);
+    MapPtr->setMetadata(M.getMDKindID("nosanitize"), MDNode::get(C, None));
+    Value *MapPtrIdx =
+        IRB.CreateGEP(Int8Ty, MapPtr, ConstantInt::get(Int32Ty, edges++));
+    LoadInst *Counter = IRB.CreateLoad(Int8Ty, MapPtrIdx);
+    Counter->setMetadata(M.getMDKindID("nosanitize"), MDNode::get(C, None));
+    IRB.CreateStore(IRB.CreateAdd(Counter, One), MapPtrIdx)
+        ->setMetadata(M.getMDKindID("nosanitize"), MDNode::get(C, None));
+  };
+
+  for (auto &F : M) {
+    if (F.isDeclaration() || isIgnoreFunction(&F)) { continue; }
+
+    std::vector<BasicBlock *> blocks;
+    for (auto &BB : F) {
+      blocks.push_back(&BB);
+    }
+
+    for (BasicBlock *BB : blocks) {
+      std::vector<BasicBlock *> preds;
+      std::set<BasicBlock *>    seen;
+      for (BasicBlock *Pred : predecessors(BB)) {
+        if (seen.insert(Pred).second) { preds.push_back(Pred); }
+      }
+
+      /* the entry, and the blocks with a single way in */
+      if (preds.size() <= 1 || !canSplitEdgesInto(BB)) {
+        instrument(BB);
+        continue;
+      }
+
+      for (BasicBlock *Pred : preds) {
+        /* always taken after Pred, already covered by it */
+        if (Pred->getSingleSuccessor() == BB) { continue; }
+        instrument(splitEdge(Pred, BB));
+      }
+    }
+  }
+
+  /* The exact map size, for the runtime */
+  GlobalVariable *MapSize = M.getGlobalVariable("__libafl_lto_map_size");
+  if (!MapSize) {
+    MapSize = new GlobalVariable(M, IntPtrTy, true,
+                                 GlobalValue::ExternalLinkage, nullptr,
+                                 "__libafl_lto_map_size");
+  }
+  MapSize->setInitializer(ConstantInt::get(IntPtrTy, edges));
+  MapSize->setLinkage(GlobalValue::ExternalLinkage);
+  MapSize->setVisibility(GlobalValue::DefaultVisibility);
+
+  if (Debug) {
+    fprintf(stderr, "Instrumented %u edges of %s.\n", edges,
+            M.getSourceFileName().c_str());
+  }
+
+  return PreservedAnalyses::none();
+}
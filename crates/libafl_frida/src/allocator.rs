@@ -582,6 +582,37 @@ impl Allocator {
 
         true
     }
+
+    /// The distance in bytes between the given access and the nearest poisoned byte, i.e. a
+    /// redzone or a freed chunk, looking at most `max` bytes before and after the access.
+    ///
+    /// Returns `None` if there is no poisoned byte in reach, or the access is not one of ours.
+    #[must_use]
+    pub fn poison_distance(
+        &self,
+        address: *const c_void,
+        size: usize,
+        max: usize,
+    ) -> Option<usize> {
+        let start = address as usize;
+        let end = start + size;
+        if size == 0 || !self.is_managed(address.cast_mut()) {
+            return None;
+        }
+
+        // each shadow byte holds one bit per byte, the lowest address in the highest bit
+        let is_poisoned = |address: usize| {
+            self.is_managed(address as *mut c_void)
+                && unsafe { (map_to_shadow!(self, address) as *const u8).read() }
+                    & (0x80 >> (address & 7))
+                    == 0
+        };
+
+        (0..=max).find(|&distance| {
+            (distance < start && is_poisoned(start - distance - 1)) || is_poisoned(end + distance)
+        })
+    }
+
     /// Maps the address to a shadow address
     #[inline]
     #[must_use]
@@ -959,3 +990,25 @@ fn check_shadow() {
     let allocation = unsafe { allocator.alloc(0x3c, 0) };
     assert!(allocator.check_shadow(unsafe { allocation.add(0x3a) }, 2));
 }
+
+#[test]
+fn poison_distance() {
+    use frida_gum::Gum;
+    let _gum = Gum::obtain();
+    let mut allocator = Allocator::default();
+    allocator.init();
+
+    let allocation = unsafe { allocator.alloc(8, 8) };
+    assert!(!allocation.is_null());
+    // touches the redzones
+    assert_eq!(allocator.poison_distance(allocation, 8, 64), Some(0));
+    assert_eq!(
+        allocator.poison_distance(unsafe { allocation.add(4) }, 1, 64),
+        Some(3)
+    );
+    assert_eq!(
+        allocator.poison_distance(unsafe { allocation.add(4) }, 1, 2),
+        None
+    );
+    assert_eq!(allocator.poison_distance(allocation, 0, 64), None);
+}
@@ -6,7 +6,7 @@ even if the target would not have crashed under normal conditions.
 this helps finding mem errors early.
 */
 
-use alloc::rc::Rc;
+use alloc::{borrow::Cow, rc::Rc};
 use core::{
     cell::Cell,
     ffi::{c_char, c_void},
@@ -17,6 +17,8 @@ use std::sync::{Mutex, MutexGuard};
 
 use backtrace::Backtrace;
 use dynasmrt::{DynasmApi, DynasmLabelApi, dynasm};
+#[cfg(target_arch = "x86_64")]
+use frida_gum::CpuContext;
 #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
 use frida_gum::instruction_writer::X86Register;
 #[cfg(target_arch = "aarch64")]
@@ -27,7 +29,8 @@ use frida_gum::{
 };
 use frida_gum_sys::Insn;
 use hashbrown::HashMap;
-use libafl_bolts::{cli::FuzzerOptions, get_thread_id, has_tls};
+use libafl::{feedbacks::MaxMapFeedback, observers::StdMapObserver};
+use libafl_bolts::{cli::FuzzerOptions, get_thread_id, has_tls, hash_std};
 use libc::wchar_t;
 use rangemap::RangeMap;
 #[cfg(target_arch = "aarch64")]
@@ -41,6 +44,8 @@ use yaxpeax_x86::protected_mode::{DisplayStyle, InstDecoder, Instruction, Opcode
 
 #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
 use crate::utils::frida_to_cs;
+#[cfg(target_arch = "x86_64")]
+use crate::utils::get_register;
 #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
 use crate::utils::{AccessType, operand_details};
 #[cfg(target_arch = "aarch64")]
@@ -56,6 +61,19 @@ unsafe extern "C" {
     fn __register_frame(begin: *mut c_void);
 }
 
+/// The size of the shadow proximity map, see [`AsanRuntime::enable_proximity`]
+pub const ASAN_PROXIMITY_MAP_SIZE: usize = 64 * 1024;
+
+/// How far from an access the shadow proximity looks for poisoned bytes, in bytes
+pub const ASAN_PROXIMITY_MAX_DISTANCE: usize = 64;
+
+/// The observer of the shadow proximity map, see [`AsanRuntime::proximity_observer`]
+pub type AsanProximityObserver = StdMapObserver<'static, u8, false>;
+
+/// Keeps inputs that get closer to poisoned memory at some access than any input before, see
+/// [`AsanRuntime::enable_proximity`]
+pub type AsanProximityFeedback = MaxMapFeedback<AsanProximityObserver, AsanProximityObserver>;
+
 #[cfg(not(target_vendor = "apple"))]
 unsafe extern "C" {
     fn tls_ptr() -> *const c_void;
@@ -311,6 +329,7 @@ pub struct AsanRuntime {
     suppressed_addresses: Vec<usize>,
     skip_ranges: Vec<SkipRange>,
     continue_on_error: bool,
+    proximity_map: Option<Box<[u8]>>,
     pc: Option<usize>,
     hooks: Vec<NativePointer>,
     pub(crate) hooks_enabled: bool,
@@ -439,6 +458,104 @@ impl AsanRuntime {
         ASAN_ERRORS.lock().unwrap()
     }
 
+    /// Records, for each instrumented load or store and each call site of a hooked libc function
+    /// (e.g. `memcpy`, `read` or `strcpy`), how close the accessed memory gets to poisoned memory,
+    /// i.e. redzones and freed chunks.
+    ///
+    /// The loads and stores are only covered on `x86_64`, where the instrumentation calls out to
+    /// the runtime after each inline check, so enabling it slows down the target noticeably.
+    /// Enable it before the first run, the blocks instrumented before are not covered.
+    /// The map holds `ASAN_PROXIMITY_MAX_DISTANCE + 1 - distance` per site, so higher is closer.
+    /// Observe it with [`Self::proximity_observer`] and use an [`AsanProximityFeedback`] on it,
+    /// to keep inputs that come closer to a memory error, even if they do not find new edges.
+    pub fn enable_proximity(&mut self) {
+        if self.proximity_map.is_none() {
+            self.proximity_map = Some(vec![0; ASAN_PROXIMITY_MAP_SIZE].into_boxed_slice());
+        }
+    }
+
+    /// Enables the shadow proximity, see [`Self::enable_proximity`], and returns an observer
+    /// of its map.
+    ///
+    /// # Safety
+    /// The observer points to the map of this runtime, it must not be used after the runtime is
+    /// dropped.
+    pub unsafe fn proximity_observer<S>(&mut self, name: S) -> AsanProximityObserver
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        self.enable_proximity();
+        let map = self.proximity_map.as_mut().unwrap();
+        unsafe { StdMapObserver::from_mut_ptr(name, map.as_mut_ptr(), map.len()) }
+    }
+
+    /// The shadow proximity map, of size [`ASAN_PROXIMITY_MAP_SIZE`], if enabled.
+    /// Must not be used after the runtime is dropped.
+    pub fn proximity_map_mut_ptr(&mut self) -> Option<*mut u8> {
+        self.proximity_map.as_mut().map(|map| map.as_mut_ptr())
+    }
+
+    /// Checks the shadow for the given access from the current hook, recording its proximity to
+    /// poisoned memory if enabled.
+    pub fn check_shadow(&mut self, address: *const c_void, size: usize) -> bool {
+        if !self
+            .allocator
+            .get_mut()
+            .unwrap()
+            .check_shadow(address, size)
+        {
+            return false;
+        }
+        self.record_proximity(address, size, self.pc());
+        true
+    }
+
+    /// If the shadow proximity is enabled, see [`Self::enable_proximity`]
+    #[must_use]
+    pub fn proximity_enabled(&self) -> bool {
+        self.proximity_map.is_some()
+    }
+
+    /// Records the proximity of the access at `address` from `pc` to poisoned memory, if enabled
+    fn record_proximity(&mut self, address: *const c_void, size: usize, pc: usize) {
+        let allocator = self.allocator.get_mut().unwrap();
+        if let Some(map) = self.proximity_map.as_mut()
+            && let Some(distance) =
+                allocator.poison_distance(address, size, ASAN_PROXIMITY_MAX_DISTANCE)
+        {
+            let index = (hash_std(&pc.to_le_bytes()) as usize) % ASAN_PROXIMITY_MAP_SIZE;
+            let closeness = (ASAN_PROXIMITY_MAX_DISTANCE + 1 - distance) as u8;
+            if map[index] < closeness {
+                map[index] = closeness;
+            }
+        }
+    }
+
+    /// Records the proximity of an instrumented load or store, from a callout after its inline
+    /// check, with the operand details of [`Self::asan_is_interesting_instruction`].
+    #[cfg(target_arch = "x86_64")]
+    pub fn record_access_proximity(
+        &mut self,
+        context: &CpuContext,
+        address: u64,
+        instruction_size: usize,
+        (width, basereg, indexreg, scale, disp): (u8, X86Register, X86Register, u8, i32),
+    ) {
+        let value = |reg| match reg {
+            X86Register::None => 0,
+            X86Register::Rip => address + instruction_size as u64,
+            _ => get_register(context, reg),
+        };
+        let accessed = value(basereg)
+            .wrapping_add(value(indexreg).wrapping_mul(u64::from(scale.max(1))))
+            .wrapping_add_signed(i64::from(disp));
+        self.record_proximity(
+            accessed as *const c_void,
+            usize::from(width),
+            address as usize,
+        );
+    }
+
     /// Make sure the specified memory is unpoisoned
     pub fn unpoison(&mut self, address: usize, size: usize) {
         self.allocator_mut()
@@ -658,8 +775,8 @@ impl AsanRuntime {
                         let _last_error_guard = LastErrorGuard::new();
                         let mut invocation = Interceptor::current_invocation();
                         let this = &mut *(invocation.replacement_data().unwrap().0 as *mut AsanRuntime);
-                        //is this necessary? The stalked return address will always be the real return address
-                     //   let real_address = this.real_address_for_stalked(invocation.return_addr());
+                        // the call site, for the reports and the shadow proximity
+                        this.set_pc(this.real_address_for_stalked(invocation.return_addr()));
                         let original = [<$name:snake:upper _PTR>].get().unwrap();
                         if this.hooks_enabled {
                             if has_tls() {
@@ -708,8 +825,8 @@ impl AsanRuntime {
                         let _last_error_guard = LastErrorGuard::new();
                         let mut invocation = Interceptor::current_invocation();
                         let this = &mut *(invocation.replacement_data().unwrap().0 as *mut AsanRuntime);
-                        //is this necessary? The stalked return address will always be the real return address
-                     //   let real_address = this.real_address_for_stalked(invocation.return_addr());
+                        // the call site, for the reports and the shadow proximity
+                        this.set_pc(this.real_address_for_stalked(invocation.return_addr()));
                         let original = [<$lib_ident:snake:upper _ $name:snake:upper _PTR>].get().unwrap();
                         if this.hooks_enabled {
                             if has_tls() {
@@ -754,6 +871,8 @@ impl AsanRuntime {
                         let _last_error_guard = LastErrorGuard::new();
                         let mut invocation = Interceptor::current_invocation();
                         let this = &mut *(invocation.replacement_data().unwrap().0 as *mut AsanRuntime);
+                        // the call site, for the reports and the shadow proximity
+                        this.set_pc(this.real_address_for_stalked(invocation.return_addr()));
                         let original = [<$name:snake:upper _PTR>].get().unwrap();
                         if $always_enabled || this.hooks_enabled {
                             if has_tls() {
@@ -813,6 +932,8 @@ impl AsanRuntime {
                         let _last_error_guard = LastErrorGuard::new();
                         let mut invocation = Interceptor::current_invocation();
                         let this = unsafe { &mut *(invocation.replacement_data().unwrap().0 as *mut AsanRuntime) };
+                        // the call site, for the reports and the shadow proximity
+                        this.set_pc(this.real_address_for_stalked(invocation.return_addr()));
                         let original = [<$lib_ident:snake:upper _ $name:snake:upper _PTR>].get().unwrap();
                         if $always_enabled || this.hooks_enabled {
                             if has_tls() {
@@ -3446,6 +3567,7 @@ impl Default for AsanRuntime {
             suppressed_addresses: Vec::new(),
             skip_ranges: Vec::new(),
             continue_on_error: false,
+            proximity_map: None,
             #[cfg(target_arch = "aarch64")]
             eh_frame: [0; ASAN_EH_FRAME_DWORD_COUNT],
             pc: None,
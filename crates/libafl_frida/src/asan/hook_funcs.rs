@@ -1672,7 +1672,7 @@ impl AsanRuntime {
         count: usize,
     ) -> usize {
         log::trace!("hook_write");
-        if !self.allocator_mut().check_shadow(buf, count)
+        if !self.check_shadow(buf, count)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "write".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1707,7 +1707,7 @@ impl AsanRuntime {
         count: usize,
     ) -> usize {
         log::trace!("hook_read");
-        if !self.allocator_mut().check_shadow(buf, count)
+        if !self.check_shadow(buf, count)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "read".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1730,7 +1730,7 @@ impl AsanRuntime {
         stream: *mut c_void,
     ) -> *mut c_void {
         log::trace!("hook_fgets");
-        if !self.allocator_mut().check_shadow(s, size as usize)
+        if !self.check_shadow(s, size as usize)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "fgets".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1753,7 +1753,7 @@ impl AsanRuntime {
         n: usize,
     ) -> i32 {
         log::trace!("hook_memcmp");
-        if !self.allocator_mut().check_shadow(s1, n)
+        if !self.check_shadow(s1, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memcmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1764,7 +1764,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(s2, n)
+        if !self.check_shadow(s2, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memcmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1787,7 +1787,7 @@ impl AsanRuntime {
         n: usize,
     ) -> *mut c_void {
         log::trace!("hook_memcpy dest {dest:#?} src {src:#?} size {n}");
-        if !self.allocator_mut().check_shadow(dest, n)
+        if !self.check_shadow(dest, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1798,7 +1798,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(src, n)
+        if !self.check_shadow(src, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1822,7 +1822,7 @@ impl AsanRuntime {
         n: usize,
     ) -> *mut c_void {
         log::trace!("hook_mempcpy");
-        if !self.allocator_mut().check_shadow(dest, n)
+        if !self.check_shadow(dest, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "mempcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1833,7 +1833,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(src, n)
+        if !self.check_shadow(src, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "mempcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1856,7 +1856,7 @@ impl AsanRuntime {
         n: usize,
     ) -> *mut c_void {
         log::trace!("hook_memmove");
-        if !self.allocator_mut().check_shadow(dest, n)
+        if !self.check_shadow(dest, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memmove".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1867,7 +1867,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(src, n)
+        if !self.check_shadow(src, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memmove".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1891,7 +1891,7 @@ impl AsanRuntime {
         n: usize,
     ) -> *mut c_void {
         log::trace!("hook_memset");
-        if !self.allocator_mut().check_shadow(dest, n)
+        if !self.check_shadow(dest, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1914,7 +1914,7 @@ impl AsanRuntime {
         n: usize,
     ) -> *mut c_void {
         log::trace!("hook_memchr");
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memchr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1938,7 +1938,7 @@ impl AsanRuntime {
         n: usize,
     ) -> *mut c_void {
         log::trace!("hook_memrchr");
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memrchr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1967,7 +1967,7 @@ impl AsanRuntime {
         needlelen: usize,
     ) -> *mut c_void {
         log::trace!("hook_memmem");
-        if !self.allocator_mut().check_shadow(haystack, haystacklen)
+        if !self.check_shadow(haystack, haystacklen)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memmem".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -1978,7 +1978,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(needle, needlelen)
+        if !self.check_shadow(needle, needlelen)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "memmem".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2001,7 +2001,7 @@ impl AsanRuntime {
         n: usize,
     ) -> usize {
         log::trace!("hook_bzero");
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "bzero".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2024,7 +2024,7 @@ impl AsanRuntime {
         n: usize,
     ) -> usize {
         log::trace!("hook_explicit_bzero");
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "explicit_bzero".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2048,7 +2048,7 @@ impl AsanRuntime {
         n: usize,
     ) -> i32 {
         log::trace!("hook_bcmp");
-        if !self.allocator_mut().check_shadow(s1, n)
+        if !self.check_shadow(s1, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "bcmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2059,7 +2059,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(s2, n)
+        if !self.check_shadow(s2, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "bcmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2085,9 +2085,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strchr");
-        if !self
-            .allocator_mut()
-            .check_shadow(s as *const c_void, unsafe { strlen(s) })
+        if !self.check_shadow(s as *const c_void, unsafe { strlen(s) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strchr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2112,9 +2110,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strrchr");
-        if !self
-            .allocator_mut()
-            .check_shadow(s as *const c_void, unsafe { strlen(s) })
+        if !self.check_shadow(s as *const c_void, unsafe { strlen(s) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strrchr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2139,9 +2135,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strcasecmp");
-        if !self
-            .allocator_mut()
-            .check_shadow(s1 as *const c_void, unsafe { strlen(s1) })
+        if !self.check_shadow(s1 as *const c_void, unsafe { strlen(s1) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcasecmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2152,9 +2146,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(s2 as *const c_void, unsafe { strlen(s2) })
+        if !self.check_shadow(s2 as *const c_void, unsafe { strlen(s2) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcasecmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2177,7 +2169,7 @@ impl AsanRuntime {
         n: usize,
     ) -> i32 {
         log::trace!("hook_strncasecmp");
-        if !self.allocator_mut().check_shadow(s1 as *const c_void, n)
+        if !self.check_shadow(s1 as *const c_void, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strncasecmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2188,7 +2180,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(s2 as *const c_void, n)
+        if !self.check_shadow(s2 as *const c_void, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strncasecmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2213,9 +2205,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strcat");
-        if !self
-            .allocator_mut()
-            .check_shadow(s1 as *const c_void, unsafe { strlen(s1) })
+        if !self.check_shadow(s1 as *const c_void, unsafe { strlen(s1) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcat".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2226,9 +2216,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(s2 as *const c_void, unsafe { strlen(s2) })
+        if !self.check_shadow(s2 as *const c_void, unsafe { strlen(s2) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcat".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2253,9 +2241,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strcmp");
-        if !self
-            .allocator_mut()
-            .check_shadow(s1 as *const c_void, unsafe { strlen(s1) })
+        if !self.check_shadow(s1 as *const c_void, unsafe { strlen(s1) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2266,9 +2252,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(s2 as *const c_void, unsafe { strlen(s2) })
+        if !self.check_shadow(s2 as *const c_void, unsafe { strlen(s2) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2294,9 +2278,7 @@ impl AsanRuntime {
             fn strnlen(s: *const c_char, n: usize) -> usize;
         }
         log::trace!("hook_strncmp");
-        if !self
-            .allocator_mut()
-            .check_shadow(s1 as *const c_void, unsafe { strnlen(s1, n) })
+        if !self.check_shadow(s1 as *const c_void, unsafe { strnlen(s1, n) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strncmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2307,9 +2289,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(s2 as *const c_void, unsafe { strnlen(s2, n) })
+        if !self.check_shadow(s2 as *const c_void, unsafe { strnlen(s2, n) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strncmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2334,9 +2314,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strcpy");
-        if !self
-            .allocator_mut()
-            .check_shadow(dest as *const c_void, unsafe { strlen(src) })
+        if !self.check_shadow(dest as *const c_void, unsafe { strlen(src) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "strcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2347,9 +2325,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(src as *const c_void, unsafe { strlen(src) })
+        if !self.check_shadow(src as *const c_void, unsafe { strlen(src) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2375,7 +2351,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strncpy");
-        if !self.allocator_mut().check_shadow(dest as *const c_void, n)
+        if !self.check_shadow(dest as *const c_void, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "strncpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2387,7 +2363,7 @@ impl AsanRuntime {
             panic!("ASAN: Crashing target!");
         }
         let mn = core::cmp::min(n, unsafe { strlen(src) } + 1);
-        if !self.allocator_mut().check_shadow(src as *const c_void, mn)
+        if !self.check_shadow(src as *const c_void, mn)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strncpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2412,9 +2388,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_stpcpy");
-        if !self
-            .allocator_mut()
-            .check_shadow(dest as *const c_void, unsafe { strlen(src) })
+        if !self.check_shadow(dest as *const c_void, unsafe { strlen(src) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "stpcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2425,9 +2399,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(src as *const c_void, unsafe { strlen(src) })
+        if !self.check_shadow(src as *const c_void, unsafe { strlen(src) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "stpcpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2463,7 +2435,7 @@ impl AsanRuntime {
         }
         log::trace!("hook_strdup");
         let size = unsafe { strlen(s) + 1 };
-        if !self.allocator_mut().check_shadow(s as *const c_void, size)
+        if !self.check_shadow(s as *const c_void, size)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strdup".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2490,7 +2462,7 @@ impl AsanRuntime {
     ) -> usize {
         log::trace!("hook_strlen");
         let size = original(s);
-        if !self.allocator_mut().check_shadow(s as *const c_void, size)
+        if !self.check_shadow(s as *const c_void, size)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strlen".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2513,7 +2485,7 @@ impl AsanRuntime {
     ) -> usize {
         log::trace!("hook_strnlen");
         let size = original(s, n);
-        if !self.allocator_mut().check_shadow(s as *const c_void, size)
+        if !self.check_shadow(s as *const c_void, size)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strnlen".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2538,9 +2510,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strstr");
-        if !self
-            .allocator_mut()
-            .check_shadow(haystack as *const c_void, unsafe { strlen(haystack) })
+        if !self.check_shadow(haystack as *const c_void, unsafe { strlen(haystack) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strstr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2551,9 +2521,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(needle as *const c_void, unsafe { strlen(needle) })
+        if !self.check_shadow(needle as *const c_void, unsafe { strlen(needle) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strstr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2578,9 +2546,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_strcasestr");
-        if !self
-            .allocator_mut()
-            .check_shadow(haystack as *const c_void, unsafe { strlen(haystack) })
+        if !self.check_shadow(haystack as *const c_void, unsafe { strlen(haystack) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcasestr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2591,9 +2557,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(needle as *const c_void, unsafe { strlen(needle) })
+        if !self.check_shadow(needle as *const c_void, unsafe { strlen(needle) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "strcasestr".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2617,9 +2581,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_atoi");
-        if !self
-            .allocator_mut()
-            .check_shadow(s as *const c_void, unsafe { strlen(s) })
+        if !self.check_shadow(s as *const c_void, unsafe { strlen(s) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "atoi".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2644,9 +2606,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_atol");
-        if !self
-            .allocator_mut()
-            .check_shadow(s as *const c_void, unsafe { strlen(s) })
+        if !self.check_shadow(s as *const c_void, unsafe { strlen(s) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "atol".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2671,9 +2631,7 @@ impl AsanRuntime {
             fn strlen(s: *const c_char) -> usize;
         }
         log::trace!("hook_atoll");
-        if !self
-            .allocator_mut()
-            .check_shadow(s as *const c_void, unsafe { strlen(s) })
+        if !self.check_shadow(s as *const c_void, unsafe { strlen(s) })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "atoll".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2696,9 +2654,7 @@ impl AsanRuntime {
     ) -> usize {
         log::trace!("hook_wcslen");
         let size = original(s);
-        if !self
-            .allocator_mut()
-            .check_shadow(s as *const c_void, (size + 1) * 2)
+        if !self.check_shadow(s as *const c_void, (size + 1) * 2)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "wcslen".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2724,9 +2680,7 @@ impl AsanRuntime {
             fn wcslen(s: *const wchar_t) -> usize;
         }
         log::trace!("hook_wcscpy");
-        if !self
-            .allocator_mut()
-            .check_shadow(dest as *const c_void, unsafe { (wcslen(src) + 1) * 2 })
+        if !self.check_shadow(dest as *const c_void, unsafe { (wcslen(src) + 1) * 2 })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "wcscpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2737,9 +2691,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(src as *const c_void, unsafe { (wcslen(src) + 1) * 2 })
+        if !self.check_shadow(src as *const c_void, unsafe { (wcslen(src) + 1) * 2 })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "wcscpy".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2765,9 +2717,7 @@ impl AsanRuntime {
             fn wcslen(s: *const wchar_t) -> usize;
         }
         log::trace!("hook_wcscmp");
-        if !self
-            .allocator_mut()
-            .check_shadow(s1 as *const c_void, unsafe { (wcslen(s1) + 1) * 2 })
+        if !self.check_shadow(s1 as *const c_void, unsafe { (wcslen(s1) + 1) * 2 })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "wcscmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2778,9 +2728,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self
-            .allocator_mut()
-            .check_shadow(s2 as *const c_void, unsafe { (wcslen(s2) + 1) * 2 })
+        if !self.check_shadow(s2 as *const c_void, unsafe { (wcslen(s2) + 1) * 2 })
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgRead((
                 "wcscmp".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2803,7 +2751,7 @@ impl AsanRuntime {
         p4: *const c_void,
         n: usize,
     ) {
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset_pattern4".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2814,7 +2762,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(p4, n / 4)
+        if !self.check_shadow(p4, n / 4)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset_pattern4".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2837,7 +2785,7 @@ impl AsanRuntime {
         p8: *const c_void,
         n: usize,
     ) {
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset_pattern8".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2848,7 +2796,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(p8, n / 8)
+        if !self.check_shadow(p8, n / 8)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset_pattern8".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2871,7 +2819,7 @@ impl AsanRuntime {
         p16: *const c_void,
         n: usize,
     ) {
-        if !self.allocator_mut().check_shadow(s, n)
+        if !self.check_shadow(s, n)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset_pattern16".to_string(),
                 self.real_address_for_stalked(self.pc()),
@@ -2882,7 +2830,7 @@ impl AsanRuntime {
         {
             panic!("ASAN: Crashing target!");
         }
-        if !self.allocator_mut().check_shadow(p16, n / 16)
+        if !self.check_shadow(p16, n / 16)
             && AsanErrors::get_mut_blocking().report_error(AsanError::BadFuncArgWrite((
                 "memset_pattern16".to_string(),
                 self.real_address_for_stalked(self.pc()),
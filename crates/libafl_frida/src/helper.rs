@@ -719,6 +719,19 @@ where
                         start,
                         output.writer().pc()
                     );
+                    #[cfg(target_arch = "x86_64")]
+                    if rt.proximity_enabled() {
+                        let rt = core::ptr::from_mut(rt);
+                        instruction.put_callout(move |context| {
+                            // # Safety
+                            // The runtimes outlive the stalker, like for the hooks
+                            unsafe {
+                                (*rt).record_access_proximity(
+                                    &context, address, instr_size, details,
+                                );
+                            }
+                        });
+                    }
                 }
 
                 #[cfg(target_arch = "aarch64")]
@@ -30,6 +30,7 @@ impl<AF, PF, const IS_CONST_MAP: bool, const MAP_SIZE: usize>
     EdgeCoverageVariant<AF, PF, IS_CONST_MAP, MAP_SIZE> for EdgeCoverageChildVariant
 {
     const DO_SIDE_EFFECTS: bool = false;
+    const SUPPORTS_JIT: bool = false;
 
     fn fn_hitcount<ET, I, S>(&mut self, emulator_modules: &mut EmulatorModules<ET, I, S>)
     where
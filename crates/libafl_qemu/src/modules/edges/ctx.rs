@@ -0,0 +1,415 @@
+//! Context-sensitive edge coverage: mixes the calling context of each edge into its id, like the
+//! `LLVM` ctx pass, or the previous edges, like n-gram coverage.
+//!
+//! The calling context is the hash of the last call sites of the call stack, tracked by a
+//! [`crate::modules::CallTracerModule`] with a [`CallContextCollector`].
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use libafl::HasMetadata;
+#[cfg(not(cpu_target = "hexagon"))]
+use libafl::inputs::Input;
+use libafl_bolts::hash_64_fast;
+use libafl_qemu_sys::GuestAddr;
+
+use super::{
+    EdgeCoverageVariant, LIBAFL_QEMU_EDGES_MAP_ALLOCATED_SIZE, LIBAFL_QEMU_EDGES_MAP_MASK_MAX,
+    LIBAFL_QEMU_EDGES_MAP_PTR, LIBAFL_QEMU_EDGES_MAP_SIZE_PTR, helpers::gen_hashed_edge_ids,
+};
+use crate::{
+    EmulatorModules, Hook,
+    modules::{
+        AddressFilter, EdgeCoverageModule, EdgeCoverageModuleBuilder, EmulatorModuleTuple,
+        PageFilter,
+        utils::filters::{StdAddressFilter, StdPageFilter},
+    },
+};
+#[cfg(not(cpu_target = "hexagon"))]
+use crate::{Qemu, modules::calls::CallTraceCollector};
+
+/// The number of call sites mixed into the calling context, 0 to disable it
+static CALL_DEPTH: AtomicUsize = AtomicUsize::new(0);
+/// The number of previous edges mixed into the context, 0 to disable it
+static NGRAM: AtomicUsize = AtomicUsize::new(0);
+
+/// The context of the current thread
+#[derive(Debug)]
+struct EdgeContext {
+    call_stack: Vec<GuestAddr>,
+    call_ctx: u64,
+    prev_edges: Vec<u64>,
+    ngram_ctx: u64,
+}
+
+impl EdgeContext {
+    const fn new() -> Self {
+        Self {
+            call_stack: Vec::new(),
+            call_ctx: 0,
+            prev_edges: Vec::new(),
+            ngram_ctx: 0,
+        }
+    }
+
+    /// Hashes the last entries, their order matters
+    fn hash_last<T: Copy + Into<u64>>(entries: &[T], n: usize) -> u64 {
+        entries
+            .iter()
+            .rev()
+            .take(n)
+            .enumerate()
+            .fold(0, |hash, (i, entry)| {
+                hash ^ hash_64_fast((*entry).into()).rotate_left(i as u32)
+            })
+    }
+
+    fn update_call_ctx(&mut self) {
+        self.call_ctx = Self::hash_last(&self.call_stack, CALL_DEPTH.load(Ordering::Relaxed));
+    }
+
+    fn push_edge(&mut self, id: u64) {
+        let n = NGRAM.load(Ordering::Relaxed);
+        if n == 0 {
+            return;
+        }
+        if self.prev_edges.len() >= n {
+            self.prev_edges.drain(..=self.prev_edges.len() - n);
+        }
+        self.prev_edges.push(id);
+        self.ngram_ctx = Self::hash_last(&self.prev_edges, n);
+    }
+
+    fn reset(&mut self) {
+        self.call_stack.clear();
+        self.call_ctx = 0;
+        self.prev_edges.clear();
+        self.ngram_ctx = 0;
+    }
+}
+
+thread_local!(static CONTEXT: UnsafeCell<EdgeContext> = const { UnsafeCell::new(EdgeContext::new()) });
+
+/// Starts the next run with an empty context
+fn reset_context() {
+    CONTEXT.with(|context| {
+        // # Safety
+        // The context is only accessed by the thread it belongs to, one hook at a time.
+        unsafe { (*context.get()).reset() };
+    });
+}
+
+/// Mixes the context into the edge id, then adds the edge to the context
+fn ctx_edge_id(id: u64) -> usize {
+    CONTEXT.with(|context| {
+        // # Safety
+        // The context is only accessed by the thread it belongs to, one hook at a time.
+        let context = unsafe { &mut *context.get() };
+        let x = ((id ^ context.call_ctx ^ context.ngram_ctx) as usize)
+            & unsafe { LIBAFL_QEMU_EDGES_MAP_MASK_MAX };
+        context.push_edge(id);
+        x
+    })
+}
+
+/// # Safety
+///
+/// Increases the context-sensitive id at `LIBAFL_QEMU_EDGES_MAP_PTR` - potentially racey if called
+/// concurrently.
+pub unsafe extern "C" fn trace_ctx_edge_hitcount(_: *const (), id: u64) {
+    unsafe {
+        let entry = LIBAFL_QEMU_EDGES_MAP_PTR.add(ctx_edge_id(id));
+        *entry = (*entry).wrapping_add(1);
+    }
+}
+
+/// # Safety
+///
+/// Fine.
+/// Worst case we set the byte to 1 multiple times.
+pub unsafe extern "C" fn trace_ctx_edge_single(_: *const (), id: u64) {
+    unsafe {
+        let entry = LIBAFL_QEMU_EDGES_MAP_PTR.add(ctx_edge_id(id));
+        *entry = 1;
+    }
+}
+
+/// Edge coverage mixing the calling context or the previous edges into each edge id.
+///
+/// The calling context needs a [`crate::modules::CallTracerModule`] with a
+/// [`CallContextCollector`], the depth of the call stack is set there.
+/// The edges are traced with hooks only, the JIT is not supported.
+#[derive(Debug)]
+pub struct EdgeCoverageCtxVariant {
+    ngram: usize,
+}
+
+impl EdgeCoverageCtxVariant {
+    /// Only mixes the calling context of the [`CallContextCollector`] into each edge id
+    #[must_use]
+    pub fn call_stack() -> Self {
+        Self { ngram: 0 }
+    }
+
+    /// Mixes the `n` previous edges into each edge id, and the calling context, if tracked
+    #[must_use]
+    pub fn ngram(n: usize) -> Self {
+        Self { ngram: n }
+    }
+
+    /// The edge ids are spread over the whole map, let variable-length maps cover it entirely
+    fn init(&self) {
+        NGRAM.store(self.ngram, Ordering::Relaxed);
+        unsafe {
+            let edges_map_size_ptr = &raw const LIBAFL_QEMU_EDGES_MAP_SIZE_PTR;
+            if !(*edges_map_size_ptr).is_null() {
+                *LIBAFL_QEMU_EDGES_MAP_SIZE_PTR = LIBAFL_QEMU_EDGES_MAP_ALLOCATED_SIZE;
+            }
+        }
+    }
+}
+
+pub type StdEdgeCoverageCtxModule =
+    EdgeCoverageModule<StdAddressFilter, StdPageFilter, EdgeCoverageCtxVariant, false, 0>;
+pub type StdEdgeCoverageCtxModuleBuilder = EdgeCoverageModuleBuilder<
+    StdAddressFilter,
+    StdPageFilter,
+    EdgeCoverageCtxVariant,
+    false,
+    false,
+    0,
+>;
+
+impl<AF, PF, const IS_CONST_MAP: bool, const MAP_SIZE: usize>
+    EdgeCoverageVariant<AF, PF, IS_CONST_MAP, MAP_SIZE> for EdgeCoverageCtxVariant
+{
+    const SUPPORTS_JIT: bool = false;
+
+    fn pre_exec(&mut self) {
+        reset_context();
+    }
+
+    fn fn_hitcount<ET, I, S>(&mut self, emulator_modules: &mut EmulatorModules<ET, I, S>)
+    where
+        AF: AddressFilter,
+        ET: EmulatorModuleTuple<I, S>,
+        PF: PageFilter,
+        I: Unpin,
+        S: HasMetadata + Unpin,
+    {
+        self.init();
+        emulator_modules.edges(
+            Hook::Function(gen_hashed_edge_ids::<AF, ET, PF, I, S, Self, IS_CONST_MAP, MAP_SIZE>),
+            Hook::Raw(trace_ctx_edge_hitcount),
+        );
+    }
+
+    fn fn_no_hitcount<ET, I, S>(&mut self, emulator_modules: &mut EmulatorModules<ET, I, S>)
+    where
+        AF: AddressFilter,
+        ET: EmulatorModuleTuple<I, S>,
+        PF: PageFilter,
+        I: Unpin,
+        S: HasMetadata + Unpin,
+    {
+        self.init();
+        emulator_modules.edges(
+            Hook::Function(gen_hashed_edge_ids::<AF, ET, PF, I, S, Self, IS_CONST_MAP, MAP_SIZE>),
+            Hook::Raw(trace_ctx_edge_single),
+        );
+    }
+}
+
+impl Default for StdEdgeCoverageCtxModuleBuilder {
+    fn default() -> Self {
+        Self {
+            variant: EdgeCoverageCtxVariant::call_stack(),
+            address_filter: StdAddressFilter::default(),
+            page_filter: StdPageFilter::default(),
+            use_hitcounts: true,
+            use_jit: false,
+        }
+    }
+}
+
+impl StdEdgeCoverageCtxModule {
+    #[must_use]
+    pub fn builder() -> StdEdgeCoverageCtxModuleBuilder {
+        EdgeCoverageModuleBuilder::default()
+    }
+}
+
+/// Tracks the calling context for the [`EdgeCoverageCtxVariant`], as the hash of the last `depth`
+/// call sites of the call stack.
+#[cfg(not(cpu_target = "hexagon"))]
+#[derive(Debug)]
+pub struct CallContextCollector;
+
+#[cfg(not(cpu_target = "hexagon"))]
+impl CallContextCollector {
+    /// Creates a new [`CallContextCollector`], mixing the last `depth` call sites into the context
+    #[must_use]
+    pub fn new(depth: usize) -> Self {
+        CALL_DEPTH.store(depth, Ordering::Relaxed);
+        Self
+    }
+}
+
+#[cfg(not(cpu_target = "hexagon"))]
+impl CallTraceCollector for CallContextCollector {
+    #[allow(clippy::unnecessary_cast)] // dependent on the target instruction size
+    fn on_call<ET, I, S>(
+        &mut self,
+        _emulator_modules: &mut EmulatorModules<ET, I, S>,
+        _state: Option<&mut S>,
+        pc: GuestAddr,
+        call_len: usize,
+    ) where
+        ET: EmulatorModuleTuple<I, S>,
+        I: Unpin,
+        S: Unpin,
+    {
+        CONTEXT.with(|context| {
+            // # Safety
+            // The context is only accessed by the thread it belongs to, one hook at a time.
+            let context = unsafe { &mut *context.get() };
+            context.call_stack.push(pc + call_len as GuestAddr);
+            context.update_call_ctx();
+        });
+    }
+
+    fn on_ret<ET, I, S>(
+        &mut self,
+        _emulator_modules: &mut EmulatorModules<ET, I, S>,
+        _state: Option<&mut S>,
+        _pc: GuestAddr,
+        ret_addr: GuestAddr,
+    ) where
+        ET: EmulatorModuleTuple<I, S>,
+        I: Unpin,
+        S: Unpin,
+    {
+        CONTEXT.with(|context| {
+            // # Safety
+            // The context is only accessed by the thread it belongs to, one hook at a time.
+            let context = unsafe { &mut *context.get() };
+            // unwind to the returned-to frame, skipping the frames left with longjmp & co.
+            if context.call_stack.contains(&ret_addr) {
+                while let Some(p) = context.call_stack.pop() {
+                    if p == ret_addr {
+                        break;
+                    }
+                }
+            }
+            context.update_call_ctx();
+        });
+    }
+
+    fn pre_exec<I>(&mut self, _qemu: Qemu, _input: &I)
+    where
+        I: Input,
+    {
+        reset_context();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::Ordering;
+
+    use libafl::observers::{CanTrack, HitcountsMapObserver, VariableMapObserver};
+    use libafl_bolts::ownedref::OwnedMutSlice;
+    use libafl_targets::{EDGES_MAP_DEFAULT_SIZE, MAX_EDGES_FOUND, edges_map_mut_ptr};
+
+    use super::{
+        CALL_DEPTH, EdgeContext, EdgeCoverageCtxVariant, NGRAM, StdEdgeCoverageCtxModule,
+        trace_ctx_edge_hitcount,
+    };
+    use crate::modules::{
+        edges::EdgeCoverageVariant,
+        utils::filters::{StdAddressFilter, StdPageFilter},
+    };
+
+    #[test]
+    fn test_edge_context() {
+        CALL_DEPTH.store(2, Ordering::Relaxed);
+        NGRAM.store(2, Ordering::Relaxed);
+        let mut context = EdgeContext::new();
+
+        // the calling context only depends on the last `depth` call sites
+        context.call_stack.extend([0x1000, 0x2000, 0x3000]);
+        context.update_call_ctx();
+        let deep = context.call_ctx;
+        context.call_stack = vec![0x4000, 0x2000, 0x3000];
+        context.update_call_ctx();
+        assert_eq!(context.call_ctx, deep);
+        context.call_stack = vec![0x3000, 0x2000];
+        context.update_call_ctx();
+        assert_ne!(context.call_ctx, deep);
+
+        // the n-gram context only keeps the last `n` edges
+        for id in [1, 2, 3] {
+            context.push_edge(id);
+        }
+        assert_eq!(context.prev_edges, vec![2, 3]);
+        let ngram = context.ngram_ctx;
+        context.reset();
+        context.push_edge(3);
+        context.push_edge(2);
+        assert_ne!(context.ngram_ctx, ngram);
+    }
+
+    #[test]
+    fn test_ngram_module() {
+        let mut edges_observer = unsafe {
+            HitcountsMapObserver::new(VariableMapObserver::from_mut_slice(
+                "edges",
+                OwnedMutSlice::from_raw_parts_mut(edges_map_mut_ptr(), EDGES_MAP_DEFAULT_SIZE),
+                &raw mut MAX_EDGES_FOUND,
+            ))
+            .track_indices()
+        };
+
+        // the context is only tracked by the hooks
+        assert!(
+            StdEdgeCoverageCtxModule::builder()
+                .variant(EdgeCoverageCtxVariant::ngram(2))
+                .map_observer(edges_observer.as_mut())
+                .jit(true)
+                .build()
+                .is_err()
+        );
+
+        let mut module = StdEdgeCoverageCtxModule::builder()
+            .variant(EdgeCoverageCtxVariant::ngram(2))
+            .map_observer(edges_observer.as_mut())
+            .build()
+            .unwrap();
+        // set by the module on the first execution
+        NGRAM.store(2, Ordering::Relaxed);
+
+        // without a call tracer, the module itself starts each run with an empty context
+        let mut run = || {
+            EdgeCoverageVariant::<StdAddressFilter, StdPageFilter, false, 0>::pre_exec(
+                &mut module.variant,
+            );
+            let map = unsafe {
+                core::slice::from_raw_parts_mut(edges_map_mut_ptr(), EDGES_MAP_DEFAULT_SIZE)
+            };
+            map.fill(0);
+            for id in [1, 2, 3, 2, 3] {
+                unsafe { trace_ctx_edge_hitcount(core::ptr::null(), id) };
+            }
+            map.iter()
+                .enumerate()
+                .filter(|(_, count)| **count != 0)
+                .map(|(entry, count)| (entry, *count))
+                .collect::<Vec<_>>()
+        };
+        let first = run();
+        // 1, 1-2, 1-2-3, 2-3-2 and 3-2-3
+        assert_eq!(first.len(), 5);
+        assert_eq!(run(), first);
+    }
+}
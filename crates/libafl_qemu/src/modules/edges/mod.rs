@@ -32,6 +32,11 @@ pub mod child;
 pub use child::{
     EdgeCoverageChildVariant, StdEdgeCoverageChildModule, StdEdgeCoverageChildModuleBuilder,
 };
+
+pub mod ctx;
+#[cfg(not(cpu_target = "hexagon"))]
+pub use ctx::CallContextCollector;
+pub use ctx::{EdgeCoverageCtxVariant, StdEdgeCoverageCtxModule, StdEdgeCoverageCtxModuleBuilder};
 use libafl::observers::ConstLenMapObserver;
 
 use super::utils::filters::HasAddressFilter;
@@ -53,6 +58,12 @@ trait EdgeCoverageVariant<AF, PF, const IS_CONST_MAP: bool, const MAP_SIZE: usiz
 {
     const DO_SIDE_EFFECTS: bool = true;
 
+    /// Whether the edges can be traced with the JIT, see [`EdgeCoverageModuleBuilder::jit`]
+    const SUPPORTS_JIT: bool = true;
+
+    /// Run before each execution, e.g. to reset the state of the tracers
+    fn pre_exec(&mut self) {}
+
     fn jit_hitcount<ET, I, S>(&mut self, _emulator_modules: &mut EmulatorModules<ET, I, S>)
     where
         AF: AddressFilter,
@@ -128,7 +139,12 @@ pub struct EdgeCoverageModule<AF, PF, V, const IS_CONST_MAP: bool, const MAP_SIZ
 impl<AF, PF, V, const IS_INITIALIZED: bool, const IS_CONST_MAP: bool, const MAP_SIZE: usize>
     EdgeCoverageModuleBuilder<AF, PF, V, IS_INITIALIZED, IS_CONST_MAP, MAP_SIZE>
 {
-    pub fn build(self) -> Result<EdgeCoverageModule<AF, PF, V, IS_CONST_MAP, MAP_SIZE>, Error> {
+    /// Fails if the JIT is used with a variant that does not support it
+    #[allow(private_bounds)] // the variants are only implemented in this module
+    pub fn build(self) -> Result<EdgeCoverageModule<AF, PF, V, IS_CONST_MAP, MAP_SIZE>, Error>
+    where
+        V: EdgeCoverageVariant<AF, PF, IS_CONST_MAP, MAP_SIZE>,
+    {
         const {
             assert!(
                 IS_INITIALIZED,
@@ -136,6 +152,12 @@ impl<AF, PF, V, const IS_INITIALIZED: bool, const IS_CONST_MAP: bool, const MAP_
             );
         };
 
+        if self.use_jit && !V::SUPPORTS_JIT {
+            return Err(Error::illegal_argument(
+                "This edge coverage variant does not support the JIT",
+            ));
+        }
+
         Ok(EdgeCoverageModule::new(
             self.address_filter,
             self.page_filter,
@@ -366,6 +388,18 @@ where
             self.variant.fn_no_hitcount(emulator_modules);
         }
     }
+
+    fn pre_exec<ET>(
+        &mut self,
+        _qemu: Qemu,
+        _emulator_modules: &mut EmulatorModules<ET, I, S>,
+        _state: &mut S,
+        _input: &I,
+    ) where
+        ET: EmulatorModuleTuple<I, S>,
+    {
+        self.variant.pre_exec();
+    }
 }
 
 impl<AF, PF, V, const IS_CONST_MAP: bool, const MAP_SIZE: usize> HasAddressFilter
@@ -24,8 +24,9 @@ pub mod edges;
 pub use edges::{
     EdgeCoverageModule, EdgeCoverageModuleBuilder, StdEdgeCoverageChildModule,
     StdEdgeCoverageChildModuleBuilder, StdEdgeCoverageClassicModule,
-    StdEdgeCoverageClassicModuleBuilder, StdEdgeCoverageFullModule,
-    StdEdgeCoverageFullModuleBuilder, StdEdgeCoverageModule, StdEdgeCoverageModuleBuilder,
+    StdEdgeCoverageClassicModuleBuilder, StdEdgeCoverageCtxModule, StdEdgeCoverageCtxModuleBuilder,
+    StdEdgeCoverageFullModule, StdEdgeCoverageFullModuleBuilder, StdEdgeCoverageModule,
+    StdEdgeCoverageModuleBuilder,
 };
 
 #[cfg(not(cpu_target = "hexagon"))]
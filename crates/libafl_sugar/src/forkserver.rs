@@ -31,8 +31,9 @@ use libafl::{
     state::{HasCorpus, StdState},
 };
 use libafl_bolts::{
-    AsSliceMut, StdTargetArgs,
+    AsSliceMut, InputLocation, StdTargetArgs,
     core_affinity::Cores,
+    fs::{InputFifo, get_unique_std_input_file},
     nonzero,
     rands::StdRand,
     shmem::{ShMem, ShMemProvider, UnixShMemProvider},
@@ -80,9 +81,28 @@ pub struct ForkserverBytesCoverageSugar<'a> {
     /// Fuzz `iterations` number of times, instead of indefinitely; implies use of `fuzz_loop_for`
     #[builder(default = None)]
     iterations: Option<u64>,
+    /// Deliver the input in this environment variable instead of stdin or `@@`.
+    /// As the environment of a forkserver child can't change in persistent mode, this disables it.
+    #[builder(default = None)]
+    input_env: Option<String>,
+    /// Stream the input into a named pipe passed as `@@`, instead of a regular file
+    #[builder(default = false)]
+    input_fifo: bool,
 }
 
 impl ForkserverBytesCoverageSugar<'_> {
+    /// Sets the arguments of the target and how it receives its input.
+    fn target_input<T: StdTargetArgs>(&self, mut builder: T, input_fifo: Option<&InputFifo>) -> T {
+        if let Some(fifo) = input_fifo {
+            builder = builder.input(InputLocation::Fifo { fifo: fifo.clone() });
+        }
+        builder = builder.parse_afl_cmdline(self.arguments);
+        if let Some(input_env) = &self.input_env {
+            builder = builder.env_input(input_env);
+        }
+        builder
+    }
+
     /// Runs the fuzzer.
     #[expect(clippy::too_many_lines)]
     pub fn run(&mut self) {
@@ -207,10 +227,19 @@ impl ForkserverBytesCoverageSugar<'_> {
             // A fuzzer with feedbacks and a corpus scheduler
             let mut fuzzer = StdFuzzer::new(scheduler, feedback, objective);
 
-            let forkserver = ForkserverExecutor::builder()
-                .program(self.binary.clone())
-                .parse_afl_cmdline(self.arguments)
-                .is_persistent(true)
+            // The executors of this client run one after the other, so they share the named pipe.
+            let input_fifo = if self.input_fifo {
+                Some(InputFifo::create(get_unique_std_input_file())?)
+            } else {
+                None
+            };
+
+            let forkserver = self
+                .target_input(
+                    ForkserverExecutor::builder().program(self.binary.clone()),
+                    input_fifo.as_ref(),
+                )
+                .is_persistent(self.input_env.is_none())
                 .autotokens(&mut tokens)
                 .coverage_map_size(MAP_SIZE)
                 .timeout(timeout)
@@ -277,12 +306,14 @@ impl ForkserverBytesCoverageSugar<'_> {
 
                 let cmplog_observer = StdCmpObserver::new("cmplog", cmpmap, true);
 
-                let cmplog_executor = ForkserverExecutor::builder()
-                    .program(exec)
+                let cmplog_executor = self
+                    .target_input(
+                        ForkserverExecutor::builder().program(exec),
+                        input_fifo.as_ref(),
+                    )
                     .debug_child(self.debug_output)
                     .shmem_provider(&mut shmem_provider_client)
-                    .parse_afl_cmdline(self.arguments)
-                    .is_persistent(true)
+                    .is_persistent(self.input_env.is_none())
                     // We give more time to the cmplog run
                     .timeout(timeout * 10)
                     .build(tuple_list!(cmplog_observer))
@@ -368,12 +399,15 @@ pub mod pybind {
         iterations: Option<u64>,
         tokens_file: Option<PathBuf>,
         timeout: Option<u64>,
+        input_env: Option<String>,
+        input_fifo: bool,
     }
 
     #[pymethods]
     impl ForkserverBytesCoverageSugar {
         /// Create a new [`ForkserverBytesCoverageSugar`]
         #[new]
+        #[expect(clippy::too_many_arguments)]
         #[pyo3(signature = (
             input_dirs,
             output_dir,
@@ -381,7 +415,9 @@ pub mod pybind {
             cores,
             iterations=None,
             tokens_file=None,
-            timeout=None
+            timeout=None,
+            input_env=None,
+            input_fifo=false
         ))]
         fn new(
             input_dirs: Vec<PathBuf>,
@@ -391,6 +427,8 @@ pub mod pybind {
             iterations: Option<u64>,
             tokens_file: Option<PathBuf>,
             timeout: Option<u64>,
+            input_env: Option<String>,
+            input_fifo: bool,
         ) -> Self {
             Self {
                 input_dirs,
@@ -400,6 +438,8 @@ pub mod pybind {
                 iterations,
                 tokens_file,
                 timeout,
+                input_env,
+                input_fifo,
             }
         }
 
@@ -417,6 +457,8 @@ pub mod pybind {
                 .timeout(self.timeout)
                 .tokens_file(self.tokens_file.clone())
                 .iterations(self.iterations)
+                .input_env(self.input_env.clone())
+                .input_fifo(self.input_fifo)
                 .build()
                 .run();
         }
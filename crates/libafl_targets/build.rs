@@ -62,7 +62,12 @@ fn main() {
         .map_or(Ok(SIXTY_FOUR_KIB), str::parse)
         .expect("Could not parse LIBAFL_ACCOUNTING_MAP_SIZE");
 
+    let data_cov_map_size: usize = option_env!("LIBAFL_DATA_COVERAGE_MAP_SIZE")
+        .map_or(Ok(SIXTY_FOUR_KIB), str::parse)
+        .expect("Could not parse LIBAFL_DATA_COVERAGE_MAP_SIZE");
+
     assert!(edges_map_default_size <= edges_map_allocated_size);
+    assert!(data_cov_map_size.is_power_of_two());
     assert!(edges_map_default_size.is_power_of_two());
 
     write!(
@@ -81,6 +86,8 @@ fn main() {
         pub const CMPLOG_MAP_H: usize = {cmplog_map_h};
         /// The size of the accounting maps
         pub const ACCOUNTING_MAP_SIZE: usize = {acc_map_size};
+        /// The size of the data coverage map
+        pub const DATA_COVERAGE_MAP_SIZE: usize = {data_cov_map_size};
 "
     )
     .expect("Could not write file");
@@ -93,6 +100,7 @@ fn main() {
     println!("cargo:rerun-if-env-changed=LIBAFL_CMPLOG_MAP_W");
     println!("cargo:rerun-if-env-changed=LIBAFL_CMPLOG_MAP_H");
     println!("cargo:rerun-if-env-changed=LIBAFL_ACCOUNTING_MAP_SIZE");
+    println!("cargo:rerun-if-env-changed=LIBAFL_DATA_COVERAGE_MAP_SIZE");
 
     #[cfg(feature = "common")]
     {
@@ -185,6 +193,15 @@ fn main() {
         libfuzzer.compile("libfuzzer");
     }
 
+    #[cfg(feature = "malloc_hooks")]
+    {
+        println!("cargo:rerun-if-changed=src/lsan.c");
+
+        cc::Build::new()
+            .file(src_dir.join("lsan.c"))
+            .compile("lsan");
+    }
+
     #[cfg(feature = "coverage")]
     {
         println!("cargo:rerun-if-changed=src/coverage.c");
@@ -206,6 +223,15 @@ fn main() {
             .compile("coverage");
     }
 
+    #[cfg(feature = "dfsan")]
+    {
+        println!("cargo:rerun-if-changed=src/dfsan.c");
+
+        cc::Build::new()
+            .file(src_dir.join("dfsan.c"))
+            .compile("dfsan");
+    }
+
     #[cfg(feature = "sancov_pcguard_dump_cov")]
     {
         println!("cargo:rerun-if-changed=src/sancov_pcguard.c");
@@ -0,0 +1,322 @@
+use alloc::{
+    borrow::{Cow, ToOwned},
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    vec::Vec,
+};
+use core::{marker::PhantomData, ops::Range};
+
+use libafl::{
+    Error, HasMetadata, HasNamedMetadata,
+    corpus::HasCurrentCorpusId,
+    executors::{Executor, HasObservers},
+    inputs::{BytesInput, HasMutatorBytes},
+    observers::{CmpValues, CmpValuesMetadata, ObserversTuple},
+    stages::{Restartable, RetryCountRestartHelper, Stage},
+    state::{HasCorpus, HasCurrentTestcase},
+};
+use libafl_bolts::Named;
+
+use crate::dfsan::{DFSAN_LABELS, DfsanCmp, set_dfsan_ranges, take_dfsan_cmps};
+
+/// The name for the `DataFlowSanitizer` tracing stage
+pub static DFSAN_TRACING_STAGE_NAME: &str = "dfsantracing";
+
+/// The default max number of executions to infer the taint of an input
+pub const DFSAN_DEFAULT_MAX_EXECUTIONS: usize = 256;
+
+/// Infers the input offsets of the comparisons from the labels of several executions: each
+/// execution splits a range of the input in up to [`DFSAN_LABELS`] labeled parts, and the parts
+/// that taint a comparison are split again, down to single bytes.
+#[derive(Debug)]
+struct TaintInference {
+    /// The ranges left to split, with the comparisons they taint
+    worklist: VecDeque<(Range<usize>, Vec<usize>)>,
+    /// The values and input offsets of the tainted comparisons, by index
+    cmps: BTreeMap<usize, (CmpValues, BTreeSet<usize>)>,
+}
+
+impl TaintInference {
+    fn new(len: usize) -> Self {
+        let mut worklist = VecDeque::new();
+        if len > 0 {
+            worklist.push_back((0..len, vec![]));
+        }
+        Self {
+            worklist,
+            cmps: BTreeMap::new(),
+        }
+    }
+
+    /// The ranges to label in the next execution, splitting the next range of the worklist
+    fn next_ranges(&mut self) -> Option<Vec<Range<usize>>> {
+        let (range, _) = self.worklist.pop_front()?;
+        let chunk = range.len().div_ceil(DFSAN_LABELS);
+        Some(
+            range
+                .clone()
+                .step_by(chunk)
+                .map(|start| start..(start + chunk).min(range.end))
+                .collect(),
+        )
+    }
+
+    /// Records the tainted comparisons of an execution with the given labeled ranges
+    fn record(&mut self, ranges: &[Range<usize>], cmps: Vec<DfsanCmp>) {
+        let mut tainted = vec![vec![]; ranges.len()];
+        for cmp in cmps {
+            let entry = self
+                .cmps
+                .entry(cmp.index)
+                .or_insert_with(|| (cmp.values, BTreeSet::new()));
+            for (bit, range) in ranges.iter().enumerate() {
+                if cmp.label & (1 << bit) == 0 {
+                    continue;
+                }
+                if range.len() == 1 {
+                    entry.1.insert(range.start);
+                } else {
+                    tainted[bit].push(cmp.index);
+                }
+            }
+        }
+        for (range, indices) in ranges.iter().zip(tainted) {
+            if !indices.is_empty() {
+                self.worklist.push_back((range.clone(), indices));
+            }
+        }
+    }
+
+    /// The tainted comparisons and their input offsets, in the order of the execution. The ranges
+    /// left to split taint their comparisons as a whole.
+    fn finish(mut self) -> Vec<(CmpValues, Vec<usize>)> {
+        for (range, indices) in self.worklist {
+            for index in indices {
+                if let Some((_, offsets)) = self.cmps.get_mut(&index) {
+                    offsets.extend(range.clone());
+                }
+            }
+        }
+        let mut taints: Vec<(CmpValues, Vec<usize>)> = vec![];
+        for (values, offsets) in self.cmps.into_values() {
+            if offsets.is_empty() {
+                continue;
+            }
+            // merge the repeated comparisons, e.g. in loops
+            match taints.last_mut() {
+                Some((last, last_offsets)) if *last == values => {
+                    last_offsets.extend(offsets);
+                    last_offsets.sort_unstable();
+                    last_offsets.dedup();
+                }
+                _ => taints.push((values, offsets.into_iter().collect())),
+            }
+        }
+        taints
+    }
+}
+
+/// Merges the tainted comparisons into the comparisons of the `CmpLog` trace, by value. The
+/// comparisons the trace missed are appended.
+fn merge_taints(meta: &mut CmpValuesMetadata, taints: Vec<(CmpValues, Vec<usize>)>) {
+    meta.taints.resize(meta.list.len(), vec![]);
+    for (values, offsets) in taints {
+        let mut found = false;
+        for (cmp, cmp_offsets) in meta.list.iter().zip(meta.taints.iter_mut()) {
+            if *cmp == values {
+                cmp_offsets.extend_from_slice(&offsets);
+                cmp_offsets.sort_unstable();
+                cmp_offsets.dedup();
+                found = true;
+            }
+        }
+        if !found {
+            meta.list.push(values);
+            meta.taints.push(offsets);
+        }
+    }
+}
+
+/// Infers which input offsets influence each comparison of the target, with the
+/// `DataFlowSanitizer` build of the target, and stores them in the `taints` of the
+/// [`CmpValuesMetadata`] for the `I2SRandReplace` mutator.
+///
+/// The tracer executor runs the `DataFlowSanitizer` build in this process, with a harness that
+/// labels its input with [`crate::dfsan_label_input`], e.g. through
+/// [`crate::libfuzzer_dfsan_test_one_input`].
+///
+/// This stage must run after the `CmpLog` tracing stage of the same input, which resets the
+/// [`CmpValuesMetadata`]: the tainted comparisons are merged into its list by value. It leaves the
+/// `TaintMetadata` of the `AflppRedQueen` mutator to the `ColorizationStage`, which only keeps the
+/// replaced bytes that preserve the path of the input.
+#[derive(Debug, Clone)]
+pub struct DfsanTracingStage<EM, TE, S, Z> {
+    name: Cow<'static, str>,
+    tracer_executor: TE,
+    max_executions: usize,
+    phantom: PhantomData<(EM, TE, S, Z)>,
+}
+
+impl<EM, TE, S, Z> Named for DfsanTracingStage<EM, TE, S, Z> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<E, EM, TE, S, Z> Stage<E, EM, S, Z> for DfsanTracingStage<EM, TE, S, Z>
+where
+    TE: HasObservers + Executor<EM, BytesInput, S, Z>,
+    TE::Observers: ObserversTuple<BytesInput, S>,
+    S: HasCorpus<BytesInput>
+        + HasCurrentTestcase<BytesInput>
+        + HasMetadata
+        + HasNamedMetadata
+        + HasCurrentCorpusId,
+{
+    #[inline]
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let input = state.current_input_cloned()?;
+        let mut inference = TaintInference::new(input.mutator_bytes().len());
+
+        let mut executions = 0;
+        while executions < self.max_executions
+            && let Some(ranges) = inference.next_ranges()
+        {
+            // # Safety
+            // The tracer executor runs the target in this thread, after the ranges are set.
+            unsafe { set_dfsan_ranges(&ranges) };
+
+            self.tracer_executor
+                .observers_mut()
+                .pre_exec_all(state, &input)?;
+            let exit_kind = self
+                .tracer_executor
+                .run_target(fuzzer, state, manager, &input)?;
+            self.tracer_executor
+                .observers_mut()
+                .post_exec_all(state, &input, &exit_kind)?;
+
+            inference.record(&ranges, unsafe { take_dfsan_cmps() });
+            executions += 1;
+        }
+        unsafe { set_dfsan_ranges(&[]) };
+
+        let taints = inference.finish();
+        let meta = state.metadata_or_insert_with(CmpValuesMetadata::new);
+        merge_taints(meta, taints);
+
+        Ok(())
+    }
+}
+
+impl<EM, TE, S, Z> Restartable<S> for DfsanTracingStage<EM, TE, S, Z>
+where
+    S: HasMetadata + HasNamedMetadata + HasCurrentCorpusId,
+{
+    fn should_restart(&mut self, state: &mut S) -> Result<bool, Error> {
+        // Tracing stage is always deterministic
+        // don't restart
+        RetryCountRestartHelper::no_retry(state, &self.name)
+    }
+
+    fn clear_progress(&mut self, state: &mut S) -> Result<(), Error> {
+        RetryCountRestartHelper::clear_progress(state, &self.name)
+    }
+}
+
+impl<EM, TE, S, Z> DfsanTracingStage<EM, TE, S, Z> {
+    /// Creates a new [`DfsanTracingStage`] with the in-process executor of the
+    /// `DataFlowSanitizer` build of the target
+    pub fn new(tracer_executor: TE) -> Self {
+        Self {
+            name: Cow::Owned(DFSAN_TRACING_STAGE_NAME.to_owned()),
+            tracer_executor,
+            max_executions: DFSAN_DEFAULT_MAX_EXECUTIONS,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the max number of executions per input. Once reached, the ranges left to split
+    /// taint their comparisons as a whole.
+    #[must_use]
+    pub fn with_max_executions(mut self, max_executions: usize) -> Self {
+        self.max_executions = max_executions;
+        self
+    }
+
+    /// Gets the underlying tracer executor
+    pub fn executor(&self) -> &TE {
+        &self.tracer_executor
+    }
+
+    /// Gets the underlying tracer executor (mut)
+    pub fn executor_mut(&mut self) -> &mut TE {
+        &mut self.tracer_executor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::ops::Range;
+
+    use libafl::observers::{CmpValues, CmpValuesMetadata};
+
+    use super::{TaintInference, merge_taints};
+    use crate::dfsan::DfsanCmp;
+
+    #[test]
+    fn test_taint_inference() {
+        // comparison 0 depends on byte 5, comparison 1 on bytes 20..24, comparison 2 on nothing
+        let deps: [Range<usize>; 3] = [5..6, 20..24, 0..0];
+        let mut inference = TaintInference::new(100);
+        let mut executions = 0;
+        while let Some(ranges) = inference.next_ranges() {
+            let cmps: Vec<DfsanCmp> = deps
+                .iter()
+                .enumerate()
+                .filter_map(|(index, dep)| {
+                    let label = ranges
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, range)| dep.start < range.end && range.start < dep.end)
+                        .fold(0, |label, (bit, _)| label | (1 << bit));
+                    (label != 0).then_some(DfsanCmp {
+                        index,
+                        values: CmpValues::U32((index as u32, 0, false)),
+                        label,
+                    })
+                })
+                .collect();
+            inference.record(&ranges, cmps);
+            executions += 1;
+        }
+        // far less than one execution per byte
+        assert!(executions < 20);
+
+        let taints = inference.finish();
+        assert_eq!(taints.len(), 2);
+        assert_eq!(taints[0].1, [5]);
+        assert_eq!(taints[1].1, [20, 21, 22, 23]);
+
+        // the cmplog trace has comparison 1 twice and misses comparison 0
+        let mut meta = CmpValuesMetadata::new();
+        meta.list = vec![
+            CmpValues::U32((7, 0, false)),
+            CmpValues::U32((1, 0, false)),
+            CmpValues::U32((1, 0, false)),
+        ];
+        merge_taints(&mut meta, taints);
+        assert_eq!(meta.list.len(), 4);
+        assert_eq!(meta.list[3], CmpValues::U32((0, 0, false)));
+        assert_eq!(
+            meta.taints,
+            [vec![], vec![20, 21, 22, 23], vec![20, 21, 22, 23], vec![5]]
+        );
+    }
+}
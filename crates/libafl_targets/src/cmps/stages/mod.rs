@@ -1,3 +1,9 @@
 /// cmplog tracing for aflpp style cmplog
 pub mod aflpptracing;
 pub use aflpptracing::*;
+
+/// taint inference with `DataFlowSanitizer`
+#[cfg(feature = "dfsan")]
+pub mod dfsantracing;
+#[cfg(feature = "dfsan")]
+pub use dfsantracing::*;
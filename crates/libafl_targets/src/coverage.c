@@ -1,5 +1,9 @@
 #include "common.h"
 
+#include <stddef.h>
+#include <stdio.h>
+#include <stdlib.h>
+
 typedef uint32_t prev_loc_t;
 
 /* Maximum ngram size */
@@ -18,6 +22,22 @@ uint32_t       *__afl_acc_memop_ptr = __afl_acc_memop_ptr_local;
 // https://github.com/AFLplusplus/AFLplusplus/blob/stable/src/afl-cc.c#L993
 #if !defined(_WIN32)
 EXT_VAR(__afl_sharedmem_fuzzing, int);
+
+// The exact number of edges, defined by the lto-edges pass of libafl_cc
+EXT_VAR(__libafl_lto_map_size, size_t) = 0;
+
+// The edges of the lto-edges pass are numbered from 0, check that they all fit
+// in the edges map before the first execution
+__attribute__((constructor)) static void __libafl_check_lto_map_size(void) {
+  if (__libafl_lto_map_size > EDGES_MAP_ALLOCATED_SIZE) {
+    fprintf(stderr,
+            "The target has %zu edges, more than the %zu of the edges map, "
+            "rebuild libafl_targets with a larger "
+            "LIBAFL_EDGES_MAP_ALLOCATED_SIZE\n",
+            __libafl_lto_map_size, (size_t)EDGES_MAP_ALLOCATED_SIZE);
+    abort();
+  }
+}
 #endif
 
 // Weak symbols, LLVM Passes overwrites them if we really use it
@@ -5,7 +5,9 @@
     feature = "sancov_pcguard_hitcounts",
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
-    feature = "sancov_ctx"
+    feature = "sancov_ctx",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "lto_edges"
 ))]
 use alloc::borrow::Cow;
 
@@ -22,6 +24,10 @@ use crate::{ACCOUNTING_MAP_SIZE, EDGES_MAP_ALLOCATED_SIZE, EDGES_MAP_DEFAULT_SIZ
 pub static mut __afl_area_ptr_local: [u8; EDGES_MAP_ALLOCATED_SIZE] = [0; EDGES_MAP_ALLOCATED_SIZE];
 pub use __afl_area_ptr_local as EDGES_MAP;
 
+/// The map for unbucketed 32-bit edge hitcounts, see [`std_edges_map_32_observer`].
+#[cfg(feature = "sancov_pcguard_hitcounts32")]
+pub static mut EDGES_MAP_32: [u32; EDGES_MAP_ALLOCATED_SIZE] = [0; EDGES_MAP_ALLOCATED_SIZE];
+
 /// The map for accounting mem writes.
 #[unsafe(no_mangle)]
 #[allow(non_upper_case_globals)] // expect breaks here for some reason
@@ -44,6 +50,10 @@ unsafe extern "C" {
     /// The area pointer points to the accounting mem operations map.
     pub static mut __afl_acc_memop_ptr: *mut u32;
 
+    /// The exact number of edges of a target instrumented by the `lto-edges` pass, or 0
+    #[cfg(not(target_os = "windows"))]
+    pub static __libafl_lto_map_size: usize;
+
     /// Start of libafl token section
     #[cfg(any(target_os = "linux", target_vendor = "apple"))]
     pub static __token_start: *const u8;
@@ -105,7 +115,9 @@ pub static mut __afl_fuzz_len: *mut u32 = &raw mut __afl_fuzz_len_local;
     feature = "sancov_pcguard_hitcounts",
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
-    feature = "sancov_ctx"
+    feature = "sancov_ctx",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "lto_edges"
 ))]
 use libafl::observers::StdMapObserver;
 #[cfg(any(
@@ -113,7 +125,9 @@ use libafl::observers::StdMapObserver;
     feature = "sancov_pcguard_hitcounts",
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
-    feature = "sancov_ctx"
+    feature = "sancov_ctx",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "lto_edges"
 ))]
 use libafl_bolts::ownedref::OwnedMutSlice;
 
@@ -130,7 +144,9 @@ use libafl_bolts::ownedref::OwnedMutSlice;
     feature = "sancov_pcguard_hitcounts",
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
-    feature = "sancov_ctx"
+    feature = "sancov_ctx",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "lto_edges"
 ))]
 pub unsafe fn edges_map_mut_slice<'a>() -> OwnedMutSlice<'a, u8> {
     unsafe { OwnedMutSlice::from_raw_parts_mut(edges_map_mut_ptr(), edges_max_num()) }
@@ -168,7 +184,9 @@ pub unsafe fn edges_map_mut_slice<'a>() -> OwnedMutSlice<'a, u8> {
     feature = "sancov_pcguard_hitcounts",
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
-    feature = "sancov_ctx"
+    feature = "sancov_ctx",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "lto_edges"
 ))]
 pub unsafe fn std_edges_map_observer<'a, S>(name: S) -> StdMapObserver<'a, u8, false>
 where
@@ -177,6 +195,30 @@ where
     unsafe { StdMapObserver::from_mut_slice(name, edges_map_mut_slice()) }
 }
 
+/// Gets a new [`StdMapObserver`] for the unbucketed 32-bit edge hitcounts of [`EDGES_MAP_32`],
+/// of the same size as the edges map.
+///
+/// Unlike the 8-bit counters, they do not wrap around, so they can be used to maximize how often
+/// each edge is hit, with a [`libafl::feedbacks::PerfFeedback`].
+///
+/// # Safety
+/// The returned observer aliases the static [`EDGES_MAP_32`].
+#[cfg(feature = "sancov_pcguard_hitcounts32")]
+pub unsafe fn std_edges_map_32_observer<'a, S>(name: S) -> StdMapObserver<'a, u32, false>
+where
+    S: Into<Cow<'static, str>>,
+{
+    unsafe {
+        StdMapObserver::from_mut_slice(
+            name,
+            OwnedMutSlice::from_raw_parts_mut(
+                &raw mut EDGES_MAP_32 as *mut u32,
+                edges_max_num().min(EDGES_MAP_ALLOCATED_SIZE),
+            ),
+        )
+    }
+}
+
 /// Gets the current edges map pt
 /// It will usually take `EDGES_MAP`, but `EDGES_MAP_PTR`,
 /// if built with the `pointer_maps` feature.
@@ -192,19 +234,36 @@ pub fn edges_map_mut_ptr() -> *mut u8 {
     }
 }
 
+/// Gets the exact number of edges of a target instrumented by the `lto-edges` pass of `libafl_cc`,
+/// or 0 for any other target. The target aborts at startup if they do not fit in the edges map.
+#[must_use]
+pub fn lto_edges_num() -> usize {
+    #[cfg(not(target_os = "windows"))]
+    unsafe {
+        __libafl_lto_map_size
+    }
+    #[cfg(target_os = "windows")]
+    0
+}
+
 /// Gets the current maximum number of edges tracked.
 #[cfg(any(
     feature = "sancov_pcguard_edges",
     feature = "sancov_pcguard_hitcounts",
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
-    feature = "sancov_ctx"
+    feature = "sancov_ctx",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "lto_edges"
 ))]
 #[must_use]
 pub fn edges_max_num() -> usize {
     unsafe {
         if MAX_EDGES_FOUND > 0 {
             MAX_EDGES_FOUND
+        } else if lto_edges_num() > 0 {
+            // checked against the size of the edges map at startup, in `coverage.c`
+            lto_edges_num()
         } else {
             #[cfg(feature = "pointer_maps")]
             {
@@ -0,0 +1,153 @@
+//! Memory-access (data) coverage, the runtime of the `data-coverage` pass of `libafl_cc`.
+//!
+//! Each instrumented load and store reports the accessed address with the random id of its
+//! instruction site. The address is bucketed, hashed with the site, and counted in the
+//! [`DATA_COVERAGE_MAP`], so new data states of the target show up as new entries.
+//!
+//! Heap addresses may differ between runs, so keep the buckets coarse, or restrict the tracked
+//! addresses with [`set_data_coverage_range`] to keep the map stable.
+
+use alloc::borrow::Cow;
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use libafl::{
+    feedbacks::MaxMapFeedback,
+    observers::{HitcountsMapObserver, StdMapObserver},
+};
+use libafl_bolts::hash_64_fast;
+
+use crate::DATA_COVERAGE_MAP_SIZE;
+
+/// The default bucket size of the addresses, as a shift: 8 bytes
+pub const DATA_COVERAGE_DEFAULT_GRANULARITY: u32 = 3;
+
+/// The map for data coverage
+pub static mut DATA_COVERAGE_MAP: [u8; DATA_COVERAGE_MAP_SIZE] = [0; DATA_COVERAGE_MAP_SIZE];
+
+static GRANULARITY: AtomicU32 = AtomicU32::new(DATA_COVERAGE_DEFAULT_GRANULARITY);
+static SAMPLING: AtomicUsize = AtomicUsize::new(1);
+static ACCESSES: AtomicUsize = AtomicUsize::new(0);
+static RANGE_START: AtomicUsize = AtomicUsize::new(0);
+static RANGE_END: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Sets the bucket size of the addresses to `1 << shift` bytes.
+///
+/// Larger buckets are less sensitive to the exact layout of the memory.
+pub fn set_data_coverage_granularity(shift: u32) {
+    GRANULARITY.store(shift.min(usize::BITS - 1), Ordering::Relaxed);
+}
+
+/// Only records one of every `every` memory accesses, to keep the overhead down.
+///
+/// The instrumentation ratio of the pass, `-data_cov_ratio`, reduces the overhead further.
+pub fn set_data_coverage_sampling(every: usize) {
+    SAMPLING.store(every.max(1), Ordering::Relaxed);
+}
+
+/// Only records the accesses to the addresses in `start..end`, e.g. a global buffer or arena.
+pub fn set_data_coverage_range(start: usize, end: usize) {
+    RANGE_START.store(start, Ordering::Relaxed);
+    RANGE_END.store(end, Ordering::Relaxed);
+}
+
+/// The index of an access in the [`DATA_COVERAGE_MAP`]
+#[inline]
+fn data_coverage_index(site: u32, addr: usize) -> usize {
+    let bucket = addr >> GRANULARITY.load(Ordering::Relaxed);
+    ((u64::from(site) ^ hash_64_fast(bucket as u64)) as usize) & (DATA_COVERAGE_MAP_SIZE - 1)
+}
+
+/// The runtime code inserted before every instrumented load and store, by the `data-coverage`
+/// pass of `libafl_cc`.
+///
+/// # Safety
+/// Increases an entry of the static [`DATA_COVERAGE_MAP`], racey if called concurrently.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn __libafl_targets_data_cov(site: u32, addr: usize) {
+    if addr < RANGE_START.load(Ordering::Relaxed) || addr >= RANGE_END.load(Ordering::Relaxed) {
+        return;
+    }
+    let sampling = SAMPLING.load(Ordering::Relaxed);
+    if sampling > 1
+        && !ACCESSES
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(sampling)
+    {
+        return;
+    }
+
+    let idx = data_coverage_index(site, addr);
+    unsafe {
+        let entry = (&raw mut DATA_COVERAGE_MAP as *mut u8).add(idx);
+        *entry = (*entry).wrapping_add(1);
+    }
+}
+
+/// The observer over the [`DATA_COVERAGE_MAP`]
+pub type DataCoverageObserver<'a> = HitcountsMapObserver<StdMapObserver<'a, u8, false>>;
+
+/// The feedback over the [`DATA_COVERAGE_MAP`], interesting for new data states
+pub type DataCoverageFeedback<'a> =
+    MaxMapFeedback<DataCoverageObserver<'a>, DataCoverageObserver<'a>>;
+
+/// Gets a new [`DataCoverageObserver`] over the [`DATA_COVERAGE_MAP`], bucketing the counts of
+/// each entry like the edge hitcounts.
+///
+/// # Safety
+/// The returned observer aliases the static [`DATA_COVERAGE_MAP`].
+pub unsafe fn data_coverage_observer<'a, S>(name: S) -> DataCoverageObserver<'a>
+where
+    S: Into<Cow<'static, str>>,
+{
+    unsafe {
+        HitcountsMapObserver::new(StdMapObserver::from_mut_ptr(
+            name,
+            &raw mut DATA_COVERAGE_MAP as *mut u8,
+            DATA_COVERAGE_MAP_SIZE,
+        ))
+    }
+}
+
+/// Gets a new [`DataCoverageFeedback`] for the given [`DataCoverageObserver`]
+#[must_use]
+pub fn data_coverage_feedback<'a>(observer: &DataCoverageObserver<'a>) -> DataCoverageFeedback<'a> {
+    MaxMapFeedback::new(observer)
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl::observers::MapObserver;
+
+    use super::{
+        __libafl_targets_data_cov, DATA_COVERAGE_DEFAULT_GRANULARITY, data_coverage_index,
+        data_coverage_observer, set_data_coverage_granularity, set_data_coverage_range,
+    };
+
+    #[test]
+    fn test_data_coverage() {
+        let mut observer = unsafe { data_coverage_observer("data") };
+        observer.reset_map().unwrap();
+
+        // the accesses to the same bucket from the same site share an entry
+        set_data_coverage_granularity(DATA_COVERAGE_DEFAULT_GRANULARITY);
+        assert_eq!(
+            data_coverage_index(7, 0x1000),
+            data_coverage_index(7, 0x1007)
+        );
+        assert_ne!(
+            data_coverage_index(7, 0x1000),
+            data_coverage_index(8, 0x1000)
+        );
+
+        set_data_coverage_range(0x1000, 0x2000);
+        unsafe {
+            __libafl_targets_data_cov(7, 0x1000);
+            __libafl_targets_data_cov(7, 0x1004);
+            __libafl_targets_data_cov(7, 0x3000);
+        }
+        set_data_coverage_range(0, usize::MAX);
+
+        assert_eq!(observer.count_bytes(), 1);
+        assert_eq!(observer.get(data_coverage_index(7, 0x1000)), 2);
+    }
+}
@@ -0,0 +1,47 @@
+#include "common.h"
+
+#include <stddef.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+// The labels of DataFlowSanitizer, one bit each since LLVM 13
+typedef uint8_t dfsan_label;
+
+// Weak symbols, only defined if linked with -fsanitize=dataflow
+#if !defined(_WIN32)
+void __attribute__((weak)) dfsan_set_label(dfsan_label label, void *addr,
+                                           size_t size);
+dfsan_label __attribute__((weak)) dfsan_read_label(const void *addr,
+                                                   size_t size);
+
+// DataFlowSanitizer adds a .dfsan suffix to the instrumented functions
+int dfsan_LLVMFuzzerTestOneInput(const uint8_t *Data, size_t Size) __asm__(
+    "LLVMFuzzerTestOneInput.dfsan") __attribute__((weak));
+#endif
+
+void __libafl_targets_dfsan_set_label(dfsan_label label, void *addr,
+                                      size_t size) {
+#if !defined(_WIN32)
+  if (dfsan_set_label) { dfsan_set_label(label, addr, size); }
+#endif
+}
+
+dfsan_label __libafl_targets_dfsan_read_label(const void *addr, size_t size) {
+#if !defined(_WIN32)
+  if (dfsan_read_label) { return dfsan_read_label(addr, size); }
+#endif
+  return 0;
+}
+
+int __libafl_targets_dfsan_test_one_input(const uint8_t *Data, size_t Size) {
+#if !defined(_WIN32)
+  if (dfsan_LLVMFuzzerTestOneInput) {
+    return dfsan_LLVMFuzzerTestOneInput(Data, Size);
+  }
+#endif
+  fprintf(stderr,
+          "No \"LLVMFuzzerTestOneInput.dfsan\" is linked. Did you build the "
+          "harness with -fsanitize=dataflow?\n");
+  abort();
+  return 0;
+}
@@ -0,0 +1,288 @@
+//! `DataFlowSanitizer` taint tracking, the runtime of targets built with the `dfsan` configuration
+//! of `libafl_cc` (`-fsanitize=dataflow -fsanitize-coverage=trace-cmp`).
+//!
+//! Each execution labels up to [`DFSAN_LABELS`] ranges of the input, one label bit each, and
+//! records the comparisons of the target along with the union of the labels of their operands.
+//! The [`crate::DfsanTracingStage`] refines the ranges over several executions, down to the input
+//! offsets each comparison depends on.
+//!
+//! The target needs LLVM 13 or later, whose `DataFlowSanitizer` routes the `trace-cmp` callbacks
+//! to the `__dfsw_` hooks defined here, with the labels of the compared values.
+
+use alloc::vec::Vec;
+use core::{
+    ffi::{CStr, c_char, c_void},
+    mem,
+    ops::Range,
+};
+
+use libafl::observers::{CmpValues, CmplogBytes};
+
+/// The number of labels of `DataFlowSanitizer`, one bit each
+pub const DFSAN_LABELS: usize = 8;
+
+/// The max number of tainted comparisons recorded per execution
+pub const DFSAN_MAX_CMPS: usize = 1 << 16;
+
+unsafe extern "C" {
+    fn __libafl_targets_dfsan_set_label(label: u8, addr: *mut c_void, size: usize);
+    fn __libafl_targets_dfsan_read_label(addr: *const c_void, size: usize) -> u8;
+    fn __libafl_targets_dfsan_test_one_input(data: *const u8, size: usize) -> i32;
+}
+
+/// A comparison of the target that depends on the labeled input
+#[derive(Debug, Clone)]
+pub struct DfsanCmp {
+    /// The index of the comparison among all the comparisons of the execution
+    pub index: usize,
+    /// The compared values
+    pub values: CmpValues,
+    /// The union of the labels of the operands, one bit per labeled range
+    pub label: u8,
+}
+
+#[derive(Debug)]
+struct DfsanTaint {
+    ranges: Vec<Range<usize>>,
+    cmps: Vec<DfsanCmp>,
+    executed: usize,
+}
+
+static mut DFSAN_TAINT: DfsanTaint = DfsanTaint {
+    ranges: Vec::new(),
+    cmps: Vec::new(),
+    executed: 0,
+};
+
+/// # Safety
+/// The taint state is only used by the thread running the target, one hook at a time.
+unsafe fn dfsan_taint() -> &'static mut DfsanTaint {
+    let taint = &raw mut DFSAN_TAINT;
+    unsafe { &mut *taint }
+}
+
+/// Sets the ranges of the input to label in the next executions, `1 << i` for the `i`th range.
+///
+/// # Safety
+/// Not thread-safe, call it from the thread running the target, between executions.
+pub unsafe fn set_dfsan_ranges(ranges: &[Range<usize>]) {
+    assert!(
+        ranges.len() <= DFSAN_LABELS,
+        "DataFlowSanitizer has only {DFSAN_LABELS} labels"
+    );
+    let taint = unsafe { dfsan_taint() };
+    taint.ranges.clear();
+    taint.ranges.extend_from_slice(ranges);
+}
+
+/// Takes the tainted comparisons recorded since the input was labeled.
+///
+/// # Safety
+/// Not thread-safe, call it from the thread running the target, between executions.
+#[must_use]
+pub unsafe fn take_dfsan_cmps() -> Vec<DfsanCmp> {
+    mem::take(unsafe { &mut dfsan_taint().cmps })
+}
+
+/// Labels the bytes of the input with the ranges of [`set_dfsan_ranges`], and starts recording
+/// the comparisons. Call it on the input buffer, right before the target runs.
+///
+/// # Safety
+/// Sets the shadow memory of `DataFlowSanitizer`, not thread-safe.
+pub unsafe fn dfsan_label_input(buf: &mut [u8]) {
+    let taint = unsafe { dfsan_taint() };
+    taint.cmps.clear();
+    taint.executed = 0;
+    unsafe {
+        __libafl_targets_dfsan_set_label(0, buf.as_mut_ptr().cast(), buf.len());
+    }
+    for (bit, range) in taint.ranges.iter().enumerate() {
+        let range = range.start.min(buf.len())..range.end.min(buf.len());
+        unsafe {
+            __libafl_targets_dfsan_set_label(
+                1 << bit,
+                buf[range.clone()].as_mut_ptr().cast(),
+                range.len(),
+            );
+        }
+    }
+}
+
+/// Calls the `DataFlowSanitizer` build of a libfuzzer-style harness on the labeled input.
+///
+/// # Safety
+/// Calls the libfuzzer harness, and sets the shadow memory of `DataFlowSanitizer`.
+#[expect(clippy::must_use_candidate)]
+pub unsafe fn libfuzzer_dfsan_test_one_input(buf: &[u8]) -> i32 {
+    // a fresh copy, not to carry labels over between executions
+    let mut buf = buf.to_vec();
+    unsafe {
+        dfsan_label_input(&mut buf);
+        __libafl_targets_dfsan_test_one_input(buf.as_ptr(), buf.len())
+    }
+}
+
+/// Records a comparison of the target, if tainted
+fn dfsan_record(values: CmpValues, label: u8) {
+    // # Safety
+    // The hooks run on the thread running the target.
+    let taint = unsafe { dfsan_taint() };
+    let index = taint.executed;
+    taint.executed += 1;
+    if label != 0 && taint.cmps.len() < DFSAN_MAX_CMPS {
+        taint.cmps.push(DfsanCmp {
+            index,
+            values,
+            label,
+        });
+    }
+}
+
+/// Records a comparison of buffers, at most as long as a [`CmplogBytes`]
+unsafe fn dfsan_record_bytes(s1: *const u8, s2: *const u8, len: usize) {
+    let len = len.min(32);
+    if len == 0 {
+        return;
+    }
+    let mut v0 = [0; 32];
+    let mut v1 = [0; 32];
+    let label = unsafe {
+        v0[..len].copy_from_slice(core::slice::from_raw_parts(s1, len));
+        v1[..len].copy_from_slice(core::slice::from_raw_parts(s2, len));
+        __libafl_targets_dfsan_read_label(s1.cast(), len)
+            | __libafl_targets_dfsan_read_label(s2.cast(), len)
+    };
+    dfsan_record(
+        CmpValues::Bytes((
+            CmplogBytes::from_buf_and_len(v0, len as u8),
+            CmplogBytes::from_buf_and_len(v1, len as u8),
+        )),
+        label,
+    );
+}
+
+macro_rules! dfsan_trace_cmp {
+    ($name:ident, $const_name:ident, $ty:ty, $variant:ident) => {
+        /// The `trace-cmp` callback of `DataFlowSanitizer`, with the labels of the operands
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $name(arg1: $ty, arg2: $ty, label1: u8, label2: u8) {
+            dfsan_record(CmpValues::$variant((arg1, arg2, false)), label1 | label2);
+        }
+
+        /// The `trace-cmp` callback of `DataFlowSanitizer` for constants, with the labels of
+        /// the operands
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $const_name(arg1: $ty, arg2: $ty, label1: u8, label2: u8) {
+            dfsan_record(CmpValues::$variant((arg1, arg2, true)), label1 | label2);
+        }
+    };
+}
+
+dfsan_trace_cmp!(
+    __dfsw___sanitizer_cov_trace_cmp1,
+    __dfsw___sanitizer_cov_trace_const_cmp1,
+    u8,
+    U8
+);
+dfsan_trace_cmp!(
+    __dfsw___sanitizer_cov_trace_cmp2,
+    __dfsw___sanitizer_cov_trace_const_cmp2,
+    u16,
+    U16
+);
+dfsan_trace_cmp!(
+    __dfsw___sanitizer_cov_trace_cmp4,
+    __dfsw___sanitizer_cov_trace_const_cmp4,
+    u32,
+    U32
+);
+dfsan_trace_cmp!(
+    __dfsw___sanitizer_cov_trace_cmp8,
+    __dfsw___sanitizer_cov_trace_const_cmp8,
+    u64,
+    U64
+);
+
+/// The `trace-switch` callback of `DataFlowSanitizer`, with the label of the value
+///
+/// # Safety
+/// Dereferences `cases`, as passed by the `sancov` instrumentation.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn __dfsw___sanitizer_cov_trace_switch(
+    val: u64,
+    cases: *const u64,
+    val_label: u8,
+    _cases_label: u8,
+) {
+    let (count, bits) = unsafe { (*cases, *cases.add(1)) };
+    for i in 0..count as usize {
+        let case = unsafe { *cases.add(i + 2) };
+        let values = match bits {
+            8 => CmpValues::U8((val as u8, case as u8, false)),
+            16 => CmpValues::U16((val as u16, case as u16, false)),
+            32 => CmpValues::U32((val as u32, case as u32, false)),
+            _ => CmpValues::U64((val, case, false)),
+        };
+        dfsan_record(values, val_label);
+    }
+}
+
+/// The `memcmp` hook of `DataFlowSanitizer`
+///
+/// # Safety
+/// Reads `n` bytes of `s1` and `s2`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dfsan_weak_hook_memcmp(
+    _caller_pc: usize,
+    s1: *const c_void,
+    s2: *const c_void,
+    n: usize,
+    _s1_label: u8,
+    _s2_label: u8,
+    _n_label: u8,
+) {
+    unsafe { dfsan_record_bytes(s1.cast(), s2.cast(), n) };
+}
+
+/// The `strncmp` hook of `DataFlowSanitizer`
+///
+/// # Safety
+/// Reads the strings `s1` and `s2`, up to `n` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dfsan_weak_hook_strncmp(
+    _caller_pc: usize,
+    s1: *const c_char,
+    s2: *const c_char,
+    n: usize,
+    _s1_label: u8,
+    _s2_label: u8,
+    _n_label: u8,
+) {
+    unsafe {
+        let len = CStr::from_ptr(s1)
+            .count_bytes()
+            .min(CStr::from_ptr(s2).count_bytes())
+            .min(n);
+        dfsan_record_bytes(s1.cast(), s2.cast(), len);
+    }
+}
+
+/// The `strcmp` hook of `DataFlowSanitizer`
+///
+/// # Safety
+/// Reads the strings `s1` and `s2`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dfsan_weak_hook_strcmp(
+    _caller_pc: usize,
+    s1: *const c_char,
+    s2: *const c_char,
+    _s1_label: u8,
+    _s2_label: u8,
+) {
+    unsafe {
+        let len = CStr::from_ptr(s1)
+            .count_bytes()
+            .min(CStr::from_ptr(s2).count_bytes());
+        dfsan_record_bytes(s1.cast(), s2.cast(), len);
+    }
+}
@@ -11,13 +11,17 @@ use libafl::executors::forkserver::FS_NEW_OPT_AUTODTCT;
 #[cfg(feature = "cmplog")]
 use libafl::executors::forkserver::SHM_CMPLOG_ENV_VAR;
 use libafl::executors::forkserver::{
-    AFL_MAP_SIZE_ENV_VAR, FORKSRV_FD, FS_ERROR_SHM_OPEN, FS_NEW_OPT_MAPSIZE,
-    FS_NEW_OPT_SHDMEM_FUZZ, FS_NEW_VERSION_MAX, FS_OPT_ERROR, MAX_INPUT_SIZE_DEFAULT, SHM_ENV_VAR,
-    SHM_FUZZ_ENV_VAR, SHM_FUZZ_MAP_SIZE_ENV_VAR, SHMEM_FUZZ_HDR_SIZE,
+    AFL_MAP_SIZE_ENV_VAR, FORKSRV_FD, FS_ERROR_SHM_OPEN, FS_NEW_OPT_ENV_INPUT, FS_NEW_OPT_MAPSIZE,
+    FS_NEW_OPT_SHDMEM_FUZZ, FS_NEW_VERSION_MAX, FS_OPT_ERROR, INPUT_ENV_FILE_ENV_VAR,
+    INPUT_ENV_NAME_ENV_VAR, MAX_INPUT_SIZE_DEFAULT, SHM_ENV_VAR, SHM_FUZZ_ENV_VAR,
+    SHM_FUZZ_MAP_SIZE_ENV_VAR, SHMEM_FUZZ_HDR_SIZE,
 };
+#[cfg(target_os = "linux")]
+use libafl::executors::limits::CGROUP_PROCS_ENV_VAR;
 use libafl_bolts::{
     Error,
     os::{ChildHandle, ForkResult},
+    target_args::env_input_value,
 };
 use nix::{
     sys::signal::{SigHandler, Signal},
@@ -378,6 +382,44 @@ impl ForkserverParent for MaybePersistentForkserverParent {
     }
 }
 
+/// Set the environment variable named by [`INPUT_ENV_NAME_ENV_VAR`] to the current input, for
+/// targets that receive their input via [`libafl_bolts::InputLocation::Env`].
+///
+/// The input is taken from the shared memory if shared memory fuzzing is enabled, else from the
+/// file named by [`INPUT_ENV_FILE_ENV_VAR`].
+fn set_env_input() -> Result<(), Error> {
+    let Some(name) = std::env::var_os(INPUT_ENV_NAME_ENV_VAR) else {
+        return Ok(());
+    };
+    let input = if unsafe { SHM_FUZZING == 1 } {
+        // # Safety
+        // The input shared memory was mapped before the forkserver was started.
+        unsafe { core::slice::from_raw_parts(INPUT_PTR, *INPUT_LENGTH_PTR as usize) }.to_vec()
+    } else {
+        let path = std::env::var_os(INPUT_ENV_FILE_ENV_VAR)
+            .ok_or_else(|| Error::illegal_state("no file to read the input from"))?;
+        std::fs::read(path)?
+    };
+    // # Safety
+    // The freshly forked child is single-threaded.
+    unsafe {
+        std::env::set_var(name, env_input_value(&input));
+    }
+    Ok(())
+}
+
+/// Move the freshly forked child into the cgroup named by [`CGROUP_PROCS_ENV_VAR`], before it runs
+/// any target code, so all of its memory is charged to the cgroup.
+///
+/// Failures are ignored, the fuzzer moves the child once it learns its pid, anyway.
+#[cfg(target_os = "linux")]
+fn join_cgroup() {
+    if let Some(procs) = std::env::var_os(CGROUP_PROCS_ENV_VAR) {
+        // Writing 0 moves the writing process itself.
+        drop(std::fs::write(procs, "0"));
+    }
+}
+
 /// Success state when [`start_forkserver`] returned.
 #[derive(Debug)]
 pub enum ForkserverState {
@@ -432,7 +474,8 @@ fn start_forkserver_internal<P: ForkserverParent>(
         ));
     }
 
-    let mut status = FS_NEW_OPT_MAPSIZE;
+    // We always set the input in the environment of the children, if asked to
+    let mut status = FS_NEW_OPT_MAPSIZE | FS_NEW_OPT_ENV_INPUT;
     if sharedmem_fuzzing {
         status |= FS_NEW_OPT_SHDMEM_FUZZ;
     }
@@ -481,6 +524,9 @@ fn start_forkserver_internal<P: ForkserverParent>(
                 // FORKSRV_FD is for communication with AFL, we don't need it in the child
                 let _ = nix::unistd::close(FORKSRV_R_FD.as_raw_fd());
                 let _ = nix::unistd::close(FORKSRV_W_FD.as_raw_fd());
+                #[cfg(target_os = "linux")]
+                join_cgroup();
+                set_env_input()?;
                 return Ok(ForkserverState::Child);
             }
             ForkResult::Parent(child_pid) => {
@@ -64,9 +64,13 @@ include!(concat!(env!("OUT_DIR"), "/constants.rs"));
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
     feature = "sancov_ctx",
-    feature = "sancov_pcguard_dump_cov"
+    feature = "sancov_pcguard_dump_cov",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "sancov_pcguard_coverage_report"
 ))]
 pub mod sancov_pcguard;
+#[cfg(feature = "sancov_pcguard_coverage_report")]
+pub mod sancov_pcguard_coverage_report;
 #[cfg(feature = "sancov_pcguard_dump_cov")]
 pub mod sancov_pcguard_dump_cov;
 #[cfg(any(
@@ -75,7 +79,9 @@ pub mod sancov_pcguard_dump_cov;
     feature = "sancov_ngram4",
     feature = "sancov_ngram8",
     feature = "sancov_ctx",
-    feature = "sancov_pcguard_dump_cov"
+    feature = "sancov_pcguard_dump_cov",
+    feature = "sancov_pcguard_hitcounts32",
+    feature = "sancov_pcguard_coverage_report"
 ))]
 pub use sancov_pcguard::*;
 
@@ -119,6 +125,16 @@ pub use coverage::*;
 pub mod value_profile;
 pub use value_profile::*;
 
+#[cfg(feature = "data_coverage")]
+pub mod data_coverage;
+#[cfg(feature = "data_coverage")]
+pub use data_coverage::*;
+
+#[cfg(feature = "dfsan")]
+pub mod dfsan;
+#[cfg(feature = "dfsan")]
+pub use dfsan::*;
+
 /// The module to hook call instructions
 #[cfg(feature = "function-logging")]
 pub mod call;
@@ -132,6 +148,11 @@ pub use cmps::*;
 #[cfg(feature = "std")]
 pub mod drcov;
 
+#[cfg(all(unix, feature = "malloc_hooks"))]
+pub mod malloc_hooks;
+#[cfg(all(unix, feature = "malloc_hooks"))]
+pub use malloc_hooks::LsanLeakChecker;
+
 #[cfg(all(windows, feature = "std", feature = "windows_asan"))]
 pub mod windows_asan;
 #[cfg(all(windows, feature = "std", feature = "windows_asan"))]
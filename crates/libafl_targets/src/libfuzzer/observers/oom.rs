@@ -33,6 +33,8 @@ static MALLOC_SIZE: AtomicUsize = AtomicUsize::new(0);
 /// Is only safe to call with valid freshly allocated pointers backed by allocations of `size`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn __sanitizer_malloc_hook(ptr: *const c_void, size: usize) {
+    libafl::observers::memory::record_allocation(size);
+    libafl::observers::leak::record_malloc();
     if RUNNING.load(Ordering::Relaxed) {
         let size = match unsafe { libafl_check_malloc_size(ptr) } {
             0 => size, // either the malloc size function didn't work or it's really zero-sized
@@ -58,6 +60,7 @@ pub unsafe extern "C" fn __sanitizer_malloc_hook(ptr: *const c_void, size: usize
 /// Is only safe to call with valid allocated pointers, about to be freed.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn __sanitizer_free_hook(ptr: *const c_void) {
+    libafl::observers::leak::record_free();
     if RUNNING.load(Ordering::Relaxed) {
         let size = unsafe { libafl_check_malloc_size(ptr) };
         MALLOC_SIZE
@@ -0,0 +1,39 @@
+#include "common.h"
+
+#include <stddef.h>
+
+EXT_FUNC(__lsan_enable, void, (void), false);
+EXT_FUNC(__lsan_disable, void, (void), false);
+EXT_FUNC(__lsan_do_recoverable_leak_check, int, (void), false);
+EXT_FUNC(__sanitizer_set_report_path, void, (const char *), false);
+EXT_FUNC(__sanitizer_get_report_path, const char *, (void), false);
+
+EXPORT_FN int libafl_targets_has_lsan(void) {
+  return CHECK_WEAK_FN(__lsan_do_recoverable_leak_check);
+}
+
+// trust the user to check this appropriately :)
+EXPORT_FN void libafl_targets_lsan_enable(void) {
+  __lsan_enable();
+}
+
+// trust the user to check this appropriately :)
+EXPORT_FN void libafl_targets_lsan_disable(void) {
+  __lsan_disable();
+}
+
+// trust the user to check this appropriately :)
+EXPORT_FN int libafl_targets_lsan_leak_check(void) {
+  return __lsan_do_recoverable_leak_check();
+}
+
+// trust the user to check this appropriately :)
+EXPORT_FN void libafl_targets_sanitizer_set_report_path(const char *path) {
+  __sanitizer_set_report_path(path);
+}
+
+// NULL if the runtime is too old to tell
+EXPORT_FN const char *libafl_targets_sanitizer_get_report_path(void) {
+  if (!CHECK_WEAK_FN(__sanitizer_get_report_path)) { return NULL; }
+  return __sanitizer_get_report_path();
+}
@@ -0,0 +1,271 @@
+//! The malloc and free hooks of the sanitizer allocators, reporting the allocations of the target
+//! to the [`libafl::observers::MemoryObserver`] and the [`libafl::observers::LeakObserver`], and
+//! the [`LsanLeakChecker`] confirming leaks with `LeakSanitizer`.
+//!
+//! With `libfuzzer_oom`, its hooks report the allocations instead.
+
+use alloc::{
+    ffi::CString,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(not(feature = "libfuzzer_oom"))]
+use core::ffi::c_void;
+use core::ffi::{CStr, c_char};
+use std::{fs, path::PathBuf};
+
+use hashbrown::HashMap;
+#[cfg(not(feature = "libfuzzer_oom"))]
+use libafl::observers::{leak, memory};
+use libafl::{Error, executors::LeakChecker};
+
+unsafe extern "C" {
+    fn libafl_targets_has_lsan() -> i32;
+    fn libafl_targets_lsan_enable();
+    fn libafl_targets_lsan_disable();
+    fn libafl_targets_lsan_leak_check() -> i32;
+    fn libafl_targets_sanitizer_set_report_path(path: *const c_char);
+    fn libafl_targets_sanitizer_get_report_path() -> *const c_char;
+}
+
+/// malloc hook which will be invoked if a sanitizer is present, records each allocation for the
+/// [`libafl::observers::MemoryObserver`] and the [`libafl::observers::LeakObserver`].
+#[cfg(not(feature = "libfuzzer_oom"))]
+#[unsafe(no_mangle)]
+pub extern "C" fn __sanitizer_malloc_hook(_ptr: *const c_void, size: usize) {
+    memory::record_allocation(size);
+    leak::record_malloc();
+}
+
+/// free hook which will be invoked if a sanitizer is present, records each free for the
+/// [`libafl::observers::LeakObserver`].
+#[cfg(not(feature = "libfuzzer_oom"))]
+#[unsafe(no_mangle)]
+pub extern "C" fn __sanitizer_free_hook(_ptr: *const c_void) {
+    leak::record_free();
+}
+
+/// Confirms leaks with `LeakSanitizer`, for the [`libafl::executors::LeakCheckExecutor`].
+///
+/// The target needs to be built with `-fsanitize=address` or `-fsanitize=leak`, and leak detection
+/// needs to be enabled with `detect_leaks=1` in `ASAN_OPTIONS` or `LSAN_OPTIONS`. Leak detection is
+/// disabled outside of the re-runs, so leaks of other runs are not blamed on the re-run input.
+///
+/// Leaked memory stays leaked, so each check only reports the leaks that are new since the last
+/// one, including the leaks from before the checker was created.
+/// The sanitizer writes the reports of the checks to a log of the checker. If the sanitizer
+/// logs to a file (`log_path`), that log is reopened, and so truncated, after each check.
+#[derive(Debug)]
+pub struct LsanLeakChecker {
+    /// The report path of the sanitizer, restored after each check
+    report_path: CString,
+    /// The number of leaked objects of each leak seen in the last check
+    leaked: HashMap<String, usize>,
+}
+
+impl LsanLeakChecker {
+    /// Creates a new [`LsanLeakChecker`], if the target was built with `LeakSanitizer`
+    #[must_use]
+    pub fn new() -> Option<Self> {
+        // # Safety
+        // Only checks if the weak symbol is defined.
+        if unsafe { libafl_targets_has_lsan() } == 0 {
+            return None;
+        }
+        // # Safety
+        // `LeakSanitizer` is present.
+        unsafe { libafl_targets_lsan_disable() };
+        let mut checker = Self {
+            report_path: current_report_path(),
+            leaked: HashMap::new(),
+        };
+        if let Err(err) = checker.new_leaks() {
+            log::warn!("Failed to check for leaks from before the leak checker: {err}");
+        }
+        Some(checker)
+    }
+
+    /// Runs a leak check, returning the report of the leaks that are new since the last one
+    fn new_leaks(&mut self) -> Result<Option<String>, Error> {
+        let prefix = std::env::temp_dir().join(format!("libafl_lsan_{}", std::process::id()));
+        let log_path = PathBuf::from(format!("{}.{}", prefix.display(), std::process::id()));
+        let prefix = CString::new(prefix.to_string_lossy().into_owned())
+            .map_err(|_| Error::illegal_state("The temp dir contains a nul byte"))?;
+
+        // # Safety
+        // `LeakSanitizer` is present, the paths are valid C strings.
+        let leaked = unsafe {
+            libafl_targets_sanitizer_set_report_path(prefix.as_ptr());
+            let leaked = libafl_targets_lsan_leak_check() != 0;
+            libafl_targets_sanitizer_set_report_path(self.report_path.as_ptr());
+            leaked
+        };
+        let report = if log_path.exists() {
+            let report = fs::read_to_string(&log_path)?;
+            fs::remove_file(&log_path)?;
+            report
+        } else {
+            String::new()
+        };
+        if !leaked {
+            self.leaked.clear();
+            return Ok(None);
+        }
+
+        let mut leaked = HashMap::new();
+        let mut new_leaks = Vec::new();
+        // The leaks are separated by empty lines, each with a header and the allocation stack
+        for leak in report.split("\n\n") {
+            let leak = leak.trim_matches('\n');
+            let Some((header, stack)) = leak.split_once('\n') else {
+                continue;
+            };
+            let Some(objects) = leaked_objects(header) else {
+                continue;
+            };
+            let kind = header.split_whitespace().next().unwrap_or_default();
+            let key = format!("{kind}\n{stack}");
+            if objects > self.leaked.get(&key).copied().unwrap_or_default() {
+                new_leaks.push(leak.to_string());
+            }
+            leaked.insert(key, objects);
+        }
+        self.leaked = leaked;
+
+        if new_leaks.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(new_leaks.join("\n\n")))
+        }
+    }
+}
+
+/// The report path the sanitizer uses now, `stderr` unless it logs to a file
+fn current_report_path() -> CString {
+    // # Safety
+    // Returns a C string owned by the sanitizer, or null.
+    let path = unsafe { libafl_targets_sanitizer_get_report_path() };
+    let path = if path.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(path) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    // The sanitizer appends the pid to the prefix it was given
+    let path = path
+        .strip_suffix(&format!(".{}", std::process::id()))
+        .map(ToString::to_string)
+        .unwrap_or(path);
+    if path.is_empty() {
+        return c"stderr".into();
+    }
+    CString::new(path).unwrap_or_else(|_| c"stderr".into())
+}
+
+/// The number of leaked objects in the header of a leak, like
+/// `Direct leak of 16 byte(s) in 1 object(s) allocated from:`
+fn leaked_objects(header: &str) -> Option<usize> {
+    if !header.contains(" leak of ") {
+        return None;
+    }
+    header
+        .split_once(" in ")?
+        .1
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+impl LeakChecker for LsanLeakChecker {
+    fn pre_rerun(&mut self) -> Result<(), Error> {
+        // # Safety
+        // `LeakSanitizer` is present, and disabled since the creation of the checker.
+        unsafe { libafl_targets_lsan_enable() };
+        Ok(())
+    }
+
+    fn check(&mut self) -> Result<Option<String>, Error> {
+        // # Safety
+        // `LeakSanitizer` is present, and enabled by `pre_rerun`.
+        unsafe { libafl_targets_lsan_disable() };
+        self.new_leaks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use std::{path::Path, process::Command, thread};
+
+    use libafl::executors::LeakChecker;
+
+    use super::LsanLeakChecker;
+
+    /// Set in the run of the test with `LeakSanitizer` preloaded
+    const LSAN_RUN_ENV: &str = "LIBAFL_TARGETS_TEST_LSAN_RUN";
+
+    /// Leaks from a thread of its own, so no pointer to the leak stays on the stack
+    fn leak(size: usize) {
+        thread::spawn(move || core::mem::forget(vec![1_u8; size]))
+            .join()
+            .unwrap();
+    }
+
+    fn check_leaks() {
+        let mut checker = LsanLeakChecker::new().expect("LeakSanitizer is not preloaded");
+
+        checker.pre_rerun().unwrap();
+        assert_eq!(checker.check().unwrap(), None);
+
+        checker.pre_rerun().unwrap();
+        leak(1234);
+        let report = checker.check().unwrap().expect("The leak was not found");
+        assert!(report.contains("Direct leak of 1234 byte(s) in 1 object(s)"));
+
+        // The same leak is not reported again
+        checker.pre_rerun().unwrap();
+        assert_eq!(checker.check().unwrap(), None);
+
+        // A new leak from the same site is
+        checker.pre_rerun().unwrap();
+        leak(1234);
+        let report = checker
+            .check()
+            .unwrap()
+            .expect("The new leak was not found");
+        assert!(report.contains("Direct leak of 2468 byte(s) in 2 object(s)"));
+    }
+
+    #[test]
+    #[ignore = "needs libasan, and ptrace which is often forbidden in containers and CI"]
+    fn test_lsan_leak_checker() {
+        if std::env::var_os(LSAN_RUN_ENV).is_some() {
+            check_leaks();
+            return;
+        }
+
+        // Run this test again, with the `LeakSanitizer` of libasan preloaded
+        let output = Command::new("cc")
+            .arg("-print-file-name=libasan.so")
+            .output()
+            .unwrap();
+        let libasan = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        assert!(Path::new(&libasan).is_absolute(), "libasan not found");
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "malloc_hooks::tests::test_lsan_leak_checker",
+                "--include-ignored",
+                "--nocapture",
+            ])
+            .env(LSAN_RUN_ENV, "1")
+            .env("LD_PRELOAD", libasan)
+            .env("ASAN_OPTIONS", "detect_leaks=1:leak_check_at_exit=0")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
@@ -20,6 +20,8 @@ use libafl::executors::hooks::ExecutorHook;
 use crate::EDGES_MAP_DEFAULT_SIZE;
 #[cfg(feature = "coverage")]
 use crate::coverage::EDGES_MAP;
+#[cfg(feature = "sancov_pcguard_hitcounts32")]
+use crate::coverage::EDGES_MAP_32;
 #[cfg(feature = "coverage")]
 use crate::coverage::MAX_EDGES_FOUND;
 #[cfg(feature = "pointer_maps")]
@@ -265,6 +267,12 @@ pub(crate) unsafe fn sanitizer_cov_pcguard_impl(guard: *mut u32) {
                 *p = val;
             }
         }
+
+        #[cfg(feature = "sancov_pcguard_hitcounts32")]
+        {
+            let p = (&raw mut EDGES_MAP_32 as *mut u32).add(pos);
+            *p = (*p).saturating_add(1);
+        }
     }
 }
 
@@ -413,3 +421,24 @@ pub fn sanitizer_cov_pc_table<'a>() -> impl Iterator<Item = &'a [PcTableEntry]>
         pc_tables.iter().copied()
     }
 }
+
+/// Returns the PC of the block of the given edge, from the PC tables.
+///
+/// The edges are numbered in the order of the PC tables, so this only holds for plain pcguard
+/// coverage, not with the `sancov_ngram4`, `sancov_ngram8` or `sancov_ctx` features.
+#[must_use]
+pub fn sanitizer_cov_pc_of_edge(edge: usize) -> Option<usize> {
+    sanitizer_cov_pc_table()
+        .flat_map(<[_]>::iter)
+        .nth(edge)
+        .map(PcTableEntry::addr)
+}
+
+/// Returns the edge of the block at the given PC, from the PC tables, see
+/// [`sanitizer_cov_pc_of_edge`].
+#[must_use]
+pub fn sanitizer_cov_edge_of_pc(pc: usize) -> Option<usize> {
+    sanitizer_cov_pc_table()
+        .flat_map(<[_]>::iter)
+        .position(|entry| entry.addr() == pc)
+}
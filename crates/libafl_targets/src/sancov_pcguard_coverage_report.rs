@@ -0,0 +1,364 @@
+//! Source-level coverage reports of a corpus, as [`lcov`](https://github.com/linux-test-project/lcov)
+//! `.info` file and as simple HTML view, listing the functions that are never reached.
+//!
+//! The target needs to be built with `-fsanitize-coverage=trace-pc-guard,pc-table` and debug info.
+//! The guards are mapped back to the PCs of their blocks with the sancov PC table, the PCs are
+//! symbolized with the `addr2line`-based symbolizer of `backtrace`.
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use libafl::{Error, corpus::CorpusId, stages::ReplayHook};
+
+use crate::{EDGES_MAP_ALLOCATED_SIZE, coverage::edges_map_mut_ptr, sanitizer_cov_pc_table};
+
+/// The coverage of a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    /// The (demangled) name of the function
+    pub name: String,
+    /// The line of the entry block of the function
+    pub line: u32,
+    /// The number of inputs that reached the function
+    pub hits: u64,
+}
+
+/// The coverage of a source file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileCoverage {
+    /// The number of inputs that reached each instrumented line
+    pub lines: BTreeMap<u32, u64>,
+    /// The instrumented functions of the file
+    pub functions: Vec<FunctionCoverage>,
+}
+
+impl FileCoverage {
+    /// The number of lines that were reached, and of all instrumented lines
+    #[must_use]
+    pub fn lines_hit(&self) -> (usize, usize) {
+        let hit = self.lines.values().filter(|hits| **hits > 0).count();
+        (hit, self.lines.len())
+    }
+
+    /// The number of functions that were reached, and of all instrumented functions
+    #[must_use]
+    pub fn functions_hit(&self) -> (usize, usize) {
+        let hit = self.functions.iter().filter(|f| f.hits > 0).count();
+        (hit, self.functions.len())
+    }
+}
+
+/// Accumulates the coverage of a corpus, per sancov guard, for source-level coverage reports.
+///
+/// Call [`CoverageReport::record`] after each run, or replay the corpus with a
+/// [`CoverageReportHook`] in a `ReplayStage`, then write the report with
+/// [`CoverageReport::write_lcov`] and [`CoverageReport::write_html`].
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    hits: Vec<u64>,
+}
+
+impl CoverageReport {
+    /// Creates a new, empty [`CoverageReport`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of guards, i.e. of entries of all sancov PC tables
+    fn guards() -> usize {
+        sanitizer_cov_pc_table().map(<[_]>::len).sum()
+    }
+
+    /// Records the coverage of the last run from the edges map, counting each guard it reached once.
+    pub fn record(&mut self) {
+        let guards = Self::guards();
+        if self.hits.len() < guards {
+            self.hits.resize(guards, 0);
+        }
+        let map = edges_map_mut_ptr();
+        for (guard, hits) in self.hits.iter_mut().enumerate() {
+            // # Safety
+            // The guard indices are the positions written by the pcguard runtime.
+            if unsafe { map.add(guard % EDGES_MAP_ALLOCATED_SIZE).read() } != 0 {
+                *hits += 1;
+            }
+        }
+    }
+
+    /// Clears the edges map, so the next run only reports its own coverage
+    pub fn reset_map(&self) {
+        let map = edges_map_mut_ptr();
+        // # Safety
+        // Only writes to the edges map, within its allocated size.
+        unsafe { map.write_bytes(0, Self::guards().min(EDGES_MAP_ALLOCATED_SIZE)) };
+    }
+
+    /// Maps the recorded coverage back to the source, per file.
+    ///
+    /// Blocks without debug info are left out.
+    #[must_use]
+    pub fn files(&self) -> BTreeMap<String, FileCoverage> {
+        let mut files: BTreeMap<String, FileCoverage> = BTreeMap::new();
+        let entries = sanitizer_cov_pc_table().flat_map(<[_]>::iter);
+        for (guard, entry) in entries.enumerate() {
+            let hits = self.hits.get(guard).copied().unwrap_or_default();
+
+            let mut location = None;
+            // the table holds the start of each block, the symbolizer expects the next instruction
+            backtrace::resolve((entry.addr() + 1) as *mut _, |symbol| {
+                if location.is_none()
+                    && let (Some(filename), Some(line)) = (symbol.filename(), symbol.lineno())
+                {
+                    let name = symbol.name().map(|name| name.to_string());
+                    location = Some((filename.display().to_string(), line, name));
+                }
+            });
+            let Some((filename, line, name)) = location else {
+                continue;
+            };
+
+            let file = files.entry(filename).or_default();
+            let line_hits = file.lines.entry(line).or_default();
+            *line_hits = (*line_hits).max(hits);
+            if entry.is_function_entry() {
+                file.functions.push(FunctionCoverage {
+                    name: name.unwrap_or_else(|| format!("{:#x}", entry.addr())),
+                    line,
+                    hits,
+                });
+            }
+        }
+        files
+    }
+
+    /// The functions that were never reached by any recorded run, with their file
+    #[must_use]
+    pub fn unreached_functions(&self) -> Vec<(String, FunctionCoverage)> {
+        unreached_functions(&self.files())
+    }
+
+    /// Writes the report as `lcov` `.info` file, e.g. for `genhtml`
+    pub fn write_lcov<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        write_lcov(&mut file, &self.files())?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Writes the report as simple HTML view to `output_dir`, starting at `index.html`
+    pub fn write_html<P: AsRef<Path>>(&self, output_dir: P) -> Result<(), Error> {
+        write_html(output_dir.as_ref(), &self.files())
+    }
+}
+
+/// A [`ReplayHook`] recording the coverage of each replayed input in a [`CoverageReport`]
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReportHook {
+    report: CoverageReport,
+}
+
+impl CoverageReportHook {
+    /// Creates a new [`CoverageReportHook`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The coverage recorded so far
+    #[must_use]
+    pub fn report(&self) -> &CoverageReport {
+        &self.report
+    }
+}
+
+impl<I, S> ReplayHook<I, S> for CoverageReportHook {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I, _id: CorpusId) -> Result<(), Error> {
+        self.report.reset_map();
+        Ok(())
+    }
+
+    fn post_exec(&mut self, _state: &mut S, _input: &I, _id: CorpusId) -> Result<(), Error> {
+        self.report.record();
+        Ok(())
+    }
+}
+
+fn unreached_functions(files: &BTreeMap<String, FileCoverage>) -> Vec<(String, FunctionCoverage)> {
+    files
+        .iter()
+        .flat_map(|(filename, file)| {
+            file.functions
+                .iter()
+                .filter(|function| function.hits == 0)
+                .map(|function| (filename.clone(), function.clone()))
+        })
+        .collect()
+}
+
+fn write_lcov<W: Write>(out: &mut W, files: &BTreeMap<String, FileCoverage>) -> Result<(), Error> {
+    for (filename, file) in files {
+        writeln!(out, "TN:")?;
+        writeln!(out, "SF:{filename}")?;
+        for function in &file.functions {
+            writeln!(out, "FN:{},{}", function.line, function.name)?;
+        }
+        for function in &file.functions {
+            writeln!(out, "FNDA:{},{}", function.hits, function.name)?;
+        }
+        let (functions_hit, functions_found) = file.functions_hit();
+        writeln!(out, "FNF:{functions_found}")?;
+        writeln!(out, "FNH:{functions_hit}")?;
+        for (line, hits) in &file.lines {
+            writeln!(out, "DA:{line},{hits}")?;
+        }
+        let (lines_hit, lines_found) = file.lines_hit();
+        writeln!(out, "LF:{lines_found}")?;
+        writeln!(out, "LH:{lines_hit}")?;
+        writeln!(out, "end_of_record")?;
+    }
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn percent((hit, found): (usize, usize)) -> String {
+    match (hit * 1000).checked_div(found) {
+        Some(permille) => format!("{}.{}% ({hit}/{found})", permille / 10, permille % 10),
+        None => "-".into(),
+    }
+}
+
+const HTML_HEADER: &str = "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><style>\
+body{font-family:sans-serif}table{border-collapse:collapse}td,th{padding:2px 8px;text-align:left}\
+pre{margin:0}.hit{background:#cfc}.miss{background:#fcc}</style>";
+
+fn write_html(output_dir: &Path, files: &BTreeMap<String, FileCoverage>) -> Result<(), Error> {
+    fs::create_dir_all(output_dir)?;
+
+    let mut index = BufWriter::new(File::create(output_dir.join("index.html"))?);
+    writeln!(
+        index,
+        "{HTML_HEADER}<title>Coverage report</title></head><body>"
+    )?;
+    writeln!(index, "<h1>Coverage report</h1><table>")?;
+    writeln!(
+        index,
+        "<tr><th>File</th><th>Lines</th><th>Functions</th></tr>"
+    )?;
+    for (i, (filename, file)) in files.iter().enumerate() {
+        writeln!(
+            index,
+            "<tr><td><a href=\"file_{i}.html\">{}</a></td><td>{}</td><td>{}</td></tr>",
+            escape_html(filename),
+            percent(file.lines_hit()),
+            percent(file.functions_hit())
+        )?;
+        write_html_file(&output_dir.join(format!("file_{i}.html")), filename, file)?;
+    }
+    writeln!(index, "</table><h2>Unreached functions</h2><table>")?;
+    writeln!(index, "<tr><th>Function</th><th>Location</th></tr>")?;
+    for (filename, function) in unreached_functions(files) {
+        writeln!(
+            index,
+            "<tr><td>{}</td><td>{}:{}</td></tr>",
+            escape_html(&function.name),
+            escape_html(&filename),
+            function.line
+        )?;
+    }
+    writeln!(index, "</table></body></html>")?;
+    index.flush()?;
+    Ok(())
+}
+
+/// Writes the source of a file, marking the instrumented lines as reached or not
+fn write_html_file(path: &Path, filename: &str, file: &FileCoverage) -> Result<(), Error> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let title = escape_html(filename);
+    writeln!(out, "{HTML_HEADER}<title>{title}</title></head><body>")?;
+    writeln!(out, "<h1>{title}</h1><a href=\"index.html\">back</a>")?;
+
+    // the source may not be available where the report is generated, list the lines instead
+    let source = fs::read_to_string(filename).unwrap_or_default();
+    let lines: Vec<(u32, &str)> = if source.is_empty() {
+        file.lines.keys().map(|line| (*line, "")).collect()
+    } else {
+        (1..).zip(source.lines()).collect()
+    };
+
+    writeln!(out, "<table>")?;
+    for (line, text) in lines {
+        let (class, hits) = match file.lines.get(&line) {
+            Some(0) => ("miss", "0".into()),
+            Some(hits) => ("hit", hits.to_string()),
+            None => ("", String::new()),
+        };
+        writeln!(
+            out,
+            "<tr class=\"{class}\"><td>{line}</td><td>{hits}</td><td><pre>{}</pre></td></tr>",
+            escape_html(text)
+        )?;
+    }
+    writeln!(out, "</table></body></html>")?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, string::String};
+
+    use super::{FileCoverage, FunctionCoverage, escape_html, unreached_functions, write_lcov};
+
+    #[test]
+    fn test_lcov_report() {
+        let mut file = FileCoverage::default();
+        file.lines.extend([(3, 2), (4, 2), (10, 0)]);
+        file.functions.extend([
+            FunctionCoverage {
+                name: "parse".into(),
+                line: 3,
+                hits: 2,
+            },
+            FunctionCoverage {
+                name: "never".into(),
+                line: 10,
+                hits: 0,
+            },
+        ]);
+        let files = BTreeMap::from([("src/parse.c".into(), file)]);
+
+        let mut lcov = vec![];
+        write_lcov(&mut lcov, &files).unwrap();
+        assert_eq!(
+            String::from_utf8(lcov).unwrap(),
+            "TN:\nSF:src/parse.c\nFN:3,parse\nFN:10,never\nFNDA:2,parse\nFNDA:0,never\n\
+             FNF:2\nFNH:1\nDA:3,2\nDA:4,2\nDA:10,0\nLF:3\nLH:2\nend_of_record\n"
+        );
+
+        let unreached = unreached_functions(&files);
+        assert_eq!(unreached.len(), 1);
+        assert_eq!(unreached[0].1.name, "never");
+        assert_eq!(escape_html("a<b>&\""), "a&lt;b&gt;&amp;&quot;");
+    }
+}
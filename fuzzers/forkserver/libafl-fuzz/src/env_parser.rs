@@ -79,9 +79,18 @@ pub fn parse_envs(opt: &mut Opt) -> Result<(), Error> {
     } else {
         opt.stats_interval = AFL_FUZZER_STATS_UPDATE_INTERVAL_SECS;
     }
+    if let Ok(res) = std::env::var("AFL_NO_UI") {
+        opt.no_ui = parse_bool(&res)?;
+    }
     if let Ok(res) = std::env::var("AFL_BROKER_PORT") {
         opt.broker_port = Some(res.parse()?);
     }
+    if let Ok(res) = std::env::var("AFL_INPUT_ENV") {
+        opt.input_env = Some(res);
+    }
+    if let Ok(res) = std::env::var("AFL_INPUT_FIFO") {
+        opt.input_fifo = parse_bool(&res)?;
+    }
     if let Ok(res) = std::env::var("AFL_EXIT_ON_SEED_ISSUES") {
         opt.exit_on_seed_issues = parse_bool(&res)?;
     }
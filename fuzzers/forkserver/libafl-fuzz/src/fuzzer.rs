@@ -49,11 +49,11 @@ use libafl_bolts::shmem::{StdShMem, StdShMemProvider};
 use libafl_bolts::{
     core_affinity::CoreId,
     current_nanos, current_time,
-    fs::get_unique_std_input_file,
+    fs::{get_unique_std_input_file, InputFifo},
     rands::StdRand,
     shmem::{ShMem, ShMemProvider, UnixShMemProvider},
     tuples::{tuple_list, Handled, Merge},
-    AsSliceMut, StdTargetArgs,
+    AsSliceMut, InputLocation, StdTargetArgs,
 };
 #[cfg(feature = "nyx")]
 use libafl_nyx::{executor::NyxExecutor, helper::NyxHelper, settings::NyxSettings};
@@ -129,6 +129,18 @@ define_run_client!(state, mgr, fuzzer_dir, core_id, opt, is_main_node, {
     }
     let shmem_buf = shmem.as_slice_mut();
 
+    // All executors of this client run one after the other, so they share the named pipe.
+    let input_fifo = if opt.input_fifo
+        && opt
+            .target_args
+            .iter()
+            .any(|arg| arg == AFL_HARNESS_FILE_INPUT)
+    {
+        Some(InputFifo::create(harness_input_path(opt, fuzzer_dir))?)
+    } else {
+        None
+    };
+
     // If we are in Nyx Mode, we need to use a different map observer.
     #[cfg(feature = "nyx")]
     let (nyx_helper, edges_observer) = {
@@ -191,6 +203,8 @@ define_run_client!(state, mgr, fuzzer_dir, core_id, opt, is_main_node, {
         .version("0.13.2".to_string())
         .exec_timeout(opt.hang_timeout)
         .target_mode(fuzzer_target_mode(opt).to_string())
+        // the main node draws the status screen, unless AFL_NO_UI is set
+        .status_screen(is_main_node && !opt.no_ui)
         .build()
         .expect("invariant; should never occur");
 
@@ -339,7 +353,7 @@ define_run_client!(state, mgr, fuzzer_dir, core_id, opt, is_main_node, {
         } else {
             // Create the base Executor
             let mut executor_builder =
-                base_forkserver_builder(opt, &mut shmem_provider, fuzzer_dir);
+                base_forkserver_builder(opt, &mut shmem_provider, fuzzer_dir, input_fifo.as_ref());
             // Set a custom exit code to be interpreted as a Crash if configured.
             if let Some(crash_exitcode) = opt.crash_exitcode {
                 executor_builder = executor_builder.crash_exitcode(crash_exitcode);
@@ -362,7 +376,8 @@ define_run_client!(state, mgr, fuzzer_dir, core_id, opt, is_main_node, {
     #[cfg(not(feature = "nyx"))]
     let mut executor = {
         // Create the base Executor
-        let mut executor_builder = base_forkserver_builder(opt, &mut shmem_provider, fuzzer_dir);
+        let mut executor_builder =
+            base_forkserver_builder(opt, &mut shmem_provider, fuzzer_dir, input_fifo.as_ref());
         // Set a custom exit code to be interpreted as a Crash if configured.
         if let Some(crash_exitcode) = opt.crash_exitcode {
             executor_builder = executor_builder.crash_exitcode(crash_exitcode);
@@ -492,11 +507,12 @@ define_run_client!(state, mgr, fuzzer_dir, core_id, opt, is_main_node, {
 
         // Create the CmpLog executor.
         // Cmplog has 25% execution overhead so we give it double the timeout
-        let cmplog_executor = base_forkserver_builder(opt, &mut shmem_provider, fuzzer_dir)
-            .timeout(Duration::from_millis(opt.hang_timeout * 2))
-            .program(cmplog_executable_path)
-            .build(tuple_list!(cmplog_observer))
-            .unwrap();
+        let cmplog_executor =
+            base_forkserver_builder(opt, &mut shmem_provider, fuzzer_dir, input_fifo.as_ref())
+                .timeout(Duration::from_millis(opt.hang_timeout * 2))
+                .program(cmplog_executable_path)
+                .build(tuple_list!(cmplog_observer))
+                .unwrap();
 
         // Create the CmpLog tracing stage.
         let tracing = AflppCmplogTracingStage::new(cmplog_executor, cmplog_ref);
@@ -569,10 +585,23 @@ define_run_client!(state, mgr, fuzzer_dir, core_id, opt, is_main_node, {
     // TODO: serialize state when exiting.
 });
 
+/// The path of the file or named pipe passed to the target as `@@`.
+fn harness_input_path(opt: &Opt, fuzzer_dir: &Path) -> PathBuf {
+    let mut file = get_unique_std_input_file();
+    if let Some(ext) = &opt.input_ext {
+        file = format!("{file}.{ext}");
+    }
+    opt.cur_input_dir
+        .as_deref()
+        .unwrap_or(fuzzer_dir)
+        .join(file)
+}
+
 fn base_forkserver_builder<'a>(
     opt: &'a Opt,
     shmem_provider: &'a mut UnixShMemProvider,
     fuzzer_dir: &Path,
+    input_fifo: Option<&InputFifo>,
 ) -> ForkserverExecutorBuilder<'a, UnixShMemProvider> {
     let mut executor = ForkserverExecutor::builder()
         .program(opt.executable.clone())
@@ -598,19 +627,20 @@ fn base_forkserver_builder<'a>(
     // Set arguments for the target if necessary
     for arg in &opt.target_args {
         if arg == AFL_HARNESS_FILE_INPUT {
-            let mut file = get_unique_std_input_file();
-            if let Some(ext) = &opt.input_ext {
-                file = format!("{file}.{ext}");
-            }
-            if let Some(cur_input_dir) = &opt.cur_input_dir {
-                executor = executor.arg_input_file(cur_input_dir.join(file));
+            if let Some(fifo) = input_fifo {
+                executor = executor
+                    .arg(&fifo.path)
+                    .input(InputLocation::Fifo { fifo: fifo.clone() });
             } else {
-                executor = executor.arg_input_file(fuzzer_dir.join(file));
+                executor = executor.arg_input_file(harness_input_path(opt, fuzzer_dir));
             }
         } else {
             executor = executor.arg(arg);
         }
     }
+    if let Some(input_env) = &opt.input_env {
+        executor = executor.env_input(input_env);
+    }
     if opt.qemu_mode {
         // We need to give the harness as the first argument to afl-qemu-trace.
         executor = executor.arg(opt.executable.clone());
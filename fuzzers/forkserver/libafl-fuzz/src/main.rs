@@ -66,7 +66,7 @@
     )
 )]
 
-use std::{collections::HashMap, path::PathBuf, time::Duration};
+use std::{collections::HashMap, io::IsTerminal, path::PathBuf, time::Duration};
 mod env_parser;
 mod feedback;
 mod scheduler;
@@ -109,6 +109,10 @@ fn main() {
     env_logger::init();
     let mut opt = Opt::parse();
     parse_envs(&mut opt).expect("invalid configuration");
+    // The status screen needs a terminal, fuzzbench only keeps the log lines
+    if cfg!(feature = "fuzzbench") || !std::io::stdout().is_terminal() {
+        opt.no_ui = true;
+    }
     executor::check_binary(&mut opt, SHMEM_ENV_VAR).expect("binary to be valid");
 
     // Create the shared memory map provider for LLMP
@@ -117,9 +121,17 @@ fn main() {
 
     // Create our Monitor
     #[cfg(not(feature = "fuzzbench"))]
-    let monitor = MultiMonitor::new(|s| println!("{s}"));
+    let monitor = {
+        // With the status screen, the log lines would scroll it away
+        let no_ui = opt.no_ui;
+        MultiMonitor::new(move |s| {
+            if no_ui {
+                println!("{s}");
+            }
+        })
+    };
     #[cfg(feature = "fuzzbench")]
-    let monitor = SimpleMonitor::new(|s| println!("{}", s));
+    let monitor = SimpleMonitor::new(|s| println!("{s}"));
 
     opt.auto_resume = if opt.auto_resume {
         true
@@ -298,12 +310,22 @@ struct Opt {
     /// in seconds
     #[clap(skip)]
     stats_interval: u64,
+    /// print the stats as log lines instead of drawing the status screen, also without a terminal
+    #[clap(skip)]
+    no_ui: bool,
 
     // New Environment Variables
     #[clap(skip)]
     cores: Option<Cores>,
     #[clap(skip)]
     broker_port: Option<u16>,
+    /// deliver the input in this environment variable instead of stdin or a file, needs a target
+    /// linked against the `libafl_targets` forkserver
+    #[clap(skip)]
+    input_env: Option<String>,
+    /// stream the input into a named pipe passed as `@@` instead of a regular file
+    #[clap(skip)]
+    input_fifo: bool,
 
     // Seed config
     #[clap(skip)]
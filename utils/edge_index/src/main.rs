@@ -0,0 +1,81 @@
+//! Looks up the testcases that first covered each edge, from the dump of an `EdgeIndexMetadata`.
+use std::{fs::File, io::BufReader, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use libafl::schedulers::edge_index::EdgeIndexEntry;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+#[command(
+    name = "edge_index",
+    about,
+    long_about = "Prints the testcase that first covered each edge, from an edge index dump"
+)]
+pub struct Opt {
+    #[arg(short, long, help = "The edge index dump to read", required = true)]
+    pub input: PathBuf,
+
+    #[arg(short, long, help = "Only print the given edge")]
+    pub edge: Option<usize>,
+
+    #[arg(short, long, value_parser = parse_pc, help = "Only print the edge at the given PC")]
+    pub pc: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Only print the edges first covered by the given corpus id"
+    )]
+    pub id: Option<usize>,
+
+    #[arg(short, long, help = "Sort by discovery time instead of by edge")]
+    pub time: bool,
+}
+
+fn parse_pc(pc: &str) -> Result<usize, String> {
+    let pc = pc.trim_start_matches("0x");
+    usize::from_str_radix(pc, 16).map_err(|err| format!("Invalid PC {pc}: {err}"))
+}
+
+fn main() -> ExitCode {
+    let opts = Opt::parse();
+
+    let entries: Vec<EdgeIndexEntry> = match File::open(&opts.input)
+        .map_err(|err| err.to_string())
+        .and_then(|file| {
+            serde_json::from_reader(BufReader::new(file)).map_err(|err| err.to_string())
+        }) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Could not read {}: {err}", opts.input.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut entries: Vec<EdgeIndexEntry> = entries
+        .into_iter()
+        .filter(|entry| opts.edge.is_none_or(|edge| entry.edge == edge))
+        .filter(|entry| opts.pc.is_none_or(|pc| entry.pc == Some(pc)))
+        .filter(|entry| opts.id.is_none_or(|id| entry.discovery.id.0 == id))
+        .collect();
+    if opts.time {
+        entries.sort_by_key(|entry| entry.discovery.time);
+    }
+
+    if entries.is_empty() {
+        eprintln!("No matching edge found");
+        return ExitCode::FAILURE;
+    }
+
+    println!("edge\tpc\tcorpus id\ttime (s)\texecutions");
+    for entry in entries {
+        let pc = entry.pc.map_or_else(|| "-".into(), |pc| format!("{pc:#x}"));
+        println!(
+            "{}\t{pc}\t{}\t{:.3}\t{}",
+            entry.edge,
+            entry.discovery.id,
+            entry.discovery.time.as_secs_f64(),
+            entry.discovery.executions
+        );
+    }
+    ExitCode::SUCCESS
+}